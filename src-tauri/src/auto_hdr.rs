@@ -0,0 +1,343 @@
+// Auto-HDR: turns HDR on for the monitor showing a fullscreen (or allow-listed) app and
+// back off once the app exits or leaves fullscreen.
+use std::mem::{size_of, zeroed};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter};
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{CloseHandle, HWND, RECT};
+use windows::Win32::Graphics::Gdi::{
+    GetMonitorInfoW, MonitorFromWindow, MONITORINFOEXW, MONITOR_DEFAULTTONULL,
+};
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetClassNameW, GetForegroundWindow, GetShellWindow, GetWindowLongW, GetWindowRect,
+    GetWindowThreadProcessId, IsWindowVisible, IsZoomed, GWL_STYLE, WS_CAPTION,
+};
+
+use crate::displays::DisplayTarget;
+use crate::settings::AutoHdrSettings;
+use crate::winDisplays::widestr_to_string;
+use crate::winHdr::{self, Status};
+
+const TICK: Duration = Duration::from_millis(500);
+
+// Desktop and taskbar windows cover a whole monitor but never count as fullscreen apps
+const SHELL_WINDOW_CLASSES: [&str; 3] = ["Progman", "WorkerW", "Shell_TrayWnd"];
+
+impl AutoHdrSettings {
+    fn is_allow_listed(&self, exe: &str) -> bool {
+        self.apps
+            .iter()
+            .any(|app| app.trim().eq_ignore_ascii_case(exe))
+    }
+}
+
+/// Holds back a changing value until it has been observed unchanged for `delay`.
+#[derive(Debug)]
+pub struct Debounce<T> {
+    delay: Duration,
+    settled: Option<T>,
+    pending: Option<(Option<T>, Instant)>,
+}
+
+impl<T: Clone + PartialEq> Debounce<T> {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            settled: None,
+            pending: None,
+        }
+    }
+
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    /// Feeds the latest observation. Returns the new settled value once it has held for
+    /// the full delay, None while nothing changed or the change is still pending.
+    pub fn observe(&mut self, value: Option<T>, now: Instant) -> Option<Option<T>> {
+        if value == self.settled {
+            self.pending = None;
+            return None;
+        }
+        let since = match &self.pending {
+            Some((pending, since)) if *pending == value => *since,
+            _ => {
+                self.pending = Some((value.clone(), now));
+                now
+            }
+        };
+        if now.duration_since(since) < self.delay {
+            return None;
+        }
+        self.pending = None;
+        self.settled = value.clone();
+        Some(value)
+    }
+
+    pub fn reset(&mut self) {
+        self.settled = None;
+        self.pending = None;
+    }
+}
+
+// A window fills the monitor without a title bar. Maximized windows also reach past every edge
+// of the monitor, so they are told apart by their zoomed state and caption.
+fn is_fullscreen(window: RECT, monitor: RECT, zoomed: bool, captioned: bool) -> bool {
+    !zoomed
+        && !captioned
+        && window.left <= monitor.left
+        && window.top <= monitor.top
+        && window.right >= monitor.right
+        && window.bottom >= monitor.bottom
+}
+
+struct ForegroundApp {
+    // Lower-case executable file name, empty if the process couldn't be opened
+    exe: String,
+    // GDI device name of the monitor the window is on
+    device_name: String,
+    fullscreen: bool,
+}
+
+fn process_exe_name(hwnd: HWND) -> Option<String> {
+    unsafe {
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+        if pid == 0 {
+            return None;
+        }
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buf = [0u16; 1024];
+        let mut len = buf.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buf.as_mut_ptr()),
+            &mut len,
+        );
+        let _ = CloseHandle(process);
+        result.ok()?;
+        let path = String::from_utf16_lossy(&buf[..len as usize]);
+        path.rsplit(['\\', '/'])
+            .next()
+            .map(|name| name.to_ascii_lowercase())
+    }
+}
+
+fn foreground_app() -> Option<ForegroundApp> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0 == 0 || hwnd == GetShellWindow() || !IsWindowVisible(hwnd).as_bool() {
+            return None;
+        }
+
+        let mut class = [0u16; 64];
+        let class_len = GetClassNameW(hwnd, &mut class).max(0) as usize;
+        let class = String::from_utf16_lossy(&class[..class_len]);
+        if SHELL_WINDOW_CLASSES.contains(&class.as_str()) {
+            return None;
+        }
+
+        let hmonitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+        if hmonitor.is_invalid() {
+            return None;
+        }
+        let mut mi: MONITORINFOEXW = zeroed();
+        mi.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
+        if !GetMonitorInfoW(hmonitor, &mut mi as *mut _ as *mut _).as_bool() {
+            return None;
+        }
+
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).ok()?;
+        let style = GetWindowLongW(hwnd, GWL_STYLE) as u32;
+        let fullscreen = is_fullscreen(
+            rect,
+            mi.monitorInfo.rcMonitor,
+            IsZoomed(hwnd).as_bool(),
+            style & WS_CAPTION.0 == WS_CAPTION.0,
+        );
+
+        Some(ForegroundApp {
+            exe: process_exe_name(hwnd).unwrap_or_default(),
+            device_name: widestr_to_string(&mi.szDevice),
+            fullscreen,
+        })
+    }
+}
+
+// Monitor that should have HDR on right now, if any
+fn wanted_monitor(settings: &AutoHdrSettings) -> Option<String> {
+    let app = foreground_app()?;
+    let triggers = (settings.fullscreen && app.fullscreen) || settings.is_allow_listed(&app.exe);
+    triggers.then_some(app.device_name)
+}
+
+struct Engaged {
+    device_name: String,
+    target: DisplayTarget,
+    // False when HDR was already on; such a monitor is left on when the app goes away
+    turned_on: bool,
+}
+
+// Releases the previously engaged monitor and engages `wanted`. Returns true if any HDR state changed.
+fn switch_to(engaged: &mut Option<Engaged>, wanted: Option<String>) -> bool {
+    let mut changed = false;
+    if let Some(previous) = engaged.take() {
+        if previous.turned_on {
+            log::info!("Auto-HDR: turning HDR off on {}", previous.device_name);
+            if winHdr::set_hdr_status(previous.target, false).is_none() {
+                log::warn!(
+                    "Auto-HDR: failed to turn HDR off on {}",
+                    previous.device_name
+                );
+            }
+            changed = true;
+        }
+    }
+
+    let Some(device_name) = wanted else {
+        return changed;
+    };
+    let Some(target) = winHdr::target_for_device(&device_name) else {
+        log::warn!("Auto-HDR: no display target found for {}", device_name);
+        return changed;
+    };
+    let turned_on = match winHdr::get_hdr_status(target) {
+        Status::Unsupported => {
+            log::debug!("Auto-HDR: {} doesn't support HDR", device_name);
+            return changed;
+        }
+        Status::On => false,
+        Status::Off => {
+            log::info!("Auto-HDR: turning HDR on on {}", device_name);
+            if winHdr::set_hdr_status(target, true) != Some(Status::On) {
+                log::warn!("Auto-HDR: failed to turn HDR on on {}", device_name);
+                return changed;
+            }
+            changed = true;
+            true
+        }
+    };
+    *engaged = Some(Engaged {
+        device_name,
+        target,
+        turned_on,
+    });
+    changed
+}
+
+static START_ONCE: Once = Once::new();
+
+pub fn start_auto_hdr_service(app_handle: AppHandle) {
+    START_ONCE.call_once(|| {
+        std::thread::spawn(move || {
+            let mut debounce: Debounce<String> = Debounce::new(Duration::from_millis(
+                AutoHdrSettings::default().debounce_ms,
+            ));
+            let mut engaged: Option<Engaged> = None;
+            loop {
+                let settings = crate::settings::get(&app_handle).auto_hdr;
+                let changed = if settings.enabled {
+                    debounce.set_delay(Duration::from_millis(settings.debounce_ms));
+                    match debounce.observe(wanted_monitor(&settings), Instant::now()) {
+                        Some(wanted) => switch_to(&mut engaged, wanted),
+                        None => false,
+                    }
+                } else {
+                    // Disabling the mode hands back any monitor it turned on
+                    debounce.reset();
+                    switch_to(&mut engaged, None)
+                };
+                if changed {
+                    let _ = app_handle.emit("display-changed", ());
+                }
+                std::thread::sleep(TICK);
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> RECT {
+        RECT {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    #[test]
+    fn debounce_waits_for_a_stable_value() {
+        let start = Instant::now();
+        let mut d: Debounce<&str> = Debounce::new(100 * MS);
+        assert_eq!(d.observe(None, start), None);
+        assert_eq!(d.observe(Some("a"), start), None);
+        assert_eq!(d.observe(Some("a"), start + 99 * MS), None);
+        assert_eq!(d.observe(Some("a"), start + 100 * MS), Some(Some("a")));
+        // Settled values aren't reported again
+        assert_eq!(d.observe(Some("a"), start + 500 * MS), None);
+        assert_eq!(d.observe(None, start + 600 * MS), None);
+        assert_eq!(d.observe(None, start + 700 * MS), Some(None));
+    }
+
+    #[test]
+    fn debounce_restarts_on_flicker() {
+        let start = Instant::now();
+        let mut d: Debounce<&str> = Debounce::new(100 * MS);
+        assert_eq!(d.observe(Some("a"), start), None);
+        assert_eq!(d.observe(Some("b"), start + 80 * MS), None);
+        assert_eq!(d.observe(Some("a"), start + 150 * MS), None);
+        assert_eq!(d.observe(Some("a"), start + 249 * MS), None);
+        assert_eq!(d.observe(Some("a"), start + 250 * MS), Some(Some("a")));
+        // Going back to the settled value drops the pending change
+        assert_eq!(d.observe(Some("b"), start + 300 * MS), None);
+        assert_eq!(d.observe(Some("a"), start + 350 * MS), None);
+        assert_eq!(d.observe(Some("b"), start + 420 * MS), None);
+        assert_eq!(d.observe(Some("b"), start + 500 * MS), None);
+    }
+
+    #[test]
+    fn debounce_zero_delay_and_reset() {
+        let start = Instant::now();
+        let mut d: Debounce<&str> = Debounce::new(Duration::ZERO);
+        assert_eq!(d.observe(Some("a"), start), Some(Some("a")));
+        d.reset();
+        assert_eq!(d.observe(Some("a"), start), Some(Some("a")));
+    }
+
+    #[test]
+    fn maximized_and_captioned_windows_are_not_fullscreen() {
+        let monitor = rect(0, 0, 1920, 1080);
+        assert!(is_fullscreen(monitor, monitor, false, false));
+        // Exclusive fullscreen on a secondary monitor can overshoot too
+        assert!(is_fullscreen(
+            rect(1920, -8, 3848, 1088),
+            rect(1920, 0, 3840, 1080),
+            false,
+            false
+        ));
+        // A maximized window hangs ~8px past every edge
+        let maximized = rect(-8, -8, 1928, 1088);
+        assert!(!is_fullscreen(maximized, monitor, true, true));
+        assert!(!is_fullscreen(maximized, monitor, true, false));
+        assert!(!is_fullscreen(maximized, monitor, false, true));
+        assert!(!is_fullscreen(
+            rect(0, 0, 1920, 1040),
+            monitor,
+            false,
+            false
+        ));
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Sender};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::displays::Displays;
+
+/// Per-monitor brightness curve stored under `settings.brightnessCalibration[<EDID serial>]`.
+/// Points map a logical brightness (what the user picks, shared by all monitors) to the native
+/// percentage of the monitor's own `BrightnessInfo` min..max range.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CalibrationCurve {
+    // (logical %, native %) pairs; sorted and clamped to 0..=100 before use
+    #[serde(default)]
+    pub points: Vec<(u32, u32)>,
+}
+
+impl CalibrationCurve {
+    // Sorted by logical value, with the implicit 0 -> 0 and 100 -> 100 ends filled in if missing
+    fn normalized(&self) -> Vec<(f64, f64)> {
+        let mut pts: Vec<(f64, f64)> = self
+            .points
+            .iter()
+            .map(|(l, n)| ((*l).min(100) as f64, (*n).min(100) as f64))
+            .collect();
+        pts.sort_by(|a, b| a.0.total_cmp(&b.0));
+        pts.dedup_by(|a, b| a.0 == b.0);
+        if pts.first().map(|p| p.0 > 0.0).unwrap_or(true) {
+            pts.insert(0, (0.0, 0.0));
+        }
+        if pts.last().map(|p| p.0 < 100.0).unwrap_or(true) {
+            pts.push((100.0, 100.0));
+        }
+        pts
+    }
+
+    /// Maps a logical brightness to this monitor's native percentage.
+    pub fn logical_to_native(&self, logical: u32) -> u32 {
+        interpolate(&self.normalized(), logical.min(100) as f64)
+            .round()
+            .clamp(0.0, 100.0) as u32
+    }
+
+    /// Maps a native percentage back to logical brightness. Assumes a non-decreasing curve;
+    /// flat segments resolve to their lowest logical value.
+    pub fn native_to_logical(&self, native: u32) -> u32 {
+        let swapped: Vec<(f64, f64)> = self.normalized().iter().map(|(l, n)| (*n, *l)).collect();
+        interpolate(&swapped, native.min(100) as f64)
+            .round()
+            .clamp(0.0, 100.0) as u32
+    }
+}
+
+// Piecewise-linear lookup over points sorted by x; values outside the range clamp to the ends
+fn interpolate(points: &[(f64, f64)], x: f64) -> f64 {
+    let Some(first) = points.first() else {
+        return x;
+    };
+    if x <= first.0 {
+        return first.1;
+    }
+    for pair in points.windows(2) {
+        let (x0, y0) = pair[0];
+        let (x1, y1) = pair[1];
+        if x <= x1 {
+            if x1 == x0 {
+                return y0;
+            }
+            return y0 + (y1 - y0) * (x - x0) / (x1 - x0);
+        }
+    }
+    points.last().map(|p| p.1).unwrap_or(x)
+}
+
+fn curve_for(
+    provider: &dyn Displays,
+    curves: &HashMap<String, CalibrationCurve>,
+    device_name: &str,
+) -> CalibrationCurve {
+    crate::monitor_identity::identity_for_device(provider, device_name)
+        .and_then(|key| curves.get(&key).cloned())
+        .unwrap_or_default()
+}
+
+/// Applies one logical brightness to every monitor through its calibration curve.
+/// `skip` names a monitor that was already set directly. Returns true if any monitor changed.
+pub fn apply_logical_brightness(
+    handle: &AppHandle,
+    provider: &dyn Displays,
+    logical: u32,
+    skip: Option<&str>,
+) -> bool {
+    let curves = crate::settings::get(handle).brightness_calibration;
+    let monitor_names = match provider.get_all_monitors_short() {
+        Ok(m) => m,
+        Err(e) => {
+            log::warn!("Failed to fetch monitors for linked brightness: {e}");
+            return false;
+        }
+    };
+
+    let mut any_changed = false;
+    for name in monitor_names {
+        if Some(name.as_str()) == skip {
+            continue;
+        }
+        let native = curve_for(provider, &curves, &name).logical_to_native(logical);
+        crate::brightness_fade::cancel_fade(&name);
+        match provider.set_monitor_brightness(name.clone(), native) {
+            Ok(()) => {
+                crate::brightness_schedule::note_manual_override(&name);
+                any_changed = true;
+            }
+            Err(e) => log::warn!("Failed to set linked brightness for {}: {}", name, e),
+        }
+    }
+    any_changed
+}
+
+struct LinkRequest {
+    app_handle: AppHandle,
+    device_name: String,
+    native: u32,
+}
+
+// Linked monitors are updated on a worker so the slider doesn't wait on their DDC/CI writes
+fn link_sender() -> &'static Sender<LinkRequest> {
+    static SENDER: OnceLock<Sender<LinkRequest>> = OnceLock::new();
+    SENDER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<LinkRequest>();
+        std::thread::spawn(move || {
+            while let Ok(mut request) = rx.recv() {
+                // A slider drag queues many values; only the newest matters
+                while let Ok(newer) = rx.try_recv() {
+                    request = newer;
+                }
+                let provider = crate::displays::active_provider();
+                let curves = crate::settings::get(&request.app_handle).brightness_calibration;
+                let logical = curve_for(provider.as_ref(), &curves, &request.device_name)
+                    .native_to_logical(request.native);
+                apply_logical_brightness(
+                    &request.app_handle,
+                    provider.as_ref(),
+                    logical,
+                    Some(&request.device_name),
+                );
+            }
+        });
+        tx
+    })
+}
+
+/// Propagates a native brightness set on `device_name` to the other monitors when
+/// "link brightness" is enabled, matching them through their calibration curves.
+/// Returns right away; the other monitors follow in the background.
+pub fn propagate_linked_brightness(handle: &AppHandle, device_name: &str, native: u32) {
+    if !crate::settings::get(handle).link_brightness {
+        return;
+    }
+    let _ = link_sender().send(LinkRequest {
+        app_handle: handle.clone(),
+        device_name: device_name.to_string(),
+        native,
+    });
+}
+
+/// Logical brightness currently shown by `device_name`, derived from its native value.
+pub fn logical_brightness_of(
+    handle: &AppHandle,
+    provider: &dyn Displays,
+    device_name: &str,
+) -> Result<u32, String> {
+    let native = provider
+        .get_monitor_brightness(device_name.to_string())?
+        .percent();
+    let curves = crate::settings::get(handle).brightness_calibration;
+    Ok(curve_for(provider, &curves, device_name).native_to_logical(native))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(points: &[(u32, u32)]) -> CalibrationCurve {
+        CalibrationCurve {
+            points: points.to_vec(),
+        }
+    }
+
+    #[test]
+    fn empty_curve_is_identity() {
+        let c = CalibrationCurve::default();
+        for v in [0, 1, 37, 50, 99, 100] {
+            assert_eq!(c.logical_to_native(v), v);
+            assert_eq!(c.native_to_logical(v), v);
+        }
+        assert_eq!(c.logical_to_native(150), 100);
+    }
+
+    #[test]
+    fn interpolates_between_points() {
+        // A monitor that is much brighter than the others: half its range matches their full
+        let c = curve(&[(100, 50)]);
+        assert_eq!(c.logical_to_native(0), 0);
+        assert_eq!(c.logical_to_native(50), 25);
+        assert_eq!(c.logical_to_native(100), 50);
+        assert_eq!(c.native_to_logical(25), 50);
+        // Above the curve's top clamps to its end
+        assert_eq!(c.native_to_logical(80), 100);
+    }
+
+    #[test]
+    fn points_are_sorted_clamped_and_deduplicated() {
+        let c = curve(&[(80, 90), (20, 10), (20, 40), (150, 200)]);
+        assert_eq!(c.logical_to_native(20), 10);
+        assert_eq!(c.logical_to_native(50), 50);
+        assert_eq!(c.logical_to_native(80), 90);
+        assert_eq!(c.logical_to_native(100), 100);
+        assert_eq!(c.logical_to_native(10), 5);
+    }
+
+    #[test]
+    fn raised_floor() {
+        // Logical 0 keeps the monitor at 30% native
+        let c = curve(&[(0, 30)]);
+        assert_eq!(c.logical_to_native(0), 30);
+        assert_eq!(c.logical_to_native(50), 65);
+        assert_eq!(c.native_to_logical(10), 0);
+        assert_eq!(c.native_to_logical(65), 50);
+    }
+
+    #[test]
+    fn round_trips_on_increasing_curves() {
+        let c = curve(&[(10, 5), (40, 30), (70, 80)]);
+        let mut last = 0;
+        for logical in 0..=100 {
+            let native = c.logical_to_native(logical);
+            assert!(native >= last, "not monotonic at {}", logical);
+            last = native;
+            let back = c.native_to_logical(native);
+            assert!(
+                back.abs_diff(logical) <= 1,
+                "{} -> {} -> {}",
+                logical,
+                native,
+                back
+            );
+        }
+    }
+
+    #[test]
+    fn flat_segment_maps_back_to_its_lowest_logical() {
+        let c = curve(&[(30, 40), (60, 40)]);
+        assert_eq!(c.logical_to_native(45), 40);
+        assert_eq!(c.native_to_logical(40), 30);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::displays::Displays;
+
+// DDC/CI requires at least 50ms between consecutive VCP writes; stay slightly above it
+pub const MIN_WRITE_INTERVAL_MS: u64 = 60;
+
+/// Smooth ease-in-out curve mapping t in [0, 1] to [0, 1].
+pub fn ease_in_out(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+/// Computes the writes performed during a fade from `from` to `to` as (offset_ms, value) pairs.
+/// Writes are at least `min_interval_ms` apart, consecutive duplicates are dropped, and the
+/// last write is always `to`, at `duration_ms` at the latest.
+pub fn fade_steps(from: u32, to: u32, duration_ms: u64, min_interval_ms: u64) -> Vec<(u64, u32)> {
+    if from == to {
+        return Vec::new();
+    }
+    let steps = (duration_ms / min_interval_ms.max(1)).max(1);
+    let mut out: Vec<(u64, u32)> = Vec::new();
+    for i in 1..=steps {
+        let t = ease_in_out(i as f64 / steps as f64);
+        let value = (from as f64 + (to as f64 - from as f64) * t).round() as u32;
+        let last_value = out.last().map(|(_, v)| *v).unwrap_or(from);
+        if value != last_value {
+            out.push((duration_ms * i / steps, value));
+        }
+    }
+    out
+}
+
+fn generations() -> &'static Mutex<HashMap<String, u64>> {
+    static GENERATIONS: OnceLock<Mutex<HashMap<String, u64>>> = OnceLock::new();
+    GENERATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn bump_generation(device_name: &str) -> u64 {
+    let Ok(mut map) = generations().lock() else {
+        return 0;
+    };
+    let entry = map.entry(device_name.to_string()).or_insert(0);
+    *entry += 1;
+    *entry
+}
+
+fn current_generation(device_name: &str) -> u64 {
+    generations()
+        .lock()
+        .ok()
+        .and_then(|map| map.get(device_name).copied())
+        .unwrap_or(0)
+}
+
+/// Stops any fade running for `device_name`. Call before applying a new brightness directly.
+pub fn cancel_fade(device_name: &str) {
+    bump_generation(device_name);
+}
+
+/// Fades brightness to `target` over `duration_ms`, blocking until done or cancelled.
+/// A later fade or `cancel_fade` for the same device stops this one at its current value.
+pub fn fade_monitor_brightness(
+    provider: &dyn Displays,
+    device_name: String,
+    target: u32,
+    duration_ms: u64,
+) -> Result<(), String> {
+    let generation = bump_generation(&device_name);
+    let target = target.min(100);
+
+    let from = provider
+        .get_monitor_brightness(device_name.clone())?
+        .percent();
+    let started = Instant::now();
+    for (offset_ms, value) in fade_steps(from, target, duration_ms, MIN_WRITE_INTERVAL_MS) {
+        // Writes themselves can take tens of milliseconds; only sleep for what's left until the step
+        if let Some(rest) = Duration::from_millis(offset_ms).checked_sub(started.elapsed()) {
+            std::thread::sleep(rest);
+        }
+        if current_generation(&device_name) != generation {
+            log::debug!("Brightness fade for {} cancelled", device_name);
+            return Ok(());
+        }
+        provider.set_monitor_brightness(device_name.clone(), value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fakeDisplays::{recorded_brightness, FakeDisplays};
+
+    #[test]
+    fn ease_in_out_shape() {
+        assert_eq!(ease_in_out(0.0), 0.0);
+        assert_eq!(ease_in_out(0.5), 0.5);
+        assert_eq!(ease_in_out(1.0), 1.0);
+        assert_eq!(ease_in_out(-1.0), 0.0);
+        assert_eq!(ease_in_out(2.0), 1.0);
+        let mut last = 0.0;
+        for i in 1..=100 {
+            let t = i as f64 / 100.0;
+            let v = ease_in_out(t);
+            assert!(v >= last, "not monotonic at {}", t);
+            // Symmetric around the midpoint
+            assert!((v + ease_in_out(1.0 - t) - 1.0).abs() < 1e-9);
+            last = v;
+        }
+        // Slow start and end
+        assert!(ease_in_out(0.1) < 0.1);
+        assert!(ease_in_out(0.9) > 0.9);
+    }
+
+    #[test]
+    fn no_steps_without_change() {
+        assert!(fade_steps(40, 40, 1000, 60).is_empty());
+    }
+
+    #[test]
+    fn steps_end_on_target_and_respect_interval() {
+        for (from, to) in [(0, 100), (100, 0), (37, 42), (80, 10)] {
+            let steps = fade_steps(from, to, 1000, 60);
+            let &(end, last) = steps.last().unwrap();
+            assert_eq!(last, to, "{} -> {}", from, to);
+            assert!(end <= 1000);
+            let mut previous = (0, from);
+            for &(offset, value) in &steps {
+                assert!(offset >= previous.0 + 60, "{} -> {}: {:?}", from, to, steps);
+                assert_ne!(value, previous.1, "duplicate write in {:?}", steps);
+                // Values move towards the target only
+                assert_eq!(value > previous.1, to > from, "{:?}", steps);
+                previous = (offset, value);
+            }
+        }
+    }
+
+    #[test]
+    fn short_fade_is_a_single_write() {
+        assert_eq!(fade_steps(10, 90, 30, 60), vec![(30, 90)]);
+        assert_eq!(fade_steps(10, 90, 0, 60), vec![(0, 90)]);
+    }
+
+    #[test]
+    fn fade_eases_recorded_writes() {
+        let provider = FakeDisplays::new();
+        let device = "\\\\.\\FADE1".to_string();
+        provider.set_monitor_brightness(device.clone(), 20).unwrap();
+        fade_monitor_brightness(&provider, device.clone(), 80, 600).unwrap();
+
+        let writes = recorded_brightness(&device);
+        assert_eq!(writes.first(), Some(&20));
+        assert_eq!(writes.last(), Some(&80));
+        assert!(writes.windows(2).all(|w| w[0] < w[1]), "{:?}", writes);
+        // One write per eased step, after the initial value
+        let expected: Vec<u32> = std::iter::once(20)
+            .chain(
+                fade_steps(20, 80, 600, MIN_WRITE_INTERVAL_MS)
+                    .into_iter()
+                    .map(|(_, v)| v),
+            )
+            .collect();
+        assert_eq!(writes, expected);
+    }
+
+    #[test]
+    fn cancelled_fade_stops_early() {
+        let device = "\\\\.\\FADE2".to_string();
+        FakeDisplays::new()
+            .set_monitor_brightness(device.clone(), 0)
+            .unwrap();
+        let fade = {
+            let device = device.clone();
+            std::thread::spawn(move || {
+                fade_monitor_brightness(&FakeDisplays::new(), device, 100, 2000)
+            })
+        };
+        std::thread::sleep(Duration::from_millis(200));
+        cancel_fade(&device);
+        fade.join().unwrap().unwrap();
+        let writes = recorded_brightness(&device);
+        assert!(writes.last().is_some_and(|&v| v < 100), "{:?}", writes);
+    }
+}
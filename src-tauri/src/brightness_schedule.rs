@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, Once, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Local, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::solar;
+
+const TICK: Duration = Duration::from_secs(30);
+// Each scheduled change is eased in rather than applied as a jump
+const STEP_FADE_MS: u64 = 2000;
+
+fn default_transition_minutes() -> u32 {
+    30
+}
+
+/// Brightness schedule stored under `settings.brightnessSchedule` in settings.json.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrightnessSchedule {
+    #[serde(default)]
+    pub enabled: bool,
+    // Coordinates used to resolve "sunrise"/"sunset" steps, in degrees (east positive)
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    // Duration of the fade from the previous step's brightness, starting at each step's time
+    #[serde(default = "default_transition_minutes")]
+    pub transition_minutes: u32,
+    #[serde(default)]
+    pub steps: Vec<ScheduleStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleStep {
+    // "HH:MM", "sunrise", "sunset", or an offset in minutes like "sunset+30" / "sunrise-15"
+    pub at: String,
+    pub brightness: u32,
+    // Optional software dimming (100 = none) to go below the hardware minimum, e.g. at night
+    #[serde(default)]
+    pub dimming: Option<u32>,
+    // Optional color temperature in Kelvin (6500 = neutral) for a night-light effect
+    #[serde(default)]
+    pub color_temperature: Option<u32>,
+    // Device name or monitor identity the step applies to; None applies to every monitor
+    // without its own steps
+    #[serde(default)]
+    pub monitor: Option<String>,
+}
+
+/// Resolves a step time into minutes after local midnight.
+/// Sun-relative steps need `sun` (local sunrise, local sunset); they resolve to None without it.
+pub fn resolve_step_minutes(at: &str, sun: Option<(f64, f64)>) -> Option<f64> {
+    let s = at.trim().to_ascii_lowercase();
+
+    for (keyword, pick_sunset) in [("sunrise", false), ("sunset", true)] {
+        if let Some(rest) = s.strip_prefix(keyword) {
+            let (sunrise, sunset) = sun?;
+            let base = if pick_sunset { sunset } else { sunrise };
+            let rest = rest.trim();
+            let offset: f64 = if rest.is_empty() {
+                0.0
+            } else {
+                rest.replace(' ', "").parse::<i32>().ok()? as f64
+            };
+            return Some((base + offset).rem_euclid(1440.0));
+        }
+    }
+
+    let (h, m) = s.split_once(':')?;
+    let h: u32 = h.trim().parse().ok()?;
+    let m: u32 = m.trim().parse().ok()?;
+    if h > 23 || m > 59 {
+        return None;
+    }
+    Some((h * 60 + m) as f64)
+}
+
+/// Computes the scheduled value at `now_minutes` (minutes after local midnight).
+/// `points` are (minute, value) pairs in any order. Returns the target value and the index
+/// (into the sorted points) of the step currently in effect, so callers can detect step changes.
+pub fn value_at(
+    points: &[(f64, u32)],
+    now_minutes: f64,
+    transition_minutes: u32,
+) -> Option<(u32, usize)> {
+    if points.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<(f64, u32)> = points.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Active step is the latest one at or before now; before the first step, yesterday's last one
+    let active = sorted
+        .iter()
+        .rposition(|(minute, _)| *minute <= now_minutes)
+        .unwrap_or(sorted.len() - 1);
+    let previous = (active + sorted.len() - 1) % sorted.len();
+
+    let (start, target) = sorted[active];
+    let from = sorted[previous].1;
+    let elapsed = (now_minutes - start).rem_euclid(1440.0);
+
+    if transition_minutes == 0 || elapsed >= transition_minutes as f64 || from == target {
+        return Some((target, active));
+    }
+
+    let t = elapsed / transition_minutes as f64;
+    let value = from as f64 + (target as f64 - from as f64) * t;
+    Some((value.round().max(0.0) as u32, active))
+}
+
+fn overrides() -> &'static Mutex<HashMap<String, Option<usize>>> {
+    static OVERRIDES: OnceLock<Mutex<HashMap<String, Option<usize>>>> = OnceLock::new();
+    OVERRIDES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// (minute, brightness) of each step, resolved for one monitor
+type Points = Vec<(f64, u32)>;
+
+// Brightness points the last tick resolved for each monitor, so an override can tell which step
+// it interrupts without waiting for the next tick
+fn resolved_points() -> &'static Mutex<HashMap<String, Points>> {
+    static POINTS: OnceLock<Mutex<HashMap<String, Points>>> = OnceLock::new();
+    POINTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn local_minutes(now: &DateTime<Local>) -> f64 {
+    (now.hour() * 60 + now.minute()) as f64 + now.second() as f64 / 60.0
+}
+
+/// Pauses the schedule for `device_name` until the next step takes effect.
+pub fn note_manual_override(device_name: &str) {
+    note_override_at(device_name, local_minutes(&Local::now()));
+}
+
+// Pins the override to the step active at `now_minutes`. Before the schedule has resolved this
+// monitor's steps, the next tick pins it instead.
+fn note_override_at(device_name: &str, now_minutes: f64) {
+    let step = resolved_points()
+        .lock()
+        .ok()
+        .and_then(|points| value_at(points.get(device_name)?, now_minutes, 0))
+        .map(|(_, step)| step);
+    if let Ok(mut map) = overrides().lock() {
+        map.insert(device_name.to_string(), step);
+    }
+}
+
+// Returns true while a manual override is holding the given step for this monitor
+fn is_overridden(device_name: &str, active_step: usize) -> bool {
+    let Ok(mut map) = overrides().lock() else {
+        return false;
+    };
+    match map.get(device_name).copied() {
+        None => false,
+        Some(None) => {
+            // Overridden before the steps were known: pin it to the step that is active now
+            map.insert(device_name.to_string(), Some(active_step));
+            true
+        }
+        Some(Some(step)) if step == active_step => true,
+        Some(Some(_)) => {
+            map.remove(device_name);
+            false
+        }
+    }
+}
+
+// Values last written per monitor, so unchanged targets aren't re-sent every tick
+#[derive(Default)]
+struct AppliedValues {
+    brightness: HashMap<String, u32>,
+    dimming: HashMap<String, u32>,
+    color_temperature: HashMap<String, u32>,
+}
+
+static START_ONCE: Once = Once::new();
+
+pub fn start_schedule_service(app_handle: AppHandle) {
+    START_ONCE.call_once(|| {
+        std::thread::spawn(move || {
+            let mut applied = AppliedValues::default();
+            loop {
+                let schedule = crate::settings::get(&app_handle).brightness_schedule;
+                if schedule.enabled && !schedule.steps.is_empty() {
+                    if apply_schedule(&schedule, &mut applied) {
+                        let _ = app_handle.emit("brightness-changed", ());
+                    }
+                } else {
+                    applied = AppliedValues::default();
+                }
+                std::thread::sleep(TICK);
+            }
+        });
+    });
+}
+
+// Interpolated value of an optional per-step setting; steps that omit it count as `default`.
+// Returns None when no step sets it, so the monitor is left alone.
+fn optional_value_at(
+    steps: &[&ScheduleStep],
+    sun: Option<(f64, f64)>,
+    now_minutes: f64,
+    transition_minutes: u32,
+    pick: fn(&ScheduleStep) -> Option<u32>,
+    default: u32,
+) -> Option<u32> {
+    if !steps.iter().any(|s| pick(s).is_some()) {
+        return None;
+    }
+    let points: Vec<(f64, u32)> = steps
+        .iter()
+        .filter_map(|s| resolve_step_minutes(&s.at, sun).map(|m| (m, pick(s).unwrap_or(default))))
+        .collect();
+    value_at(&points, now_minutes, transition_minutes).map(|(v, _)| v)
+}
+
+fn apply_schedule(schedule: &BrightnessSchedule, applied: &mut AppliedValues) -> bool {
+    let now = Local::now();
+    let now_minutes = local_minutes(&now);
+    let offset_minutes = now.offset().local_minus_utc() / 60;
+
+    let sun = match (schedule.latitude, schedule.longitude) {
+        (Some(lat), Some(lon)) => solar::sun_times(now.ordinal(), lat, lon).map(|t| {
+            (
+                solar::utc_minutes_to_local(t.sunrise_utc_minutes, offset_minutes),
+                solar::utc_minutes_to_local(t.sunset_utc_minutes, offset_minutes),
+            )
+        }),
+        _ => None,
+    };
+
+    let provider = crate::displays::active_provider();
+    let monitor_names = match provider.get_all_monitors_short() {
+        Ok(m) => m,
+        Err(e) => {
+            log::warn!("Brightness schedule: failed to fetch monitors: {e}");
+            return false;
+        }
+    };
+
+    let mut any_changed = false;
+    for name in monitor_names {
+        // Monitor-specific steps replace the global ones for that monitor
+        let identity = crate::monitor_identity::identity_for_device(provider.as_ref(), &name);
+        let own: Vec<&ScheduleStep> = schedule
+            .steps
+            .iter()
+            .filter(|s| {
+                s.monitor.as_deref() == Some(name.as_str())
+                    || (s.monitor.is_some() && s.monitor == identity)
+            })
+            .collect();
+        let steps: Vec<&ScheduleStep> = if own.is_empty() {
+            schedule
+                .steps
+                .iter()
+                .filter(|s| s.monitor.is_none())
+                .collect()
+        } else {
+            own
+        };
+
+        let points: Vec<(f64, u32)> = steps
+            .iter()
+            .filter_map(|s| match resolve_step_minutes(&s.at, sun) {
+                Some(minute) => Some((minute, s.brightness)),
+                None => {
+                    log::warn!("Brightness schedule: skipping unresolvable step '{}'", s.at);
+                    None
+                }
+            })
+            .collect();
+        if let Ok(mut resolved) = resolved_points().lock() {
+            resolved.insert(name.clone(), points.clone());
+        }
+
+        // Dimming and color temperature follow the schedule even while brightness is overridden
+        if let Some(dim) = optional_value_at(
+            &steps,
+            sun,
+            now_minutes,
+            schedule.transition_minutes,
+            |s| s.dimming,
+            100,
+        ) {
+            let dim = dim.min(100);
+            if applied.dimming.get(&name) != Some(&dim) {
+                match provider.set_monitor_dimming(name.clone(), dim) {
+                    Ok(()) => {
+                        applied.dimming.insert(name.clone(), dim);
+                    }
+                    Err(e) => log::warn!("Brightness schedule: failed to dim {}: {}", name, e),
+                }
+            }
+        }
+        if let Some(kelvin) = optional_value_at(
+            &steps,
+            sun,
+            now_minutes,
+            schedule.transition_minutes,
+            |s| s.color_temperature,
+            crate::color_temperature::NEUTRAL_KELVIN,
+        ) {
+            if applied.color_temperature.get(&name) != Some(&kelvin) {
+                match provider.set_monitor_color_temperature(name.clone(), kelvin) {
+                    Ok(_) => {
+                        applied.color_temperature.insert(name.clone(), kelvin);
+                    }
+                    Err(e) => log::warn!(
+                        "Brightness schedule: failed to set color temperature for {}: {}",
+                        name,
+                        e
+                    ),
+                }
+            }
+        }
+
+        let Some((target, active_step)) =
+            value_at(&points, now_minutes, schedule.transition_minutes)
+        else {
+            continue;
+        };
+        let target = target.min(100);
+
+        if is_overridden(&name, active_step) {
+            // Forget what we applied so the next step re-applies even if the value matches
+            applied.brightness.remove(&name);
+            continue;
+        }
+        if applied.brightness.get(&name) == Some(&target) {
+            continue;
+        }
+
+        match crate::brightness_fade::fade_monitor_brightness(
+            provider.as_ref(),
+            name.clone(),
+            target,
+            STEP_FADE_MS,
+        ) {
+            Ok(()) => {
+                log::info!("Brightness schedule: set {} to {}%", name, target);
+                applied.brightness.insert(name, target);
+                any_changed = true;
+            }
+            Err(e) => log::warn!("Brightness schedule: failed to set {}: {}", name, e),
+        }
+    }
+    any_changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Local sunrise 06:00, sunset 21:00
+    const SUN: Option<(f64, f64)> = Some((360.0, 1260.0));
+
+    #[test]
+    fn clock_times() {
+        assert_eq!(resolve_step_minutes("07:30", None), Some(450.0));
+        assert_eq!(resolve_step_minutes(" 0:05 ", None), Some(5.0));
+        assert_eq!(resolve_step_minutes("24:00", None), None);
+        assert_eq!(resolve_step_minutes("12:60", None), None);
+        assert_eq!(resolve_step_minutes("noon", None), None);
+    }
+
+    #[test]
+    fn sun_relative_times() {
+        assert_eq!(resolve_step_minutes("sunrise", SUN), Some(360.0));
+        assert_eq!(resolve_step_minutes("Sunset", SUN), Some(1260.0));
+        assert_eq!(resolve_step_minutes("sunrise+15", SUN), Some(375.0));
+        assert_eq!(resolve_step_minutes("sunset-30", SUN), Some(1230.0));
+        assert_eq!(resolve_step_minutes("sunset + 45", SUN), Some(1305.0));
+        assert_eq!(resolve_step_minutes("sunset+soon", SUN), None);
+        // Without coordinates there is nothing to resolve against
+        assert_eq!(resolve_step_minutes("sunrise", None), None);
+    }
+
+    #[test]
+    fn sun_offsets_wrap_past_midnight() {
+        assert_eq!(resolve_step_minutes("sunset+240", SUN), Some(60.0));
+        assert_eq!(resolve_step_minutes("sunrise-400", SUN), Some(1400.0));
+    }
+
+    #[test]
+    fn value_without_points() {
+        assert_eq!(value_at(&[], 600.0, 30), None);
+    }
+
+    #[test]
+    fn value_holds_after_transition() {
+        let points = [(1260.0, 20), (420.0, 80)];
+        assert_eq!(value_at(&points, 600.0, 30), Some((80, 0)));
+        assert_eq!(value_at(&points, 1300.0, 30), Some((20, 1)));
+    }
+
+    #[test]
+    fn value_eases_during_transition() {
+        let points = [(420.0, 80), (1260.0, 20)];
+        assert_eq!(value_at(&points, 420.0, 30), Some((20, 0)));
+        assert_eq!(value_at(&points, 435.0, 30), Some((50, 0)));
+        assert_eq!(value_at(&points, 1275.0, 30), Some((50, 1)));
+        // No transition jumps straight to the target
+        assert_eq!(value_at(&points, 421.0, 0), Some((80, 0)));
+    }
+
+    #[test]
+    fn value_before_first_step_uses_yesterdays_last() {
+        let points = [(420.0, 80), (1260.0, 20)];
+        assert_eq!(value_at(&points, 120.0, 30), Some((20, 1)));
+    }
+
+    #[test]
+    fn transition_wraps_past_midnight() {
+        // 23:50 step fading over 30 minutes is halfway at 00:05
+        let points = [(420.0, 80), (1430.0, 20)];
+        assert_eq!(value_at(&points, 5.0, 30), Some((50, 1)));
+        assert_eq!(value_at(&points, 25.0, 30), Some((20, 1)));
+    }
+
+    #[test]
+    fn override_just_before_a_step_ends_with_it() {
+        let points = vec![(420.0, 80), (1260.0, 20)];
+        for device in ["OVERRIDE1", "OVERRIDE2"] {
+            resolved_points()
+                .lock()
+                .unwrap()
+                .insert(device.to_string(), points.clone());
+        }
+
+        // Changed at 20:59:50; the 21:00 step applies at the first tick after it
+        note_override_at("OVERRIDE1", 1259.8);
+        assert!(!is_overridden("OVERRIDE1", 1));
+        assert!(!is_overridden("OVERRIDE1", 1));
+
+        // Changed right after 21:00 holds until the next morning's step
+        note_override_at("OVERRIDE2", 1260.2);
+        assert!(is_overridden("OVERRIDE2", 1));
+        assert!(is_overridden("OVERRIDE2", 1));
+        assert!(!is_overridden("OVERRIDE2", 0));
+    }
+
+    #[test]
+    fn override_before_steps_are_known_pins_at_next_tick() {
+        note_override_at("OVERRIDE3", 600.0);
+        assert!(is_overridden("OVERRIDE3", 0));
+        assert!(is_overridden("OVERRIDE3", 0));
+        assert!(!is_overridden("OVERRIDE3", 1));
+    }
+}
@@ -87,7 +87,7 @@ pub enum Commands {
         /// Monitor index (0-based)
         #[arg(long)]
         monitor_idx: usize,
-        /// Input source value
+        /// Input source: a key like hdmi1, a hex code like 0x1B or a name given to the input
         #[arg(long)]
         source: String,
     },
@@ -97,6 +97,12 @@ pub enum Commands {
         #[arg(long)]
         monitor_idx: usize,
     },
+    /// List the input sources a monitor offers, with the names given to them
+    ListInputs {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+    },
     /// Set resolution for a monitor
     SetResolution {
         /// Monitor index (0-based)
@@ -112,6 +118,36 @@ pub enum Commands {
         #[arg(long)]
         refresh_hz: Option<u32>,
     },
+    /// List every display mode a monitor reports
+    ListModes {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+        /// Include modes the monitor doesn't advertise (raw driver modes)
+        #[arg(long)]
+        all: bool,
+    },
+    /// Switch to the mode closest to the requested one
+    SetMode {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+        /// Width in pixels
+        #[arg(long)]
+        width: u32,
+        /// Height in pixels
+        #[arg(long)]
+        height: u32,
+        /// Refresh rate in Hz, fractional allowed (e.g. 59.94); highest available if omitted
+        #[arg(long)]
+        refresh: Option<f64>,
+        /// How to pick a mode: exact, closest, exact_aspect or native
+        #[arg(long, default_value = "exact")]
+        policy: String,
+        /// Prefer interlaced modes
+        #[arg(long)]
+        interlaced: bool,
+    },
     /// Set orientation for a monitor
     SetOrientation {
         /// Monitor index (0-based)
@@ -129,6 +165,18 @@ pub enum Commands {
         /// Brightness percentage (0-100)
         #[arg(long)]
         percent: u32,
+        /// Fade to the new brightness over this many milliseconds instead of jumping
+        #[arg(long)]
+        fade_ms: Option<u64>,
+    },
+    /// Dim a monitor in software (gamma ramp), below its hardware minimum if needed
+    SetDimming {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+        /// Remaining light in percent (100 = no dimming)
+        #[arg(long)]
+        percent: u32,
     },
     /// Get brightness for a monitor
     GetBrightness {
@@ -136,6 +184,36 @@ pub enum Commands {
         #[arg(long)]
         monitor_idx: usize,
     },
+    /// Set color temperature for a monitor (night light)
+    SetColorTemp {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+        /// White point in Kelvin (1000-10000, 6500 = neutral)
+        #[arg(long)]
+        kelvin: u32,
+    },
+    /// Get color temperature for a monitor
+    GetColorTemp {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+    },
+    /// List ICC color profiles installed for displays
+    ListColorProfiles {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+    },
+    /// Associate an ICC color profile with a monitor and make it the default
+    SetColorProfile {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+        /// Installed profile file name, or a full path to a profile to install
+        #[arg(long)]
+        profile: String,
+    },
     /// Set scale for a monitor
     SetScale {
         /// Monitor index (0-based)
@@ -145,6 +223,24 @@ pub enum Commands {
         #[arg(long)]
         percent: u32,
     },
+    /// Set a custom scale for all monitors (applies after signing out)
+    SetCustomScale {
+        /// Scale percentage (100-500), e.g. 115
+        #[arg(long, conflicts_with = "off")]
+        percent: Option<u32>,
+        /// Turn custom scaling off and return to per-monitor scaling
+        #[arg(long)]
+        off: bool,
+    },
+    /// Set how a lower resolution is fitted to the panel
+    SetScaling {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+        /// identity, centered, stretched, aspect_ratio or preferred
+        #[arg(long)]
+        mode: String,
+    },
     /// Enable or disable HDR for a monitor
     SetHdr {
         /// Monitor index (0-based)
@@ -154,12 +250,57 @@ pub enum Commands {
         #[arg(long)]
         enable: bool,
     },
+    /// Enable or disable variable refresh rate (G-Sync/FreeSync)
+    SetVrr {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+        /// Enable VRR (true/false)
+        #[arg(long)]
+        enable: bool,
+    },
+    /// Set SDR content brightness for a monitor while HDR is on
+    SetSdrWhite {
+        /// Monitor index (0-based)
+        #[arg(long)]
+        monitor_idx: usize,
+        /// SDR white level in nits (80-480)
+        #[arg(long)]
+        nits: u32,
+    },
     /// Get DDC/CI capabilities for a monitor
     GetCaps {
         /// Monitor index (0-based)
         #[arg(long)]
         monitor_idx: usize,
     },
+    /// Switch monitors to the inputs of a KVM preset; lists the presets without --preset
+    Kvm {
+        /// Preset name
+        #[arg(long)]
+        preset: Option<String>,
+    },
+    /// Export settings, profiles and remembered monitor state to a file
+    ExportConfig {
+        /// Output file (.json or .toml)
+        #[arg(long)]
+        path: std::path::PathBuf,
+        /// json or toml; taken from the file extension if omitted
+        #[arg(long)]
+        format: Option<String>,
+    },
+    /// Import a file written by export-config
+    ImportConfig {
+        /// File to import (.json or .toml)
+        #[arg(long)]
+        path: std::path::PathBuf,
+        /// merge (imported entries win, others are kept) or replace
+        #[arg(long, default_value = "merge")]
+        mode: String,
+        /// json or toml; taken from the file extension if omitted
+        #[arg(long)]
+        format: Option<String>,
+    },
     /// Start the GUI application
     Ui,
 }
@@ -186,7 +327,16 @@ pub fn run_cli() -> Result<bool, String> {
 }
 
 fn handle_command(command: Commands) -> Result<(), String> {
-    let provider = crate::displays::active_provider();
+    // Forward to the running app when there is one, so it sees the change too
+    let remote = crate::ipc::connect().map(crate::remoteDisplays::RemoteDisplays::new);
+    let local;
+    let provider: &dyn crate::displays::Displays = match &remote {
+        Some(remote) => remote,
+        None => {
+            local = crate::displays::active_provider();
+            local.as_ref()
+        }
+    };
 
     match command {
         Commands::List => {
@@ -209,7 +359,14 @@ fn handle_command(command: Commands) -> Result<(), String> {
         } => {
             let monitors = provider.get_all_monitors()?;
             let device_name = get_device_name(&monitors, monitor_idx)?;
-            provider.set_monitor_input_source(device_name, source.clone())?;
+            // The running app resolves input names itself
+            let input = match &remote {
+                Some(_) => source.clone(),
+                None => {
+                    crate::inputs::resolve_name(&local_input_names(&monitors[monitor_idx]), &source)
+                }
+            };
+            provider.set_monitor_input_source(device_name, input)?;
             println!(
                 "Successfully set input source to '{}' for monitor {}",
                 source, monitor_idx
@@ -221,6 +378,40 @@ fn handle_command(command: Commands) -> Result<(), String> {
             let input = provider.get_monitor_input_source(device_name)?;
             println!("Monitor {} input source: {}", monitor_idx, input);
         }
+        Commands::ListInputs { monitor_idx } => {
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            let inputs: Vec<crate::inputs::InputSource> = match &remote {
+                Some(remote) => remote.call(
+                    "list_monitor_inputs",
+                    serde_json::json!({ "deviceName": device_name }),
+                )?,
+                None => {
+                    let monitor = &monitors[monitor_idx];
+                    let caps = provider.get_monitor_ddc_caps(device_name.clone()).ok();
+                    crate::inputs::inputs_for(
+                        &monitor.manufacturer,
+                        caps.as_deref(),
+                        &local_input_names(monitor),
+                    )
+                }
+            };
+            let current = provider.get_monitor_input_source(device_name).ok();
+            println!("Monitor {} input sources:", monitor_idx);
+            for input in &inputs {
+                let marker = if current.as_ref() == Some(&input.key) {
+                    "*"
+                } else {
+                    " "
+                };
+                match &input.name {
+                    Some(name) => {
+                        println!("{} {:<12} {} ({})", marker, input.key, input.label, name)
+                    }
+                    None => println!("{} {:<12} {}", marker, input.key, input.label),
+                }
+            }
+        }
         Commands::SetResolution {
             monitor_idx,
             width,
@@ -238,6 +429,69 @@ fn handle_command(command: Commands) -> Result<(), String> {
                 width, height, refresh_str, monitor_idx
             );
         }
+        Commands::ListModes { monitor_idx, all } => {
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            let modes = provider.list_monitor_modes(device_name, all)?;
+            println!("Monitor {} modes:", monitor_idx);
+            for mode in modes {
+                let mut flags: Vec<&str> = Vec::new();
+                if mode.interlaced {
+                    flags.push("interlaced");
+                }
+                if mode.scaled {
+                    flags.push("scaled");
+                }
+                println!(
+                    "  {}x{}@{}Hz ({}bpp){}",
+                    mode.width,
+                    mode.height,
+                    mode.refresh_rate,
+                    mode.bits_per_pixel,
+                    if flags.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" [{}]", flags.join(", "))
+                    }
+                );
+            }
+        }
+        Commands::SetMode {
+            monitor_idx,
+            width,
+            height,
+            refresh,
+            policy,
+            interlaced,
+        } => {
+            let policy = serde_json::from_value(serde_json::Value::String(policy.clone()))
+                .map_err(|_| {
+                    format!(
+                        "Unknown policy '{}'. Use exact, closest, exact_aspect or native",
+                        policy
+                    )
+                })?;
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            let mode = provider.set_monitor_mode(
+                device_name,
+                crate::mode_selection::ModeRequest {
+                    width,
+                    height,
+                    refresh_rate: refresh,
+                    policy,
+                    interlaced,
+                },
+            )?;
+            println!(
+                "Successfully set mode {}x{}@{}Hz{} for monitor {}",
+                mode.width,
+                mode.height,
+                mode.refresh_rate,
+                if mode.interlaced { " interlaced" } else { "" },
+                monitor_idx
+            );
+        }
         Commands::SetOrientation {
             monitor_idx,
             degrees,
@@ -256,25 +510,125 @@ fn handle_command(command: Commands) -> Result<(), String> {
         Commands::SetBrightness {
             monitor_idx,
             percent,
+            fade_ms,
         } => {
             if percent > 100 {
                 return Err("Brightness must be between 0 and 100".to_string());
             }
             let monitors = provider.get_all_monitors()?;
             let device_name = get_device_name(&monitors, monitor_idx)?;
-            provider.set_monitor_brightness(device_name, percent)?;
+            match (fade_ms, &remote) {
+                (Some(ms), Some(remote)) => {
+                    remote.fade_monitor_brightness(device_name, percent, ms)?
+                }
+                (Some(ms), None) => crate::brightness_fade::fade_monitor_brightness(
+                    provider,
+                    device_name,
+                    percent,
+                    ms,
+                )?,
+                (None, _) => provider.set_monitor_brightness(device_name, percent)?,
+            }
             println!(
                 "Successfully set brightness to {}% for monitor {}",
                 percent, monitor_idx
             );
         }
+        Commands::SetDimming {
+            monitor_idx,
+            percent,
+        } => {
+            if percent > 100 {
+                return Err("Dimming must be between 0 and 100".to_string());
+            }
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            provider.set_monitor_dimming(device_name, percent)?;
+            println!(
+                "Successfully set dimming to {}% for monitor {}",
+                percent, monitor_idx
+            );
+        }
+        Commands::SetColorTemp {
+            monitor_idx,
+            kelvin,
+        } => {
+            if !(crate::color_temperature::MIN_KELVIN..=crate::color_temperature::MAX_KELVIN)
+                .contains(&kelvin)
+            {
+                return Err("Color temperature must be between 1000K and 10000K".to_string());
+            }
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            let info = provider.set_monitor_color_temperature(device_name, kelvin)?;
+            println!(
+                "Successfully set color temperature to {}K for monitor {} (via: {})",
+                info.kelvin, monitor_idx, info.source
+            );
+        }
+        Commands::GetColorTemp { monitor_idx } => {
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            let info = provider.get_monitor_color_temperature(device_name)?;
+            println!(
+                "Monitor {} color temperature: {}K (via: {})",
+                monitor_idx, info.kelvin, info.source
+            );
+        }
+        Commands::ListColorProfiles { monitor_idx } => {
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            let profiles = provider.list_color_profiles(device_name)?;
+            if profiles.is_empty() {
+                println!("No color profiles installed.");
+                return Ok(());
+            }
+            for profile in profiles {
+                let marker = if profile.is_default {
+                    "*"
+                } else if profile.associated {
+                    "+"
+                } else {
+                    " "
+                };
+                println!("{} {} - {}", marker, profile.name, profile.description);
+                if let Some(white) = profile.white_point {
+                    println!("    White point: x={:.4} y={:.4}", white.x, white.y);
+                }
+                if let Some(gamut) = profile.gamut {
+                    println!(
+                        "    Primaries:   R({:.4}, {:.4}) G({:.4}, {:.4}) B({:.4}, {:.4})",
+                        gamut.red.x,
+                        gamut.red.y,
+                        gamut.green.x,
+                        gamut.green.y,
+                        gamut.blue.x,
+                        gamut.blue.y
+                    );
+                }
+            }
+            println!();
+            println!("* default, + associated with this monitor");
+        }
+        Commands::SetColorProfile {
+            monitor_idx,
+            profile,
+        } => {
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            provider.set_monitor_color_profile(device_name, profile.clone())?;
+            println!(
+                "Successfully set color profile to {} for monitor {}",
+                profile, monitor_idx
+            );
+        }
         Commands::GetBrightness { monitor_idx } => {
             let monitors = provider.get_all_monitors()?;
             let device_name = get_device_name(&monitors, monitor_idx)?;
             let brightness = provider.get_monitor_brightness(device_name)?;
             println!(
-                "Monitor {} brightness: {}% (min: {}, max: {})",
-                monitor_idx, brightness.current, brightness.min, brightness.max
+                "Monitor {} brightness: {}% (min: {}, max: {}, via: {})",
+                monitor_idx, brightness.current, brightness.min, brightness.max, brightness.source
             );
         }
         Commands::SetScale {
@@ -289,6 +643,28 @@ fn handle_command(command: Commands) -> Result<(), String> {
                 percent, monitor_idx
             );
         }
+        Commands::SetCustomScale { percent, off } => {
+            if percent.is_none() && !off {
+                return Err("Pass --percent <value> or --off".to_string());
+            }
+            provider.set_custom_scale(percent)?;
+            match percent {
+                Some(p) => println!(
+                    "Custom scaling set to {}%. Sign out and back in to apply it.",
+                    p
+                ),
+                None => println!("Custom scaling turned off. Sign out and back in to apply it."),
+            }
+        }
+        Commands::SetScaling { monitor_idx, mode } => {
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            provider.set_monitor_scaling_mode(device_name, mode.clone())?;
+            println!(
+                "Successfully set scaling mode to {} for monitor {}",
+                mode, monitor_idx
+            );
+        }
         Commands::SetHdr {
             monitor_idx,
             enable,
@@ -299,6 +675,28 @@ fn handle_command(command: Commands) -> Result<(), String> {
             let status = if enable { "enabled" } else { "disabled" };
             println!("Successfully {} HDR for monitor {}", status, monitor_idx);
         }
+        Commands::SetVrr {
+            monitor_idx,
+            enable,
+        } => {
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            provider.set_monitor_vrr(device_name, enable)?;
+            let status = if enable { "enabled" } else { "disabled" };
+            println!(
+                "Successfully {} variable refresh rate for monitor {}",
+                status, monitor_idx
+            );
+        }
+        Commands::SetSdrWhite { monitor_idx, nits } => {
+            let monitors = provider.get_all_monitors()?;
+            let device_name = get_device_name(&monitors, monitor_idx)?;
+            provider.set_sdr_white_level(device_name, nits)?;
+            println!(
+                "Successfully set SDR white level to {} nits for monitor {}",
+                nits, monitor_idx
+            );
+        }
         Commands::GetCaps { monitor_idx } => {
             let monitors = provider.get_all_monitors()?;
             let device_name = get_device_name(&monitors, monitor_idx)?;
@@ -306,12 +704,169 @@ fn handle_command(command: Commands) -> Result<(), String> {
             println!("Monitor {} DDC/CI Capabilities:", monitor_idx);
             println!("{}", caps);
         }
+        Commands::Kvm { preset } => {
+            let presets: Vec<crate::kvm::KvmPreset> = match &remote {
+                Some(remote) => remote.call("list_kvm_presets", serde_json::json!({}))?,
+                None => local_settings()?.kvm_presets,
+            };
+            let Some(name) = preset else {
+                if presets.is_empty() {
+                    println!("No KVM presets.");
+                }
+                for preset in &presets {
+                    let inputs: Vec<String> = preset
+                        .targets
+                        .iter()
+                        .map(|t| format!("{} -> {}", t.monitor, t.input))
+                        .collect();
+                    println!("{}: {}", preset.name, inputs.join(", "));
+                }
+                return Ok(());
+            };
+            let results: Vec<crate::kvm::KvmResult> = match &remote {
+                Some(remote) => {
+                    remote.call("apply_kvm_preset", serde_json::json!({ "name": name }))?
+                }
+                None => {
+                    let preset = presets
+                        .iter()
+                        .find(|p| p.name == name)
+                        .ok_or_else(|| format!("KVM preset '{}' not found", name))?;
+                    crate::kvm::apply(preset, &local_settings()?.input_names)?
+                }
+            };
+            let mut failed = 0;
+            for result in &results {
+                match &result.error {
+                    None => println!("  {} -> {}", result.monitor, result.input),
+                    Some(e) => {
+                        failed += 1;
+                        println!("  {} -> {} failed: {}", result.monitor, result.input, e);
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(format!(
+                    "{} of {} monitor(s) didn't switch",
+                    failed,
+                    results.len()
+                ));
+            }
+            println!("Switched {} monitor(s) to '{}'", results.len(), name);
+        }
+        Commands::ExportConfig { path, format } => {
+            use crate::config_transfer::FileFormat;
+            let format = match format {
+                Some(f) => parse_arg::<FileFormat>(&f, "format", "json or toml")?,
+                None => FileFormat::from_path(&path),
+            };
+            if let Some(remote) = &remote {
+                remote.call::<()>(
+                    "export_config",
+                    serde_json::json!({ "path": absolute(&path)?, "format": format }),
+                )?;
+            } else {
+                let store_path = crate::settings::store_file_path()?;
+                let store = crate::settings::read_store_file(&store_path)?;
+                let text = crate::config_transfer::export_store(&store, format)?;
+                std::fs::write(&path, text)
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            }
+            println!("Exported configuration to {}", path.display());
+        }
+        Commands::ImportConfig { path, mode, format } => {
+            use crate::config_transfer::{FileFormat, ImportMode};
+            let mode = parse_arg::<ImportMode>(&mode, "mode", "merge or replace")?;
+            let format = match format {
+                Some(f) => parse_arg::<FileFormat>(&f, "format", "json or toml")?,
+                None => FileFormat::from_path(&path),
+            };
+            let (summary, target) = if let Some(remote) = &remote {
+                let summary: crate::config_transfer::ImportSummary = remote.call(
+                    "import_config",
+                    serde_json::json!({
+                        "path": absolute(&path)?,
+                        "mode": mode,
+                        "format": format,
+                    }),
+                )?;
+                (summary, "the running app".to_string())
+            } else {
+                // The app keeps settings.json in memory and would overwrite the import
+                if crate::ipc::app_running() {
+                    return Err(
+                        "WinDisplay is running but didn't answer; try again, or close it first"
+                            .to_string(),
+                    );
+                }
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                let store_path = crate::settings::store_file_path()?;
+                let mut store = crate::settings::read_store_file(&store_path)?;
+                let summary =
+                    crate::config_transfer::import_into_store(&mut store, &text, format, mode)?;
+                crate::settings::write_store_file(&store_path, &store)?;
+                (summary, store_path.display().to_string())
+            };
+            println!(
+                "Imported {}{} profile(s) and state for {} monitor(s) into {}",
+                if summary.settings { "settings, " } else { "" },
+                summary.profiles,
+                summary.monitor_states,
+                target
+            );
+            for line in &summary.remapped {
+                println!("  Matched by model: {}", line);
+            }
+        }
         Commands::Ui => unreachable!(),
     }
 
     Ok(())
 }
 
+// Parses a snake_case option value into one of the serde enums
+fn parse_arg<T: serde::de::DeserializeOwned>(
+    value: &str,
+    name: &str,
+    expected: &str,
+) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| format!("Unknown {} '{}'. Use {}", name, value, expected))
+}
+
+// Settings straight from settings.json, for when the app isn't running
+fn local_settings() -> Result<crate::settings::Settings, String> {
+    let store = crate::settings::read_store_file(&crate::settings::store_file_path()?)?;
+    Ok(crate::settings::from_value(
+        store
+            .get(crate::settings::SETTINGS_KEY)
+            .cloned()
+            .unwrap_or_default(),
+    ))
+}
+
+// Names given to the monitor's inputs in the app, keyed by input key
+fn local_input_names(
+    monitor: &crate::displays::DisplayInfo,
+) -> std::collections::HashMap<String, String> {
+    local_settings()
+        .ok()
+        .and_then(|mut settings| {
+            settings
+                .input_names
+                .remove(&crate::monitor_identity::identity_key(monitor))
+        })
+        .unwrap_or_default()
+}
+
+// The running app resolves relative paths against its own working directory
+fn absolute(path: &std::path::Path) -> Result<String, String> {
+    std::path::absolute(path)
+        .map(|p| p.display().to_string())
+        .map_err(|e| format!("Invalid path {}: {}", path.display(), e))
+}
+
 fn get_device_name(monitors: &[DisplayInfo], idx: usize) -> Result<String, String> {
     monitors
         .get(idx)
@@ -331,7 +886,7 @@ fn print_monitor_info(idx: usize, monitor: &DisplayInfo) {
         "  Resolution:   {}x{}@{}Hz ({}bpp)",
         monitor.current.width,
         monitor.current.height,
-        monitor.current.refresh_hz,
+        monitor.current.refresh_rate,
         monitor.current.bits_per_pixel
     );
     println!(
@@ -339,8 +894,11 @@ fn print_monitor_info(idx: usize, monitor: &DisplayInfo) {
         monitor.max_native.width, monitor.max_native.height, monitor.max_native.refresh_hz
     );
     println!("  Orientation:  {}°", monitor.orientation);
+    if let Some(mode) = &monitor.scaling_mode {
+        println!("  Scaling:      {}", mode);
+    }
     println!(
-        "  Scale:        {}% (might be incorrect)",
+        "  Scale:        {}%",
         (monitor.scale * 100.0).round() as u32
     );
 
@@ -350,11 +908,12 @@ fn print_monitor_info(idx: usize, monitor: &DisplayInfo) {
             .scales
             .iter()
             .map(|s| {
-                let percent = (s.scale * 100.0) as u32;
+                let percent = (s.scale * 100.0).round() as u32;
+                let marker = if s.is_current { "*" } else { "" };
                 if s.is_recommended {
-                    format!("{}% (recommended)", percent)
+                    format!("{}%{} (recommended)", percent, marker)
                 } else {
-                    format!("{}%", percent)
+                    format!("{}%{}", percent, marker)
                 }
             })
             .collect();
@@ -362,6 +921,37 @@ fn print_monitor_info(idx: usize, monitor: &DisplayInfo) {
     }
 
     println!("  HDR:          {}", monitor.hdr_status);
+    if let Some(color) = &monitor.advanced_color {
+        println!(
+            "  Color:        {}, {} bits per channel{}",
+            color.encoding,
+            color.bits_per_channel,
+            if color.force_disabled {
+                " (HDR blocked by Windows)"
+            } else {
+                ""
+            }
+        );
+        if let Some(nits) = color.sdr_white_level_nits {
+            println!("  SDR White:    {} nits", nits);
+        }
+    }
+    match &monitor.vrr {
+        Some(vrr) => println!(
+            "  VRR:          {} ({}-{}Hz)",
+            if vrr.enabled { "on" } else { "off" },
+            vrr.min_hz,
+            vrr.max_hz
+        ),
+        None => println!("  VRR:          unsupported"),
+    }
+    println!(
+        "  ICC Profile:  {}",
+        monitor
+            .color_profile
+            .as_deref()
+            .unwrap_or("(system default)")
+    );
     println!(
         "  Position:     ({}, {})",
         monitor.position_x, monitor.position_y
@@ -0,0 +1,183 @@
+// Pure Kelvin <-> RGB conversions for night-light style color temperature control.
+// Based on Tanner Helland's blackbody approximation, normalized so 6500K is neutral.
+
+pub const MIN_KELVIN: u32 = 1000;
+pub const MAX_KELVIN: u32 = 10000;
+pub const NEUTRAL_KELVIN: u32 = 6500;
+
+// MCCS VCP 0x0C reports color temperature relative to 3000K in steps of VCP 0x0B
+pub const VCP_COLOR_TEMP_BASE_KELVIN: u32 = 3000;
+
+/// Raw blackbody color for `kelvin`, each channel in 0..=1.
+pub fn kelvin_to_rgb(kelvin: u32) -> [f64; 3] {
+    let t = kelvin.clamp(MIN_KELVIN, 40000) as f64 / 100.0;
+
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.698727446 * (t - 60.0).powf(-0.1332047592)
+    };
+
+    let green = if t <= 66.0 {
+        99.4708025861 * t.ln() - 161.1195681661
+    } else {
+        288.1221695283 * (t - 60.0).powf(-0.0755148492)
+    };
+
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.5177312231 * (t - 10.0).ln() - 305.0447927307
+    };
+
+    [
+        (red / 255.0).clamp(0.0, 1.0),
+        (green / 255.0).clamp(0.0, 1.0),
+        (blue / 255.0).clamp(0.0, 1.0),
+    ]
+}
+
+/// Per-channel multipliers that shift a 6500K white point to `kelvin`.
+/// Returns [1, 1, 1] at 6500K; warmer values reduce green and blue.
+pub fn white_balance(kelvin: u32) -> [f64; 3] {
+    let kelvin = kelvin.clamp(MIN_KELVIN, MAX_KELVIN);
+    if kelvin == NEUTRAL_KELVIN {
+        return [1.0, 1.0, 1.0];
+    }
+    let target = kelvin_to_rgb(kelvin);
+    let neutral = kelvin_to_rgb(NEUTRAL_KELVIN);
+    let mut out: [f64; 3] = std::array::from_fn(|i| {
+        if neutral[i] > 0.0 {
+            target[i] / neutral[i]
+        } else {
+            1.0
+        }
+    });
+    // Keep the brightest channel at full scale so the picture is tinted, not dimmed
+    let max = out.iter().cloned().fold(0.0_f64, f64::max);
+    if max > 0.0 {
+        for v in out.iter_mut() {
+            *v = (*v / max).clamp(0.0, 1.0);
+        }
+    }
+    out
+}
+
+/// DDC/CI RGB video gains (VCP 0x16 / 0x18 / 0x1A) for `kelvin`, given each gain's maximum.
+pub fn kelvin_to_vcp_gains(kelvin: u32, max_gain: [u32; 3]) -> [u32; 3] {
+    let wb = white_balance(kelvin);
+    [
+        (wb[0] * max_gain[0] as f64).round() as u32,
+        (wb[1] * max_gain[1] as f64).round() as u32,
+        (wb[2] * max_gain[2] as f64).round() as u32,
+    ]
+}
+
+/// Value for VCP 0x0C (color temperature request) given the increment reported by VCP 0x0B.
+pub fn kelvin_to_vcp_color_temp(kelvin: u32, increment: u32) -> u32 {
+    let increment = increment.max(1);
+    let above_base = kelvin.saturating_sub(VCP_COLOR_TEMP_BASE_KELVIN);
+    (above_base + increment / 2) / increment
+}
+
+/// Inverse of `kelvin_to_vcp_color_temp`.
+pub fn vcp_color_temp_to_kelvin(value: u32, increment: u32) -> u32 {
+    VCP_COLOR_TEMP_BASE_KELVIN + value * increment.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_unit_range(rgb: [f64; 3]) -> bool {
+        rgb.iter().all(|c| (0.0..=1.0).contains(c))
+    }
+
+    #[test]
+    fn blackbody_range_ends() {
+        // Candle light: full red, no blue
+        let warm = kelvin_to_rgb(MIN_KELVIN);
+        assert_eq!(warm[0], 1.0);
+        assert_eq!(warm[2], 0.0);
+        assert!(warm[1] > 0.0 && warm[1] < 0.5);
+        // Blue sky: full blue, red below full
+        let cool = kelvin_to_rgb(MAX_KELVIN);
+        assert_eq!(cool[2], 1.0);
+        assert!(cool[0] < 1.0);
+        for kelvin in (0..=40000).step_by(250) {
+            assert!(in_unit_range(kelvin_to_rgb(kelvin)), "{}K", kelvin);
+        }
+        assert_eq!(kelvin_to_rgb(0), kelvin_to_rgb(MIN_KELVIN));
+    }
+
+    #[test]
+    fn neutral_is_untouched() {
+        assert_eq!(white_balance(NEUTRAL_KELVIN), [1.0, 1.0, 1.0]);
+        // Neighbours stay close to neutral, so there is no jump around 6500K
+        for kelvin in [6400, 6600] {
+            assert!(
+                white_balance(kelvin).iter().all(|c| *c > 0.95),
+                "{}K",
+                kelvin
+            );
+        }
+    }
+
+    #[test]
+    fn warmer_reduces_green_and_blue() {
+        let mut previous = white_balance(NEUTRAL_KELVIN);
+        for kelvin in (MIN_KELVIN..NEUTRAL_KELVIN).rev().step_by(500) {
+            let wb = white_balance(kelvin);
+            assert_eq!(wb[0], 1.0, "{}K", kelvin);
+            assert!(wb[2] <= wb[1], "{}K: {:?}", kelvin, wb);
+            assert!(wb[2] <= previous[2], "{}K", kelvin);
+            previous = wb;
+        }
+    }
+
+    #[test]
+    fn cooler_reduces_red() {
+        let wb = white_balance(MAX_KELVIN);
+        assert_eq!(wb[2], 1.0);
+        assert!(wb[0] < 1.0);
+        assert!(in_unit_range(wb));
+    }
+
+    #[test]
+    fn white_balance_clamps_to_supported_range() {
+        assert_eq!(white_balance(10), white_balance(MIN_KELVIN));
+        assert_eq!(white_balance(50000), white_balance(MAX_KELVIN));
+    }
+
+    #[test]
+    fn gains_scale_with_each_maximum() {
+        assert_eq!(
+            kelvin_to_vcp_gains(NEUTRAL_KELVIN, [100, 255, 50]),
+            [100, 255, 50]
+        );
+        let wb = white_balance(3000);
+        let gains = kelvin_to_vcp_gains(3000, [200, 200, 200]);
+        assert_eq!(gains[0], 200);
+        for i in 0..3 {
+            assert_eq!(gains[i], (wb[i] * 200.0).round() as u32);
+        }
+        assert!(gains[2] < gains[1] && gains[1] < gains[0]);
+        assert_eq!(kelvin_to_vcp_gains(3000, [0, 0, 0]), [0, 0, 0]);
+    }
+
+    #[test]
+    fn vcp_color_temp_round_trip() {
+        assert_eq!(kelvin_to_vcp_color_temp(6500, 50), 70);
+        assert_eq!(vcp_color_temp_to_kelvin(70, 50), 6500);
+        // Rounds to the nearest increment
+        assert_eq!(kelvin_to_vcp_color_temp(6520, 50), 70);
+        assert_eq!(kelvin_to_vcp_color_temp(6530, 50), 71);
+        // Below the 3000K base there is nothing lower to ask for
+        assert_eq!(kelvin_to_vcp_color_temp(2000, 50), 0);
+        // A zero increment from a misbehaving monitor counts as 1K
+        assert_eq!(kelvin_to_vcp_color_temp(3100, 0), 100);
+        assert_eq!(vcp_color_temp_to_kelvin(100, 0), 3100);
+    }
+}
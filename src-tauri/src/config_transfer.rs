@@ -0,0 +1,746 @@
+// Portable export/import of the whole configuration: settings (including the brightness
+// shortcut, brightness schedule and Auto-HDR rules), display profiles and remembered monitor
+// state. Files are versioned JSON or TOML; monitors are matched by EDID identity.
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter};
+
+use crate::displays::DisplayInfo;
+use crate::profiles::DisplayProfile;
+use crate::settings::{MONITOR_STATE_KEY, PROFILES_KEY, SETTINGS_KEY};
+use crate::state_persistence::MonitorState;
+
+/// Value of the "format" field, so a random JSON file isn't imported by mistake.
+pub const FORMAT: &str = "windisplay-config";
+/// Bump when the file layout changes; settings carry their own schema version.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileFormat {
+    Json,
+    Toml,
+}
+
+impl FileFormat {
+    /// TOML for ".toml" files, JSON for everything else.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => FileFormat::Toml,
+            _ => FileFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    // Imported entries win, everything else is kept
+    #[default]
+    Merge,
+    // Each section in the file replaces the local one; sections missing from the file are kept
+    Replace,
+}
+
+/// Identity of a monitor plus what's needed to recognise the same model elsewhere.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorIdentity {
+    pub identity: String,
+    #[serde(default)]
+    pub manufacturer: String,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub serial: String,
+    #[serde(default)]
+    pub name: String,
+}
+
+impl From<&DisplayInfo> for MonitorIdentity {
+    fn from(info: &DisplayInfo) -> Self {
+        Self {
+            identity: crate::monitor_identity::identity_key(info),
+            manufacturer: info.manufacturer.trim().to_string(),
+            model: info.model.trim().to_string(),
+            serial: info.serial.trim().to_string(),
+            name: info.friendly_name.clone(),
+        }
+    }
+}
+
+/// The exported file. Every section is optional so hand-written partial files can be imported.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBundle {
+    pub format: String,
+    pub format_version: u32,
+    #[serde(default)]
+    pub app_version: String,
+    #[serde(default)]
+    pub exported_at: String,
+    // Monitors connected at export time
+    #[serde(default)]
+    pub monitors: Vec<MonitorIdentity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<Vec<DisplayProfile>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor_state: Option<HashMap<String, MonitorState>>,
+}
+
+/// What an import changed, for the CLI and the UI to report.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub settings: bool,
+    pub profiles: usize,
+    pub monitor_states: usize,
+    // "old identity -> new identity (monitor name)" for monitors matched by model
+    pub remapped: Vec<String>,
+}
+
+/// Reads the configuration sections out of settings.json contents.
+pub fn from_store(store: &Map<String, Value>) -> ConfigBundle {
+    let mut settings = match store.get(SETTINGS_KEY) {
+        Some(Value::Object(map)) => map.clone(),
+        _ => Map::new(),
+    };
+    crate::settings::migrate(&mut settings);
+    let section = |key: &str| store.get(key).cloned().unwrap_or(Value::Null);
+    ConfigBundle {
+        format: FORMAT.to_string(),
+        format_version: FORMAT_VERSION,
+        settings: Some(settings),
+        profiles: Some(serde_json::from_value(section(PROFILES_KEY)).unwrap_or_default()),
+        monitor_state: Some(serde_json::from_value(section(MONITOR_STATE_KEY)).unwrap_or_default()),
+        ..Default::default()
+    }
+}
+
+/// Writes the sections present in `bundle` back into settings.json contents.
+pub fn into_store(bundle: &ConfigBundle, store: &mut Map<String, Value>) -> Result<(), String> {
+    if let Some(settings) = &bundle.settings {
+        store.insert(SETTINGS_KEY.to_string(), Value::Object(settings.clone()));
+    }
+    if let Some(profiles) = &bundle.profiles {
+        let value = serde_json::to_value(profiles).map_err(|e| e.to_string())?;
+        store.insert(PROFILES_KEY.to_string(), value);
+    }
+    if let Some(states) = &bundle.monitor_state {
+        let value = serde_json::to_value(states).map_err(|e| e.to_string())?;
+        store.insert(MONITOR_STATE_KEY.to_string(), value);
+    }
+    Ok(())
+}
+
+// Schedule steps may name a monitor by GDI device name, which means nothing on another machine
+fn schedule_steps_mut(settings: &mut Map<String, Value>) -> Vec<&mut Value> {
+    match settings
+        .get_mut("brightnessSchedule")
+        .and_then(|s| s.get_mut("steps"))
+    {
+        Some(Value::Array(steps)) => steps
+            .iter_mut()
+            .filter_map(|step| step.get_mut("monitor"))
+            .filter(|monitor| monitor.is_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// KVM preset targets name monitors the same way
+fn kvm_targets_mut(settings: &mut Map<String, Value>) -> Vec<&mut Value> {
+    match settings.get_mut("kvmPresets") {
+        Some(Value::Array(presets)) => presets
+            .iter_mut()
+            .filter_map(|preset| preset.get_mut("targets"))
+            .filter_map(Value::as_array_mut)
+            .flat_map(|targets| targets.iter_mut())
+            .filter_map(|target| target.get_mut("monitor"))
+            .filter(|monitor| monitor.is_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Stamps an export with the connected monitors, swaps device names for identities and drops
+/// secrets.
+pub fn prepare_export(bundle: &mut ConfigBundle, connected: &[(String, MonitorIdentity)]) {
+    bundle.monitors = connected.iter().map(|(_, m)| m.clone()).collect();
+    if let Some(settings) = &mut bundle.settings {
+        // A merge import keeps the password already set on the target machine
+        if let Some(Value::Object(mqtt)) = settings.get_mut("mqtt") {
+            mqtt.remove("password");
+        }
+        for monitor in schedule_steps_mut(settings) {
+            if let Some((_, m)) = connected.iter().find(|(device, _)| monitor == device) {
+                *monitor = Value::String(m.identity.clone());
+            }
+        }
+        for monitor in kvm_targets_mut(settings) {
+            if let Some((_, m)) = connected.iter().find(|(device, _)| monitor == device) {
+                *monitor = Value::String(m.identity.clone());
+            }
+        }
+    }
+}
+
+fn same_model(a: &MonitorIdentity, b: &MonitorIdentity) -> bool {
+    (!a.manufacturer.is_empty() || !a.model.is_empty())
+        && a.manufacturer.eq_ignore_ascii_case(&b.manufacturer)
+        && a.model.eq_ignore_ascii_case(&b.model)
+}
+
+/// Maps identities from the exporting machine to monitors connected here. An identical identity
+/// wins; otherwise a monitor of the same manufacturer and model that isn't already taken.
+pub fn match_monitors(
+    exported: &[MonitorIdentity],
+    local: &[MonitorIdentity],
+) -> Vec<(MonitorIdentity, MonitorIdentity)> {
+    let mut taken: Vec<bool> = local
+        .iter()
+        .map(|l| exported.iter().any(|e| e.identity == l.identity))
+        .collect();
+    let mut pairs = Vec::new();
+    for e in exported {
+        if local.iter().any(|l| l.identity == e.identity) {
+            continue;
+        }
+        if let Some(i) = (0..local.len()).find(|&i| !taken[i] && same_model(e, &local[i])) {
+            taken[i] = true;
+            pairs.push((e.clone(), local[i].clone()));
+        }
+    }
+    pairs
+}
+
+fn rename_key<T>(map: &mut HashMap<String, T>, from: &str, to: &str) {
+    if let Some(value) = map.remove(from) {
+        map.insert(to.to_string(), value);
+    }
+}
+
+/// Rewrites every identity-keyed entry of `bundle` for the monitors connected here.
+pub fn remap_identities(bundle: &mut ConfigBundle, local: &[MonitorIdentity]) -> Vec<String> {
+    let pairs = match_monitors(&bundle.monitors, local);
+    for (from, to) in &pairs {
+        if let Some(profiles) = &mut bundle.profiles {
+            for setup in profiles.iter_mut().flat_map(|p| p.monitors.iter_mut()) {
+                if setup.identity == from.identity {
+                    setup.identity = to.identity.clone();
+                }
+            }
+        }
+        if let Some(states) = &mut bundle.monitor_state {
+            rename_key(states, &from.identity, &to.identity);
+        }
+        if let Some(settings) = &mut bundle.settings {
+            for key in ["brightnessCalibration", "inputNames"] {
+                if let Some(Value::Object(per_monitor)) = settings.get_mut(key) {
+                    if let Some(value) = per_monitor.remove(&from.identity) {
+                        per_monitor.insert(to.identity.clone(), value);
+                    }
+                }
+            }
+            for monitor in schedule_steps_mut(settings) {
+                if monitor.as_str() == Some(from.identity.as_str()) {
+                    *monitor = Value::String(to.identity.clone());
+                }
+            }
+            for monitor in kvm_targets_mut(settings) {
+                if monitor.as_str() == Some(from.identity.as_str()) {
+                    *monitor = Value::String(to.identity.clone());
+                }
+            }
+        }
+    }
+    pairs
+        .iter()
+        .map(|(from, to)| format!("{} -> {} ({})", from.identity, to.identity, to.name))
+        .collect()
+}
+
+// Objects are merged key by key; anything else is replaced
+fn merge_value(into: &mut Value, from: Value) {
+    match (into, from) {
+        (Value::Object(into), Value::Object(from)) => {
+            for (key, value) in from {
+                match into.get_mut(&key) {
+                    Some(existing) => merge_value(existing, value),
+                    None => {
+                        into.insert(key, value);
+                    }
+                }
+            }
+        }
+        (into, from) => *into = from,
+    }
+}
+
+/// Combines `incoming` into `current` and reports what it brought in.
+pub fn combine(
+    current: &mut ConfigBundle,
+    incoming: ConfigBundle,
+    mode: ImportMode,
+) -> ImportSummary {
+    let summary = ImportSummary {
+        settings: incoming.settings.is_some(),
+        profiles: incoming.profiles.as_ref().map_or(0, Vec::len),
+        monitor_states: incoming.monitor_state.as_ref().map_or(0, HashMap::len),
+        remapped: Vec::new(),
+    };
+    if mode == ImportMode::Replace {
+        current.settings = incoming.settings.or(current.settings.take());
+        current.profiles = incoming.profiles.or(current.profiles.take());
+        current.monitor_state = incoming.monitor_state.or(current.monitor_state.take());
+        return summary;
+    }
+
+    if let Some(settings) = incoming.settings {
+        let mut merged = Value::Object(current.settings.take().unwrap_or_default());
+        merge_value(&mut merged, Value::Object(settings));
+        if let Value::Object(map) = merged {
+            current.settings = Some(map);
+        }
+    }
+    if let Some(profiles) = incoming.profiles {
+        let existing = current.profiles.get_or_insert_with(Vec::new);
+        for profile in profiles {
+            match existing.iter_mut().find(|p| p.name == profile.name) {
+                Some(slot) => *slot = profile,
+                None => existing.push(profile),
+            }
+        }
+    }
+    if let Some(states) = incoming.monitor_state {
+        current
+            .monitor_state
+            .get_or_insert_with(HashMap::new)
+            .extend(states);
+    }
+    summary
+}
+
+// TOML has no null; a missing key reads back as the same default
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+pub fn serialize(bundle: &ConfigBundle, format: FileFormat) -> Result<String, String> {
+    let mut value = serde_json::to_value(bundle).map_err(|e| e.to_string())?;
+    match format {
+        FileFormat::Json => serde_json::to_string_pretty(&value).map_err(|e| e.to_string()),
+        FileFormat::Toml => {
+            strip_nulls(&mut value);
+            toml::to_string_pretty(&value).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Parses an exported file, rejecting other files and newer layouts, and migrates its settings.
+pub fn parse(text: &str, format: FileFormat) -> Result<ConfigBundle, String> {
+    let mut bundle: ConfigBundle = match format {
+        FileFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string())?,
+        FileFormat::Toml => toml::from_str(text).map_err(|e| e.to_string())?,
+    };
+    if bundle.format != FORMAT {
+        return Err(format!(
+            "Not a WinDisplay configuration file (format '{}')",
+            bundle.format
+        ));
+    }
+    if bundle.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "The file was exported by a newer WinDisplay (format {}, this version reads up to {})",
+            bundle.format_version, FORMAT_VERSION
+        ));
+    }
+    if let Some(settings) = &mut bundle.settings {
+        crate::settings::migrate(settings);
+    }
+    Ok(bundle)
+}
+
+fn connected_monitors() -> Vec<(String, MonitorIdentity)> {
+    match crate::displays::active_provider().get_all_monitors() {
+        Ok(monitors) => {
+            crate::monitor_identity::remember(&monitors);
+            monitors
+                .iter()
+                .map(|m| (m.device_name.clone(), MonitorIdentity::from(m)))
+                .collect()
+        }
+        Err(e) => {
+            log::warn!("Config transfer: failed to list monitors: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Builds the export file text from settings.json contents.
+pub fn export_store(store: &Map<String, Value>, format: FileFormat) -> Result<String, String> {
+    let mut bundle = from_store(store);
+    bundle.app_version = env!("CARGO_PKG_VERSION").to_string();
+    bundle.exported_at = chrono::Local::now().to_rfc3339();
+    prepare_export(&mut bundle, &connected_monitors());
+    serialize(&bundle, format)
+}
+
+/// Imports the file text into settings.json contents.
+pub fn import_into_store(
+    store: &mut Map<String, Value>,
+    text: &str,
+    format: FileFormat,
+    mode: ImportMode,
+) -> Result<ImportSummary, String> {
+    let mut incoming = parse(text, format)?;
+    let local: Vec<MonitorIdentity> = connected_monitors().into_iter().map(|(_, m)| m).collect();
+    let remapped = remap_identities(&mut incoming, &local);
+    let mut current = from_store(store);
+    let mut summary = combine(&mut current, incoming, mode);
+    summary.remapped = remapped;
+    // Same checks as an edit made in the app: bad values are fixed, unknown keys kept
+    if let Some(settings) = current.settings.take() {
+        let settings = crate::settings::from_value(Value::Object(settings));
+        if let Value::Object(map) = serde_json::to_value(settings).map_err(|e| e.to_string())? {
+            current.settings = Some(map);
+        }
+    }
+    into_store(&current, store)?;
+    Ok(summary)
+}
+
+fn format_for(path: &Path, format: Option<FileFormat>) -> FileFormat {
+    format.unwrap_or_else(|| FileFormat::from_path(path))
+}
+
+/// Writes the configuration to `path`; the format follows the extension unless given.
+#[tauri::command]
+pub fn export_config(
+    app_handle: AppHandle,
+    path: String,
+    format: Option<FileFormat>,
+) -> Result<(), String> {
+    let path = Path::new(&path);
+    let text = export_store(
+        &crate::settings::store_snapshot(&app_handle)?,
+        format_for(path, format),
+    )?;
+    std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[tauri::command]
+pub fn import_config(
+    app_handle: AppHandle,
+    path: String,
+    mode: ImportMode,
+    format: Option<FileFormat>,
+) -> Result<ImportSummary, String> {
+    let path = Path::new(&path);
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut store = crate::settings::store_snapshot(&app_handle)?;
+    let summary = import_into_store(&mut store, &text, format_for(path, format), mode)?;
+    crate::settings::restore_snapshot(&app_handle, store)?;
+    let _ = app_handle.emit("config-imported", &summary);
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::MonitorSetup;
+    use serde_json::json;
+
+    fn monitor(identity: &str, manufacturer: &str, model: &str) -> MonitorIdentity {
+        MonitorIdentity {
+            identity: identity.to_string(),
+            manufacturer: manufacturer.to_string(),
+            model: model.to_string(),
+            serial: String::new(),
+            name: format!("{} {}", manufacturer, model),
+        }
+    }
+
+    fn profile(name: &str, identities: &[&str]) -> DisplayProfile {
+        DisplayProfile {
+            name: name.to_string(),
+            monitors: identities
+                .iter()
+                .map(|identity| MonitorSetup {
+                    identity: identity.to_string(),
+                    width: 2560,
+                    height: 1440,
+                    refresh_hz: 60,
+                    orientation: 0,
+                    scale_percent: 100,
+                    color_profile: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn state(brightness: u32) -> MonitorState {
+        MonitorState {
+            brightness: Some(brightness),
+            ..Default::default()
+        }
+    }
+
+    fn settings(value: Value) -> Option<Map<String, Value>> {
+        match value {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn matches_monitors_by_model_once() {
+        let exported = [
+            monitor("DEL-A1", "DEL", "U2720Q"),
+            monitor("DEL-A2", "DEL", "U2720Q"),
+            monitor("GSM-B1", "GSM", "27GL850"),
+            monitor("SAM-C1", "SAM", "Odyssey"),
+        ];
+        let local = [
+            // Present on both machines, so it keeps its entries and isn't handed out again
+            monitor("GSM-B1", "GSM", "27GL850"),
+            monitor("DEL-X1", "del", "u2720q"),
+            monitor("GSM-X2", "GSM", "27GL850"),
+            monitor("DEL-X3", "DEL", "U2720Q"),
+        ];
+        let pairs = match_monitors(&exported, &local);
+        let pairs: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|(from, to)| (from.identity.as_str(), to.identity.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("DEL-A1", "DEL-X1"), ("DEL-A2", "DEL-X3")]);
+    }
+
+    #[test]
+    fn monitors_without_a_model_are_not_matched() {
+        let exported = [monitor("GENERIC-1", "", "")];
+        let local = [monitor("GENERIC-2", "", "")];
+        assert!(match_monitors(&exported, &local).is_empty());
+    }
+
+    #[test]
+    fn remaps_every_identity_keyed_entry() {
+        let mut bundle = ConfigBundle {
+            monitors: vec![
+                monitor("DEL-A1", "DEL", "U2720Q"),
+                monitor("GSM-B1", "GSM", "27GL850"),
+            ],
+            settings: settings(json!({
+                "brightnessCalibration": { "DEL-A1": { "points": [[50, 40]] } },
+                "inputNames": { "DEL-A1": { "hdmi1": "Laptop" }, "GSM-B1": { "dp1": "PC" } },
+                "brightnessSchedule": {
+                    "steps": [
+                        { "at": "07:00", "brightness": 80, "monitor": "DEL-A1" },
+                        { "at": "22:00", "brightness": 20 },
+                    ],
+                },
+                "kvmPresets": [{
+                    "name": "Laptop",
+                    "targets": [
+                        { "monitor": "DEL-A1", "input": "hdmi1" },
+                        { "monitor": "GSM-B1", "input": "dp1" },
+                    ],
+                }],
+            })),
+            profiles: Some(vec![profile("Desk", &["DEL-A1", "GSM-B1"])]),
+            monitor_state: Some(HashMap::from([
+                ("DEL-A1".to_string(), state(70)),
+                ("GSM-B1".to_string(), state(30)),
+            ])),
+            ..Default::default()
+        };
+        let local = [
+            monitor("DEL-X1", "DEL", "U2720Q"),
+            monitor("GSM-B1", "GSM", "27GL850"),
+        ];
+
+        let remapped = remap_identities(&mut bundle, &local);
+        assert_eq!(remapped, vec!["DEL-A1 -> DEL-X1 (DEL U2720Q)"]);
+
+        let identities: Vec<&str> = bundle.profiles.as_ref().unwrap()[0]
+            .monitors
+            .iter()
+            .map(|m| m.identity.as_str())
+            .collect();
+        assert_eq!(identities, vec!["DEL-X1", "GSM-B1"]);
+        let states = bundle.monitor_state.as_ref().unwrap();
+        assert_eq!(states["DEL-X1"], state(70));
+        assert_eq!(states["GSM-B1"], state(30));
+        assert!(!states.contains_key("DEL-A1"));
+
+        let settings = Value::Object(bundle.settings.unwrap());
+        assert_eq!(
+            settings["brightnessCalibration"],
+            json!({ "DEL-X1": { "points": [[50, 40]] } })
+        );
+        assert_eq!(
+            settings["inputNames"],
+            json!({ "DEL-X1": { "hdmi1": "Laptop" }, "GSM-B1": { "dp1": "PC" } })
+        );
+        let steps = &settings["brightnessSchedule"]["steps"];
+        assert_eq!(steps[0]["monitor"], "DEL-X1");
+        assert!(steps[1].get("monitor").is_none());
+        let targets = &settings["kvmPresets"][0]["targets"];
+        assert_eq!(targets[0]["monitor"], "DEL-X1");
+        assert_eq!(targets[1]["monitor"], "GSM-B1");
+    }
+
+    #[test]
+    fn merge_keeps_what_the_file_leaves_out() {
+        let mut current = ConfigBundle {
+            settings: settings(json!({
+                "theme": "dark",
+                "mqtt": { "host": "broker.lan", "password": "secret" },
+            })),
+            profiles: Some(vec![profile("Desk", &["DEL-A1"]), profile("Couch", &[])]),
+            monitor_state: Some(HashMap::from([
+                ("DEL-A1".to_string(), state(70)),
+                ("GSM-B1".to_string(), state(30)),
+            ])),
+            ..Default::default()
+        };
+        let incoming = ConfigBundle {
+            settings: settings(json!({
+                "linkBrightness": true,
+                "mqtt": { "host": "mqtt.home", "port": 1884 },
+            })),
+            profiles: Some(vec![profile("Desk", &["GSM-B1"]), profile("Travel", &[])]),
+            monitor_state: Some(HashMap::from([("DEL-A1".to_string(), state(90))])),
+            ..Default::default()
+        };
+
+        let summary = combine(&mut current, incoming, ImportMode::Merge);
+        assert!(summary.settings);
+        assert_eq!((summary.profiles, summary.monitor_states), (2, 1));
+
+        assert_eq!(
+            Value::Object(current.settings.unwrap()),
+            json!({
+                "theme": "dark",
+                "linkBrightness": true,
+                "mqtt": { "host": "mqtt.home", "port": 1884, "password": "secret" },
+            })
+        );
+        // Profiles with the same name are replaced in place, new ones appended
+        let profiles = current.profiles.unwrap();
+        let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Desk", "Couch", "Travel"]);
+        assert_eq!(profiles[0].monitors[0].identity, "GSM-B1");
+        let states = current.monitor_state.unwrap();
+        assert_eq!(states["DEL-A1"], state(90));
+        assert_eq!(states["GSM-B1"], state(30));
+    }
+
+    #[test]
+    fn replace_swaps_only_the_sections_in_the_file() {
+        let mut current = ConfigBundle {
+            settings: settings(json!({ "theme": "dark", "linkBrightness": true })),
+            profiles: Some(vec![profile("Desk", &[]), profile("Couch", &[])]),
+            monitor_state: Some(HashMap::from([("DEL-A1".to_string(), state(70))])),
+            ..Default::default()
+        };
+        let incoming = ConfigBundle {
+            settings: settings(json!({ "theme": "light" })),
+            profiles: Some(vec![profile("Travel", &[])]),
+            ..Default::default()
+        };
+
+        let summary = combine(&mut current, incoming, ImportMode::Replace);
+        assert_eq!((summary.profiles, summary.monitor_states), (1, 0));
+        assert_eq!(
+            Value::Object(current.settings.unwrap()),
+            json!({ "theme": "light" })
+        );
+        let profiles = current.profiles.unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].name, "Travel");
+        assert_eq!(current.monitor_state.unwrap()["DEL-A1"], state(70));
+    }
+
+    #[test]
+    fn export_drops_secrets_and_device_names() {
+        let mut bundle = from_store(
+            &settings(json!({
+                "settings": {
+                    "mqtt": { "host": "broker.lan", "password": "secret" },
+                    "brightnessSchedule": {
+                        "steps": [{ "at": "07:00", "brightness": 80, "monitor": r"\\.\DISPLAY2" }],
+                    },
+                    "kvmPresets": [{
+                        "name": "Laptop",
+                        "targets": [{ "monitor": r"\\.\DISPLAY2", "input": "hdmi1" }],
+                    }],
+                },
+            }))
+            .unwrap(),
+        );
+        let connected = [(
+            r"\\.\DISPLAY2".to_string(),
+            monitor("DEL-A1", "DEL", "U2720Q"),
+        )];
+        prepare_export(&mut bundle, &connected);
+
+        assert_eq!(bundle.monitors, vec![connected[0].1.clone()]);
+        let settings = Value::Object(bundle.settings.clone().unwrap());
+        assert_eq!(
+            settings["version"],
+            json!(crate::settings::SETTINGS_VERSION)
+        );
+        assert!(settings["mqtt"].get("password").is_none());
+        assert_eq!(
+            settings["brightnessSchedule"]["steps"][0]["monitor"],
+            "DEL-A1"
+        );
+        assert_eq!(settings["kvmPresets"][0]["targets"][0]["monitor"], "DEL-A1");
+
+        for format in [FileFormat::Json, FileFormat::Toml] {
+            let text = serialize(&bundle, format).unwrap();
+            let parsed = parse(&text, format).unwrap();
+            assert_eq!(parsed.monitors, bundle.monitors);
+            assert_eq!(parsed.settings, bundle.settings);
+        }
+    }
+
+    #[test]
+    fn rejects_other_files_and_newer_formats() {
+        let err = parse(
+            r#"{ "format": "other", "formatVersion": 1 }"#,
+            FileFormat::Json,
+        )
+        .unwrap_err();
+        assert!(err.contains("Not a WinDisplay"), "{}", err);
+        let newer = format!(
+            "format = \"{}\"\nformatVersion = {}\n",
+            FORMAT,
+            FORMAT_VERSION + 1
+        );
+        let err = parse(&newer, FileFormat::Toml).unwrap_err();
+        assert!(err.contains("newer WinDisplay"), "{}", err);
+        // A partial hand-written file only carries the sections it names
+        let partial = format!(
+            "format = \"{}\"\nformatVersion = 1\n[settings]\ntheme = \"dark\"\n",
+            FORMAT
+        );
+        let bundle = parse(&partial, FileFormat::Toml).unwrap();
+        assert!(bundle.profiles.is_none() && bundle.monitor_state.is_none());
+        assert_eq!(bundle.settings.unwrap()["theme"], "dark");
+    }
+}
@@ -4,11 +4,15 @@ use std::sync::Mutex;
 use tauri::{AppHandle, Emitter, Manager};
 #[cfg(target_os = "windows")]
 use windows::Win32::{
-    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    Devices::Usb::GUID_DEVINTERFACE_USB_DEVICE,
+    Foundation::{HANDLE, HWND, LPARAM, LRESULT, WPARAM},
     System::LibraryLoader::GetModuleHandleW,
     UI::WindowsAndMessaging::{
-        CreateWindowExW, DefWindowProcW, RegisterClassW, HMENU, WINDOW_EX_STYLE, WM_DISPLAYCHANGE,
-        WNDCLASSW, WS_OVERLAPPEDWINDOW,
+        CreateWindowExW, DefWindowProcW, RegisterClassW, RegisterDeviceNotificationW,
+        DBT_DEVICEARRIVAL, DBT_DEVICEREMOVECOMPLETE, DBT_DEVTYP_DEVICEINTERFACE,
+        DEVICE_NOTIFY_WINDOW_HANDLE, DEV_BROADCAST_DEVICEINTERFACE_W, DEV_BROADCAST_HDR, HMENU,
+        WINDOW_EX_STYLE, WM_DEVICECHANGE, WM_DISPLAYCHANGE, WM_SETTINGCHANGE, WNDCLASSW,
+        WS_OVERLAPPEDWINDOW,
     },
 };
 
@@ -25,11 +29,12 @@ unsafe extern "system" fn window_proc(
     match msg {
         WM_DISPLAYCHANGE => {
             // Display configuration changed
+            crate::monitor_identity::invalidate();
             if let Ok(guard) = APP_HANDLE.lock() {
                 if let Some(app) = guard.as_ref() {
                     let _ = app.emit("display-changed", ());
                     // Optionally reveal UI if user enabled it in settings
-                    if crate::settings::should_show_ui_on_monitor_change_handle(app) {
+                    if crate::settings::get(app).show_ui_on_monitor_change {
                         if let Some(window) = app.get_webview_window("main") {
                             let _ = window.unminimize();
                             let _ = window.show();
@@ -40,6 +45,42 @@ unsafe extern "system" fn window_proc(
             }
             LRESULT(0)
         }
+        WM_DEVICECHANGE => {
+            // USB devices come and go; KVM presets can be tied to one (e.g. a USB switch)
+            let event = wparam.0 as u32;
+            let header = lparam.0 as *const DEV_BROADCAST_HDR;
+            if (event == DBT_DEVICEARRIVAL || event == DBT_DEVICEREMOVECOMPLETE)
+                && !header.is_null()
+                && (*header).dbch_devicetype == DBT_DEVTYP_DEVICEINTERFACE
+            {
+                let interface = header as *const DEV_BROADCAST_DEVICEINTERFACE_W;
+                let name = std::ptr::addr_of!((*interface).dbcc_name) as *const u16;
+                let path = windows::core::PCWSTR(name).to_string().unwrap_or_default();
+                if let Ok(guard) = APP_HANDLE.lock() {
+                    if let Some(app) = guard.as_ref() {
+                        crate::kvm::usb_device_changed(app, &path, event == DBT_DEVICEARRIVAL);
+                    }
+                }
+            }
+            LRESULT(1)
+        }
+        WM_SETTINGCHANGE => {
+            // "ImmersiveColorSet" comes with a switch between light and dark mode, which changes
+            // the taskbar color the tray icon is drawn for
+            let area = lparam.0 as *const u16;
+            if !area.is_null()
+                && windows::core::PCWSTR(area)
+                    .to_string()
+                    .is_ok_and(|area| area == "ImmersiveColorSet")
+            {
+                if let Ok(guard) = APP_HANDLE.lock() {
+                    if let Some(app) = guard.as_ref() {
+                        crate::tray_icon::theme_changed(app);
+                    }
+                }
+            }
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
         _ => DefWindowProcW(hwnd, msg, wparam, lparam),
     }
 }
@@ -91,6 +132,21 @@ pub fn start_display_monitor(app_handle: AppHandle) -> Result<(), String> {
                 return;
             }
 
+            // Ask for USB device arrivals and removals as well
+            let filter = DEV_BROADCAST_DEVICEINTERFACE_W {
+                dbcc_size: std::mem::size_of::<DEV_BROADCAST_DEVICEINTERFACE_W>() as u32,
+                dbcc_devicetype: DBT_DEVTYP_DEVICEINTERFACE.0,
+                dbcc_classguid: GUID_DEVINTERFACE_USB_DEVICE,
+                ..Default::default()
+            };
+            if let Err(e) = RegisterDeviceNotificationW(
+                HANDLE(hwnd.0),
+                &filter as *const _ as *const core::ffi::c_void,
+                DEVICE_NOTIFY_WINDOW_HANDLE,
+            ) {
+                log::warn!("Failed to register for USB device notifications: {}", e);
+            }
+
             // Message loop
             let mut msg = windows::Win32::UI::WindowsAndMessaging::MSG::default();
             while windows::Win32::UI::WindowsAndMessaging::GetMessageW(
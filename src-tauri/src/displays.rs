@@ -1,20 +1,69 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Resolution {
     pub width: u32,
     pub height: u32,
     pub bits_per_pixel: u32,
     pub refresh_hz: u32,
+    // Actual refresh rate, e.g. 59.94 for a mode Windows lists as 59 Hz
+    pub refresh_rate: f64,
+    pub interlaced: bool,
+    // Produced by GPU/monitor scaling (stretched or centered) rather than native timing
+    pub scaled: bool,
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// Scaling modes that can be set; a driver-defined "custom" mode may also be reported.
+pub const SCALING_MODES: [&str; 5] = [
+    "identity",
+    "centered",
+    "stretched",
+    "aspect_ratio",
+    "preferred",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ScaleInfo {
     pub scale: f32,
     pub is_recommended: bool,
+    pub is_current: bool,
+}
+
+/// DisplayConfig address of a monitor: adapter LUID plus the target id on that adapter.
+/// Unlike enumeration order, it stays the same across the different Windows display APIs.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DisplayTarget {
+    pub adapter_luid: u64,
+    pub target_id: u32,
+}
+
+// Range of the "SDR content brightness" slider in Windows HDR settings
+pub const MIN_SDR_WHITE_NITS: u32 = 80;
+pub const MAX_SDR_WHITE_NITS: u32 = 480;
+
+/// Advanced color (HDR) details reported by DisplayConfig for the active signal.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdvancedColorInfo {
+    // Signal encoding: "rgb", "ycbcr444", "ycbcr422", "ycbcr420", "intensity" or "unknown"
+    pub encoding: String,
+    pub bits_per_channel: u32,
+    pub wide_color_enforced: bool,
+    // HDR is supported but blocked by the driver or a policy (e.g. on battery)
+    pub force_disabled: bool,
+    // SDR content brightness in nits (80-480), as set in Windows HDR settings
+    pub sdr_white_level_nits: Option<u32>,
+}
+
+/// Variable refresh rate (G-Sync/FreeSync/Adaptive-Sync) range advertised in the EDID.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VrrInfo {
+    pub min_hz: u32,
+    pub max_hz: u32,
+    // Whether adaptive sync is currently on for this monitor
+    pub enabled: bool,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DisplayInfo {
     pub device_name: String,
     pub friendly_name: String,
@@ -41,16 +90,55 @@ pub struct DisplayInfo {
     pub scales: Vec<ScaleInfo>,
     // HDR status: "unsupported", "on", "off"
     pub hdr_status: String,
+    // Encoding, bit depth and SDR white level; None when DisplayConfig doesn't report them
+    pub advanced_color: Option<AdvancedColorInfo>,
     // Whether DDC/CI input switch (VCP 0x60) appears supported for this monitor
     #[serde(skip_serializing_if = "Option::is_none")]
     pub supports_input_switch: Option<bool>,
+    // File name of the default ICC/ICM profile, None when Windows uses its built-in sRGB
+    pub color_profile: Option<String>,
+    // DisplayConfig target driving this monitor; HDR and other per-target settings use it
+    pub display_target: Option<DisplayTarget>,
+    // How a resolution below native is fitted to the panel, one of SCALING_MODES or "custom"
+    pub scaling_mode: Option<String>,
+    // None when the monitor doesn't support variable refresh rate
+    pub vrr: Option<VrrInfo>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BrightnessInfo {
     pub min: u32,
     pub current: u32,
     pub max: u32,
+    // Control path in use: "ddc" (DDC/CI), "wmi" (internal panel) or "gamma" (software ramp)
+    pub source: String,
+}
+
+impl BrightnessInfo {
+    /// Current value as a percentage of the monitor's min..max range, which is what setters take.
+    pub fn percent(&self) -> u32 {
+        let span = self.max.saturating_sub(self.min).max(1);
+        ((self.current.saturating_sub(self.min) * 100 + span / 2) / span).min(100)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ColorTemperatureInfo {
+    pub kelvin: u32,
+    // Control path in use: "ddc" (VCP 0x0C), "ddc_gain" (RGB gains 0x16/0x18/0x1A) or "gamma"
+    pub source: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ColorProfileInfo {
+    // File name in the system color directory, e.g. "DELL U2720Q.icm"
+    pub name: String,
+    pub description: String,
+    // Whether the profile is associated with the monitor (listed in its color settings)
+    pub associated: bool,
+    pub is_default: bool,
+    pub white_point: Option<crate::icc::Chromaticity>,
+    pub gamut: Option<crate::icc::Gamut>,
 }
 
 pub trait Displays {
@@ -63,6 +151,18 @@ pub trait Displays {
         height: u32,
         refresh_hz: Option<u32>,
     ) -> Result<(), String>;
+    // Every mode the driver reports; `include_raw` adds modes the monitor doesn't advertise
+    fn list_monitor_modes(
+        &self,
+        device_name: String,
+        include_raw: bool,
+    ) -> Result<Vec<Resolution>, String>;
+    // Switches to the mode `mode_selection` picks for the request and returns it
+    fn set_monitor_mode(
+        &self,
+        device_name: String,
+        request: crate::mode_selection::ModeRequest,
+    ) -> Result<Resolution, String>;
     fn set_monitor_orientation(
         &self,
         device_name: String,
@@ -70,9 +170,34 @@ pub trait Displays {
     ) -> Result<(), String>;
     fn get_monitor_brightness(&self, device_name: String) -> Result<BrightnessInfo, String>;
     fn set_monitor_brightness(&self, device_name: String, percent: u32) -> Result<(), String>;
+    // Software dimming via gamma ramp on top of hardware brightness, 100 = none
+    fn set_monitor_dimming(&self, device_name: String, percent: u32) -> Result<(), String>;
+    // White point in Kelvin, 6500 = neutral; lower values are warmer (night light)
+    fn set_monitor_color_temperature(
+        &self,
+        device_name: String,
+        kelvin: u32,
+    ) -> Result<ColorTemperatureInfo, String>;
+    fn get_monitor_color_temperature(
+        &self,
+        device_name: String,
+    ) -> Result<ColorTemperatureInfo, String>;
+    // Installed display ICC profiles, flagged with their association to `device_name`
+    fn list_color_profiles(&self, device_name: String) -> Result<Vec<ColorProfileInfo>, String>;
+    fn get_monitor_color_profile(&self, device_name: String) -> Result<Option<String>, String>;
+    // Associates the profile with the monitor if needed and makes it the default
+    fn set_monitor_color_profile(&self, device_name: String, profile: String)
+        -> Result<(), String>;
     fn identify_monitors(&self, app_handle: tauri::AppHandle) -> Result<(), String>;
     fn set_monitor_scale(&self, device_name: String, scale_percent: u32) -> Result<(), String>;
+    // System-wide custom scaling in percent (applies at next sign-in), None turns it off
+    fn set_custom_scale(&self, percent: Option<u32>) -> Result<(), String>;
+    fn set_monitor_scaling_mode(&self, device_name: String, mode: String) -> Result<(), String>;
     fn enable_hdr(&self, device_name: String, enable: bool) -> Result<(), String>;
+    // Only monitors whose EDID advertises a VRR range accept it
+    fn set_monitor_vrr(&self, device_name: String, enable: bool) -> Result<(), String>;
+    // SDR content brightness while HDR is on, in nits (80-480)
+    fn set_sdr_white_level(&self, device_name: String, nits: u32) -> Result<(), String>;
     fn set_monitor_input_source(&self, device_name: String, input: String) -> Result<(), String>;
     fn get_monitor_input_source(&self, device_name: String) -> Result<String, String>;
     fn get_monitor_ddc_caps(&self, device_name: String) -> Result<String, String>;
@@ -97,7 +222,9 @@ pub fn active_provider() -> Box<dyn Displays> {
 // Tauri commands delegate to the selected provider
 #[tauri::command]
 pub fn get_all_monitors() -> Result<Vec<DisplayInfo>, String> {
-    active_provider().get_all_monitors()
+    let monitors = active_provider().get_all_monitors()?;
+    crate::monitor_identity::remember(&monitors);
+    Ok(monitors)
 }
 
 #[tauri::command]
@@ -110,6 +237,22 @@ pub fn set_monitor_resolution(
     active_provider().set_monitor_resolution(device_name, width, height, refresh_hz)
 }
 
+#[tauri::command]
+pub fn list_monitor_modes(
+    device_name: String,
+    include_raw: Option<bool>,
+) -> Result<Vec<Resolution>, String> {
+    active_provider().list_monitor_modes(device_name, include_raw.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn set_monitor_mode(
+    device_name: String,
+    request: crate::mode_selection::ModeRequest,
+) -> Result<Resolution, String> {
+    active_provider().set_monitor_mode(device_name, request)
+}
+
 #[tauri::command]
 pub fn set_monitor_orientation(
     device_name: String,
@@ -124,8 +267,69 @@ pub fn get_monitor_brightness(device_name: String) -> Result<BrightnessInfo, Str
 }
 
 #[tauri::command]
-pub fn set_monitor_brightness(device_name: String, percent: u32) -> Result<(), String> {
-    active_provider().set_monitor_brightness(device_name, percent)
+pub fn set_monitor_brightness(
+    app_handle: tauri::AppHandle,
+    device_name: String,
+    percent: u32,
+) -> Result<(), String> {
+    crate::brightness_schedule::note_manual_override(&device_name);
+    crate::brightness_fade::cancel_fade(&device_name);
+    let provider = active_provider();
+    provider.set_monitor_brightness(device_name.clone(), percent)?;
+    crate::brightness_calibration::propagate_linked_brightness(&app_handle, &device_name, percent);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn fade_monitor_brightness(
+    device_name: String,
+    target: u32,
+    duration_ms: u64,
+) -> Result<(), String> {
+    crate::brightness_schedule::note_manual_override(&device_name);
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::brightness_fade::fade_monitor_brightness(
+            active_provider().as_ref(),
+            device_name,
+            target,
+            duration_ms,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn set_monitor_dimming(device_name: String, percent: u32) -> Result<(), String> {
+    active_provider().set_monitor_dimming(device_name, percent)
+}
+
+#[tauri::command]
+pub fn set_monitor_color_temperature(
+    device_name: String,
+    kelvin: u32,
+) -> Result<ColorTemperatureInfo, String> {
+    active_provider().set_monitor_color_temperature(device_name, kelvin)
+}
+
+#[tauri::command]
+pub fn get_monitor_color_temperature(device_name: String) -> Result<ColorTemperatureInfo, String> {
+    active_provider().get_monitor_color_temperature(device_name)
+}
+
+#[tauri::command]
+pub fn list_color_profiles(device_name: String) -> Result<Vec<ColorProfileInfo>, String> {
+    active_provider().list_color_profiles(device_name)
+}
+
+#[tauri::command]
+pub fn get_monitor_color_profile(device_name: String) -> Result<Option<String>, String> {
+    active_provider().get_monitor_color_profile(device_name)
+}
+
+#[tauri::command]
+pub fn set_monitor_color_profile(device_name: String, profile: String) -> Result<(), String> {
+    active_provider().set_monitor_color_profile(device_name, profile)
 }
 
 #[tauri::command]
@@ -138,13 +342,40 @@ pub fn set_monitor_scale(device_name: String, scale_percent: u32) -> Result<(),
     active_provider().set_monitor_scale(device_name, scale_percent)
 }
 
+#[tauri::command]
+pub fn set_custom_scale(percent: Option<u32>) -> Result<(), String> {
+    active_provider().set_custom_scale(percent)
+}
+
+#[tauri::command]
+pub fn set_monitor_scaling_mode(device_name: String, mode: String) -> Result<(), String> {
+    active_provider().set_monitor_scaling_mode(device_name, mode)
+}
+
 #[tauri::command]
 pub fn enable_hdr(device_name: String, enable: bool) -> Result<(), String> {
     active_provider().enable_hdr(device_name, enable)
 }
 
 #[tauri::command]
-pub fn set_monitor_input_source(device_name: String, input: String) -> Result<(), String> {
+pub fn set_monitor_vrr(device_name: String, enable: bool) -> Result<(), String> {
+    active_provider().set_monitor_vrr(device_name, enable)
+}
+
+#[tauri::command]
+pub fn set_sdr_white_level(device_name: String, nits: u32) -> Result<(), String> {
+    active_provider().set_sdr_white_level(device_name, nits)
+}
+
+/// `input` may also be a name the user gave one of the monitor's inputs.
+#[tauri::command]
+pub fn set_monitor_input_source(
+    app_handle: tauri::AppHandle,
+    device_name: String,
+    input: String,
+) -> Result<(), String> {
+    let input =
+        crate::inputs::resolve_name(&crate::inputs::names_for(&app_handle, &device_name), &input);
     active_provider().set_monitor_input_source(device_name, input)
 }
 
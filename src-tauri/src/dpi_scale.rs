@@ -0,0 +1,177 @@
+// DPI scaling step math. DisplayConfig's undocumented DPI packets describe a monitor's scales as
+// steps relative to its recommended one; this maps them to percentages and back. Pure Rust.
+
+/// Scaling steps Windows offers per monitor, in percent.
+pub const DPI_STEPS: [u32; 12] = [100, 125, 150, 175, 200, 225, 250, 300, 350, 400, 450, 500];
+
+/// Bounds for the system-wide custom scaling (Settings > Display > Custom scaling).
+pub const MIN_CUSTOM_PERCENT: u32 = 100;
+pub const MAX_CUSTOM_PERCENT: u32 = 500;
+
+// 100% scaling
+const BASE_DPI: u32 = 96;
+
+/// Values from the GET_DPI_SCALE packet: steps from the recommended scale down to the minimum,
+/// to the current scale and up to the maximum.
+#[derive(Debug, Clone, Copy)]
+pub struct RelativeDpi {
+    pub min_rel: i32,
+    pub cur_rel: i32,
+    pub max_rel: i32,
+}
+
+/// A monitor's allowed scales as indices into `DPI_STEPS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DpiRange {
+    pub min_idx: usize,
+    pub max_idx: usize,
+    pub recommended_idx: usize,
+    pub current_idx: usize,
+}
+
+impl DpiRange {
+    /// The minimum is reported as steps below the recommended scale, so the recommended
+    /// index is `-min_rel` (the minimum being 100%).
+    pub fn from_relative(rel: RelativeDpi) -> Self {
+        let last = DPI_STEPS.len() as i32 - 1;
+        let recommended = (-rel.min_rel).clamp(0, last);
+        let mut min_idx = (recommended + rel.min_rel).clamp(0, last);
+        let mut max_idx = (recommended + rel.max_rel).clamp(0, last);
+        if min_idx > max_idx {
+            std::mem::swap(&mut min_idx, &mut max_idx);
+        }
+        let current = (recommended + rel.cur_rel).clamp(min_idx, max_idx);
+        Self {
+            min_idx: min_idx as usize,
+            max_idx: max_idx as usize,
+            recommended_idx: recommended as usize,
+            current_idx: current as usize,
+        }
+    }
+
+    pub fn allowed_percents(&self) -> &'static [u32] {
+        &DPI_STEPS[self.min_idx..=self.max_idx]
+    }
+
+    pub fn current_percent(&self) -> u32 {
+        DPI_STEPS[self.current_idx]
+    }
+
+    /// Relative value for the SET_DPI_SCALE packet, or an error listing the steps this
+    /// monitor allows.
+    pub fn relative_for(&self, percent: u32) -> Result<i32, String> {
+        match DPI_STEPS.iter().position(|p| *p == percent) {
+            Some(idx) if (self.min_idx..=self.max_idx).contains(&idx) => {
+                Ok(idx as i32 - self.recommended_idx as i32)
+            }
+            _ => Err(format!(
+                "Unsupported scale {}% for this display. Allowed: {}. Other values need custom scaling ({}-{}%)",
+                percent,
+                self.allowed_percents()
+                    .iter()
+                    .map(|p| format!("{}%", p))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                MIN_CUSTOM_PERCENT,
+                MAX_CUSTOM_PERCENT
+            )),
+        }
+    }
+}
+
+/// LogPixels value for a custom scaling percentage.
+pub fn percent_to_dpi(percent: u32) -> u32 {
+    (percent * BASE_DPI + 50) / 100
+}
+
+pub fn dpi_to_percent(dpi: u32) -> u32 {
+    (dpi * 100 + BASE_DPI / 2) / BASE_DPI
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(min_rel: i32, cur_rel: i32, max_rel: i32) -> DpiRange {
+        DpiRange::from_relative(RelativeDpi {
+            min_rel,
+            cur_rel,
+            max_rel,
+        })
+    }
+
+    #[test]
+    fn recommended_scale_is_counted_up_from_100() {
+        // A laptop panel recommending 150% that allows up to 300%
+        let r = range(-2, 0, 5);
+        assert_eq!(
+            r,
+            DpiRange {
+                min_idx: 0,
+                max_idx: 7,
+                recommended_idx: 2,
+                current_idx: 2,
+            }
+        );
+        assert_eq!(r.current_percent(), 150);
+        assert_eq!(
+            r.allowed_percents(),
+            &[100, 125, 150, 175, 200, 225, 250, 300]
+        );
+        assert_eq!(range(-2, 1, 5).current_percent(), 175);
+        assert_eq!(range(-2, -2, 5).current_percent(), 100);
+        // A desktop monitor recommending 100% that only allows 100-175%
+        let r = range(0, 0, 3);
+        assert_eq!(r.recommended_idx, 0);
+        assert_eq!(r.allowed_percents(), &[100, 125, 150, 175]);
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        let r = range(-20, 30, 40);
+        assert_eq!(r.recommended_idx, DPI_STEPS.len() - 1);
+        assert_eq!(r.min_idx, 0);
+        assert_eq!(r.max_idx, DPI_STEPS.len() - 1);
+        assert_eq!(r.current_percent(), 500);
+        // The current scale stays within the allowed steps
+        assert_eq!(range(-2, 9, 2).current_percent(), 200);
+        assert_eq!(range(-2, -9, 2).current_percent(), 100);
+        // A minimum above the maximum is swapped around
+        let r = range(2, 0, 1);
+        assert_eq!((r.min_idx, r.max_idx), (1, 2));
+        assert_eq!(r.current_percent(), 125);
+    }
+
+    #[test]
+    fn relative_steps_for_percentages() {
+        let r = range(-2, 0, 5);
+        assert_eq!(r.relative_for(100), Ok(-2));
+        assert_eq!(r.relative_for(150), Ok(0));
+        assert_eq!(r.relative_for(300), Ok(5));
+        // Every allowed step round-trips through from_relative
+        for &percent in r.allowed_percents() {
+            let rel = r.relative_for(percent).unwrap();
+            assert_eq!(range(-2, rel, 5).current_percent(), percent);
+        }
+        let err = r.relative_for(350).unwrap_err();
+        assert!(err.contains("Allowed: 100%, 125%, 150%"), "{}", err);
+        assert!(err.contains("300%."), "{}", err);
+        assert!(r.relative_for(110).is_err());
+    }
+
+    #[test]
+    fn custom_scaling_dpi() {
+        for (percent, dpi) in [(100, 96), (125, 120), (150, 144), (175, 168), (200, 192)] {
+            assert_eq!(percent_to_dpi(percent), dpi);
+            assert_eq!(dpi_to_percent(dpi), percent);
+        }
+        // Custom values round to the nearest DPI and back
+        assert_eq!(percent_to_dpi(110), 106);
+        assert_eq!(dpi_to_percent(106), 110);
+        // A DPI step is just over 1%, so the rest come back within a percent
+        for percent in MIN_CUSTOM_PERCENT..=MAX_CUSTOM_PERCENT {
+            let back = dpi_to_percent(percent_to_dpi(percent));
+            assert!(back.abs_diff(percent) <= 1, "{}% -> {}%", percent, back);
+        }
+    }
+}
@@ -0,0 +1,593 @@
+// EDID reader: identity, range limits and the variable refresh rate capabilities advertised in
+// the base block, CTA-861 and DisplayID extensions. Pure Rust so it works on every platform.
+use serde::Serialize;
+
+const BLOCK_LEN: usize = 128;
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+
+// Display descriptor tags
+const TAG_SERIAL: u8 = 0xFF;
+const TAG_RANGE_LIMITS: u8 = 0xFD;
+const TAG_NAME: u8 = 0xFC;
+
+// Extension block tags
+const EXT_CTA: u8 = 0x02;
+const EXT_DISPLAYID: u8 = 0x70;
+
+// IEEE OUIs of CTA vendor-specific data blocks
+const OUI_AMD: u32 = 0x00001A;
+const OUI_HDMI_FORUM: u32 = 0xC45DD8;
+
+// DisplayID 1.x Video Timing Range Limits and 2.x Dynamic Video Timing Range Limits blocks
+const DISPLAYID_V1_RANGE_LIMITS: u8 = 0x09;
+const DISPLAYID_RANGE_LIMITS: u8 = 0x25;
+
+// A range narrower than this isn't worth switching to variable refresh (same cut-off as Linux)
+const MIN_VRR_SPAN_HZ: u32 = 10;
+
+/// Monitor Range Limits descriptor (0xFD) of the base block.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RangeLimits {
+    pub min_vertical_hz: u32,
+    pub max_vertical_hz: u32,
+    pub min_horizontal_khz: u32,
+    pub max_horizontal_khz: u32,
+    // 0 when not given
+    pub max_pixel_clock_mhz: u32,
+    // The monitor accepts any timing within the range, not just GTF/CVT formulas
+    pub range_limits_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct VrrRange {
+    pub min_hz: u32,
+    pub max_hz: u32,
+    // Where the range came from: "freesync", "hdmi_vrr", "displayid" or "range_limits"
+    pub source: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Edid {
+    // Three-letter PnP id, e.g. "DEL"
+    pub manufacturer_id: String,
+    pub product_code: u16,
+    pub serial_number: u32,
+    pub week_of_manufacture: u8,
+    pub year_of_manufacture: u16,
+    // e.g. "1.4"
+    pub version: String,
+    // Monitor name and serial string descriptors
+    pub name: Option<String>,
+    pub serial: Option<String>,
+    pub range_limits: Option<RangeLimits>,
+    // EDID 1.4 "continuous frequency" feature bit
+    pub continuous_frequency: bool,
+    pub vrr: Option<VrrRange>,
+}
+
+fn manufacturer_id(bytes: [u8; 2]) -> String {
+    let packed = u16::from_be_bytes(bytes);
+    [10, 5, 0]
+        .iter()
+        .map(|shift| {
+            let letter = ((packed >> shift) & 0x1F) as u8;
+            if (1..=26).contains(&letter) {
+                (b'A' + letter - 1) as char
+            } else {
+                '?'
+            }
+        })
+        .collect()
+}
+
+// Descriptor text: up to 13 bytes, terminated by a line feed and padded with spaces
+fn descriptor_text(desc: &[u8]) -> Option<String> {
+    let text = desc.get(5..18)?;
+    let end = text.iter().position(|&b| b == 0x0A).unwrap_or(text.len());
+    let s: String = text[..end]
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '?'
+            }
+        })
+        .collect();
+    let s = s.trim().to_string();
+    (!s.is_empty()).then_some(s)
+}
+
+fn range_limits(desc: &[u8], version_minor: u8) -> Option<RangeLimits> {
+    let desc = desc.get(0..18)?;
+    // EDID 1.4 rate offsets: 0b10 adds 255 to the maximum, 0b11 to both minimum and maximum
+    let offsets = if version_minor >= 4 { desc[4] } else { 0 };
+    let offset = |bits: u8, is_min: bool| match (bits & 0x03, is_min) {
+        (0b11, _) | (0b10, false) => 255,
+        _ => 0,
+    };
+    Some(RangeLimits {
+        min_vertical_hz: desc[5] as u32 + offset(offsets, true),
+        max_vertical_hz: desc[6] as u32 + offset(offsets, false),
+        min_horizontal_khz: desc[7] as u32 + offset(offsets >> 2, true),
+        max_horizontal_khz: desc[8] as u32 + offset(offsets >> 2, false),
+        max_pixel_clock_mhz: desc[9] as u32 * 10,
+        range_limits_only: desc[10] == 0x01,
+    })
+}
+
+// CTA-861 data block collection, which ends where the timings at offset ext[2] start:
+// (tag, payload) pairs
+fn cta_data_blocks(ext: &[u8]) -> Vec<(u8, &[u8])> {
+    let end = (ext.get(2).copied().unwrap_or(0) as usize).min(ext.len());
+    let mut blocks = Vec::new();
+    let mut pos = 4;
+    while pos < end {
+        let tag = ext[pos] >> 5;
+        let len = (ext[pos] & 0x1F) as usize;
+        let Some(payload) = ext[..end].get(pos + 1..pos + 1 + len) else {
+            break;
+        };
+        blocks.push((tag, payload));
+        pos += 1 + len;
+    }
+    blocks
+}
+
+fn vrr_from_cta(ext: &[u8], max_vertical_hz: Option<u32>) -> Option<VrrRange> {
+    for (tag, payload) in cta_data_blocks(ext) {
+        // Vendor-specific data block
+        if tag != 3 || payload.len() < 3 {
+            continue;
+        }
+        let oui = u32::from_le_bytes([payload[0], payload[1], payload[2], 0]);
+        match oui {
+            // AMD VSDB: OUI, version, feature caps, min and max refresh rate
+            OUI_AMD if payload.len() >= 7 => {
+                let (min_hz, max_hz) = (payload[5] as u32, payload[6] as u32);
+                if max_hz > min_hz && min_hz > 0 {
+                    return Some(VrrRange {
+                        min_hz,
+                        max_hz,
+                        source: "freesync",
+                    });
+                }
+            }
+            // HDMI Forum VSDB: VRRmin in 6 bits, VRRmax in 10 bits (0 = up to the range limit)
+            OUI_HDMI_FORUM if payload.len() >= 10 => {
+                let min_hz = (payload[8] & 0x3F) as u32;
+                let max_hz = (((payload[8] >> 6) as u32) << 8) | payload[9] as u32;
+                let max_hz = if max_hz == 0 {
+                    max_vertical_hz.unwrap_or(0)
+                } else {
+                    max_hz
+                };
+                if min_hz > 0 && max_hz > min_hz {
+                    return Some(VrrRange {
+                        min_hz,
+                        max_hz,
+                        source: "hdmi_vrr",
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// DisplayID 2.x: the range limits block flags seamless refresh changes (adaptive sync) in bit 7.
+// DisplayID 1.x has no such flag, so like the base block a continuous range has to do.
+fn vrr_from_displayid(ext: &[u8]) -> Option<VrrRange> {
+    let section_len = *ext.get(2)? as usize;
+    let end = (5 + section_len).min(ext.len());
+    let mut pos = 5;
+    while pos + 3 <= end {
+        let tag = ext[pos];
+        let len = ext[pos + 2] as usize;
+        let payload = ext.get(pos + 3..pos + 3 + len)?;
+        if tag == DISPLAYID_RANGE_LIMITS && payload.len() >= 9 {
+            let min_hz = payload[6] as u32;
+            let max_hz = (((payload[8] & 0x03) as u32) << 8) | payload[7] as u32;
+            let seamless = payload[8] & 0x80 != 0;
+            if seamless && min_hz > 0 && max_hz > min_hz {
+                return Some(VrrRange {
+                    min_hz,
+                    max_hz,
+                    source: "displayid",
+                });
+            }
+        }
+        if tag == DISPLAYID_V1_RANGE_LIMITS && payload.len() >= 15 {
+            let (min_hz, max_hz) = (payload[10] as u32, payload[11] as u32);
+            // Bit 4: discrete frequency display, only the listed timings work
+            let continuous = payload[14] & 0x10 == 0;
+            if continuous && min_hz > 0 && max_hz > min_hz + MIN_VRR_SPAN_HZ {
+                return Some(VrrRange {
+                    min_hz,
+                    max_hz,
+                    source: "displayid",
+                });
+            }
+        }
+        if tag == 0 && len == 0 {
+            break;
+        }
+        pos += 3 + len;
+    }
+    None
+}
+
+/// Parses an EDID (base block plus any extension blocks that are present).
+pub fn parse_edid(data: &[u8]) -> Result<Edid, String> {
+    if data.len() < BLOCK_LEN {
+        return Err(format!("EDID is too short ({} bytes)", data.len()));
+    }
+    if data[0..8] != HEADER {
+        return Err("Not an EDID (bad header)".to_string());
+    }
+    let checksum = data[..BLOCK_LEN]
+        .iter()
+        .fold(0u8, |sum, b| sum.wrapping_add(*b));
+    if checksum != 0 {
+        log::debug!("EDID base block checksum mismatch, parsing anyway");
+    }
+
+    let version_minor = data[19];
+    let continuous_frequency = version_minor >= 4 && data[24] & 0x01 != 0;
+
+    let mut name = None;
+    let mut serial = None;
+    let mut limits = None;
+    for desc in data[54..126].chunks_exact(18) {
+        // Display descriptors start with a zero pixel clock
+        if desc[0] != 0 || desc[1] != 0 {
+            continue;
+        }
+        match desc[3] {
+            TAG_NAME => name = descriptor_text(desc),
+            TAG_SERIAL => serial = descriptor_text(desc),
+            TAG_RANGE_LIMITS => limits = range_limits(desc, version_minor),
+            _ => {}
+        }
+    }
+
+    let max_vertical_hz = limits.map(|l| l.max_vertical_hz);
+    let extensions = data[BLOCK_LEN..].chunks_exact(BLOCK_LEN);
+    let mut vrr = None;
+    for ext in extensions {
+        vrr = match ext[0] {
+            EXT_CTA => vrr_from_cta(ext, max_vertical_hz),
+            EXT_DISPLAYID => vrr_from_displayid(ext),
+            _ => None,
+        };
+        if vrr.is_some() {
+            break;
+        }
+    }
+    // Adaptive-Sync over DisplayPort is only advertised by a continuous range limits descriptor
+    if vrr.is_none() && continuous_frequency {
+        vrr = limits
+            .filter(|l| {
+                l.range_limits_only && l.max_vertical_hz > l.min_vertical_hz + MIN_VRR_SPAN_HZ
+            })
+            .map(|l| VrrRange {
+                min_hz: l.min_vertical_hz,
+                max_hz: l.max_vertical_hz,
+                source: "range_limits",
+            });
+    }
+
+    Ok(Edid {
+        manufacturer_id: manufacturer_id([data[8], data[9]]),
+        product_code: u16::from_le_bytes([data[10], data[11]]),
+        serial_number: u32::from_le_bytes([data[12], data[13], data[14], data[15]]),
+        week_of_manufacture: data[16],
+        year_of_manufacture: 1990 + data[17] as u16,
+        version: format!("{}.{}", data[18], version_minor),
+        name,
+        serial,
+        range_limits: limits,
+        continuous_frequency,
+        vrr,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_checksum(mut block: Vec<u8>) -> Vec<u8> {
+        let sum = block[..BLOCK_LEN - 1]
+            .iter()
+            .fold(0u8, |sum, b| sum.wrapping_add(*b));
+        block[BLOCK_LEN - 1] = 0u8.wrapping_sub(sum);
+        block
+    }
+
+    fn text_descriptor(tag: u8, text: &str) -> [u8; 18] {
+        let mut desc = [0u8; 18];
+        desc[3] = tag;
+        desc[5..18].copy_from_slice(b"\n            ");
+        desc[5..5 + text.len()].copy_from_slice(text.as_bytes());
+        if text.len() < 13 {
+            desc[5 + text.len()] = 0x0A;
+        }
+        desc
+    }
+
+    // Range limits descriptor; `offsets` is the EDID 1.4 rate offset byte
+    fn limits_descriptor(offsets: u8, min_v: u8, max_v: u8, range_only: bool) -> [u8; 18] {
+        let mut desc = [0u8; 18];
+        desc[3] = TAG_RANGE_LIMITS;
+        desc[4] = offsets;
+        desc[5..10].copy_from_slice(&[min_v, max_v, 30, 160, 60]);
+        desc[10] = if range_only { 0x01 } else { 0x00 };
+        desc
+    }
+
+    // Base block of a Dell monitor with the given version, feature byte and descriptors
+    fn base(minor: u8, features: u8, descriptors: &[[u8; 18]], extensions: u8) -> Vec<u8> {
+        let mut block = vec![0u8; BLOCK_LEN];
+        block[..8].copy_from_slice(&HEADER);
+        block[8..10].copy_from_slice(&[0x10, 0xAC]);
+        block[10..12].copy_from_slice(&0xA0C3u16.to_le_bytes());
+        block[12..16].copy_from_slice(&0x3230_4C53u32.to_le_bytes());
+        block[16] = 12;
+        block[17] = 30;
+        block[18] = 1;
+        block[19] = minor;
+        block[24] = features;
+        for (i, desc) in descriptors.iter().enumerate() {
+            block[54 + i * 18..72 + i * 18].copy_from_slice(desc);
+        }
+        block[126] = extensions;
+        with_checksum(block)
+    }
+
+    fn cta(blocks: &[Vec<u8>]) -> Vec<u8> {
+        let mut ext = vec![EXT_CTA, 3, 0, 0];
+        for payload in blocks {
+            ext.push(payload[0] << 5 | (payload.len() - 1) as u8);
+            ext.extend(&payload[1..]);
+        }
+        ext[2] = ext.len() as u8;
+        ext.resize(BLOCK_LEN, 0);
+        with_checksum(ext)
+    }
+
+    fn displayid(blocks: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut ext = vec![EXT_DISPLAYID, 0x20, 0, 0, 0];
+        for (tag, payload) in blocks {
+            ext.extend([*tag, 0, payload.len() as u8]);
+            ext.extend(payload);
+        }
+        ext[2] = (ext.len() - 5) as u8;
+        ext.resize(BLOCK_LEN, 0);
+        with_checksum(ext)
+    }
+
+    // Vendor-specific data block payloads, led by the block tag
+    fn amd_vsdb(min_hz: u8, max_hz: u8) -> Vec<u8> {
+        vec![3, 0x1A, 0x00, 0x00, 0x01, 0x01, min_hz, max_hz, 0x00]
+    }
+
+    fn hdmi_forum_vsdb(min_hz: u8, max_hz: u16) -> Vec<u8> {
+        let mut vsdb = vec![3, 0xD8, 0x5D, 0xC4, 0x01, 0x78, 0x00, 0x00, 0x00];
+        vsdb.push(((max_hz >> 8) as u8) << 6 | min_hz);
+        vsdb.push(max_hz as u8);
+        vsdb
+    }
+
+    fn edid(mut base: Vec<u8>, extensions: &[Vec<u8>]) -> Edid {
+        for ext in extensions {
+            base.extend(ext);
+        }
+        parse_edid(&base).unwrap()
+    }
+
+    #[test]
+    fn reads_identity_and_descriptors() {
+        let descriptors = [
+            text_descriptor(TAG_NAME, "DELL U2720Q"),
+            text_descriptor(TAG_SERIAL, "ABC123456789X"),
+            limits_descriptor(0, 48, 75, false),
+        ];
+        let e = edid(base(3, 0, &descriptors, 0), &[]);
+        assert_eq!(e.manufacturer_id, "DEL");
+        assert_eq!(e.product_code, 0xA0C3);
+        assert_eq!(e.serial_number, 0x3230_4C53);
+        assert_eq!((e.week_of_manufacture, e.year_of_manufacture), (12, 2020));
+        assert_eq!(e.version, "1.3");
+        assert_eq!(e.name.as_deref(), Some("DELL U2720Q"));
+        assert_eq!(e.serial.as_deref(), Some("ABC123456789X"));
+        let limits = e.range_limits.unwrap();
+        assert_eq!((limits.min_vertical_hz, limits.max_vertical_hz), (48, 75));
+        assert_eq!(
+            (limits.min_horizontal_khz, limits.max_horizontal_khz),
+            (30, 160)
+        );
+        assert_eq!(limits.max_pixel_clock_mhz, 600);
+        assert!(!e.continuous_frequency);
+        assert_eq!(e.vrr, None);
+    }
+
+    #[test]
+    fn amd_vsdb_range() {
+        let e = edid(base(3, 0, &[], 1), &[cta(&[amd_vsdb(48, 144)])]);
+        assert_eq!(
+            e.vrr,
+            Some(VrrRange {
+                min_hz: 48,
+                max_hz: 144,
+                source: "freesync",
+            })
+        );
+        // An empty range isn't VRR
+        assert_eq!(
+            edid(base(3, 0, &[], 1), &[cta(&[amd_vsdb(60, 60)])]).vrr,
+            None
+        );
+    }
+
+    #[test]
+    fn hdmi_forum_vsdb_range() {
+        // Another data block in front of the VSDB is skipped
+        let audio = vec![1, 0x09, 0x7F, 0x07];
+        let e = edid(
+            base(3, 0, &[], 1),
+            &[cta(&[audio, hdmi_forum_vsdb(40, 300)])],
+        );
+        assert_eq!(
+            e.vrr,
+            Some(VrrRange {
+                min_hz: 40,
+                max_hz: 300,
+                source: "hdmi_vrr",
+            })
+        );
+        // VRRmax 0 means up to the range limits maximum
+        let e = edid(
+            base(4, 0, &[limits_descriptor(0, 24, 120, false)], 1),
+            &[cta(&[hdmi_forum_vsdb(24, 0)])],
+        );
+        assert_eq!(e.vrr.map(|v| (v.min_hz, v.max_hz)), Some((24, 120)));
+        let e = edid(base(4, 0, &[], 1), &[cta(&[hdmi_forum_vsdb(24, 0)])]);
+        assert_eq!(e.vrr, None);
+    }
+
+    #[test]
+    fn displayid_2_range() {
+        let block = |min_hz: u8, max_hz: u16, seamless: bool| {
+            let flags = (max_hz >> 8) as u8 | if seamless { 0x80 } else { 0 };
+            vec![0, 0, 0, 0, 0, 0, min_hz, max_hz as u8, flags]
+        };
+        let e = edid(
+            base(4, 0, &[], 1),
+            &[displayid(&[(DISPLAYID_RANGE_LIMITS, block(48, 360, true))])],
+        );
+        assert_eq!(
+            e.vrr,
+            Some(VrrRange {
+                min_hz: 48,
+                max_hz: 360,
+                source: "displayid",
+            })
+        );
+        let fixed = displayid(&[(DISPLAYID_RANGE_LIMITS, block(48, 360, false))]);
+        assert_eq!(edid(base(4, 0, &[], 1), &[fixed]).vrr, None);
+    }
+
+    #[test]
+    fn displayid_1_range() {
+        let block = |min_hz: u8, max_hz: u8, flags: u8| {
+            let mut payload = vec![0u8; 15];
+            payload[10] = min_hz;
+            payload[11] = max_hz;
+            payload[14] = flags;
+            payload
+        };
+        let e = edid(
+            base(4, 0, &[], 1),
+            &[displayid(&[(
+                DISPLAYID_V1_RANGE_LIMITS,
+                block(40, 165, 0x60),
+            )])],
+        );
+        assert_eq!(
+            e.vrr.map(|v| (v.min_hz, v.max_hz, v.source)),
+            Some((40, 165, "displayid"))
+        );
+        // Discrete frequency displays and narrow ranges don't count
+        for payload in [block(40, 165, 0x10), block(56, 61, 0)] {
+            let ext = displayid(&[(DISPLAYID_V1_RANGE_LIMITS, payload)]);
+            assert_eq!(edid(base(4, 0, &[], 1), &[ext]).vrr, None);
+        }
+    }
+
+    #[test]
+    fn continuous_range_limits() {
+        let continuous = [limits_descriptor(0, 48, 144, true)];
+        let e = edid(base(4, 0x01, &continuous, 0), &[]);
+        assert!(e.continuous_frequency);
+        assert_eq!(
+            e.vrr,
+            Some(VrrRange {
+                min_hz: 48,
+                max_hz: 144,
+                source: "range_limits",
+            })
+        );
+        // Needs the continuous frequency bit, range-limits-only timing and a wide enough range
+        assert_eq!(edid(base(4, 0, &continuous, 0), &[]).vrr, None);
+        let formulas = [limits_descriptor(0, 48, 144, false)];
+        assert_eq!(edid(base(4, 0x01, &formulas, 0), &[]).vrr, None);
+        let narrow = [limits_descriptor(0, 56, 61, true)];
+        assert_eq!(edid(base(4, 0x01, &narrow, 0), &[]).vrr, None);
+        // The continuous bit means something else before EDID 1.4
+        assert_eq!(edid(base(3, 0x01, &continuous, 0), &[]).vrr, None);
+    }
+
+    #[test]
+    fn range_limit_offsets() {
+        // 0b10: +255 on the maximum vertical rate, 0b11 << 2: +255 on both horizontal rates
+        let desc = [limits_descriptor(0b1110, 48, 5, true)];
+        let e = edid(base(4, 0x01, &desc, 0), &[]);
+        let limits = e.range_limits.unwrap();
+        assert_eq!((limits.min_vertical_hz, limits.max_vertical_hz), (48, 260));
+        assert_eq!(
+            (limits.min_horizontal_khz, limits.max_horizontal_khz),
+            (285, 415)
+        );
+        assert_eq!(e.vrr.map(|v| (v.min_hz, v.max_hz)), Some((48, 260)));
+        // 0b11: +255 on both vertical rates
+        let desc = [limits_descriptor(0b11, 1, 105, true)];
+        let limits = edid(base(4, 0x01, &desc, 0), &[]).range_limits.unwrap();
+        assert_eq!((limits.min_vertical_hz, limits.max_vertical_hz), (256, 360));
+        // Ignored before EDID 1.4
+        let desc = [limits_descriptor(0b10, 48, 5, true)];
+        let limits = edid(base(3, 0, &desc, 0), &[]).range_limits.unwrap();
+        assert_eq!(limits.max_vertical_hz, 5);
+    }
+
+    #[test]
+    fn extensions_take_precedence_over_range_limits() {
+        let continuous = [limits_descriptor(0, 48, 144, true)];
+        let e = edid(
+            base(4, 0x01, &continuous, 2),
+            &[cta(&[]), cta(&[amd_vsdb(40, 120)])],
+        );
+        assert_eq!(e.vrr.map(|v| v.source), Some("freesync"));
+    }
+
+    #[test]
+    fn rejects_short_and_foreign_data() {
+        let full = base(4, 0, &[], 0);
+        assert!(parse_edid(&full[..127]).unwrap_err().contains("too short"));
+        assert!(parse_edid(&[]).is_err());
+        let mut foreign = full.clone();
+        foreign[0] = 0x01;
+        assert!(parse_edid(&foreign).unwrap_err().contains("header"));
+    }
+
+    #[test]
+    fn tolerates_truncation_and_bad_checksums() {
+        // Drivers sometimes report a stale checksum; the data is still usable
+        let mut data = base(3, 0, &[text_descriptor(TAG_NAME, "Monitor")], 0);
+        data[127] = data[127].wrapping_add(1);
+        assert_eq!(parse_edid(&data).unwrap().name.as_deref(), Some("Monitor"));
+
+        // A partial extension block is ignored
+        let mut data = base(3, 0, &[], 1);
+        data.extend(&cta(&[amd_vsdb(48, 144)])[..64]);
+        assert_eq!(parse_edid(&data).unwrap().vrr, None);
+
+        // Data blocks running past the declared length are dropped
+        let mut ext = cta(&[amd_vsdb(48, 144)]);
+        ext[2] = 8;
+        assert_eq!(edid(base(3, 0, &[], 1), &[ext]).vrr, None);
+        let mut ext = displayid(&[(DISPLAYID_RANGE_LIMITS, vec![0; 9])]);
+        ext[7] = 200;
+        assert_eq!(edid(base(3, 0, &[], 1), &[ext]).vrr, None);
+    }
+}
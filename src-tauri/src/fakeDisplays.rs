@@ -1,35 +1,108 @@
-use crate::displays::{BrightnessInfo, DisplayInfo, Displays, Resolution};
+use crate::displays::{
+    BrightnessInfo, ColorProfileInfo, ColorTemperatureInfo, DisplayInfo, DisplayTarget, Displays,
+    Resolution, VrrInfo, SCALING_MODES,
+};
+use crate::mode_selection::{select_mode, ModeRequest};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 
 pub struct FakeDisplays;
 
+// Brightness writes received by any FakeDisplays instance, in order, as (device_name, percent).
+// Providers are created per call, so the state lives in statics.
+#[cfg(test)]
+fn brightness_log() -> &'static Mutex<Vec<(String, u32)>> {
+    static LOG: OnceLock<Mutex<Vec<(String, u32)>>> = OnceLock::new();
+    LOG.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn brightness_state() -> &'static Mutex<HashMap<String, u32>> {
+    static STATE: OnceLock<Mutex<HashMap<String, u32>>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Default ICC profile per device, None = system sRGB
+fn color_profile_state() -> &'static Mutex<HashMap<String, String>> {
+    static STATE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Scaling mode per device, unset = "preferred"
+fn scaling_mode_state() -> &'static Mutex<HashMap<String, String>> {
+    static STATE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// VRR on/off per device, unset = off
+fn vrr_state() -> &'static Mutex<HashMap<String, bool>> {
+    static STATE: OnceLock<Mutex<HashMap<String, bool>>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Only the first fake monitor supports variable refresh rate
+const FAKE_VRR_DEVICE: &str = "\\\\.\\DISPLAY1";
+
+const FAKE_COLOR_PROFILES: [&str; 2] = ["sRGB Color Space Profile.icm", "Fake Calibrated.icc"];
+
+/// Returns the brightness values set on `device_name` so far, in order.
+#[cfg(test)]
+pub fn recorded_brightness(device_name: &str) -> Vec<u32> {
+    brightness_log()
+        .lock()
+        .map(|log| {
+            log.iter()
+                .filter(|(name, _)| name == device_name)
+                .map(|(_, v)| *v)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 impl FakeDisplays {
     pub fn new() -> Self {
         Self
     }
 }
 
+fn fake_mode(
+    width: u32,
+    height: u32,
+    refresh_hz: u32,
+    interlaced: bool,
+    scaled: bool,
+) -> Resolution {
+    Resolution {
+        width,
+        height,
+        bits_per_pixel: 32,
+        refresh_hz,
+        refresh_rate: crate::mode_selection::nominal_refresh_rate(refresh_hz),
+        interlaced,
+        scaled,
+    }
+}
+
+// The progressive modes every fake monitor lists; `all` adds the NTSC, interlaced and scaled
+// variants a real driver reports too
+fn fake_modes(all: bool) -> Vec<Resolution> {
+    let mut modes = vec![
+        fake_mode(1920, 1080, 60, false, false),
+        fake_mode(2560, 1440, 60, false, false),
+        fake_mode(3840, 2160, 60, false, false),
+    ];
+    if all {
+        modes.extend([
+            fake_mode(1920, 1080, 59, false, false),
+            fake_mode(1920, 1080, 30, true, false),
+            fake_mode(1280, 720, 60, false, true),
+        ]);
+    }
+    modes
+}
+
 impl Displays for FakeDisplays {
     fn get_all_monitors(&self) -> Result<Vec<DisplayInfo>, String> {
-        let modes = vec![
-            Resolution {
-                width: 1920,
-                height: 1080,
-                bits_per_pixel: 32,
-                refresh_hz: 60,
-            },
-            Resolution {
-                width: 2560,
-                height: 1440,
-                bits_per_pixel: 32,
-                refresh_hz: 60,
-            },
-            Resolution {
-                width: 3840,
-                height: 2160,
-                bits_per_pixel: 32,
-                refresh_hz: 60,
-            },
-        ];
+        let modes = fake_modes(false);
 
         let displays = (0..4)
             .map(|i| DisplayInfo {
@@ -58,7 +131,32 @@ impl Displays for FakeDisplays {
                 scale: if i == 0 { 1.25 } else { 1.0 },
                 scales: vec![],
                 hdr_status: "unsupported".to_string(),
+                advanced_color: None,
                 supports_input_switch: Some(true),
+                color_profile: color_profile_state()
+                    .lock()
+                    .ok()
+                    .and_then(|state| state.get(&format!("\\\\.\\DISPLAY{}", i + 1)).cloned()),
+                display_target: Some(DisplayTarget {
+                    adapter_luid: 1,
+                    target_id: i as u32,
+                }),
+                scaling_mode: Some(
+                    scaling_mode_state()
+                        .lock()
+                        .ok()
+                        .and_then(|state| state.get(&format!("\\\\.\\DISPLAY{}", i + 1)).cloned())
+                        .unwrap_or_else(|| "preferred".to_string()),
+                ),
+                vrr: (i == 0).then(|| VrrInfo {
+                    min_hz: 48,
+                    max_hz: 144,
+                    enabled: vrr_state()
+                        .lock()
+                        .ok()
+                        .and_then(|state| state.get(FAKE_VRR_DEVICE).copied())
+                        .unwrap_or(false),
+                }),
             })
             .collect();
 
@@ -79,15 +177,115 @@ impl Displays for FakeDisplays {
         Ok(())
     }
 
-    fn get_monitor_brightness(&self, _device_name: String) -> Result<BrightnessInfo, String> {
+    fn list_monitor_modes(
+        &self,
+        _device_name: String,
+        _include_raw: bool,
+    ) -> Result<Vec<Resolution>, String> {
+        Ok(fake_modes(true))
+    }
+
+    fn set_monitor_mode(
+        &self,
+        _device_name: String,
+        request: ModeRequest,
+    ) -> Result<Resolution, String> {
+        let modes = fake_modes(true);
+        select_mode(&modes, &request, Some((3840, 2160)))
+            .map(|i| modes[i].clone())
+            .ok_or_else(|| "No display mode matches the request".to_string())
+    }
+
+    fn get_monitor_brightness(&self, device_name: String) -> Result<BrightnessInfo, String> {
+        let current = brightness_state()
+            .lock()
+            .ok()
+            .and_then(|state| state.get(&device_name).copied())
+            .unwrap_or(50);
         Ok(BrightnessInfo {
             min: 0,
-            current: 50,
+            current,
             max: 100,
+            source: "ddc".to_string(),
         })
     }
 
-    fn set_monitor_brightness(&self, _device_name: String, _percent: u32) -> Result<(), String> {
+    fn set_monitor_brightness(&self, device_name: String, percent: u32) -> Result<(), String> {
+        let percent = percent.min(100);
+        #[cfg(test)]
+        if let Ok(mut log) = brightness_log().lock() {
+            log.push((device_name.clone(), percent));
+        }
+        if let Ok(mut state) = brightness_state().lock() {
+            state.insert(device_name, percent);
+        }
+        Ok(())
+    }
+
+    fn set_monitor_dimming(&self, _device_name: String, _percent: u32) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn set_monitor_color_temperature(
+        &self,
+        _device_name: String,
+        kelvin: u32,
+    ) -> Result<ColorTemperatureInfo, String> {
+        Ok(ColorTemperatureInfo {
+            kelvin: kelvin.clamp(
+                crate::color_temperature::MIN_KELVIN,
+                crate::color_temperature::MAX_KELVIN,
+            ),
+            source: "gamma".to_string(),
+        })
+    }
+
+    fn get_monitor_color_temperature(
+        &self,
+        _device_name: String,
+    ) -> Result<ColorTemperatureInfo, String> {
+        Ok(ColorTemperatureInfo {
+            kelvin: crate::color_temperature::NEUTRAL_KELVIN,
+            source: "gamma".to_string(),
+        })
+    }
+
+    fn list_color_profiles(&self, device_name: String) -> Result<Vec<ColorProfileInfo>, String> {
+        let current = self.get_monitor_color_profile(device_name)?;
+        Ok(FAKE_COLOR_PROFILES
+            .iter()
+            .map(|name| ColorProfileInfo {
+                name: name.to_string(),
+                description: name
+                    .trim_end_matches(".icm")
+                    .trim_end_matches(".icc")
+                    .to_string(),
+                associated: true,
+                is_default: current.as_deref() == Some(*name),
+                white_point: None,
+                gamut: None,
+            })
+            .collect())
+    }
+
+    fn get_monitor_color_profile(&self, device_name: String) -> Result<Option<String>, String> {
+        Ok(color_profile_state()
+            .lock()
+            .ok()
+            .and_then(|state| state.get(&device_name).cloned()))
+    }
+
+    fn set_monitor_color_profile(
+        &self,
+        device_name: String,
+        profile: String,
+    ) -> Result<(), String> {
+        if !FAKE_COLOR_PROFILES.contains(&profile.as_str()) {
+            return Err(format!("Color profile '{}' is not installed", profile));
+        }
+        if let Ok(mut state) = color_profile_state().lock() {
+            state.insert(device_name, profile);
+        }
         Ok(())
     }
 
@@ -107,10 +305,61 @@ impl Displays for FakeDisplays {
         Ok(())
     }
 
+    fn set_custom_scale(&self, percent: Option<u32>) -> Result<(), String> {
+        use crate::dpi_scale::{MAX_CUSTOM_PERCENT, MIN_CUSTOM_PERCENT};
+        match percent {
+            Some(p) if !(MIN_CUSTOM_PERCENT..=MAX_CUSTOM_PERCENT).contains(&p) => Err(format!(
+                "Custom scale must be between {}% and {}%",
+                MIN_CUSTOM_PERCENT, MAX_CUSTOM_PERCENT
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn set_monitor_scaling_mode(&self, device_name: String, mode: String) -> Result<(), String> {
+        if !SCALING_MODES.contains(&mode.as_str()) {
+            return Err(format!(
+                "Unknown scaling mode '{}'. Supported: {}",
+                mode,
+                SCALING_MODES.join(", ")
+            ));
+        }
+        if let Ok(mut state) = scaling_mode_state().lock() {
+            state.insert(device_name, mode);
+        }
+        Ok(())
+    }
+
     fn enable_hdr(&self, _device_name: String, _enable: bool) -> Result<(), String> {
         Ok(())
     }
 
+    fn set_monitor_vrr(&self, device_name: String, enable: bool) -> Result<(), String> {
+        if device_name != FAKE_VRR_DEVICE {
+            return Err(format!(
+                "{} doesn't support variable refresh rate",
+                device_name
+            ));
+        }
+        if let Ok(mut state) = vrr_state().lock() {
+            state.insert(device_name, enable);
+        }
+        Ok(())
+    }
+
+    fn set_sdr_white_level(&self, _device_name: String, nits: u32) -> Result<(), String> {
+        if !(crate::displays::MIN_SDR_WHITE_NITS..=crate::displays::MAX_SDR_WHITE_NITS)
+            .contains(&nits)
+        {
+            return Err(format!(
+                "SDR white level must be between {} and {} nits",
+                crate::displays::MIN_SDR_WHITE_NITS,
+                crate::displays::MAX_SDR_WHITE_NITS
+            ));
+        }
+        Ok(())
+    }
+
     fn set_monitor_input_source(&self, _device_name: String, _input: String) -> Result<(), String> {
         // Pretend success in fake provider
         Ok(())
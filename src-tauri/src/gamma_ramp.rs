@@ -0,0 +1,261 @@
+// Pure gamma ramp math used for software brightness, dimming and color temperature.
+// The platform layer composes the per-monitor state into a ramp and uploads it.
+
+/// 256 entries per channel (R, G, B), as expected by SetDeviceGammaRamp.
+pub type GammaRamp = [[u16; 256]; 3];
+
+// Lowest multiplier we ever produce, so a "0%" software brightness never turns the screen black
+pub const MIN_LEVEL: f64 = 0.1;
+
+// GdiIcmGammaRange Windows uses when the registry doesn't set one: every ramp entry has to stay
+// within this many 256ths of full scale of the identity ramp, or SetDeviceGammaRamp refuses it.
+// 256 lifts the limit.
+pub const DEFAULT_GAMMA_RANGE: u32 = 128;
+
+/// Software adjustments applied on top of whatever the hardware is doing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GammaState {
+    // Software brightness in 0..=100 (used when the monitor has no DDC/CI or WMI control)
+    pub brightness: u32,
+    // Extra dimming in 0..=100, 100 = none; goes below the hardware minimum ("sub-zero")
+    pub dimming: u32,
+    // Per-channel white balance multipliers in 0..=1 (color temperature)
+    pub rgb: [f64; 3],
+}
+
+impl Default for GammaState {
+    fn default() -> Self {
+        Self {
+            brightness: 100,
+            dimming: 100,
+            rgb: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+impl GammaState {
+    /// True when the state leaves the picture untouched, so the identity ramp can be restored.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Per-channel multipliers combining brightness, dimming and white balance.
+    pub fn channel_levels(&self) -> [f64; 3] {
+        let scale = percent_to_level(self.brightness) * percent_to_level(self.dimming);
+        [
+            (scale * self.rgb[0]).clamp(0.0, 1.0),
+            (scale * self.rgb[1]).clamp(0.0, 1.0),
+            (scale * self.rgb[2]).clamp(0.0, 1.0),
+        ]
+    }
+}
+
+/// Maps a 0..=100 percentage onto MIN_LEVEL..=1.0.
+pub fn percent_to_level(percent: u32) -> f64 {
+    let t = percent.min(100) as f64 / 100.0;
+    MIN_LEVEL + (1.0 - MIN_LEVEL) * t
+}
+
+/// Inverse of `percent_to_level`, rounded to the nearest percent.
+pub fn level_to_percent(level: f64) -> u32 {
+    let t = (level.clamp(MIN_LEVEL, 1.0) - MIN_LEVEL) / (1.0 - MIN_LEVEL);
+    (t * 100.0).round() as u32
+}
+
+/// Builds a linear ramp scaled per channel by `levels` (each clamped to 0..=1).
+pub fn build_ramp(levels: [f64; 3]) -> Box<GammaRamp> {
+    let mut ramp: Box<GammaRamp> = Box::new([[0u16; 256]; 3]);
+    for (channel, level) in levels.iter().enumerate() {
+        let level = level.clamp(0.0, 1.0);
+        for i in 0..256 {
+            let value = (i as f64 * 257.0 * level).round();
+            ramp[channel][i] = value.clamp(0.0, 65535.0) as u16;
+        }
+    }
+    ramp
+}
+
+/// Lowest level whose ramp Windows accepts with GdiIcmGammaRange set to `range`; about 0.5 by
+/// default, so software brightness and dimming bottom out at half output there.
+pub fn min_level_for_range(range: u32) -> f64 {
+    let max_deviation = (range.min(256) * 256).saturating_sub(1) as f64;
+    (1.0 - max_deviation / 65535.0).max(0.0)
+}
+
+/// `build_ramp` with each level raised to what GdiIcmGammaRange `range` allows.
+pub fn build_ramp_within(levels: [f64; 3], range: u32) -> Box<GammaRamp> {
+    let min = min_level_for_range(range);
+    build_ramp(levels.map(|level| level.clamp(min, 1.0)))
+}
+
+/// Recovers the per-channel levels of a ramp produced by `build_ramp` (from its top entry).
+pub fn ramp_levels(ramp: &GammaRamp) -> [f64; 3] {
+    [
+        ramp[0][255] as f64 / 65535.0,
+        ramp[1][255] as f64 / 65535.0,
+        ramp[2][255] as f64 / 65535.0,
+    ]
+}
+
+/// Reads back a state from a ramp `build_ramp` produced, e.g. in another process. Brightness and
+/// dimming can't be told apart, so the whole scale reads as brightness. None for ramps we didn't
+/// produce, such as calibration curves.
+pub fn state_from_ramp(ramp: &GammaRamp) -> Option<GammaState> {
+    let levels = ramp_levels(ramp);
+    let linear = build_ramp(levels)
+        .iter()
+        .flatten()
+        .zip(ramp.iter().flatten())
+        .all(|(&expected, &actual)| expected.abs_diff(actual) <= 1);
+    let scale = levels.iter().cloned().fold(0.0_f64, f64::max);
+    if !linear || scale < MIN_LEVEL * 0.5 {
+        return None;
+    }
+    Some(GammaState {
+        brightness: level_to_percent(scale),
+        dimming: 100,
+        rgb: levels.map(|level| (level / scale).min(1.0)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_ramp() {
+        let ramp = build_ramp([1.0; 3]);
+        for channel in ramp.iter() {
+            assert_eq!(channel[0], 0);
+            assert_eq!(channel[128], 128 * 257);
+            assert_eq!(channel[255], 65535);
+        }
+        assert_eq!(ramp_levels(&ramp), [1.0; 3]);
+        assert!(GammaState::default().is_identity());
+    }
+
+    #[test]
+    fn levels_round_trip() {
+        for levels in [[0.5, 0.75, 0.25], [0.1, 0.1, 0.1], [1.0, 0.83, 0.61]] {
+            let back = ramp_levels(&build_ramp(levels));
+            for (a, b) in levels.iter().zip(back) {
+                assert!((a - b).abs() < 1.0 / 65535.0, "{:?} -> {:?}", levels, back);
+            }
+        }
+    }
+
+    #[test]
+    fn levels_are_clamped() {
+        let ramp = build_ramp([1.5, -0.5, f64::INFINITY]);
+        assert_eq!(ramp_levels(&ramp), [1.0, 0.0, 1.0]);
+        assert!(ramp[1].iter().all(|&v| v == 0));
+    }
+
+    #[test]
+    fn ramps_are_monotonic() {
+        for level in [0.1, 0.37, 0.5, 0.99, 1.0] {
+            let ramp = build_ramp([level; 3]);
+            for channel in ramp.iter() {
+                assert!(channel.windows(2).all(|w| w[0] <= w[1]), "level {}", level);
+            }
+        }
+    }
+
+    #[test]
+    fn ramps_stay_within_the_default_gamma_range() {
+        let min = min_level_for_range(DEFAULT_GAMMA_RANGE);
+        assert!((min - 0.5).abs() < 0.001, "{}", min);
+        assert_eq!(min_level_for_range(256), 0.0);
+        assert_eq!(min_level_for_range(1000), 0.0);
+
+        for levels in [[MIN_LEVEL; 3], [0.0, 0.3, 0.7], [1.0; 3]] {
+            let ramp = build_ramp_within(levels, DEFAULT_GAMMA_RANGE);
+            for channel in ramp.iter() {
+                for (i, &value) in channel.iter().enumerate() {
+                    let identity = i as u32 * 257;
+                    assert!(identity.abs_diff(value as u32) < DEFAULT_GAMMA_RANGE * 256);
+                }
+            }
+            for (level, back) in levels.iter().zip(ramp_levels(&ramp)) {
+                assert!(back >= min - 1e-4 && back >= level - 1e-4);
+            }
+        }
+        // Levels already in range are left alone, and a lifted limit allows the full depth
+        assert_eq!(
+            build_ramp_within([0.8, 0.6, 0.5], DEFAULT_GAMMA_RANGE),
+            build_ramp([0.8, 0.6, 0.5])
+        );
+        assert_eq!(
+            build_ramp_within([MIN_LEVEL; 3], 256),
+            build_ramp([MIN_LEVEL; 3])
+        );
+    }
+
+    #[test]
+    fn state_read_back_from_ramp() {
+        let identity = state_from_ramp(&build_ramp([1.0; 3])).unwrap();
+        assert!(identity.is_identity());
+
+        let state = GammaState {
+            brightness: 70,
+            dimming: 100,
+            rgb: [1.0, 0.8, 0.6],
+        };
+        let back = state_from_ramp(&build_ramp(state.channel_levels())).unwrap();
+        assert_eq!((back.brightness, back.dimming), (70, 100));
+        for (a, b) in state.rgb.iter().zip(back.rgb) {
+            assert!((a - b).abs() < 0.001, "{:?}", back.rgb);
+        }
+
+        // Dimming folds into brightness
+        let dimmed = GammaState {
+            brightness: 100,
+            dimming: 50,
+            ..GammaState::default()
+        };
+        let back = state_from_ramp(&build_ramp(dimmed.channel_levels())).unwrap();
+        assert_eq!(back.brightness, 50);
+
+        // A calibration curve isn't ours
+        let mut curve = build_ramp([1.0; 3]);
+        for channel in curve.iter_mut() {
+            for (i, value) in channel.iter_mut().enumerate() {
+                *value = ((i as f64 / 255.0).powf(1.2) * 65535.0).round() as u16;
+            }
+        }
+        assert_eq!(state_from_ramp(&curve), None);
+        assert_eq!(state_from_ramp(&build_ramp([0.0; 3])), None);
+    }
+
+    #[test]
+    fn percent_level_round_trip() {
+        assert_eq!(percent_to_level(0), MIN_LEVEL);
+        assert_eq!(percent_to_level(100), 1.0);
+        assert_eq!(percent_to_level(250), 1.0);
+        for percent in 0..=100 {
+            assert_eq!(level_to_percent(percent_to_level(percent)), percent);
+        }
+        assert_eq!(level_to_percent(0.0), 0);
+        assert_eq!(level_to_percent(2.0), 100);
+    }
+
+    #[test]
+    fn channel_levels_combine_brightness_dimming_and_white_balance() {
+        let state = GammaState {
+            brightness: 100,
+            dimming: 0,
+            rgb: [1.0, 0.5, 0.25],
+        };
+        assert!(!state.is_identity());
+        let levels = state.channel_levels();
+        assert!((levels[0] - MIN_LEVEL).abs() < 1e-9);
+        assert!((levels[1] - MIN_LEVEL * 0.5).abs() < 1e-9);
+        assert!((levels[2] - MIN_LEVEL * 0.25).abs() < 1e-9);
+        // Never above full output, even with gains over 1
+        let bright = GammaState {
+            rgb: [2.0, 1.0, 1.0],
+            ..GammaState::default()
+        };
+        assert_eq!(bright.channel_levels(), [1.0, 1.0, 1.0]);
+    }
+}
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::sync::Once;
+use std::sync::{Mutex, Once, OnceLock};
 use std::time::Instant;
 
 use global_hotkey::{
@@ -8,12 +8,104 @@ use global_hotkey::{
 };
 use global_hotkey::{GlobalHotKeyEvent, HotKeyState};
 use tauri::{AppHandle, Emitter};
+use windows::Win32::Foundation::{LPARAM, WPARAM};
+use windows::Win32::System::Threading::GetCurrentThreadId;
 use windows::Win32::UI::WindowsAndMessaging::{
-    DispatchMessageW, GetMessageW, TranslateMessage, MSG,
+    DispatchMessageW, GetMessageW, PostThreadMessageW, TranslateMessage, MSG, WM_APP,
 };
 
+use crate::kvm::KvmPreset;
+use crate::settings::KeyboardBrightnessShortcut;
+
+// Posted to the hotkey thread when settings change (brightness keys, KVM preset hotkeys)
+const WM_APP_SETTINGS_CHANGED: u32 = WM_APP + 1;
+
 static START_ONCE: Once = Once::new();
 
+fn wants_hotkeys(app_handle: &AppHandle) -> bool {
+    crate::settings::get(app_handle).keyboard_brightness_shortcut
+        != KeyboardBrightnessShortcut::System
+}
+
+// Registers F14/F15 (brightness keys) or releases them back to Windows
+fn sync_registration(
+    manager: &GlobalHotKeyManager,
+    hotkeys: &[(HotKey, i32)],
+    registered: &mut bool,
+    wanted: bool,
+) {
+    if wanted == *registered {
+        return;
+    }
+    for (hk, _) in hotkeys {
+        let result = if wanted {
+            manager.register(*hk)
+        } else {
+            manager.unregister(*hk)
+        };
+        match result {
+            Ok(()) if wanted => log::info!("Registered hotkey id={}", hk.id()),
+            Ok(()) => log::info!("Unregistered hotkey id={}", hk.id()),
+            Err(e) => log::warn!("Failed to update hotkey id={}: {e}", hk.id()),
+        }
+    }
+    *registered = wanted;
+}
+
+// KVM preset hotkeys currently registered: hotkey id -> preset name
+fn kvm_hotkeys() -> &'static Mutex<HashMap<u32, String>> {
+    static KVM_HOTKEYS: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+    KVM_HOTKEYS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Registers the hotkeys of settings.kvmPresets, replacing the previous set when it changed
+fn sync_kvm_hotkeys(
+    manager: &GlobalHotKeyManager,
+    registered: &mut Vec<(HotKey, String)>,
+    presets: &[KvmPreset],
+) {
+    let wanted: Vec<(HotKey, String)> = presets
+        .iter()
+        .filter(|p| !p.hotkey.is_empty())
+        .filter_map(|p| match p.hotkey.parse::<HotKey>() {
+            Ok(hk) => Some((hk, p.name.clone())),
+            Err(e) => {
+                log::warn!(
+                    "Invalid hotkey '{}' for KVM preset '{}': {e}",
+                    p.hotkey,
+                    p.name
+                );
+                None
+            }
+        })
+        .collect();
+    if wanted == *registered {
+        return;
+    }
+    for (hk, _) in registered.drain(..) {
+        let _ = manager.unregister(hk);
+    }
+    for (hk, name) in wanted {
+        match manager.register(hk) {
+            Ok(()) => {
+                log::info!("Registered hotkey {} for KVM preset '{}'", hk, name);
+                registered.push((hk, name));
+            }
+            Err(e) => log::warn!(
+                "Failed to register hotkey {} for KVM preset '{}': {e}",
+                hk,
+                name
+            ),
+        }
+    }
+    if let Ok(mut ids) = kvm_hotkeys().lock() {
+        *ids = registered
+            .iter()
+            .map(|(hk, name)| (hk.id(), name.clone()))
+            .collect();
+    }
+}
+
 pub fn start_hotkey_service(app_handle: AppHandle) {
     START_ONCE.call_once(|| {
         // Thread that owns the Win32 message loop and the hotkey manager
@@ -27,19 +119,29 @@ pub fn start_hotkey_service(app_handle: AppHandle) {
                 }
             };
 
-            // Register brightness keys and F14/F15 and map ids to deltas
-            let mut id_to_delta: HashMap<u32, i32> = HashMap::new();
-            for (code, delta) in [(Code::F15, 5), (Code::F14, -5)] {
-                let hk = HotKey::new(None::<Modifiers>, code);
-                if let Err(e) = manager.register(hk) {
-                    log::warn!("Failed to register hotkey for {:?}: {e}", code);
-                } else {
-                    id_to_delta.insert(hk.id(), delta);
-                    log::info!("Registered hotkey for {:?} (id={})", code, hk.id());
-                }
-            }
+            // Brightness keys F14/F15 and their deltas; only registered while the setting asks
+            let hotkeys: Vec<(HotKey, i32)> = [(Code::F15, 5), (Code::F14, -5)]
+                .into_iter()
+                .map(|(code, delta)| (HotKey::new(None::<Modifiers>, code), delta))
+                .collect();
+            let id_to_delta: HashMap<u32, i32> = hotkeys
+                .iter()
+                .map(|(hk, delta)| (hk.id(), *delta))
+                .collect();
+            let mut registered = false;
+            sync_registration(
+                &manager,
+                &hotkeys,
+                &mut registered,
+                wants_hotkeys(&app_handle),
+            );
+            let mut kvm_registered: Vec<(HotKey, String)> = Vec::new();
+            sync_kvm_hotkeys(
+                &manager,
+                &mut kvm_registered,
+                &crate::settings::get(&app_handle).kvm_presets,
+            );
 
-            let id_to_delta_listener = id_to_delta.clone();
             let app_for_events = app_handle.clone();
 
             // Separate listener thread for hotkey events
@@ -48,10 +150,18 @@ pub fn start_hotkey_service(app_handle: AppHandle) {
                     Ok(event) => {
                         // Only react on key press
                         if event.state == HotKeyState::Pressed {
-                            if let Some(delta) = id_to_delta_listener.get(&event.id) {
-                                if adjust_brightness_all(*delta) {
+                            if let Some(delta) = id_to_delta.get(&event.id) {
+                                if adjust_brightness_all(&app_for_events, *delta) {
                                     let _ = app_for_events.emit("brightness-changed", ());
                                 }
+                            } else if let Some(name) = kvm_hotkeys()
+                                .lock()
+                                .ok()
+                                .and_then(|ids| ids.get(&event.id).cloned())
+                            {
+                                // Switching inputs takes seconds; keep handling keys meanwhile
+                                let app = app_for_events.clone();
+                                std::thread::spawn(move || crate::kvm::run_preset(&app, &name));
                             }
                         }
                     }
@@ -59,9 +169,37 @@ pub fn start_hotkey_service(app_handle: AppHandle) {
                 }
             });
 
+            // Settings edits wake the message loop; hotkeys must be (un)registered on this thread
+            let thread_id = GetCurrentThreadId();
+            let settings_rx = crate::settings::subscribe();
+            std::thread::spawn(move || {
+                while settings_rx.recv().is_ok() {
+                    let _ = PostThreadMessageW(
+                        thread_id,
+                        WM_APP_SETTINGS_CHANGED,
+                        WPARAM(0),
+                        LPARAM(0),
+                    );
+                }
+            });
+
             // Minimal Win32 message loop to satisfy Windows requirement
             let mut message: MSG = MSG::default();
             while GetMessageW(&mut message, None, 0, 0).into() {
+                if message.hwnd.0 == 0 && message.message == WM_APP_SETTINGS_CHANGED {
+                    sync_registration(
+                        &manager,
+                        &hotkeys,
+                        &mut registered,
+                        wants_hotkeys(&app_handle),
+                    );
+                    sync_kvm_hotkeys(
+                        &manager,
+                        &mut kvm_registered,
+                        &crate::settings::get(&app_handle).kvm_presets,
+                    );
+                    continue;
+                }
                 let _ = TranslateMessage(&message);
                 let _ = DispatchMessageW(&message);
             }
@@ -69,8 +207,14 @@ pub fn start_hotkey_service(app_handle: AppHandle) {
     });
 }
 
-fn adjust_brightness_all(delta: i32) -> bool {
+fn adjust_brightness_all(app_handle: &AppHandle, delta: i32) -> bool {
     let provider = crate::displays::active_provider();
+
+    // Linked mode moves every monitor to the same logical brightness through its calibration curve
+    if crate::settings::get(app_handle).link_brightness {
+        return adjust_linked_brightness(app_handle, provider.as_ref(), delta);
+    }
+
     let mut max_times_ns: HashMap<&'static str, u128> = HashMap::new();
     let mut update_max = |label: &'static str, elapsed_ns: u128| {
         let entry = max_times_ns.entry(label).or_insert(0);
@@ -107,6 +251,7 @@ fn adjust_brightness_all(delta: i32) -> bool {
                 }
                 let next_u32 = next as u32;
                 if next_u32 != info.current {
+                    crate::brightness_fade::cancel_fade(&name);
                     let t_set_start = Instant::now();
                     match provider.set_monitor_brightness(name.clone(), next_u32) {
                         Ok(()) => {
@@ -119,6 +264,7 @@ fn adjust_brightness_all(delta: i32) -> bool {
                                 info.min,
                                 info.max
                             );
+                            crate::brightness_schedule::note_manual_override(&name);
                             any_changed = true;
                         }
                         Err(e) => log::warn!("Failed to set brightness for {}: {}", name, e),
@@ -140,3 +286,28 @@ fn adjust_brightness_all(delta: i32) -> bool {
     }
     any_changed
 }
+
+fn adjust_linked_brightness(
+    app_handle: &AppHandle,
+    provider: &dyn crate::displays::Displays,
+    delta: i32,
+) -> bool {
+    let Some(first) = provider
+        .get_all_monitors_short()
+        .ok()
+        .and_then(|names| names.into_iter().next())
+    else {
+        return false;
+    };
+    let current =
+        match crate::brightness_calibration::logical_brightness_of(app_handle, provider, &first) {
+            Ok(v) => v as i32,
+            Err(e) => {
+                log::warn!("Failed to read brightness for {}: {}", first, e);
+                return false;
+            }
+        };
+    let next = (current + delta).clamp(0, 100) as u32;
+    log::info!("Set linked brightness from {} to {}", current, next);
+    crate::brightness_calibration::apply_logical_brightness(app_handle, provider, next, None)
+}
@@ -0,0 +1,955 @@
+// Optional REST and WebSocket API on 127.0.0.1 for home automation (Home Assistant, Stream Deck,
+// ...). Every request needs the API token; calls go through the same JSON-RPC dispatcher as the
+// control pipe, so the UI and the brightness schedule see changes made here. Only the methods in
+// `ALLOWED_METHODS` are reachable; configuration, modes and scaling stay with the app and CLI.
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, Once, OnceLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Listener};
+
+use crate::displays::DisplayInfo;
+use crate::rpc;
+use crate::settings::HttpApiSettings;
+use crate::websocket;
+
+const MAX_LINE: u64 = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+const MAX_BODY: usize = 1024 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+// The whole request must arrive within this, however slowly its bytes trickle in
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+// Connections served at once, WebSocket clients included; more are turned away with a 503
+const MAX_CONNECTIONS: usize = 32;
+// WebSocket clients get a ping this often so dead connections are noticed
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+// App events pushed to WebSocket clients
+const EVENTS: [&str; 2] = ["display-changed", "brightness-changed"];
+// Random bytes in the API token (hex encoded, so twice as many characters)
+const TOKEN_BYTES: usize = 32;
+// JSON-RPC methods callable from /api/rpc and the WebSocket: monitors, brightness, input, power,
+// HDR and display profiles. Anything writing files or changing the desktop layout is left out.
+const ALLOWED_METHODS: [&str; 11] = [
+    "get_all_monitors",
+    "get_monitor_brightness",
+    "set_monitor_brightness",
+    "fade_monitor_brightness",
+    "get_monitor_input_source",
+    "set_monitor_input_source",
+    "list_monitor_inputs",
+    "set_monitor_power",
+    "enable_hdr",
+    "list_display_profiles",
+    "apply_display_profile",
+];
+
+fn token_cache() -> &'static Mutex<Option<String>> {
+    static TOKEN: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+    TOKEN.get_or_init(|| Mutex::new(None))
+}
+
+#[cfg(target_os = "windows")]
+fn fill_random(buf: &mut [u8]) -> Result<(), String> {
+    use windows::Win32::Security::Cryptography::{
+        BCryptGenRandom, BCRYPT_ALG_HANDLE, BCRYPT_USE_SYSTEM_PREFERRED_RNG,
+    };
+    unsafe {
+        BCryptGenRandom(
+            BCRYPT_ALG_HANDLE::default(),
+            buf,
+            BCRYPT_USE_SYSTEM_PREFERRED_RNG,
+        )
+    }
+    .ok()
+    .map_err(|e| format!("BCryptGenRandom failed: {}", e))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn fill_random(buf: &mut [u8]) -> Result<(), String> {
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(buf))
+        .map_err(|e| format!("Failed to read /dev/urandom: {}", e))
+}
+
+// From the OS CSPRNG; there is no fallback, a guessable token would be worse than none
+fn new_token() -> Result<String, String> {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    fill_random(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// The API token, created the first time it's needed
+fn token(app_handle: &AppHandle) -> Result<String, String> {
+    if let Some(token) = token_cache().lock().ok().and_then(|cache| cache.clone()) {
+        return Ok(token);
+    }
+    let mut token = crate::settings::http_api_token_handle(app_handle);
+    // Tokens saved by older versions weren't drawn from the CSPRNG; they're replaced once
+    if token.len() != TOKEN_BYTES * 2 {
+        if !token.is_empty() {
+            log::info!("Replacing the local API token with a stronger one");
+        }
+        token = new_token()?;
+        if let Err(e) = crate::settings::save_http_api_token_handle(app_handle, &token) {
+            log::warn!("Failed to save the local API token: {}", e);
+        }
+    }
+    if let Ok(mut cache) = token_cache().lock() {
+        *cache = Some(token.clone());
+    }
+    Ok(token)
+}
+
+// Same time whatever the first wrong character is
+fn token_matches(given: &str, expected: &str) -> bool {
+    !expected.is_empty()
+        && given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn clients() -> &'static Mutex<Vec<Sender<String>>> {
+    static CLIENTS: OnceLock<Mutex<Vec<Sender<String>>>> = OnceLock::new();
+    CLIENTS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn broadcast(message: String) {
+    if let Ok(mut clients) = clients().lock() {
+        clients.retain(|tx| tx.send(message.clone()).is_ok());
+    }
+}
+
+// Dropping the senders ends every WebSocket connection
+fn disconnect_clients() {
+    if let Ok(mut clients) = clients().lock() {
+        clients.clear();
+    }
+}
+
+struct Request {
+    method: String,
+    path: Vec<String>,
+    query: HashMap<String, String>,
+    // Lowercase names
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = s
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// Fails reads once `until` has passed, so a client can't hold a connection open by sending a
+// byte just inside every read timeout
+struct Deadline<R> {
+    inner: R,
+    until: Instant,
+}
+
+impl<R: Read> Read for Deadline<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if Instant::now() >= self.until {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "Request took too long",
+            ));
+        }
+        self.inner.read(buf)
+    }
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> Result<String, String> {
+    let mut line = String::new();
+    reader
+        .take(MAX_LINE)
+        .read_line(&mut line)
+        .map_err(|e| e.to_string())?;
+    Ok(line.trim_end().to_string())
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> Result<Request, String> {
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or("Empty request")?.to_string();
+    let target = parts.next().ok_or("Missing request target")?;
+
+    let mut headers = HashMap::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err("Too many headers".to_string());
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY {
+        return Err("Request body too large".to_string());
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    Ok(Request {
+        method,
+        path: path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(percent_decode)
+            .collect(),
+        query: query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(k, v)| (percent_decode(k), percent_decode(v)))
+            .collect(),
+        headers,
+        body,
+    })
+}
+
+fn respond(stream: &mut TcpStream, status: u16, body: &str) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// "Authorization: Bearer <token>", or "?token=" for WebSocket clients that can't set headers
+fn request_token(request: &Request) -> Option<&str> {
+    request
+        .headers
+        .get("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .or_else(|| request.query.get("token").map(String::as_str))
+}
+
+fn authorized(app_handle: &AppHandle, request: &Request) -> bool {
+    match token(app_handle) {
+        Ok(expected) => request_token(request).is_some_and(|given| token_matches(given, &expected)),
+        Err(e) => {
+            log::warn!("Local API has no token: {}", e);
+            false
+        }
+    }
+}
+
+// Index, device name (with or without the \\.\ prefix) or EDID identity
+fn find_monitor(id: &str) -> Result<DisplayInfo, (u16, String)> {
+    let monitors = crate::displays::get_all_monitors().map_err(|e| (500, e))?;
+    let found = match id.parse::<usize>() {
+        Ok(index) => monitors.get(index).cloned(),
+        Err(_) => monitors.into_iter().find(|m| {
+            m.device_name.eq_ignore_ascii_case(id)
+                || m.device_name
+                    .trim_start_matches(r"\\.\")
+                    .eq_ignore_ascii_case(id)
+                || crate::monitor_identity::identity_key(m) == id
+        }),
+    };
+    found.ok_or_else(|| (404, format!("Monitor '{}' not found", id)))
+}
+
+fn check_allowed(method: &str) -> Result<(), rpc::RpcError> {
+    if ALLOWED_METHODS.contains(&method) {
+        Ok(())
+    } else {
+        Err(rpc::RpcError {
+            code: rpc::METHOD_NOT_FOUND,
+            message: format!("Method '{}' isn't available over the local API", method),
+        })
+    }
+}
+
+// JSON-RPC from /api/rpc and WebSocket clients, limited to `ALLOWED_METHODS`
+fn handle_rpc(app_handle: &AppHandle, line: &str) -> String {
+    rpc::respond(line, |method, params| {
+        check_allowed(method)?;
+        rpc::dispatch(app_handle, method, params)
+    })
+}
+
+fn call(app_handle: &AppHandle, method: &str, params: Value) -> Result<Value, (u16, String)> {
+    rpc::dispatch(app_handle, method, params).map_err(|e| {
+        let status = match e.code {
+            rpc::INVALID_PARAMS => 400,
+            rpc::METHOD_NOT_FOUND => 404,
+            _ => 500,
+        };
+        (status, e.message)
+    })
+}
+
+// What a REST route resolves to before any monitor is looked up
+#[derive(Debug, PartialEq)]
+enum Route<'a> {
+    // One monitor's DisplayInfo
+    Monitor(&'a str),
+    // A JSON-RPC method; `monitor` (index, device name or identity) becomes its "deviceName"
+    Call {
+        method: &'static str,
+        monitor: Option<&'a str>,
+        params: Map<String, Value>,
+    },
+}
+
+fn rpc_call<'a>(
+    method: &'static str,
+    monitor: Option<&'a str>,
+    params: Map<String, Value>,
+) -> Route<'a> {
+    Route::Call {
+        method,
+        monitor,
+        params,
+    }
+}
+
+fn match_route<'a>(
+    method: &str,
+    path: &'a [String],
+    body: &Map<String, Value>,
+) -> Option<Route<'a>> {
+    let path: Vec<&'a str> = path.iter().map(String::as_str).collect();
+    let read = method == "GET";
+    // Stream Deck and Home Assistant actions tend to send POST; both are accepted
+    let write = method == "PUT" || method == "POST";
+
+    let route = match path.as_slice() {
+        ["api", "monitors"] if read => rpc_call("get_all_monitors", None, Map::new()),
+        ["api", "monitors", id] if read => Route::Monitor(id),
+        ["api", "monitors", id, "brightness"] if read => {
+            rpc_call("get_monitor_brightness", Some(id), Map::new())
+        }
+        // {"percent": 40}, or {"percent": 40, "durationMs": 2000} to fade
+        ["api", "monitors", id, "brightness"] if write => {
+            let mut params = body.clone();
+            if body.contains_key("durationMs") {
+                let target = body.get("percent").cloned().unwrap_or(Value::Null);
+                params.insert("target".to_string(), target);
+                rpc_call("fade_monitor_brightness", Some(id), params)
+            } else {
+                rpc_call("set_monitor_brightness", Some(id), params)
+            }
+        }
+        ["api", "monitors", id, "input"] if read => {
+            rpc_call("get_monitor_input_source", Some(id), Map::new())
+        }
+        // {"input": "hdmi1"}
+        ["api", "monitors", id, "input"] if write => {
+            rpc_call("set_monitor_input_source", Some(id), body.clone())
+        }
+        // {"powerOn": false}
+        ["api", "monitors", id, "power"] if write => {
+            rpc_call("set_monitor_power", Some(id), body.clone())
+        }
+        // {"enable": true}
+        ["api", "monitors", id, "hdr"] if write => rpc_call("enable_hdr", Some(id), body.clone()),
+        ["api", "profiles"] if read => rpc_call("list_display_profiles", None, Map::new()),
+        ["api", "profiles", name, "apply"] if write => {
+            let mut params = Map::new();
+            params.insert("name".to_string(), Value::String(name.to_string()));
+            rpc_call("apply_display_profile", None, params)
+        }
+        _ => return None,
+    };
+    Some(route)
+}
+
+fn route(app_handle: &AppHandle, request: &Request) -> Result<Value, (u16, String)> {
+    let body: Map<String, Value> = if request.body.is_empty() {
+        Map::new()
+    } else {
+        serde_json::from_slice(&request.body)
+            .map_err(|e| (400, format!("Body must be a JSON object: {}", e)))?
+    };
+    match match_route(&request.method, &request.path, &body) {
+        Some(Route::Monitor(id)) => {
+            serde_json::to_value(find_monitor(id)?).map_err(|e| (500, e.to_string()))
+        }
+        Some(Route::Call {
+            method,
+            monitor,
+            mut params,
+        }) => {
+            if let Some(id) = monitor {
+                params.insert(
+                    "deviceName".to_string(),
+                    Value::String(find_monitor(id)?.device_name),
+                );
+            }
+            call(app_handle, method, Value::Object(params))
+        }
+        None => Err((
+            404,
+            format!(
+                "No route for {} /{}",
+                request.method,
+                request.path.join("/")
+            ),
+        )),
+    }
+}
+
+fn send_frame(writer: &Mutex<TcpStream>, opcode: u8, payload: &[u8]) -> bool {
+    match writer.lock() {
+        Ok(mut stream) => websocket::write_frame(&mut *stream, opcode, payload).is_ok(),
+        Err(_) => false,
+    }
+}
+
+// Pushes app events to the client and answers JSON-RPC requests it sends as text messages
+fn serve_websocket(
+    app_handle: &AppHandle,
+    mut stream: TcpStream,
+    key: &str,
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        websocket::accept_key(key)
+    )?;
+    stream.set_read_timeout(None)?;
+    let writer = Arc::new(Mutex::new(stream.try_clone()?));
+
+    let (tx, rx) = mpsc::channel::<String>();
+    if let Ok(mut clients) = clients().lock() {
+        clients.push(tx);
+    }
+    let event_writer = writer.clone();
+    let event_stream = stream.try_clone()?;
+    std::thread::spawn(move || {
+        loop {
+            let sent = match rx.recv_timeout(PING_INTERVAL) {
+                Ok(message) => send_frame(&event_writer, websocket::OP_TEXT, message.as_bytes()),
+                Err(RecvTimeoutError::Timeout) => {
+                    send_frame(&event_writer, websocket::OP_PING, &[])
+                }
+                Err(RecvTimeoutError::Disconnected) => false,
+            };
+            if !sent {
+                break;
+            }
+        }
+        let _ = event_stream.shutdown(Shutdown::Both);
+    });
+
+    while let Ok((opcode, payload)) = websocket::read_frame(&mut stream) {
+        let sent = match opcode {
+            websocket::OP_TEXT => {
+                let response = handle_rpc(app_handle, &String::from_utf8_lossy(&payload));
+                send_frame(&writer, websocket::OP_TEXT, response.as_bytes())
+            }
+            websocket::OP_PING => send_frame(&writer, websocket::OP_PONG, &payload),
+            websocket::OP_CLOSE => {
+                send_frame(&writer, websocket::OP_CLOSE, &payload);
+                false
+            }
+            _ => true,
+        };
+        if !sent {
+            break;
+        }
+    }
+    stream.shutdown(Shutdown::Both)
+}
+
+fn handle_connection(app_handle: &AppHandle, mut stream: TcpStream) {
+    let request = match stream
+        .try_clone()
+        .map_err(|e| e.to_string())
+        .and_then(|clone| {
+            read_request(&mut BufReader::new(Deadline {
+                inner: clone,
+                until: Instant::now() + REQUEST_TIMEOUT,
+            }))
+        }) {
+        Ok(request) => request,
+        Err(e) => {
+            let _ = respond(&mut stream, 400, &json!({ "error": e }).to_string());
+            return;
+        }
+    };
+
+    if !authorized(app_handle, &request) {
+        let body = json!({ "error": "Missing or invalid token" }).to_string();
+        let _ = respond(&mut stream, 401, &body);
+        return;
+    }
+
+    let result = match request.path.as_slice() {
+        [api, events] if api == "api" && events == "events" => {
+            match request.headers.get("sec-websocket-key") {
+                Some(key) => {
+                    let key = key.clone();
+                    if let Err(e) = serve_websocket(app_handle, stream, &key) {
+                        log::debug!("Local API WebSocket closed: {}", e);
+                    }
+                    return;
+                }
+                None => respond(
+                    &mut stream,
+                    400,
+                    &json!({ "error": "Expected a WebSocket upgrade" }).to_string(),
+                ),
+            }
+        }
+        // Raw JSON-RPC, limited to ALLOWED_METHODS
+        [api, rpc] if api == "api" && rpc == "rpc" && request.method == "POST" => {
+            let response = handle_rpc(app_handle, &String::from_utf8_lossy(&request.body));
+            respond(&mut stream, 200, &response)
+        }
+        _ => match route(app_handle, &request) {
+            Ok(Value::Null) => respond(&mut stream, 204, ""),
+            Ok(value) => respond(&mut stream, 200, &value.to_string()),
+            Err((status, message)) => respond(
+                &mut stream,
+                status,
+                &json!({ "error": message }).to_string(),
+            ),
+        },
+    };
+    if let Err(e) = result {
+        log::debug!("Local API response failed: {}", e);
+    }
+}
+
+// One of the `MAX_CONNECTIONS` connection threads, given back when dropped
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    fn take(active: &Arc<AtomicUsize>) -> Option<Self> {
+        active
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < MAX_CONNECTIONS).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Self(active.clone()))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn accept_loop(app_handle: AppHandle, listener: TcpListener, stop: Arc<AtomicBool>) {
+    // Non-blocking so a settings change can stop the server between connections
+    if let Err(e) = listener.set_nonblocking(true) {
+        log::warn!("Local API can't poll for connections: {}", e);
+        return;
+    }
+    let active = Arc::new(AtomicUsize::new(0));
+    while !stop.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((mut stream, _)) => {
+                // Timeouts go on before anything is read, so a silent client can't hold a slot
+                let prepared = stream
+                    .set_nonblocking(false)
+                    .and_then(|_| stream.set_read_timeout(Some(READ_TIMEOUT)))
+                    .and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT)));
+                if let Err(e) = prepared {
+                    log::debug!("Local API connection dropped: {}", e);
+                    continue;
+                }
+                let Some(slot) = ConnectionSlot::take(&active) else {
+                    let body = json!({ "error": "Too many connections" }).to_string();
+                    let _ = respond(&mut stream, 503, &body);
+                    continue;
+                };
+                let app_handle = app_handle.clone();
+                std::thread::spawn(move || {
+                    handle_connection(&app_handle, stream);
+                    drop(slot);
+                });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(ACCEPT_POLL),
+            Err(e) => {
+                log::warn!("Local API connection failed: {}", e);
+                std::thread::sleep(ACCEPT_POLL);
+            }
+        }
+    }
+}
+
+struct Server {
+    port: u16,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+// Starts, stops or moves the server to match the settings
+fn sync_server(app_handle: &AppHandle, server: &mut Option<Server>, settings: &HttpApiSettings) {
+    let wanted_port = settings.enabled.then_some(settings.port);
+    if server.as_ref().map(|s| s.port) == wanted_port {
+        return;
+    }
+    if let Some(old) = server.take() {
+        old.stop.store(true, Ordering::SeqCst);
+        let _ = old.thread.join();
+        disconnect_clients();
+        log::info!("Local API on port {} stopped", old.port);
+    }
+    let Some(port) = wanted_port else {
+        return;
+    };
+    match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => {
+            let stop = Arc::new(AtomicBool::new(false));
+            let stop_for_thread = stop.clone();
+            let app_handle = app_handle.clone();
+            let thread =
+                std::thread::spawn(move || accept_loop(app_handle, listener, stop_for_thread));
+            log::info!("Local API listening on http://127.0.0.1:{}", port);
+            *server = Some(Server { port, stop, thread });
+        }
+        Err(e) => log::warn!("Failed to start the local API on port {}: {}", port, e),
+    }
+}
+
+static START_ONCE: Once = Once::new();
+
+pub fn start_http_api_service(app_handle: AppHandle) {
+    START_ONCE.call_once(|| {
+        for event in EVENTS {
+            app_handle.listen(event, move |e| {
+                let payload = serde_json::from_str::<Value>(e.payload()).unwrap_or(Value::Null);
+                broadcast(json!({ "event": event, "payload": payload }).to_string());
+            });
+        }
+        let settings_rx = crate::settings::subscribe();
+        std::thread::spawn(move || {
+            let mut server = None;
+            let mut settings = crate::settings::get(&app_handle).http_api;
+            loop {
+                sync_server(&app_handle, &mut server, &settings);
+                match settings_rx.recv() {
+                    Ok(new_settings) => settings = new_settings.http_api,
+                    Err(_) => break,
+                }
+            }
+        });
+    });
+}
+
+#[tauri::command]
+pub fn get_http_api_token(app_handle: AppHandle) -> Result<String, String> {
+    token(&app_handle)
+}
+
+/// Replaces the token; clients using the old one are disconnected.
+#[tauri::command]
+pub fn regenerate_http_api_token(app_handle: AppHandle) -> Result<String, String> {
+    let token = new_token()?;
+    crate::settings::save_http_api_token_handle(&app_handle, &token)?;
+    if let Ok(mut cache) = token_cache().lock() {
+        *cache = Some(token.clone());
+    }
+    disconnect_clients();
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const TOKEN: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    fn parse(raw: &str) -> Result<Request, String> {
+        read_request(&mut Cursor::new(raw.as_bytes().to_vec()))
+    }
+
+    fn get(target: &str, headers: &str) -> Request {
+        parse(&format!("GET {} HTTP/1.1\r\n{}\r\n", target, headers)).unwrap()
+    }
+
+    fn body(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    fn route_of<'a>(method: &str, path: &'a [String], request_body: Value) -> Option<Route<'a>> {
+        match_route(method, path, &body(request_body))
+    }
+
+    fn path(target: &str) -> Vec<String> {
+        get(target, "").path
+    }
+
+    #[test]
+    fn parses_request_line_headers_and_body() {
+        let request = parse(
+            "POST /api/monitors/%5C%5C.%5CDISPLAY1/brightness?token=abc&x=1%202 HTTP/1.1\r\n\
+             Host: 127.0.0.1\r\nContent-Type: application/json\r\nContent-Length: 15\r\n\r\n\
+             {\"percent\": 40}",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(
+            request.path,
+            ["api", "monitors", r"\\.\DISPLAY1", "brightness"]
+        );
+        assert_eq!(request.query["token"], "abc");
+        assert_eq!(request.query["x"], "1 2");
+        assert_eq!(request.headers["content-type"], "application/json");
+        assert_eq!(request.body, b"{\"percent\": 40}");
+    }
+
+    #[test]
+    fn rejects_malformed_requests() {
+        assert!(parse("").is_err());
+        assert!(parse("GET\r\n\r\n").is_err());
+        let too_many: String = (0..=MAX_HEADERS)
+            .map(|i| format!("X-{}: 1\r\n", i))
+            .collect();
+        assert!(parse(&format!("GET / HTTP/1.1\r\n{}\r\n", too_many)).is_err());
+        let too_large = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        assert!(parse(&too_large).is_err());
+        // Shorter body than announced
+        assert!(parse("POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}").is_err());
+    }
+
+    #[test]
+    fn request_stops_at_the_deadline() {
+        let mut reader = BufReader::new(Deadline {
+            inner: Cursor::new(b"GET / HTTP/1.1\r\n\r\n".to_vec()),
+            until: Instant::now(),
+        });
+        assert!(read_request(&mut reader).is_err());
+    }
+
+    #[test]
+    fn token_from_header_or_query() {
+        let header = get(
+            "/api/monitors",
+            &format!("Authorization: Bearer {}\r\n", TOKEN),
+        );
+        assert_eq!(request_token(&header), Some(TOKEN));
+        let query = get(&format!("/api/events?token={}", TOKEN), "");
+        assert_eq!(request_token(&query), Some(TOKEN));
+        assert_eq!(request_token(&get("/api/monitors", "")), None);
+        // Only the Bearer scheme is accepted
+        let basic = get(
+            "/api/monitors",
+            &format!("Authorization: Basic {}\r\n", TOKEN),
+        );
+        assert_eq!(request_token(&basic), None);
+    }
+
+    #[test]
+    fn token_check() {
+        assert!(token_matches(TOKEN, TOKEN));
+        // Wrong in the first or only the last character, a prefix, or longer
+        let first = format!("1{}", &TOKEN[1..]);
+        let last = format!("{}0", &TOKEN[..TOKEN.len() - 1]);
+        assert!(!token_matches(&first, TOKEN));
+        assert!(!token_matches(&last, TOKEN));
+        assert!(!token_matches(&TOKEN[..10], TOKEN));
+        assert!(!token_matches(&format!("{}0", TOKEN), TOKEN));
+        assert!(!token_matches("", TOKEN));
+        // No token configured never matches, not even an empty one
+        assert!(!token_matches("", ""));
+    }
+
+    #[test]
+    fn routes_map_to_methods() {
+        let cases: [(&str, &str, Value, &str, Option<&str>); 10] = [
+            ("GET", "/api/monitors", json!({}), "get_all_monitors", None),
+            (
+                "GET",
+                "/api/monitors/1/brightness",
+                json!({}),
+                "get_monitor_brightness",
+                Some("1"),
+            ),
+            (
+                "PUT",
+                "/api/monitors/1/brightness",
+                json!({"percent": 40}),
+                "set_monitor_brightness",
+                Some("1"),
+            ),
+            (
+                "POST",
+                "/api/monitors/DISPLAY2/brightness",
+                json!({"percent": 40, "durationMs": 500}),
+                "fade_monitor_brightness",
+                Some("DISPLAY2"),
+            ),
+            (
+                "GET",
+                "/api/monitors/0/input",
+                json!({}),
+                "get_monitor_input_source",
+                Some("0"),
+            ),
+            (
+                "POST",
+                "/api/monitors/0/input",
+                json!({"input": "hdmi1"}),
+                "set_monitor_input_source",
+                Some("0"),
+            ),
+            (
+                "POST",
+                "/api/monitors/0/power",
+                json!({"powerOn": false}),
+                "set_monitor_power",
+                Some("0"),
+            ),
+            (
+                "PUT",
+                "/api/monitors/0/hdr",
+                json!({"enable": true}),
+                "enable_hdr",
+                Some("0"),
+            ),
+            (
+                "GET",
+                "/api/profiles",
+                json!({}),
+                "list_display_profiles",
+                None,
+            ),
+            (
+                "POST",
+                "/api/profiles/Gaming/apply",
+                json!({}),
+                "apply_display_profile",
+                None,
+            ),
+        ];
+        for (verb, target, request_body, expected, expected_monitor) in cases {
+            let path = path(target);
+            match route_of(verb, &path, request_body) {
+                Some(Route::Call {
+                    method, monitor, ..
+                }) => {
+                    assert_eq!(method, expected, "{} {}", verb, target);
+                    assert_eq!(monitor, expected_monitor, "{} {}", verb, target);
+                    assert!(ALLOWED_METHODS.contains(&method), "{}", method);
+                }
+                other => panic!("{} {} routed to {:?}", verb, target, other),
+            }
+        }
+    }
+
+    #[test]
+    fn route_params() {
+        let fade = path("/api/monitors/0/brightness");
+        let Some(Route::Call { params, .. }) =
+            route_of("POST", &fade, json!({"percent": 30, "durationMs": 500}))
+        else {
+            panic!("no fade route");
+        };
+        assert_eq!(params["target"], 30);
+        assert_eq!(params["durationMs"], 500);
+
+        let apply = path("/api/profiles/Movie%20night/apply");
+        let Some(Route::Call { params, .. }) = route_of("POST", &apply, json!({})) else {
+            panic!("no apply route");
+        };
+        assert_eq!(params["name"], "Movie night");
+
+        let monitor = path("/api/monitors/DISPLAY1");
+        assert_eq!(
+            route_of("GET", &monitor, json!({})),
+            Some(Route::Monitor("DISPLAY1"))
+        );
+    }
+
+    #[test]
+    fn unknown_routes_and_verbs() {
+        assert_eq!(route_of("GET", &path("/api/config"), json!({})), None);
+        assert_eq!(
+            route_of("DELETE", &path("/api/monitors/0/brightness"), json!({})),
+            None
+        );
+        // Writes need PUT or POST, reads GET
+        assert_eq!(
+            route_of("GET", &path("/api/monitors/0/power"), json!({})),
+            None
+        );
+        assert_eq!(route_of("POST", &path("/api/monitors"), json!({})), None);
+    }
+
+    #[test]
+    fn only_allowed_rpc_methods() {
+        assert!(check_allowed("set_monitor_brightness").is_ok());
+        for method in [
+            "export_config",
+            "import_config",
+            "set_monitor_resolution",
+            "set_custom_scale",
+        ] {
+            let err = check_allowed(method).unwrap_err();
+            assert_eq!(err.code, rpc::METHOD_NOT_FOUND, "{}", method);
+        }
+    }
+
+    #[test]
+    fn connection_slots_are_capped_and_returned() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let slots: Vec<ConnectionSlot> = (0..MAX_CONNECTIONS)
+            .map(|_| ConnectionSlot::take(&active).unwrap())
+            .collect();
+        assert!(ConnectionSlot::take(&active).is_none());
+        drop(slots);
+        assert_eq!(active.load(Ordering::SeqCst), 0);
+        assert!(ConnectionSlot::take(&active).is_some());
+    }
+}
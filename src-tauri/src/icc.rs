@@ -0,0 +1,470 @@
+// Minimal ICC profile reader: header, description and the colorimetric tags needed to show
+// a display profile's white point and gamut. Pure Rust so it works on every platform.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+const HEADER_LEN: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Chromaticity {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// CIE xy coordinates of the red, green and blue primaries.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Gamut {
+    pub red: Chromaticity,
+    pub green: Chromaticity,
+    pub blue: Chromaticity,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IccProfile {
+    pub description: String,
+    // Major.minor, e.g. "2.1" or "4.3"
+    pub version: String,
+    // Profile class signature, "mntr" for display profiles
+    pub device_class: String,
+    // Data color space signature, e.g. "RGB"
+    pub color_space: String,
+    pub white_point: Option<Chromaticity>,
+    pub gamut: Option<Gamut>,
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// s15Fixed16Number: signed 16.16 fixed point
+fn be_s15f16(data: &[u8], offset: usize) -> Option<f64> {
+    be_u32(data, offset).map(|v| v as i32 as f64 / 65536.0)
+}
+
+fn signature(data: &[u8], offset: usize) -> Option<String> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(
+        String::from_utf8_lossy(bytes)
+            .trim_end_matches([' ', '\0'])
+            .to_string(),
+    )
+}
+
+// Tag signature -> tag data (including its 4-byte type signature)
+fn tag_table(data: &[u8]) -> Result<HashMap<[u8; 4], &[u8]>, String> {
+    let count = be_u32(data, HEADER_LEN).ok_or("Missing tag table")? as usize;
+    if count > (data.len() - HEADER_LEN - 4) / 12 {
+        return Err(format!("Tag count {} exceeds profile size", count));
+    }
+    let mut tags = HashMap::new();
+    for i in 0..count {
+        let entry = HEADER_LEN + 4 + i * 12;
+        let sig: [u8; 4] = data[entry..entry + 4].try_into().unwrap_or_default();
+        let offset = be_u32(data, entry + 4).unwrap_or(0) as usize;
+        let size = be_u32(data, entry + 8).unwrap_or(0) as usize;
+        match offset
+            .checked_add(size)
+            .and_then(|end| data.get(offset..end))
+        {
+            Some(tag) => {
+                tags.insert(sig, tag);
+            }
+            None => log::debug!(
+                "Skipping ICC tag '{}' outside the profile",
+                String::from_utf8_lossy(&sig)
+            ),
+        }
+    }
+    Ok(tags)
+}
+
+fn read_xyz(tag: &[u8]) -> Option<[f64; 3]> {
+    if tag.get(0..4)? != b"XYZ " {
+        return None;
+    }
+    Some([be_s15f16(tag, 8)?, be_s15f16(tag, 12)?, be_s15f16(tag, 16)?])
+}
+
+// 'sf32' 3x3 matrix, used by the chromatic adaptation tag
+fn read_matrix(tag: &[u8]) -> Option<[[f64; 3]; 3]> {
+    if tag.get(0..4)? != b"sf32" {
+        return None;
+    }
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = be_s15f16(tag, 8 + (i * 3 + j) * 4)?;
+        }
+    }
+    Some(m)
+}
+
+// v2 'desc' (ASCII) or v4 'mluc' (UTF-16BE, English preferred)
+fn read_description(tag: &[u8]) -> Option<String> {
+    match tag.get(0..4)? {
+        b"desc" => {
+            let len = be_u32(tag, 8)? as usize;
+            let text = tag.get(12..12 + len)?;
+            Some(
+                String::from_utf8_lossy(text)
+                    .trim_end_matches('\0')
+                    .to_string(),
+            )
+        }
+        b"mluc" => {
+            let records = be_u32(tag, 8)? as usize;
+            let record_size = be_u32(tag, 12)? as usize;
+            let mut chosen: Option<(usize, usize)> = None;
+            for i in 0..records {
+                let rec = 16 + i * record_size;
+                let lang = tag.get(rec..rec + 2)?;
+                let len = be_u32(tag, rec + 4)? as usize;
+                let offset = be_u32(tag, rec + 8)? as usize;
+                if chosen.is_none() || lang == b"en" {
+                    chosen = Some((offset, len));
+                }
+                if lang == b"en" {
+                    break;
+                }
+            }
+            let (offset, len) = chosen?;
+            let bytes = tag.get(offset..offset + len)?;
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            Some(
+                String::from_utf16_lossy(&units)
+                    .trim_end_matches('\0')
+                    .to_string(),
+            )
+        }
+        _ => None,
+    }
+}
+
+fn invert(m: [[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let c =
+        |r0: usize, c0: usize, r1: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    Some([
+        [
+            c(1, 1, 2, 2) / det,
+            -c(0, 1, 2, 2) / det,
+            c(0, 1, 1, 2) / det,
+        ],
+        [
+            -c(1, 0, 2, 2) / det,
+            c(0, 0, 2, 2) / det,
+            -c(0, 0, 1, 2) / det,
+        ],
+        [
+            c(1, 0, 2, 1) / det,
+            -c(0, 0, 2, 1) / det,
+            c(0, 0, 1, 1) / det,
+        ],
+    ])
+}
+
+fn multiply(m: [[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    std::array::from_fn(|i| m[i][0] * v[0] + m[i][1] * v[1] + m[i][2] * v[2])
+}
+
+/// CIE xy chromaticity of an XYZ triple, None for black.
+pub fn xyz_to_xy(xyz: [f64; 3]) -> Option<Chromaticity> {
+    let sum = xyz[0] + xyz[1] + xyz[2];
+    if sum <= 0.0 {
+        return None;
+    }
+    Some(Chromaticity {
+        x: xyz[0] / sum,
+        y: xyz[1] / sum,
+    })
+}
+
+/// Parses an ICC profile (v2 or v4) from its raw bytes.
+pub fn parse_profile(data: &[u8]) -> Result<IccProfile, String> {
+    if data.len() < HEADER_LEN + 4 {
+        return Err("Profile is too short".to_string());
+    }
+    if &data[36..40] != b"acsp" {
+        return Err("Not an ICC profile (missing 'acsp' signature)".to_string());
+    }
+    let declared = be_u32(data, 0).unwrap_or(0) as usize;
+    if declared > data.len() {
+        return Err(format!(
+            "Profile is truncated ({} of {} bytes)",
+            data.len(),
+            declared
+        ));
+    }
+
+    let major = data[8];
+    let minor = data[9] >> 4;
+    let body = if declared >= HEADER_LEN + 4 {
+        &data[..declared]
+    } else {
+        data
+    };
+    let tags = tag_table(body)?;
+
+    let description = tags
+        .get(b"desc")
+        .and_then(|t| read_description(t))
+        .unwrap_or_default();
+
+    // Colorants are stored adapted to the D50 connection space; 'chad' lets us undo that
+    // and report the display's actual primaries. In v4 the white point tag is always D50 too.
+    let unadapt = tags
+        .get(b"chad")
+        .and_then(|t| read_matrix(t))
+        .and_then(invert);
+    let actual = |xyz: [f64; 3]| unadapt.map(|m| multiply(m, xyz)).unwrap_or(xyz);
+
+    let white_point = tags
+        .get(b"wtpt")
+        .and_then(|t| read_xyz(t))
+        .map(|xyz| if major >= 4 { actual(xyz) } else { xyz })
+        .and_then(xyz_to_xy);
+
+    let colorant = |sig: &[u8; 4]| {
+        tags.get(sig)
+            .and_then(|t| read_xyz(t))
+            .map(actual)
+            .and_then(xyz_to_xy)
+    };
+    let gamut = match (colorant(b"rXYZ"), colorant(b"gXYZ"), colorant(b"bXYZ")) {
+        (Some(red), Some(green), Some(blue)) => Some(Gamut { red, green, blue }),
+        _ => None,
+    };
+
+    Ok(IccProfile {
+        description,
+        version: format!("{}.{}", major, minor),
+        device_class: signature(data, 12).unwrap_or_default(),
+        color_space: signature(data, 16).unwrap_or_default(),
+        white_point,
+        gamut,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s15f16(v: f64) -> [u8; 4] {
+        ((v * 65536.0).round() as i32).to_be_bytes()
+    }
+
+    fn xyz_tag(xyz: [f64; 3]) -> Vec<u8> {
+        let mut tag = b"XYZ \0\0\0\0".to_vec();
+        for v in xyz {
+            tag.extend(s15f16(v));
+        }
+        tag
+    }
+
+    // XYZ with Y = 1 for a chromaticity
+    fn xyz_of(x: f64, y: f64) -> [f64; 3] {
+        [x / y, 1.0, (1.0 - x - y) / y]
+    }
+
+    fn desc_tag(text: &str) -> Vec<u8> {
+        let mut tag = b"desc\0\0\0\0".to_vec();
+        tag.extend(((text.len() + 1) as u32).to_be_bytes());
+        tag.extend(text.as_bytes());
+        tag.push(0);
+        tag
+    }
+
+    fn mluc_tag(records: &[(&[u8; 2], &str)]) -> Vec<u8> {
+        let mut tag = b"mluc\0\0\0\0".to_vec();
+        tag.extend((records.len() as u32).to_be_bytes());
+        tag.extend(12u32.to_be_bytes());
+        let mut strings = Vec::new();
+        let start = 16 + records.len() * 12;
+        for (lang, text) in records {
+            let units: Vec<u8> = text.encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+            tag.extend(*lang);
+            tag.extend(b"US");
+            tag.extend((units.len() as u32).to_be_bytes());
+            tag.extend(((start + strings.len()) as u32).to_be_bytes());
+            strings.extend(units);
+        }
+        tag.extend(strings);
+        tag
+    }
+
+    fn sf32_tag(m: [[f64; 3]; 3]) -> Vec<u8> {
+        let mut tag = b"sf32\0\0\0\0".to_vec();
+        for v in m.iter().flatten() {
+            tag.extend(s15f16(*v));
+        }
+        tag
+    }
+
+    // A display profile with the given version byte and tags, laid out after the tag table
+    fn profile(version: [u8; 2], tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut data = vec![0u8; HEADER_LEN];
+        data[8..10].copy_from_slice(&version);
+        data[12..16].copy_from_slice(b"mntr");
+        data[16..20].copy_from_slice(b"RGB ");
+        data[36..40].copy_from_slice(b"acsp");
+        data.extend((tags.len() as u32).to_be_bytes());
+        let mut offset = HEADER_LEN + 4 + tags.len() * 12;
+        let mut body: Vec<u8> = Vec::new();
+        for (sig, tag) in tags {
+            data.extend(*sig);
+            data.extend((offset as u32).to_be_bytes());
+            data.extend((tag.len() as u32).to_be_bytes());
+            offset += tag.len();
+            body.extend(tag);
+        }
+        data.extend(body);
+        let len = data.len() as u32;
+        data[0..4].copy_from_slice(&len.to_be_bytes());
+        data
+    }
+
+    fn assert_xy(actual: Option<Chromaticity>, x: f64, y: f64) {
+        let actual = actual.expect("missing chromaticity");
+        assert!(
+            (actual.x - x).abs() < 1e-4 && (actual.y - y).abs() < 1e-4,
+            "{:?} is not ({}, {})",
+            actual,
+            x,
+            y
+        );
+    }
+
+    #[test]
+    fn reads_header_and_v2_description() {
+        let data = profile([2, 0x10], &[(b"desc", desc_tag("DELL U2720Q"))]);
+        let p = parse_profile(&data).unwrap();
+        assert_eq!(p.version, "2.1");
+        assert_eq!(p.device_class, "mntr");
+        assert_eq!(p.color_space, "RGB");
+        assert_eq!(p.description, "DELL U2720Q");
+        assert_eq!(p.white_point, None);
+        assert_eq!(p.gamut, None);
+    }
+
+    #[test]
+    fn prefers_english_mluc_description() {
+        let tag = mluc_tag(&[(b"de", "Bildschirm"), (b"en", "Display ✓")]);
+        let p = parse_profile(&profile([4, 0x30], &[(b"desc", tag)])).unwrap();
+        assert_eq!(p.version, "4.3");
+        assert_eq!(p.description, "Display ✓");
+
+        let tag = mluc_tag(&[(b"fr", "Écran")]);
+        let p = parse_profile(&profile([4, 0x30], &[(b"desc", tag)])).unwrap();
+        assert_eq!(p.description, "Écran");
+    }
+
+    #[test]
+    fn reads_gamut_and_white_point() {
+        // sRGB primaries with a D65 white point, no chromatic adaptation
+        let data = profile(
+            [2, 0x10],
+            &[
+                (b"wtpt", xyz_tag(xyz_of(0.3127, 0.3290))),
+                (b"rXYZ", xyz_tag(xyz_of(0.64, 0.33))),
+                (b"gXYZ", xyz_tag(xyz_of(0.30, 0.60))),
+                (b"bXYZ", xyz_tag(xyz_of(0.15, 0.06))),
+            ],
+        );
+        let p = parse_profile(&data).unwrap();
+        assert_xy(p.white_point, 0.3127, 0.3290);
+        let gamut = p.gamut.unwrap();
+        assert_xy(Some(gamut.red), 0.64, 0.33);
+        assert_xy(Some(gamut.green), 0.30, 0.60);
+        assert_xy(Some(gamut.blue), 0.15, 0.06);
+    }
+
+    #[test]
+    fn undoes_chromatic_adaptation() {
+        let chad = [[1.25, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.5]];
+        let adapted = |xyz: [f64; 3]| multiply(chad, xyz);
+        let white = xyz_of(0.3127, 0.3290);
+        let tags = |major: u8| {
+            profile(
+                [major, 0],
+                &[
+                    (b"chad", sf32_tag(chad)),
+                    (b"wtpt", xyz_tag(adapted(white))),
+                    (b"rXYZ", xyz_tag(adapted(xyz_of(0.68, 0.32)))),
+                    (b"gXYZ", xyz_tag(adapted(xyz_of(0.265, 0.69)))),
+                    (b"bXYZ", xyz_tag(adapted(xyz_of(0.15, 0.06)))),
+                ],
+            )
+        };
+        let v4 = parse_profile(&tags(4)).unwrap();
+        assert_xy(v4.white_point, 0.3127, 0.3290);
+        assert_xy(Some(v4.gamut.unwrap().red), 0.68, 0.32);
+        assert_xy(Some(v4.gamut.unwrap().green), 0.265, 0.69);
+
+        // v2 white points are stored as measured, so only colorants are unadapted
+        let v2 = parse_profile(&tags(2)).unwrap();
+        let stored = xyz_to_xy(adapted(white));
+        assert_xy(v2.white_point, stored.unwrap().x, stored.unwrap().y);
+        assert_xy(Some(v2.gamut.unwrap().red), 0.68, 0.32);
+    }
+
+    #[test]
+    fn rejects_short_foreign_and_truncated_data() {
+        assert!(parse_profile(&[0u8; 64]).is_err());
+
+        let mut data = profile([2, 0], &[(b"desc", desc_tag("x"))]);
+        data[36..40].copy_from_slice(b"nope");
+        assert!(parse_profile(&data).unwrap_err().contains("acsp"));
+
+        let data = profile([2, 0], &[(b"desc", desc_tag("Display"))]);
+        let err = parse_profile(&data[..data.len() - 4]).unwrap_err();
+        assert!(err.contains("truncated"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_oversized_tag_count() {
+        let mut data = profile([2, 0], &[]);
+        data[HEADER_LEN..HEADER_LEN + 4].copy_from_slice(&1000u32.to_be_bytes());
+        assert!(parse_profile(&data).unwrap_err().contains("Tag count"));
+    }
+
+    #[test]
+    fn skips_tags_with_bad_offsets() {
+        let mut data = profile(
+            [2, 0],
+            &[
+                (b"desc", desc_tag("Display")),
+                (b"rXYZ", xyz_tag(xyz_of(0.64, 0.33))),
+            ],
+        );
+        // Point 'desc' past the end of the profile
+        let entry = HEADER_LEN + 4;
+        data[entry + 4..entry + 8].copy_from_slice(&0xFFFF_FFF0u32.to_be_bytes());
+        let p = parse_profile(&data).unwrap();
+        assert_eq!(p.description, "");
+        assert_eq!(p.gamut, None);
+    }
+
+    #[test]
+    fn tolerates_bad_text_offsets() {
+        let mut tag = mluc_tag(&[(b"en", "Display")]);
+        // Record's string offset beyond the tag
+        tag[24..28].copy_from_slice(&500u32.to_be_bytes());
+        let p = parse_profile(&profile([4, 0], &[(b"desc", tag)])).unwrap();
+        assert_eq!(p.description, "");
+
+        let mut tag = desc_tag("Display");
+        tag[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+        let p = parse_profile(&profile([2, 0], &[(b"desc", tag)])).unwrap();
+        assert_eq!(p.description, "");
+    }
+}
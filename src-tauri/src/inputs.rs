@@ -0,0 +1,404 @@
+// Input sources (VCP 0x60): one table maps names to codes and codes back to names. It holds the
+// MCCS values, the codes monitors commonly use beyond them and vendor quirks; a monitor's list is
+// narrowed to what its capability string advertises and labelled with the user's own names.
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::displays::DisplayInfo;
+
+struct InputDef {
+    code: u32,
+    key: &'static str,
+    label: &'static str,
+    // Other names accepted when switching
+    aliases: &'static [&'static str],
+}
+
+const fn input(
+    code: u32,
+    key: &'static str,
+    label: &'static str,
+    aliases: &'static [&'static str],
+) -> InputDef {
+    InputDef {
+        code,
+        key,
+        label,
+        aliases,
+    }
+}
+
+const STANDARD: &[InputDef] = &[
+    input(0x01, "vga1", "VGA 1", &["vga"]),
+    input(0x02, "vga2", "VGA 2", &[]),
+    input(0x03, "dvi1", "DVI 1", &["dvi"]),
+    input(0x04, "dvi2", "DVI 2", &[]),
+    input(0x05, "composite1", "Composite 1", &["composite"]),
+    input(0x06, "composite2", "Composite 2", &[]),
+    input(0x07, "svideo1", "S-Video 1", &["s-video", "svideo"]),
+    input(0x08, "svideo2", "S-Video 2", &[]),
+    input(0x09, "tuner1", "Tuner 1", &["tuner"]),
+    input(0x0A, "tuner2", "Tuner 2", &[]),
+    input(0x0B, "tuner3", "Tuner 3", &[]),
+    input(0x0C, "component1", "Component 1", &["component"]),
+    input(0x0D, "component2", "Component 2", &[]),
+    input(0x0E, "component3", "Component 3", &[]),
+    input(0x0F, "dp1", "DisplayPort 1", &["displayport", "dp"]),
+    input(0x10, "dp2", "DisplayPort 2", &[]),
+    input(0x11, "hdmi1", "HDMI 1", &["hdmi"]),
+    input(0x12, "hdmi2", "HDMI 2", &[]),
+    // Not in MCCS but widely used (Dell, HP, Samsung, ...)
+    input(0x13, "hdmi3", "HDMI 3", &[]),
+    input(
+        0x19,
+        "usbc1",
+        "USB-C / TB 1",
+        &["usbc", "usb-c", "tb", "thunderbolt"],
+    ),
+    input(0x1A, "usbc2", "USB-C / TB 2", &[]),
+    input(0x1B, "usbc3", "USB-C / TB 3", &[]),
+    input(0x31, "usbc4", "USB-C / TB 4", &[]),
+];
+
+/// Codes a vendor uses instead of (or besides) the standard ones.
+struct VendorQuirks {
+    // EDID manufacturer ids
+    manufacturers: &'static [&'static str],
+    inputs: &'static [InputDef],
+}
+
+const VENDOR_QUIRKS: &[VendorQuirks] = &[VendorQuirks {
+    manufacturers: &["GSM"],
+    inputs: &[
+        input(0xD0, "dp1_lg", "DisplayPort 1 (LG alt)", &[]),
+        input(0xD1, "dp2_usbc_lg", "DP 2 / USB-C (LG alt)", &[]),
+        input(0xD2, "usbc_lg", "USB-C (LG alt)", &[]),
+        input(0x90, "hdmi1_lg", "HDMI 1 (LG alt)", &[]),
+        input(0x91, "hdmi2_lg", "HDMI 2 (LG alt)", &[]),
+    ],
+}];
+
+fn all_inputs() -> impl Iterator<Item = &'static InputDef> {
+    STANDARD
+        .iter()
+        .chain(VENDOR_QUIRKS.iter().flat_map(|v| v.inputs.iter()))
+}
+
+fn by_code(code: u32) -> Option<&'static InputDef> {
+    all_inputs().find(|def| def.code == code)
+}
+
+/// VCP 0x60 value for an input key or alias ("hdmi1", "usbc"), or a hex code ("0x1B", "1b").
+pub fn code_of(input: &str) -> Option<u32> {
+    let input = input.trim().to_ascii_lowercase();
+    all_inputs()
+        .find(|def| def.key == input || def.aliases.contains(&input.as_str()))
+        .map(|def| def.code)
+        .or_else(|| u32::from_str_radix(input.trim_start_matches("0x"), 16).ok())
+}
+
+/// Input key for a VCP 0x60 value, or the code as "0x1C" when no table knows it.
+pub fn key_of(code: u32) -> String {
+    by_code(code)
+        .map(|def| def.key.to_string())
+        .unwrap_or_else(|| format!("0x{:02X}", code))
+}
+
+/// Codes listed for VCP 0x60 in a capability string such as
+/// "(prot(monitor)type(lcd)cmds(01 02 03)vcp(02 10 12 60(0F 11 12) D6(01 04))mccs_ver(2.2))".
+/// None when the string has no such list.
+pub fn advertised_codes(caps: &str) -> Option<Vec<u32>> {
+    let caps = caps.to_ascii_lowercase();
+    let start = caps.find("vcp(")? + "vcp(".len();
+
+    // Codes, some followed by their values in parentheses: "02 10 60(0F 11) D6(01 04)"
+    let mut rest = group(&caps[start..]);
+    while let Some(open) = rest.find('(') {
+        // Monitors that leave out the spaces run codes together ("021060(0F11)")
+        let code = rest[..open].split_whitespace().last().unwrap_or("");
+        let values = group(&rest[open + 1..]);
+        if code.len().is_multiple_of(2) && code.ends_with("60") {
+            return Some(hex_bytes(values));
+        }
+        rest = rest[open + 1 + values.len()..]
+            .strip_prefix(')')
+            .unwrap_or("");
+    }
+    None
+}
+
+// Text up to the parenthesis that closes an already opened group
+fn group(text: &str) -> &str {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return &text[..i],
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    text
+}
+
+// "0F 11 12", and "0F1112" from monitors that leave out the spaces
+fn hex_bytes(text: &str) -> Vec<u32> {
+    text.split_whitespace()
+        .flat_map(|token| {
+            token
+                .as_bytes()
+                .chunks(2)
+                .filter_map(|pair| std::str::from_utf8(pair).ok())
+                .filter_map(|pair| u32::from_str_radix(pair, 16).ok())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// An input a monitor can switch to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputSource {
+    pub key: String,
+    pub code: u32,
+    pub label: String,
+    // Name the user gave it, e.g. "Work Laptop"
+    pub name: Option<String>,
+}
+
+/// Inputs of a monitor: those its capability string advertises, or when it doesn't say, the
+/// standard ones plus its vendor's. `names` maps input keys to the user's names.
+pub fn inputs_for(
+    manufacturer: &str,
+    caps: Option<&str>,
+    names: &HashMap<String, String>,
+) -> Vec<InputSource> {
+    let source = |code: u32| {
+        let key = key_of(code);
+        InputSource {
+            code,
+            label: by_code(code)
+                .map(|def| def.label.to_string())
+                .unwrap_or_else(|| format!("Input {}", key)),
+            name: names.get(&key).cloned(),
+            key,
+        }
+    };
+    match caps.and_then(advertised_codes).filter(|c| !c.is_empty()) {
+        Some(codes) => codes.into_iter().map(source).collect(),
+        None => STANDARD
+            .iter()
+            .chain(
+                VENDOR_QUIRKS
+                    .iter()
+                    .filter(|v| {
+                        v.manufacturers
+                            .iter()
+                            .any(|m| m.eq_ignore_ascii_case(manufacturer.trim()))
+                    })
+                    .flat_map(|v| v.inputs.iter()),
+            )
+            .map(|def| source(def.code))
+            .collect(),
+    }
+}
+
+/// Turns a user-given input name into its key; anything else is returned as is.
+pub fn resolve_name(names: &HashMap<String, String>, input: &str) -> String {
+    names
+        .iter()
+        .find(|(_, name)| name.eq_ignore_ascii_case(input.trim()))
+        .map(|(key, _)| key.clone())
+        .unwrap_or_else(|| input.to_string())
+}
+
+fn find_monitor(device_name: &str) -> Result<DisplayInfo, String> {
+    crate::displays::get_all_monitors()?
+        .into_iter()
+        .find(|m| m.device_name == device_name)
+        .ok_or_else(|| format!("Monitor '{}' not found", device_name))
+}
+
+/// The user's input names for a monitor, keyed by input key.
+pub fn names_for(app_handle: &AppHandle, device_name: &str) -> HashMap<String, String> {
+    let provider = crate::displays::active_provider();
+    crate::monitor_identity::identity_for_device(provider.as_ref(), device_name)
+        .and_then(|identity| {
+            crate::settings::get(app_handle)
+                .input_names
+                .remove(&identity)
+        })
+        .unwrap_or_default()
+}
+
+// Capability strings take a second or more to read over DDC/CI and don't change for a monitor;
+// keyed by monitor identity
+fn caps_cache() -> &'static Mutex<HashMap<String, String>> {
+    static CAPS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    CAPS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Inputs `monitor` offers, with the user's names for them.
+pub fn inputs_of(app_handle: &AppHandle, monitor: &DisplayInfo) -> Vec<InputSource> {
+    let identity = crate::monitor_identity::identity_key(monitor);
+    let cached = caps_cache()
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&identity).cloned());
+    let caps = cached.or_else(|| {
+        let caps = crate::displays::active_provider()
+            .get_monitor_ddc_caps(monitor.device_name.clone())
+            .ok()?;
+        if let Ok(mut cache) = caps_cache().lock() {
+            cache.insert(identity.clone(), caps.clone());
+        }
+        Some(caps)
+    });
+    let names = crate::settings::get(app_handle)
+        .input_names
+        .remove(&identity)
+        .unwrap_or_default();
+    inputs_for(&monitor.manufacturer, caps.as_deref(), &names)
+}
+
+#[tauri::command]
+pub fn list_monitor_inputs(
+    app_handle: AppHandle,
+    device_name: String,
+) -> Result<Vec<InputSource>, String> {
+    Ok(inputs_of(&app_handle, &find_monitor(&device_name)?))
+}
+
+/// Names an input of a monitor ("Work Laptop"); an empty name removes it.
+#[tauri::command]
+pub fn set_input_name(
+    app_handle: AppHandle,
+    device_name: String,
+    input: String,
+    name: String,
+) -> Result<(), String> {
+    let monitor = find_monitor(&device_name)?;
+    let code = code_of(&input).ok_or_else(|| format!("Unknown input '{}'", input))?;
+    let mut settings = crate::settings::get_user(&app_handle);
+    let names = settings
+        .input_names
+        .entry(crate::monitor_identity::identity_key(&monitor))
+        .or_default();
+    let key = key_of(code);
+    let name = name.trim();
+    if name.is_empty() {
+        names.remove(&key);
+    } else if names
+        .iter()
+        .any(|(other, existing)| *other != key && existing.eq_ignore_ascii_case(name))
+    {
+        return Err(format!("Another input is already named '{}'", name));
+    } else {
+        names.insert(key, name.to_string());
+    }
+    crate::settings::save(&app_handle, settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Dell U2720Q, spaced out as MCCS intends
+    const DELL_CAPS: &str = "(prot(monitor)type(LCD)model(U2720Q)cmds(01 02 03 07 0C E3 F3)vcp(02 04 05 08 10 12 14(01 04 05 06 08 09 0B 0C) 16 18 1A 52 60(0F 11 1B) AA(01 02 04) AC AE B2 B6 C6 C8 C9 D6(01 04 05) DC(00 03 05) DF E0 E1 E2(00 1D 01 02 04 0E 12 14 1F 20 21 22 23 24 25 26 27) F0(0C) F1 F2 FD)mswhql(1)asset_eep(40)mccs_ver(2.1))";
+    // LG 27UK850, which runs codes and values together
+    const LG_CAPS: &str = "(prot(monitor)type(LCD)model(LG HDR 4K)cmds(01 02 03 0C E3 F3)vcp(020405080B0C101214(05 06 08 0B)16181A5260(11 12 0F 10)6C6E7087ACAEB6C0C6C8C9D6(01 04)DFE4E5E6E7E8E9EAEBED(00 10 20 40)EE(00 01 02 03)F0(00 01)F1F2F4F5(00 01 02)F6(00 01 02)F7(00 01 02 03)F8(00 01)F9FAFBFEFF)mccs_ver(2.1)mswhql(1))";
+
+    #[test]
+    fn code_of_keys_aliases_and_hex() {
+        assert_eq!(code_of("hdmi1"), Some(0x11));
+        assert_eq!(code_of(" HDMI "), Some(0x11));
+        assert_eq!(code_of("DisplayPort"), Some(0x0F));
+        assert_eq!(code_of("USB-C"), Some(0x19));
+        assert_eq!(code_of("hdmi1_lg"), Some(0x90));
+        // Upper-case hex used to miss the lower-cased table
+        assert_eq!(code_of("0F"), Some(0x0F));
+        assert_eq!(code_of("1A"), Some(0x1A));
+        assert_eq!(code_of("0x1B"), Some(0x1B));
+        assert_eq!(code_of("0X1c"), Some(0x1C));
+        assert_eq!(code_of("1b"), Some(0x1B));
+        assert_eq!(code_of("hdmi9"), None);
+        assert_eq!(code_of(""), None);
+    }
+
+    #[test]
+    fn key_of_round_trips() {
+        assert_eq!(key_of(0x0F), "dp1");
+        assert_eq!(key_of(0x1A), "usbc2");
+        assert_eq!(key_of(0x1B), "usbc3");
+        assert_eq!(key_of(0xD1), "dp2_usbc_lg");
+        assert_eq!(key_of(0x1C), "0x1C");
+        assert_eq!(key_of(0x05), "composite1");
+        for code in all_inputs().map(|def| def.code).chain([0x1C, 0xFF]) {
+            assert_eq!(code_of(&key_of(code)), Some(code), "0x{:02X}", code);
+        }
+    }
+
+    #[test]
+    fn advertised_codes_from_capability_strings() {
+        assert_eq!(advertised_codes(DELL_CAPS), Some(vec![0x0F, 0x11, 0x1B]));
+        assert_eq!(
+            advertised_codes(LG_CAPS),
+            Some(vec![0x11, 0x12, 0x0F, 0x10])
+        );
+        // Values run together too
+        assert_eq!(
+            advertised_codes("vcp(021060(0F111B)D6(0104))"),
+            Some(vec![0x0F, 0x11, 0x1B])
+        );
+        // Nested groups before 0x60 are skipped whole, and 60 inside another code's values
+        // isn't the input list
+        assert_eq!(
+            advertised_codes("(vcp(02 E2(00 60 01) DC(00 02(01 03)) 60(11 12) D6(01)))"),
+            Some(vec![0x11, 0x12])
+        );
+        // Codes merely ending in 6 and 0 across a pair boundary aren't 0x60
+        assert_eq!(advertised_codes("vcp(10 160(11 12))"), None);
+        // "cmds(..)" isn't the VCP list and some monitors don't list 0x60 values at all
+        assert_eq!(
+            advertised_codes("(cmds(60(11)) vcp(02 10 60 D6(01 04)))"),
+            None
+        );
+        assert_eq!(advertised_codes("(prot(monitor)type(lcd))"), None);
+        assert_eq!(advertised_codes("vcp(60())"), Some(vec![]));
+        // Truncated reads don't panic
+        assert_eq!(advertised_codes("vcp(02 60(0F 1"), Some(vec![0x0F, 0x01]));
+        assert_eq!(advertised_codes("vcp(02 60("), Some(vec![]));
+    }
+
+    #[test]
+    fn inputs_for_narrows_to_advertised_and_names_them() {
+        let names = HashMap::from([("usbc3".to_string(), "Work Laptop".to_string())]);
+        let inputs = inputs_for("DEL", Some(DELL_CAPS), &names);
+        let keys: Vec<&str> = inputs.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, ["dp1", "hdmi1", "usbc3"]);
+        assert_eq!(inputs[2].label, "USB-C / TB 3");
+        assert_eq!(inputs[2].name.as_deref(), Some("Work Laptop"));
+        assert_eq!(inputs[0].name, None);
+
+        let unknown = inputs_for("DEL", Some("vcp(60(1C))"), &HashMap::new());
+        assert_eq!(
+            (unknown[0].key.as_str(), unknown[0].label.as_str()),
+            ("0x1C", "Input 0x1C")
+        );
+
+        // Without a list: the standard inputs, plus vendor codes only for that vendor
+        let lg = inputs_for(" gsm ", None, &HashMap::new());
+        let dell = inputs_for("DEL", Some("(prot(monitor))"), &HashMap::new());
+        assert_eq!(dell.len(), STANDARD.len());
+        assert_eq!(lg.len(), STANDARD.len() + VENDOR_QUIRKS[0].inputs.len());
+        assert_eq!(
+            inputs_for("DEL", Some("vcp(60())"), &HashMap::new()).len(),
+            STANDARD.len()
+        );
+
+        assert_eq!(resolve_name(&names, " work laptop"), "usbc3");
+        assert_eq!(resolve_name(&names, "hdmi1"), "hdmi1");
+    }
+}
@@ -0,0 +1,483 @@
+// Local control endpoint of the running app: a per-user named pipe on Windows, a Unix socket
+// elsewhere. Each line is one JSON-RPC request (see rpc.rs) and gets one response line back.
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Once};
+
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::rpc;
+
+#[cfg(not(target_os = "windows"))]
+fn user_name() -> String {
+    std::env::var("USER").unwrap_or_else(|_| "default".to_string())
+}
+
+// Calls `f` with the SID of the user this process runs as, taken from the process token
+#[cfg(target_os = "windows")]
+fn with_user_sid<T>(f: impl FnOnce(windows::Win32::Foundation::PSID) -> T) -> Option<T> {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{GetTokenInformation, TokenUser, TOKEN_QUERY, TOKEN_USER};
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    let mut token = HANDLE::default();
+    unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) }.ok()?;
+    let mut len = 0u32;
+    let _ = unsafe { GetTokenInformation(token, TokenUser, None, 0, &mut len) };
+    // u64 elements keep the TOKEN_USER at the start of the buffer aligned
+    let mut buf = vec![0u64; (len as usize).div_ceil(8)];
+    let rc = unsafe {
+        GetTokenInformation(
+            token,
+            TokenUser,
+            Some(buf.as_mut_ptr().cast()),
+            len,
+            &mut len,
+        )
+    };
+    let _ = unsafe { CloseHandle(token) };
+    rc.ok()?;
+    let user = unsafe { &*(buf.as_ptr() as *const TOKEN_USER) };
+    Some(f(user.User.Sid))
+}
+
+// "S-1-5-21-..." form of the current user's SID
+#[cfg(target_os = "windows")]
+fn user_sid_string() -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{LocalFree, HLOCAL};
+    use windows::Win32::Security::Authorization::ConvertSidToStringSidW;
+
+    with_user_sid(|sid| {
+        let mut text = PWSTR::null();
+        unsafe { ConvertSidToStringSidW(sid, &mut text) }.ok()?;
+        let sid = unsafe { text.to_string() }.ok();
+        unsafe { LocalFree(HLOCAL(text.0.cast())) };
+        sid
+    })
+    .flatten()
+}
+
+/// `\\.\pipe\WinDisplay-<user SID>`. The SID comes from the process token rather than the
+/// environment, the pipe's DACL admits only that user and clients check the pipe's owner before
+/// sending anything (see `owned_by_current_user`).
+#[cfg(target_os = "windows")]
+pub fn endpoint() -> String {
+    format!(
+        r"\\.\pipe\WinDisplay-{}",
+        user_sid_string().unwrap_or_else(|| "unknown".to_string())
+    )
+}
+
+// Security descriptor owned by the current user whose protected DACL grants access to that user
+// only, so other accounts can neither connect nor create further instances of the pipe
+#[cfg(target_os = "windows")]
+struct PipeSecurity(windows::Win32::Security::PSECURITY_DESCRIPTOR);
+
+#[cfg(target_os = "windows")]
+impl PipeSecurity {
+    fn new() -> Result<Self, String> {
+        use windows::Win32::Security::Authorization::{
+            ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+        };
+        use windows::Win32::Security::PSECURITY_DESCRIPTOR;
+
+        let sid = user_sid_string().ok_or_else(|| "Failed to read the user SID".to_string())?;
+        let sddl =
+            crate::winDisplays::to_wide_null_terminated(&format!("O:{sid}D:P(A;;GA;;;{sid})"));
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                windows::core::PCWSTR(sddl.as_ptr()),
+                SDDL_REVISION_1,
+                &mut descriptor,
+                None,
+            )
+        }
+        .map_err(|e| format!("Failed to build the pipe security descriptor: {}", e))?;
+        Ok(Self(descriptor))
+    }
+
+    fn attributes(&self) -> windows::Win32::Security::SECURITY_ATTRIBUTES {
+        windows::Win32::Security::SECURITY_ATTRIBUTES {
+            nLength: std::mem::size_of::<windows::Win32::Security::SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: self.0 .0,
+            bInheritHandle: false.into(),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for PipeSecurity {
+    fn drop(&mut self) {
+        use windows::Win32::Foundation::{LocalFree, HLOCAL};
+        unsafe { LocalFree(HLOCAL(self.0 .0)) };
+    }
+}
+
+// Whether the pipe behind `file` was created by the current user. Pipe names are global, so
+// another account could otherwise squat the name before the app starts and read CLI requests.
+#[cfg(target_os = "windows")]
+fn owned_by_current_user(file: &std::fs::File) -> bool {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::{LocalFree, HANDLE, HLOCAL, PSID};
+    use windows::Win32::Security::Authorization::{GetSecurityInfo, SE_KERNEL_OBJECT};
+    use windows::Win32::Security::{EqualSid, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR};
+
+    let mut owner = PSID::default();
+    let mut descriptor = PSECURITY_DESCRIPTOR::default();
+    let rc = unsafe {
+        GetSecurityInfo(
+            HANDLE(file.as_raw_handle() as _),
+            SE_KERNEL_OBJECT,
+            OWNER_SECURITY_INFORMATION,
+            Some(&mut owner),
+            None,
+            None,
+            None,
+            Some(&mut descriptor),
+        )
+    };
+    if rc.is_err() {
+        return false;
+    }
+    let same = with_user_sid(|sid| unsafe { EqualSid(owner, sid) }.is_ok()).unwrap_or(false);
+    unsafe { LocalFree(HLOCAL(descriptor.0)) };
+    same
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn endpoint() -> String {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    dir.join(format!("windisplay-{}.sock", user_name()))
+        .display()
+        .to_string()
+}
+
+// Answers requests until the client disconnects
+fn serve_connection<S: std::io::Read + Write>(handle_line: &dyn Fn(&str) -> String, stream: S) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut response = handle_line(line.trim());
+        response.push('\n');
+        let stream = reader.get_mut();
+        if stream
+            .write_all(response.as_bytes())
+            .and_then(|_| stream.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+// Serves `endpoint` on one thread per connection; `handle_line` answers each request line
+#[cfg(target_os = "windows")]
+fn listen(
+    endpoint: &str,
+    handle_line: impl Fn(&str) -> String + Send + Sync + 'static,
+) -> Result<(), String> {
+    use crate::winDisplays::to_wide_null_terminated;
+    use std::os::windows::io::FromRawHandle;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{CloseHandle, ERROR_PIPE_CONNECTED};
+    use windows::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX};
+    use windows::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS,
+        PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    let handle_line = Arc::new(handle_line);
+    let name = to_wide_null_terminated(endpoint);
+    let security = PipeSecurity::new()?;
+    let attributes = security.attributes();
+    let mut first = true;
+    loop {
+        // The first instance fails if another process already owns the name
+        let open_mode = if first {
+            PIPE_ACCESS_DUPLEX | FILE_FLAG_FIRST_PIPE_INSTANCE
+        } else {
+            PIPE_ACCESS_DUPLEX
+        };
+        let handle = unsafe {
+            CreateNamedPipeW(
+                PCWSTR(name.as_ptr()),
+                open_mode,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                64 * 1024,
+                64 * 1024,
+                0,
+                Some(&attributes),
+            )
+        };
+        if handle.is_invalid() {
+            return Err(format!(
+                "Failed to create pipe {}: {}",
+                endpoint,
+                windows::core::Error::from_win32()
+            ));
+        }
+        first = false;
+
+        if let Err(e) = unsafe { ConnectNamedPipe(handle, None) } {
+            // A client that connected between create and connect is fine
+            if e.code() != ERROR_PIPE_CONNECTED.to_hresult() {
+                log::warn!("IPC connection failed: {}", e);
+                let _ = unsafe { CloseHandle(handle) };
+                continue;
+            }
+        }
+
+        // The File owns the handle and closes it when the connection ends
+        let stream = unsafe { std::fs::File::from_raw_handle(handle.0 as _) };
+        let handle_line = handle_line.clone();
+        std::thread::spawn(move || serve_connection(&*handle_line, stream));
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn listen(
+    path: &str,
+    handle_line: impl Fn(&str) -> String + Send + Sync + 'static,
+) -> Result<(), String> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let handle_line = Arc::new(handle_line);
+    if UnixStream::connect(path).is_ok() {
+        return Err(format!("{} is already served by another instance", path));
+    }
+    // Left behind by an instance that didn't shut down cleanly
+    let _ = std::fs::remove_file(path);
+    let listener =
+        UnixListener::bind(path).map_err(|e| format!("Failed to bind {}: {}", path, e))?;
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let handle_line = handle_line.clone();
+                std::thread::spawn(move || serve_connection(&*handle_line, stream));
+            }
+            Err(e) => log::warn!("IPC connection failed: {}", e),
+        }
+    }
+    Ok(())
+}
+
+static START_ONCE: Once = Once::new();
+
+pub fn start_ipc_server(app_handle: AppHandle) {
+    START_ONCE.call_once(|| {
+        std::thread::spawn(move || {
+            log::info!("IPC server listening on {}", endpoint());
+            let handle_line = move |line: &str| rpc::handle_line(&app_handle, line);
+            if let Err(e) = listen(&endpoint(), handle_line) {
+                log::warn!("IPC server stopped: {}", e);
+            }
+        });
+    });
+}
+
+#[cfg(target_os = "windows")]
+type Stream = std::fs::File;
+#[cfg(not(target_os = "windows"))]
+type Stream = std::os::unix::net::UnixStream;
+
+#[cfg(target_os = "windows")]
+fn open_stream() -> Option<Stream> {
+    // ERROR_PIPE_BUSY: every instance is taken until the server creates the next one
+    const ERROR_PIPE_BUSY: i32 = 231;
+    for _ in 0..20 {
+        match std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(endpoint())
+        {
+            Ok(file) if owned_by_current_user(&file) => return Some(file),
+            Ok(_) => {
+                log::warn!(
+                    "{} is not owned by the current user, not connecting",
+                    endpoint()
+                );
+                return None;
+            }
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+#[cfg(not(target_os = "windows"))]
+fn open_stream() -> Option<Stream> {
+    Stream::connect(endpoint()).ok()
+}
+
+/// Connection to the running app.
+pub struct IpcClient {
+    reader: BufReader<Stream>,
+    next_id: u64,
+}
+
+/// None when the app isn't running.
+pub fn connect() -> Option<IpcClient> {
+    open_stream().map(IpcClient::new)
+}
+
+/// Whether the app serves the endpoint, even when it can't take a connection right now.
+#[cfg(target_os = "windows")]
+pub fn app_running() -> bool {
+    use crate::winDisplays::to_wide_null_terminated;
+    use windows::core::PCWSTR;
+    use windows::Win32::System::Pipes::WaitNamedPipeW;
+
+    const ERROR_FILE_NOT_FOUND: i32 = 2;
+    let name = to_wide_null_terminated(&endpoint());
+    // Times out rather than failing with "not found" while every instance is busy
+    unsafe { WaitNamedPipeW(PCWSTR(name.as_ptr()), 1) }.as_bool()
+        || std::io::Error::last_os_error().raw_os_error() != Some(ERROR_FILE_NOT_FOUND)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn app_running() -> bool {
+    open_stream().is_some()
+}
+
+impl IpcClient {
+    fn new(stream: Stream) -> Self {
+        Self {
+            reader: BufReader::new(stream),
+            next_id: 0,
+        }
+    }
+
+    /// Calls a method of the running app; errors carry the app's message.
+    pub fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let request = rpc::Request {
+            jsonrpc: "2.0".to_string(),
+            id: Value::from(self.next_id),
+            method: method.to_string(),
+            params,
+        };
+        let mut line = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+        line.push('\n');
+        let stream = self.reader.get_mut();
+        stream
+            .write_all(line.as_bytes())
+            .and_then(|_| stream.flush())
+            .map_err(|e| format!("Failed to send to WinDisplay: {}", e))?;
+
+        let mut reply = String::new();
+        match self.reader.read_line(&mut reply) {
+            Ok(0) => return Err("WinDisplay closed the connection".to_string()),
+            Ok(_) => {}
+            Err(e) => return Err(format!("Failed to read from WinDisplay: {}", e)),
+        }
+        let response: rpc::Response = serde_json::from_str(&reply)
+            .map_err(|e| format!("Invalid response from WinDisplay: {}", e))?;
+        match response.error {
+            Some(e) => Err(e.message),
+            None => Ok(response.result.unwrap_or(Value::Null)),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    // Serves the fake provider on a socket of its own, so a running app isn't disturbed
+    fn serve() -> String {
+        let path = std::env::temp_dir()
+            .join(format!("windisplay-test-{}.sock", std::process::id()))
+            .display()
+            .to_string();
+        let server_path = path.clone();
+        std::thread::spawn(move || {
+            listen(&server_path, |line| {
+                rpc::respond(line, rpc::dispatch_provider)
+            })
+        });
+        for _ in 0..100 {
+            if UnixStream::connect(&path).is_ok() {
+                return path;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("{} never came up", path);
+    }
+
+    fn send(reader: &mut BufReader<UnixStream>, line: &str) -> rpc::Response {
+        let stream = reader.get_mut();
+        stream.write_all(line.as_bytes()).unwrap();
+        stream.write_all(b"\n").unwrap();
+        let mut reply = String::new();
+        reader.read_line(&mut reply).unwrap();
+        serde_json::from_str(&reply).unwrap()
+    }
+
+    #[test]
+    fn round_trips_requests_over_the_socket() {
+        let path = serve();
+
+        let mut raw = BufReader::new(UnixStream::connect(&path).unwrap());
+        let response = send(&mut raw, "{not json");
+        assert_eq!(response.error.unwrap().code, rpc::PARSE_ERROR);
+        assert_eq!(response.id, Value::Null);
+        let response = send(&mut raw, r#"{"jsonrpc":"2.0","id":1}"#);
+        assert_eq!(response.error.unwrap().code, rpc::INVALID_REQUEST);
+        let response = send(&mut raw, r#"{"jsonrpc":"2.0","id":2,"method":"reboot"}"#);
+        assert_eq!(response.id, json!(2));
+        assert_eq!(response.error.unwrap().code, rpc::METHOD_NOT_FOUND);
+        let response = send(
+            &mut raw,
+            r#"{"jsonrpc":"2.0","id":"a","method":"get_monitor_brightness","params":{}}"#,
+        );
+        assert_eq!(response.id, json!("a"));
+        let error = response.error.unwrap();
+        assert_eq!(error.code, rpc::INVALID_PARAMS);
+        assert!(error.message.contains("deviceName"), "{}", error.message);
+
+        // A second connection is served alongside the first
+        let mut client = IpcClient::new(UnixStream::connect(&path).unwrap());
+        let monitors = client.call("get_all_monitors", Value::Null).unwrap();
+        let device = monitors[0]["device_name"].as_str().unwrap().to_string();
+        let profile = "Fake Calibrated.icc";
+        client
+            .call(
+                "set_monitor_color_profile",
+                json!({ "deviceName": device, "profile": profile }),
+            )
+            .unwrap();
+        assert_eq!(
+            client
+                .call("get_monitor_color_profile", json!({ "deviceName": device }))
+                .unwrap(),
+            json!(profile)
+        );
+        let err = client.call("reboot", json!({})).unwrap_err();
+        assert_eq!(err, "Unknown method 'reboot'");
+        // The first connection is still open after the second one's calls
+        let response = send(&mut raw, r#"{"id":3,"method":"get_all_monitors"}"#);
+        assert!(response.error.is_none());
+        assert_eq!(response.result.unwrap(), monitors);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
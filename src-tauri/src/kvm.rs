@@ -0,0 +1,403 @@
+// KVM presets: switch a group of monitors to their assigned inputs in one go, e.g. everything to
+// the work laptop. Presets live in settings.kvmPresets and run from the UI, CLI, tray, hotkeys or
+// when a USB device (typically a USB switch) arrives or goes away.
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::displays::DisplayInfo;
+
+// A USB switch re-enumerates several devices at once; they trigger the preset only once
+const USB_TRIGGER_COOLDOWN: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KvmTarget {
+    // Monitor identity (see `monitor_identity`) or GDI device name
+    pub monitor: String,
+    // Anything set_monitor_input_source accepts: "hdmi1", "usbc1", "0x0F", ... or a name the
+    // user gave one of the monitor's inputs (settings.inputNames), resolved when the preset runs
+    pub input: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsbEvent {
+    #[default]
+    Connect,
+    Disconnect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KvmPreset {
+    pub name: String,
+    pub targets: Vec<KvmTarget>,
+    // Global shortcut such as "Ctrl+Alt+1"; empty for none
+    pub hotkey: String,
+    // "VID:PID" of a USB device (e.g. "046D:C52B") whose arrival or removal runs the preset
+    pub usb_device: String,
+    pub usb_event: UsbEvent,
+}
+
+/// Outcome for one monitor of a preset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KvmResult {
+    pub monitor: String,
+    pub input: String,
+    // None when the switch succeeded
+    pub error: Option<String>,
+}
+
+/// Parses "046D:C52B" (or "046d-c52b") into vendor and product ids.
+pub fn parse_usb_id(text: &str) -> Option<(u16, u16)> {
+    let (vid, pid) = text.trim().split_once([':', '-'])?;
+    Some((
+        u16::from_str_radix(vid.trim(), 16).ok()?,
+        u16::from_str_radix(pid.trim(), 16).ok()?,
+    ))
+}
+
+/// Vendor and product ids in a device interface path like "\\?\USB#VID_046D&PID_C52B#...".
+pub fn usb_id_from_path(path: &str) -> Option<(u16, u16)> {
+    let upper = path.to_ascii_uppercase();
+    let hex_after = |tag: &str| {
+        let start = upper.find(tag)? + tag.len();
+        u16::from_str_radix(upper.get(start..start + 4)?, 16).ok()
+    };
+    Some((hex_after("VID_")?, hex_after("PID_")?))
+}
+
+fn matches(target: &KvmTarget, monitor: &DisplayInfo) -> bool {
+    target.monitor == monitor.device_name
+        || target.monitor == crate::monitor_identity::identity_key(monitor)
+}
+
+// The input code or key to send for `target`, resolving a name the user gave one of the
+// monitor's inputs the same way set_monitor_input_source does
+fn target_input(
+    target: &KvmTarget,
+    monitor: &DisplayInfo,
+    input_names: &HashMap<String, HashMap<String, String>>,
+) -> String {
+    match input_names.get(&crate::monitor_identity::identity_key(monitor)) {
+        Some(names) => crate::inputs::resolve_name(names, &target.input),
+        None => target.input.clone(),
+    }
+}
+
+/// Switches every target of `preset` at once (DDC/CI round trips take a while per monitor)
+/// and reports each one. Monitors that aren't connected are reported, not skipped silently.
+/// `input_names` are the user's input names (settings.inputNames) that targets may refer to.
+pub fn apply(
+    preset: &KvmPreset,
+    input_names: &HashMap<String, HashMap<String, String>>,
+) -> Result<Vec<KvmResult>, String> {
+    let monitors = crate::displays::active_provider().get_all_monitors()?;
+    crate::monitor_identity::remember(&monitors);
+
+    Ok(std::thread::scope(|scope| {
+        let handles: Vec<_> = preset
+            .targets
+            .iter()
+            .map(|target| {
+                let monitor = monitors.iter().find(|m| matches(target, m));
+                let handle = scope.spawn(move || match monitor {
+                    Some(m) => crate::displays::active_provider()
+                        .set_monitor_input_source(
+                            m.device_name.clone(),
+                            target_input(target, m, input_names),
+                        )
+                        .err(),
+                    None => Some("Monitor is not connected".to_string()),
+                });
+                (target, monitor, handle)
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|(target, monitor, handle)| KvmResult {
+                monitor: monitor
+                    .map(|m| m.friendly_name.clone())
+                    .unwrap_or_else(|| target.monitor.clone()),
+                input: target.input.clone(),
+                error: handle
+                    .join()
+                    .unwrap_or_else(|_| Some("Input switch failed".to_string())),
+            })
+            .collect()
+    }))
+}
+
+fn find_preset(app_handle: &AppHandle, name: &str) -> Result<KvmPreset, String> {
+    crate::settings::get(app_handle)
+        .kvm_presets
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("KVM preset '{}' not found", name))
+}
+
+/// Runs a preset from the tray, a hotkey or a USB trigger: logs the outcome and lets the UI
+/// refresh. Call it off the UI thread.
+pub fn run_preset(app_handle: &AppHandle, name: &str) {
+    let input_names = crate::settings::get(app_handle).input_names;
+    let results =
+        match find_preset(app_handle, name).and_then(|preset| apply(&preset, &input_names)) {
+            Ok(results) => results,
+            Err(e) => {
+                log::warn!("KVM preset '{}' failed: {}", name, e);
+                return;
+            }
+        };
+    for result in &results {
+        match &result.error {
+            None => log::info!(
+                "KVM preset '{}': {} -> {}",
+                name,
+                result.monitor,
+                result.input
+            ),
+            Some(e) => log::warn!(
+                "KVM preset '{}': {} -> {} failed: {}",
+                name,
+                result.monitor,
+                result.input,
+                e
+            ),
+        }
+    }
+    let _ = app_handle.emit("display-changed", ());
+}
+
+fn last_usb_triggers() -> &'static Mutex<HashMap<String, Instant>> {
+    static LAST: OnceLock<Mutex<HashMap<String, Instant>>> = OnceLock::new();
+    LAST.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn waits_for(preset: &KvmPreset, id: (u16, u16), event: UsbEvent) -> bool {
+    preset.usb_event == event && parse_usb_id(&preset.usb_device) == Some(id)
+}
+
+// Whether `preset` may run again at `now`, and if so records that it did
+fn take_usb_trigger(last: &mut HashMap<String, Instant>, preset: &str, now: Instant) -> bool {
+    if last
+        .get(preset)
+        .is_some_and(|at| now.duration_since(*at) < USB_TRIGGER_COOLDOWN)
+    {
+        return false;
+    }
+    last.insert(preset.to_string(), now);
+    true
+}
+
+/// Called for every USB device arrival/removal; runs the presets that wait for it.
+pub fn usb_device_changed(app_handle: &AppHandle, device_path: &str, connected: bool) {
+    let Some(id) = usb_id_from_path(device_path) else {
+        return;
+    };
+    log::info!(
+        "USB device {:04X}:{:04X} {}",
+        id.0,
+        id.1,
+        if connected {
+            "connected"
+        } else {
+            "disconnected"
+        }
+    );
+    let event = if connected {
+        UsbEvent::Connect
+    } else {
+        UsbEvent::Disconnect
+    };
+    for preset in crate::settings::get(app_handle).kvm_presets {
+        if !waits_for(&preset, id, event) {
+            continue;
+        }
+        if let Ok(mut last) = last_usb_triggers().lock() {
+            if !take_usb_trigger(&mut last, &preset.name, Instant::now()) {
+                continue;
+            }
+        }
+        let app_handle = app_handle.clone();
+        std::thread::spawn(move || run_preset(&app_handle, &preset.name));
+    }
+}
+
+#[tauri::command]
+pub fn list_kvm_presets(app_handle: AppHandle) -> Vec<KvmPreset> {
+    crate::settings::get(&app_handle).kvm_presets
+}
+
+/// Saves `preset`, replacing one with the same name. Targets given by device name are stored
+/// by monitor identity so the preset survives the monitor moving to another port.
+#[tauri::command]
+pub fn save_kvm_preset(app_handle: AppHandle, mut preset: KvmPreset) -> Result<KvmPreset, String> {
+    preset.name = preset.name.trim().to_string();
+    if preset.name.is_empty() {
+        return Err("Preset name must not be empty".to_string());
+    }
+    let monitors = crate::displays::active_provider().get_all_monitors()?;
+    for target in &mut preset.targets {
+        if let Some(monitor) = monitors.iter().find(|m| m.device_name == target.monitor) {
+            target.monitor = crate::monitor_identity::identity_key(monitor);
+        }
+    }
+    let mut settings = crate::settings::get_user(&app_handle);
+    match settings
+        .kvm_presets
+        .iter_mut()
+        .find(|p| p.name == preset.name)
+    {
+        Some(existing) => *existing = preset.clone(),
+        None => settings.kvm_presets.push(preset.clone()),
+    }
+    crate::settings::save(&app_handle, settings)?;
+    Ok(preset)
+}
+
+#[tauri::command]
+pub fn delete_kvm_preset(app_handle: AppHandle, name: String) -> Result<(), String> {
+    let mut settings = crate::settings::get_user(&app_handle);
+    let before = settings.kvm_presets.len();
+    settings.kvm_presets.retain(|p| p.name != name);
+    if settings.kvm_presets.len() == before {
+        return Err(format!("KVM preset '{}' not found", name));
+    }
+    crate::settings::save(&app_handle, settings)
+}
+
+#[tauri::command]
+pub fn apply_kvm_preset(app_handle: AppHandle, name: String) -> Result<Vec<KvmResult>, String> {
+    let input_names = crate::settings::get(&app_handle).input_names;
+    apply(&find_preset(&app_handle, &name)?, &input_names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displays::Displays;
+    use crate::fakeDisplays::FakeDisplays;
+
+    fn monitor() -> DisplayInfo {
+        let mut monitor = FakeDisplays::new().get_all_monitors().unwrap().remove(0);
+        monitor.manufacturer = "DEL".to_string();
+        monitor.model = "DELL U2720Q".to_string();
+        monitor.serial = "SER123".to_string();
+        monitor
+    }
+
+    fn target(monitor: &str, input: &str) -> KvmTarget {
+        KvmTarget {
+            monitor: monitor.to_string(),
+            input: input.to_string(),
+        }
+    }
+
+    fn usb_preset(usb_device: &str, usb_event: UsbEvent) -> KvmPreset {
+        KvmPreset {
+            name: "Laptop".to_string(),
+            usb_device: usb_device.to_string(),
+            usb_event,
+            ..KvmPreset::default()
+        }
+    }
+
+    #[test]
+    fn targets_match_by_identity_or_device_name() {
+        let monitor = monitor();
+        assert!(matches(&target("SER123", "hdmi1"), &monitor));
+        assert!(matches(&target(&monitor.device_name, "hdmi1"), &monitor));
+        assert!(!matches(&target("SER999", "hdmi1"), &monitor));
+        assert!(!matches(&target("DEL:DELL U2720Q", "hdmi1"), &monitor));
+    }
+
+    #[test]
+    fn target_inputs_resolve_user_names() {
+        let monitor = monitor();
+        let input_names = HashMap::from([(
+            "SER123".to_string(),
+            HashMap::from([("hdmi2".to_string(), "Work Laptop".to_string())]),
+        )]);
+        assert_eq!(
+            target_input(&target("SER123", "work laptop"), &monitor, &input_names),
+            "hdmi2"
+        );
+        assert_eq!(
+            target_input(&target("SER123", "dp1"), &monitor, &input_names),
+            "dp1"
+        );
+        // Names of another monitor don't apply
+        let other = HashMap::from([(
+            "SER999".to_string(),
+            HashMap::from([("hdmi2".to_string(), "Work Laptop".to_string())]),
+        )]);
+        assert_eq!(
+            target_input(&target("SER123", "Work Laptop"), &monitor, &other),
+            "Work Laptop"
+        );
+    }
+
+    #[test]
+    fn parses_usb_ids() {
+        assert_eq!(parse_usb_id("046D:C52B"), Some((0x046D, 0xC52B)));
+        assert_eq!(parse_usb_id(" 046d-c52b "), Some((0x046D, 0xC52B)));
+        assert_eq!(parse_usb_id("046D"), None);
+        assert_eq!(parse_usb_id("logitech:receiver"), None);
+        assert_eq!(parse_usb_id("12345:C52B"), None);
+        assert_eq!(
+            usb_id_from_path(r"\\?\USB#VID_046D&PID_C52B#5&1b2c3d4&0&1#{a5dcbf10-6530-11d2-901f}"),
+            Some((0x046D, 0xC52B))
+        );
+        assert_eq!(
+            usb_id_from_path(r"\\?\usb#vid_1a86&pid_7523#6&2f0e&0&2"),
+            Some((0x1A86, 0x7523))
+        );
+        assert_eq!(usb_id_from_path(r"\\?\HID#VID_046D#7&1"), None);
+    }
+
+    #[test]
+    fn presets_wait_for_their_device_and_event() {
+        let preset = usb_preset("046D:C52B", UsbEvent::Connect);
+        assert!(waits_for(&preset, (0x046D, 0xC52B), UsbEvent::Connect));
+        assert!(!waits_for(&preset, (0x046D, 0xC52B), UsbEvent::Disconnect));
+        assert!(!waits_for(&preset, (0x046D, 0xC52C), UsbEvent::Connect));
+        assert!(!waits_for(
+            &usb_preset("", UsbEvent::Connect),
+            (0, 0),
+            UsbEvent::Connect
+        ));
+    }
+
+    #[test]
+    fn usb_triggers_cool_down_per_preset() {
+        let mut last = HashMap::new();
+        let start = Instant::now();
+        assert!(take_usb_trigger(&mut last, "Laptop", start));
+        // The other devices of the same switch re-enumerating right after
+        assert!(!take_usb_trigger(
+            &mut last,
+            "Laptop",
+            start + Duration::from_secs(1)
+        ));
+        assert!(take_usb_trigger(
+            &mut last,
+            "Desktop",
+            start + Duration::from_secs(1)
+        ));
+        assert!(take_usb_trigger(
+            &mut last,
+            "Laptop",
+            start + USB_TRIGGER_COOLDOWN
+        ));
+        // The cooldown restarts from the last run
+        assert!(!take_usb_trigger(
+            &mut last,
+            "Laptop",
+            start + USB_TRIGGER_COOLDOWN + Duration::from_secs(1)
+        ));
+    }
+}
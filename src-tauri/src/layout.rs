@@ -0,0 +1,235 @@
+// Desktop layout fix-up after a monitor changes size (e.g. rotating to portrait), so neighbours
+// stay attached instead of overlapping it or leaving a gap. Pure Rust.
+
+/// A monitor's area on the virtual desktop, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitorRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MonitorRect {
+    pub fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
+}
+
+// Growth along one axis. The edge facing the primary monitor stays put, so the resized monitor
+// never pushes into the primary (which must stay at the origin); monitors beyond the far edge
+// move by the size difference. Returns the new start and whether the end edge was the anchor.
+fn resize_axis(
+    start: i32,
+    end: i32,
+    new_len: u32,
+    primary_start: i32,
+    is_primary: bool,
+) -> (i32, bool) {
+    if !is_primary && end <= primary_start {
+        (end - new_len as i32, true)
+    } else {
+        (start, false)
+    }
+}
+
+// Moves a monitor along one axis if it lies entirely past the resized monitor's moving edge
+fn shift_axis(
+    start: &mut i32,
+    len: u32,
+    old_start: i32,
+    old_end: i32,
+    anchored_end: bool,
+    delta: i32,
+) {
+    if anchored_end {
+        if *start + len as i32 <= old_start {
+            *start -= delta;
+        }
+    } else if *start >= old_end {
+        *start += delta;
+    }
+}
+
+/// Returns the layout after monitor `index` takes `new_size` (width, height), keeping every
+/// other monitor's placement relative to it. `primary` is the index of the primary monitor.
+pub fn resize_in_layout(
+    rects: &[MonitorRect],
+    index: usize,
+    primary: usize,
+    new_size: (u32, u32),
+) -> Vec<MonitorRect> {
+    let mut out = rects.to_vec();
+    let (Some(old), Some(primary_rect)) = (rects.get(index).copied(), rects.get(primary)) else {
+        return out;
+    };
+    let is_primary = index == primary;
+    let (new_x, anchored_right) =
+        resize_axis(old.x, old.right(), new_size.0, primary_rect.x, is_primary);
+    let (new_y, anchored_bottom) =
+        resize_axis(old.y, old.bottom(), new_size.1, primary_rect.y, is_primary);
+    let dx = new_size.0 as i32 - old.width as i32;
+    let dy = new_size.1 as i32 - old.height as i32;
+
+    for (i, rect) in out.iter_mut().enumerate() {
+        if i == index {
+            continue;
+        }
+        shift_axis(
+            &mut rect.x,
+            rect.width,
+            old.x,
+            old.right(),
+            anchored_right,
+            dx,
+        );
+        shift_axis(
+            &mut rect.y,
+            rect.height,
+            old.y,
+            old.bottom(),
+            anchored_bottom,
+            dy,
+        );
+    }
+    out[index] = MonitorRect {
+        x: new_x,
+        y: new_y,
+        width: new_size.0,
+        height: new_size.1,
+    };
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> MonitorRect {
+        MonitorRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn rotating_the_primary_moves_neighbours_past_it() {
+        let layout = [
+            rect(0, 0, 1920, 1080),
+            rect(1920, 0, 2560, 1440),
+            rect(0, 1080, 1920, 1080),
+        ];
+        let out = resize_in_layout(&layout, 0, 0, (1080, 1920));
+        assert_eq!(
+            out,
+            [
+                rect(0, 0, 1080, 1920),
+                rect(1080, 0, 2560, 1440),
+                rect(0, 1920, 1920, 1080),
+            ]
+        );
+    }
+
+    #[test]
+    fn monitor_left_of_primary_grows_away_from_it() {
+        let layout = [
+            rect(-3840, 0, 1920, 1080),
+            rect(-1920, 0, 1920, 1080),
+            rect(0, 0, 2560, 1440),
+        ];
+        let out = resize_in_layout(&layout, 1, 2, (1080, 1920));
+        assert_eq!(
+            out,
+            [
+                rect(-3000, 0, 1920, 1080),
+                rect(-1080, 0, 1080, 1920),
+                rect(0, 0, 2560, 1440),
+            ]
+        );
+        // Still attached on both sides
+        assert_eq!(out[0].right(), out[1].x);
+        assert_eq!(out[1].right(), out[2].x);
+    }
+
+    #[test]
+    fn monitor_above_primary_grows_upwards() {
+        let layout = [rect(0, 0, 1920, 1080), rect(0, -1440, 2560, 1440)];
+        let out = resize_in_layout(&layout, 1, 0, (1440, 2560));
+        assert_eq!(out, [rect(0, 0, 1920, 1080), rect(0, -2560, 1440, 2560)]);
+        assert_eq!(out[1].bottom(), out[0].y);
+    }
+
+    #[test]
+    fn vertical_stack() {
+        let layout = [
+            rect(0, 0, 1920, 1080),
+            rect(0, 1080, 1920, 1080),
+            rect(0, 2160, 1920, 1080),
+        ];
+        let out = resize_in_layout(&layout, 1, 0, (1080, 1920));
+        assert_eq!(
+            out,
+            [
+                rect(0, 0, 1920, 1080),
+                rect(0, 1080, 1080, 1920),
+                rect(0, 3000, 1920, 1080),
+            ]
+        );
+    }
+
+    #[test]
+    fn three_monitor_row() {
+        let layout = [
+            rect(-1920, 0, 1920, 1080),
+            rect(0, 0, 1920, 1080),
+            rect(1920, 0, 1920, 1080),
+        ];
+        // Only monitors past the resized one's far edge move
+        let out = resize_in_layout(&layout, 2, 1, (1080, 1920));
+        assert_eq!(&out[..2], &layout[..2]);
+        assert_eq!(out[2], rect(1920, 0, 1080, 1920));
+
+        let out = resize_in_layout(&layout, 1, 1, (1080, 1920));
+        assert_eq!(
+            out,
+            [
+                rect(-1920, 0, 1920, 1080),
+                rect(0, 0, 1080, 1920),
+                rect(1080, 0, 1920, 1080),
+            ]
+        );
+
+        // Primary on the left: the one beyond the middle follows it
+        let row = [
+            rect(0, 0, 1920, 1080),
+            rect(1920, 0, 1920, 1080),
+            rect(3840, 0, 1920, 1080),
+        ];
+        let out = resize_in_layout(&row, 1, 0, (1080, 1920));
+        assert_eq!(out[0], row[0]);
+        assert_eq!(out[1], rect(1920, 0, 1080, 1920));
+        assert_eq!(out[2], rect(3000, 0, 1920, 1080));
+    }
+
+    #[test]
+    fn no_op_resize_and_bad_indices() {
+        let layout = [
+            rect(-1920, 0, 1920, 1080),
+            rect(0, 0, 2560, 1440),
+            rect(2560, 200, 1920, 1080),
+        ];
+        for index in 0..layout.len() {
+            let size = (layout[index].width, layout[index].height);
+            assert_eq!(resize_in_layout(&layout, index, 1, size), layout);
+        }
+        assert_eq!(resize_in_layout(&layout, 5, 1, (800, 600)), layout);
+        assert_eq!(resize_in_layout(&layout, 0, 5, (800, 600)), layout);
+        assert!(resize_in_layout(&[], 0, 0, (800, 600)).is_empty());
+    }
+}
@@ -1,26 +1,47 @@
 use tauri::WindowEvent;
 
+#[cfg(target_os = "windows")]
+mod auto_hdr;
+mod brightness_calibration;
+mod brightness_fade;
+mod brightness_schedule;
 mod cli;
+mod color_temperature;
+mod config_transfer;
 mod display_monitor;
 mod displays;
+mod dpi_scale;
+mod edid;
 mod fakeDisplays;
+mod gamma_ramp;
 #[cfg(target_os = "windows")]
 mod hotkeys;
+mod http_api;
+mod icc;
+mod inputs;
+mod ipc;
+mod kvm;
+mod layout;
+mod mode_selection;
+mod monitor_identity;
+mod mqtt;
+mod mqtt_bridge;
+mod policy;
 mod positioning;
+mod profiles;
+mod remoteDisplays;
+mod rpc;
 mod settings;
+mod solar;
+mod state_persistence;
+mod tray_icon;
+mod tray_menu;
+mod websocket;
 #[cfg(target_os = "windows")]
 mod winDisplays;
 pub mod winHdr;
-
-const AUTOSTART_BASE_LABEL: &str = "Start at login";
-
-fn autostart_label(enabled: bool) -> String {
-    if enabled {
-        format!("{AUTOSTART_BASE_LABEL} ✓")
-    } else {
-        AUTOSTART_BASE_LABEL.to_string()
-    }
-}
+#[cfg(target_os = "windows")]
+mod winPolicy;
 
 pub fn run() {
     match cli::run_cli() {
@@ -38,7 +59,6 @@ pub fn run() {
 
     use crate::positioning;
     use tauri::{
-        menu::{Menu, MenuItem},
         tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
         Manager, WebviewUrl, WebviewWindowBuilder,
     };
@@ -58,16 +78,44 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             displays::get_all_monitors,
             displays::set_monitor_resolution,
+            displays::list_monitor_modes,
+            displays::set_monitor_mode,
             displays::set_monitor_orientation,
             displays::get_monitor_brightness,
             displays::set_monitor_brightness,
+            displays::fade_monitor_brightness,
+            displays::set_monitor_dimming,
+            displays::set_monitor_color_temperature,
+            displays::get_monitor_color_temperature,
             displays::identify_monitors,
             displays::set_monitor_scale,
+            displays::set_custom_scale,
+            displays::set_monitor_scaling_mode,
             displays::enable_hdr,
+            displays::set_monitor_vrr,
+            displays::set_sdr_white_level,
             displays::set_monitor_input_source,
             displays::get_monitor_input_source,
             displays::get_monitor_ddc_caps,
             displays::set_monitor_power,
+            displays::list_color_profiles,
+            displays::get_monitor_color_profile,
+            displays::set_monitor_color_profile,
+            inputs::list_monitor_inputs,
+            inputs::set_input_name,
+            profiles::list_display_profiles,
+            profiles::save_display_profile,
+            profiles::apply_display_profile,
+            profiles::delete_display_profile,
+            kvm::list_kvm_presets,
+            kvm::save_kvm_preset,
+            kvm::delete_kvm_preset,
+            kvm::apply_kvm_preset,
+            config_transfer::export_config,
+            config_transfer::import_config,
+            policy::get_policy,
+            http_api::get_http_api_token,
+            http_api::regenerate_http_api_token,
         ])
         .setup(|app| {
             // Log settings file location
@@ -75,6 +123,8 @@ pub fn run() {
                 settings_path.push("settings.json");
                 log::info!("Settings file: {}", settings_path.display());
             }
+            // Migrate settings.json and start publishing edits as "settings-changed"
+            crate::settings::start_settings_watcher(app.handle());
 
             // helper to reveal main window
             fn reveal_main_window(app_handle: &tauri::AppHandle) {
@@ -97,7 +147,13 @@ pub fn run() {
                     };
                     if !marker.exists() {
                         let manager = app.autolaunch();
-                        if !manager.is_enabled().unwrap_or(false) {
+                        // A policy default can keep it off
+                        let wanted = crate::policy::current()
+                            .defaults
+                            .get(crate::policy::AUTOSTART_KEY)
+                            .and_then(|v| v.as_bool())
+                            != Some(false);
+                        if wanted && !manager.is_enabled().unwrap_or(false) {
                             let _ = manager.enable();
                         }
                         if let Some(parent) = marker.parent() {
@@ -118,8 +174,23 @@ pub fn run() {
                     let _ = manager.enable();
                 }
             }
+            // Machine policy has the last word on "Start at login"
+            #[cfg(all(desktop, not(debug_assertions)))]
+            {
+                use tauri_plugin_autostart::ManagerExt;
+                if let Some(enabled) = crate::policy::current().autostart() {
+                    let manager = app.autolaunch();
+                    if manager.is_enabled().unwrap_or(false) != enabled {
+                        let _ = if enabled {
+                            manager.enable()
+                        } else {
+                            manager.disable()
+                        };
+                    }
+                }
+            }
             // Show a notification on startup to inform the user the app is running in the tray
-            if crate::settings::should_show_startup_notification_app(&app) {
+            if crate::settings::get(app.handle()).show_startup_notification {
                 app.notification()
                     .builder()
                     .title("WinDisplay")
@@ -128,26 +199,15 @@ pub fn run() {
                     .unwrap();
             }
 
-            // Build a tray context menu
-            let show_item = MenuItem::with_id(app, "show", "Show", true, Some(""))?;
-            let autostart_item = {
-                use tauri_plugin_autostart::ManagerExt;
-                let enabled = app.autolaunch().is_enabled().unwrap_or(false);
-                let label = autostart_label(enabled);
-                MenuItem::with_id(app, "autostart_toggle", label.as_str(), true, Some(""))?
-            };
-            let about_item = MenuItem::with_id(app, "about", "About", true, Some(""))?;
-            let exit_item = MenuItem::with_id(app, "exit", "Exit", true, Some(""))?;
-            let menu =
-                Menu::with_items(app, &[&show_item, &autostart_item, &about_item, &exit_item])?;
+            // Build a tray context menu; monitors and profiles are filled in once it's up
+            let menu = tray_menu::initial_menu(app.handle())?;
 
             // Create tray icon using default app icon
-            let tray_builder = TrayIconBuilder::new()
+            let tray_builder = TrayIconBuilder::with_id(tray_menu::TRAY_ID)
                 .menu(&menu)
                 .icon(app.default_window_icon().unwrap().clone())
                 .tooltip("WinDisplay");
 
-            let autostart_item_handle = autostart_item.clone();
             let tray_builder =
                 tray_builder.on_menu_event(move |app_handle, event| match event.id().as_ref() {
                     "show" => {
@@ -162,8 +222,7 @@ pub fn run() {
                         } else {
                             manager.enable()
                         };
-                        let new_label = autostart_label(!currently_enabled);
-                        let _ = autostart_item_handle.set_text(new_label.as_str());
+                        tray_menu::refresh();
                     }
                     "about" => {
                         if let Some(window) = app_handle.get_webview_window("about") {
@@ -188,7 +247,9 @@ pub fn run() {
                     "exit" => {
                         app_handle.exit(0);
                     }
-                    _ => {}
+                    id => {
+                        tray_menu::handle_menu_event(app_handle, id);
+                    }
                 });
 
             let tray_builder = tray_builder.on_tray_icon_event(|tray, event| {
@@ -214,6 +275,7 @@ pub fn run() {
             });
 
             tray_builder.build(app)?;
+            tray_menu::start_menu_updates(app.handle().clone());
 
             // Start monitoring for display changes
             if let Err(e) = display_monitor::start_display_monitor(app.handle().clone()) {
@@ -222,17 +284,29 @@ pub fn run() {
 
             // UI reveal on monitor change handled in display_monitor.rs based on settings
 
-            // Start global hotkey service (Windows only) if enabled by settings
+            // Put remembered brightness/input/power back on startup and when a monitor reconnects
+            state_persistence::start_state_persistence_service(app.handle().clone());
+
+            // Drive brightness from the time-of-day schedule (no-op while disabled in settings)
+            brightness_schedule::start_schedule_service(app.handle().clone());
+
+            // Toggle HDR for fullscreen apps (no-op while disabled in settings)
             #[cfg(target_os = "windows")]
-            {
-                if crate::settings::should_register_brightness_hotkeys_app(&app) {
-                    crate::hotkeys::start_hotkey_service(app.handle().clone());
-                } else {
-                    log::info!(
-                        "Skipping brightness hotkeys startup: keyboardBrightnessShortcut is 'system'"
-                    );
-                }
-            }
+            auto_hdr::start_auto_hdr_service(app.handle().clone());
+
+            // Local JSON-RPC endpoint so the CLI and scripts can drive the running app
+            ipc::start_ipc_server(app.handle().clone());
+
+            // Token-protected HTTP/WebSocket API on 127.0.0.1 (no-op while disabled in settings)
+            http_api::start_http_api_service(app.handle().clone());
+
+            // Publish monitors to an MQTT broker (no-op while disabled in settings)
+            mqtt_bridge::start_mqtt_service(app.handle().clone());
+
+            // Global hotkey service (Windows only); keys are registered only while
+            // keyboardBrightnessShortcut isn't 'system' and follow the setting live
+            #[cfg(target_os = "windows")]
+            crate::hotkeys::start_hotkey_service(app.handle().clone());
 
             // Keep main window hidden until tray click (config also sets visible: false)
             if let Some(window) = app.get_webview_window("main") {
@@ -241,7 +315,7 @@ pub fn run() {
                 let app_handle_for_event = app.handle().clone();
                 window.on_window_event(move |event| {
                     if let WindowEvent::Focused(false) = event {
-                        if crate::settings::should_hide_ui_on_focus_out_handle(&app_handle_for_event) {
+                        if crate::settings::get(&app_handle_for_event).should_hide_ui_on_focus_out {
                             let _ = window_for_event.hide();
                         }
                     }
@@ -0,0 +1,314 @@
+// Picks a display mode out of the list a monitor reports, so a request that isn't an exact
+// match (or a fractional refresh rate) still lands on a sensible mode. Pure Rust.
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::displays::Resolution;
+
+// Rates Windows reports rounded down in DEVMODE when the real rate is x/1.001 (59.94 -> 59)
+const NTSC_NOMINAL_RATES: [u32; 6] = [24, 30, 48, 60, 120, 240];
+
+// Two aspect ratios within this are treated as equal (1366x768 counts as 16:9)
+const ASPECT_TOLERANCE: f64 = 0.01;
+
+// Refresh rates within this count as the same rate for exact matching
+const REFRESH_TOLERANCE: f64 = 0.01;
+
+fn round_rate(rate: f64) -> f64 {
+    (rate * 1000.0).round() / 1000.0
+}
+
+/// Best guess at the real refresh rate of a mode Windows reports as `hz`.
+pub fn nominal_refresh_rate(hz: u32) -> f64 {
+    if NTSC_NOMINAL_RATES.contains(&(hz + 1)) {
+        round_rate((hz + 1) as f64 * 1000.0 / 1001.0)
+    } else {
+        hz as f64
+    }
+}
+
+/// Refresh rate from a DisplayConfig rational, None when the denominator is zero.
+pub fn rational_refresh_rate(numerator: u32, denominator: u32) -> Option<f64> {
+    if denominator == 0 || numerator == 0 {
+        return None;
+    }
+    Some(round_rate(numerator as f64 / denominator as f64))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModePolicy {
+    /// Only the requested size and refresh rate; fails if the monitor doesn't offer them
+    #[default]
+    Exact,
+    /// The closest size to the requested one
+    Closest,
+    /// The closest size with the same aspect ratio as the requested one
+    ExactAspect,
+    /// The monitor's native size, whatever size was requested
+    Native,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeRequest {
+    pub width: u32,
+    pub height: u32,
+    // None picks the highest refresh rate available at the chosen size
+    #[serde(default)]
+    pub refresh_rate: Option<f64>,
+    #[serde(default)]
+    pub policy: ModePolicy,
+    // Interlaced modes are only preferred when asked for
+    #[serde(default)]
+    pub interlaced: bool,
+}
+
+fn aspect(width: u32, height: u32) -> f64 {
+    width as f64 / height.max(1) as f64
+}
+
+fn same_rate(mode: &Resolution, rate: f64) -> bool {
+    (mode.refresh_rate - rate).abs() < REFRESH_TOLERANCE
+        || (mode.refresh_hz as f64 - rate).abs() < REFRESH_TOLERANCE
+}
+
+// Nearest size by squared distance, larger area on ties
+fn closest_size<'a>(
+    modes: impl Iterator<Item = &'a Resolution>,
+    width: u32,
+    height: u32,
+) -> Option<(u32, u32)> {
+    let distance = |m: &Resolution| {
+        let dw = m.width as i64 - width as i64;
+        let dh = m.height as i64 - height as i64;
+        dw * dw + dh * dh
+    };
+    modes
+        .min_by(|a, b| {
+            distance(a).cmp(&distance(b)).then_with(|| {
+                (b.width as u64 * b.height as u64).cmp(&(a.width as u64 * a.height as u64))
+            })
+        })
+        .map(|m| (m.width, m.height))
+}
+
+// Lower is better: matching scan type, then refresh rate, then native timing, then color depth
+fn compare(a: &Resolution, b: &Resolution, request: &ModeRequest) -> Ordering {
+    let scan_mismatch = |m: &Resolution| m.interlaced != request.interlaced;
+    let refresh_cost = |m: &Resolution| match request.refresh_rate {
+        Some(rate) => (m.refresh_rate - rate).abs(),
+        None => -m.refresh_rate,
+    };
+    scan_mismatch(a)
+        .cmp(&scan_mismatch(b))
+        .then_with(|| refresh_cost(a).total_cmp(&refresh_cost(b)))
+        .then_with(|| a.scaled.cmp(&b.scaled))
+        .then_with(|| b.bits_per_pixel.cmp(&a.bits_per_pixel))
+}
+
+/// Index of the mode in `modes` that best satisfies `request`, or None if the policy can't be met.
+/// `native` is the monitor's preferred size; without it `Native` falls back to the largest mode.
+pub fn select_mode(
+    modes: &[Resolution],
+    request: &ModeRequest,
+    native: Option<(u32, u32)>,
+) -> Option<usize> {
+    let size = match request.policy {
+        ModePolicy::Exact => (request.width, request.height),
+        ModePolicy::Closest => closest_size(modes.iter(), request.width, request.height)?,
+        ModePolicy::ExactAspect => {
+            let wanted = aspect(request.width, request.height);
+            closest_size(
+                modes
+                    .iter()
+                    .filter(|m| (aspect(m.width, m.height) - wanted).abs() < ASPECT_TOLERANCE),
+                request.width,
+                request.height,
+            )?
+        }
+        ModePolicy::Native => native.or_else(|| {
+            modes
+                .iter()
+                .max_by_key(|m| m.width as u64 * m.height as u64)
+                .map(|m| (m.width, m.height))
+        })?,
+    };
+
+    modes
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| (m.width, m.height) == size)
+        .filter(|(_, m)| match (request.policy, request.refresh_rate) {
+            (ModePolicy::Exact, Some(rate)) => same_rate(m, rate),
+            _ => true,
+        })
+        .min_by(|(_, a), (_, b)| compare(a, b, request))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(width: u32, height: u32, refresh_hz: u32) -> Resolution {
+        Resolution {
+            width,
+            height,
+            bits_per_pixel: 32,
+            refresh_hz,
+            refresh_rate: nominal_refresh_rate(refresh_hz),
+            interlaced: false,
+            scaled: false,
+        }
+    }
+
+    fn request(
+        width: u32,
+        height: u32,
+        refresh_rate: Option<f64>,
+        policy: ModePolicy,
+    ) -> ModeRequest {
+        ModeRequest {
+            width,
+            height,
+            refresh_rate,
+            policy,
+            interlaced: false,
+        }
+    }
+
+    fn size_of(modes: &[Resolution], index: Option<usize>) -> Option<(u32, u32, u32)> {
+        index.map(|i| (modes[i].width, modes[i].height, modes[i].refresh_hz))
+    }
+
+    fn modes() -> Vec<Resolution> {
+        vec![
+            mode(1280, 800, 60),
+            mode(1366, 768, 60),
+            mode(1920, 1080, 59),
+            mode(1920, 1080, 60),
+            mode(1920, 1080, 144),
+            mode(2560, 1440, 60),
+            mode(2560, 1440, 165),
+        ]
+    }
+
+    #[test]
+    fn fractional_refresh_rates() {
+        assert_eq!(nominal_refresh_rate(59), 59.94);
+        assert_eq!(nominal_refresh_rate(23), 23.976);
+        assert_eq!(nominal_refresh_rate(60), 60.0);
+        assert_eq!(nominal_refresh_rate(144), 144.0);
+        assert_eq!(rational_refresh_rate(60000, 1001), Some(59.94));
+        assert_eq!(rational_refresh_rate(144, 1), Some(144.0));
+        assert_eq!(rational_refresh_rate(60, 0), None);
+        assert_eq!(rational_refresh_rate(0, 1), None);
+    }
+
+    #[test]
+    fn exact_tells_59_94_from_60() {
+        let modes = modes();
+        let pick = |rate| {
+            size_of(
+                &modes,
+                select_mode(&modes, &request(1920, 1080, rate, ModePolicy::Exact), None),
+            )
+        };
+        assert_eq!(pick(Some(59.94)), Some((1920, 1080, 59)));
+        // The rate as DEVMODE lists it works too
+        assert_eq!(pick(Some(59.0)), Some((1920, 1080, 59)));
+        assert_eq!(pick(Some(60.0)), Some((1920, 1080, 60)));
+        assert_eq!(pick(None), Some((1920, 1080, 144)));
+        assert_eq!(pick(Some(75.0)), None);
+    }
+
+    #[test]
+    fn exact_needs_the_requested_size() {
+        let modes = modes();
+        let exact = request(1600, 900, None, ModePolicy::Exact);
+        assert_eq!(select_mode(&modes, &exact, None), None);
+        assert_eq!(select_mode(&[], &exact, None), None);
+    }
+
+    #[test]
+    fn closest_size_prefers_larger_area_on_ties() {
+        let modes = modes();
+        let closest = |width, height, rate| {
+            size_of(
+                &modes,
+                select_mode(
+                    &modes,
+                    &request(width, height, rate, ModePolicy::Closest),
+                    None,
+                ),
+            )
+        };
+        assert_eq!(closest(1900, 1000, None), Some((1920, 1080, 144)));
+        // Nearest rate at the chosen size, even when the exact rate isn't offered
+        assert_eq!(closest(2500, 1400, Some(120.0)), Some((2560, 1440, 165)));
+        // Halfway between 1080p and 1440p
+        assert_eq!(closest(2240, 1260, Some(60.0)), Some((2560, 1440, 60)));
+        assert_eq!(
+            select_mode(&[], &request(1920, 1080, None, ModePolicy::Closest), None),
+            None
+        );
+    }
+
+    #[test]
+    fn exact_aspect_keeps_the_shape() {
+        let modes = modes();
+        let pick = |policy| {
+            size_of(
+                &modes,
+                select_mode(&modes, &request(1280, 720, None, policy), None),
+            )
+        };
+        // 1280x800 is nearer, but 16:10
+        assert_eq!(pick(ModePolicy::Closest), Some((1280, 800, 60)));
+        assert_eq!(pick(ModePolicy::ExactAspect), Some((1366, 768, 60)));
+        // Equally near 16:9 sizes resolve to the larger one
+        let tie = request(2240, 1260, None, ModePolicy::ExactAspect);
+        assert_eq!(
+            size_of(&modes, select_mode(&modes, &tie, None)),
+            Some((2560, 1440, 165))
+        );
+        // No mode with the requested aspect
+        let square = request(1024, 1024, None, ModePolicy::ExactAspect);
+        assert_eq!(select_mode(&modes, &square, None), None);
+    }
+
+    #[test]
+    fn native_uses_the_preferred_size() {
+        let modes = modes();
+        let native = request(800, 600, Some(60.0), ModePolicy::Native);
+        assert_eq!(
+            size_of(&modes, select_mode(&modes, &native, Some((1920, 1080)))),
+            Some((1920, 1080, 60))
+        );
+        // Without a known native size, the largest mode
+        assert_eq!(
+            size_of(&modes, select_mode(&modes, &native, None)),
+            Some((2560, 1440, 60))
+        );
+        // A native size the monitor doesn't list
+        assert_eq!(select_mode(&modes, &native, Some((3840, 2160))), None);
+        assert_eq!(select_mode(&[], &native, None), None);
+    }
+
+    #[test]
+    fn prefers_progressive_native_timing_and_depth() {
+        let mut interlaced = mode(1920, 1080, 60);
+        interlaced.interlaced = true;
+        let mut scaled = mode(1920, 1080, 60);
+        scaled.scaled = true;
+        let mut shallow = mode(1920, 1080, 60);
+        shallow.bits_per_pixel = 16;
+        let modes = vec![interlaced, scaled, shallow, mode(1920, 1080, 60)];
+
+        let mut wanted = request(1920, 1080, Some(60.0), ModePolicy::Exact);
+        assert_eq!(select_mode(&modes, &wanted, None), Some(3));
+        wanted.interlaced = true;
+        assert_eq!(select_mode(&modes, &wanted, None), Some(0));
+    }
+}
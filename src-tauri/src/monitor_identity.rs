@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::displays::{DisplayInfo, Displays};
+
+/// Stable key for a physical monitor: the EDID serial when present, otherwise
+/// manufacturer + model, and the GDI device name as a last resort.
+/// Used to key per-monitor settings so they follow the monitor across ports and reboots.
+pub fn identity_key(info: &DisplayInfo) -> String {
+    let serial = info.serial.trim();
+    if !serial.is_empty() && serial != "0" {
+        return serial.to_string();
+    }
+    let manufacturer = info.manufacturer.trim();
+    let model = info.model.trim();
+    if !manufacturer.is_empty() || !model.is_empty() {
+        return format!("{}:{}", manufacturer, model);
+    }
+    info.device_name.clone()
+}
+
+// device_name -> identity key; rebuilt lazily because get_all_monitors is slow (EDID via WMI)
+fn cache() -> &'static Mutex<HashMap<String, String>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Records identities from a fresh monitor list, replacing the previous mapping.
+pub fn remember(monitors: &[DisplayInfo]) {
+    if let Ok(mut map) = cache().lock() {
+        map.clear();
+        for m in monitors {
+            map.insert(m.device_name.clone(), identity_key(m));
+        }
+    }
+}
+
+/// Drops the cached mapping; device names can be reassigned when displays change.
+pub fn invalidate() {
+    if let Ok(mut map) = cache().lock() {
+        map.clear();
+    }
+}
+
+/// Returns the identity key for `device_name`, enumerating monitors if it isn't cached yet.
+pub fn identity_for_device(provider: &dyn Displays, device_name: &str) -> Option<String> {
+    if let Some(key) = cache()
+        .lock()
+        .ok()
+        .and_then(|map| map.get(device_name).cloned())
+    {
+        return Some(key);
+    }
+    match provider.get_all_monitors() {
+        Ok(monitors) => {
+            remember(&monitors);
+            monitors
+                .iter()
+                .find(|m| m.device_name == device_name)
+                .map(identity_key)
+        }
+        Err(e) => {
+            log::warn!(
+                "Failed to resolve monitor identity for {}: {}",
+                device_name,
+                e
+            );
+            None
+        }
+    }
+}
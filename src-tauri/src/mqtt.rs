@@ -0,0 +1,496 @@
+// Minimal MQTT 3.1.1 client: QoS 0 publish/subscribe, retained messages, a last will and
+// keep-alive pings. Pure Rust like websocket.rs; the bridge in mqtt_bridge.rs drives it.
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// Largest packet accepted from the broker; commands are a few bytes
+const MAX_PACKET: usize = 256 * 1024;
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xC0;
+const PINGRESP: u8 = 0xD0;
+const DISCONNECT: u8 = 0xE0;
+
+pub struct ConnectOptions {
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub keep_alive_secs: u16,
+    // Retained message the broker publishes if the connection drops: (topic, payload)
+    pub will: Option<(String, Vec<u8>)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Packet {
+    ConnAck {
+        return_code: u8,
+    },
+    Publish {
+        topic: String,
+        payload: Vec<u8>,
+        // Set for QoS 1 messages, which need a PUBACK
+        packet_id: Option<u16>,
+    },
+    SubAck,
+    PingResp,
+    Other(u8),
+}
+
+fn push_str(buf: &mut Vec<u8>, s: &[u8]) {
+    buf.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    buf.extend_from_slice(s);
+}
+
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut out = vec![header];
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    out.extend(body);
+    out
+}
+
+pub fn connect_packet(options: &ConnectOptions) -> Vec<u8> {
+    let mut flags = 0x02; // clean session
+    if options.will.is_some() {
+        flags |= 0x04 | 0x20; // will, retained
+    }
+    if options.username.is_some() {
+        flags |= 0x80;
+    }
+    if options.password.is_some() {
+        flags |= 0x40;
+    }
+    let mut body = Vec::new();
+    push_str(&mut body, b"MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&options.keep_alive_secs.to_be_bytes());
+    push_str(&mut body, options.client_id.as_bytes());
+    if let Some((topic, message)) = &options.will {
+        push_str(&mut body, topic.as_bytes());
+        push_str(&mut body, message);
+    }
+    if let Some(username) = &options.username {
+        push_str(&mut body, username.as_bytes());
+    }
+    if let Some(password) = &options.password {
+        push_str(&mut body, password.as_bytes());
+    }
+    packet(CONNECT, body)
+}
+
+pub fn publish_packet(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_str(&mut body, topic.as_bytes());
+    body.extend_from_slice(payload);
+    packet(PUBLISH | retain as u8, body)
+}
+
+pub fn subscribe_packet(packet_id: u16, filters: &[&str]) -> Vec<u8> {
+    let mut body = packet_id.to_be_bytes().to_vec();
+    for filter in filters {
+        push_str(&mut body, filter.as_bytes());
+        body.push(0); // QoS 0
+    }
+    packet(SUBSCRIBE, body)
+}
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn take_str(body: &[u8], pos: &mut usize) -> std::io::Result<String> {
+    let len_bytes = body
+        .get(*pos..*pos + 2)
+        .ok_or_else(|| invalid("Truncated MQTT string"))?;
+    let len = u16::from_be_bytes([len_bytes[0], len_bytes[1]]) as usize;
+    let bytes = body
+        .get(*pos + 2..*pos + 2 + len)
+        .ok_or_else(|| invalid("Truncated MQTT string"))?;
+    *pos += 2 + len;
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
+pub fn read_packet<R: Read>(reader: &mut R) -> std::io::Result<Packet> {
+    let mut header = [0u8; 1];
+    reader.read_exact(&mut header)?;
+    let mut len = 0usize;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        len |= ((byte[0] & 0x7F) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+        if shift > 21 {
+            return Err(invalid("Malformed MQTT remaining length"));
+        }
+    }
+    if len > MAX_PACKET {
+        return Err(invalid("MQTT packet too large"));
+    }
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body)?;
+
+    Ok(match header[0] & 0xF0 {
+        CONNACK => Packet::ConnAck {
+            return_code: *body.get(1).ok_or_else(|| invalid("Truncated CONNACK"))?,
+        },
+        PUBLISH => {
+            let qos = (header[0] >> 1) & 0x03;
+            let mut pos = 0;
+            let topic = take_str(&body, &mut pos)?;
+            let packet_id = if qos > 0 {
+                let id = body
+                    .get(pos..pos + 2)
+                    .ok_or_else(|| invalid("Truncated PUBLISH"))?;
+                pos += 2;
+                Some(u16::from_be_bytes([id[0], id[1]]))
+            } else {
+                None
+            };
+            Packet::Publish {
+                topic,
+                payload: body[pos..].to_vec(),
+                packet_id,
+            }
+        }
+        SUBACK => Packet::SubAck,
+        PINGRESP => Packet::PingResp,
+        other => Packet::Other(other),
+    })
+}
+
+/// Writing half of a broker connection; read packets from `reader()` on another thread.
+pub struct MqttConnection {
+    stream: TcpStream,
+    next_packet_id: u16,
+}
+
+impl MqttConnection {
+    /// Connects and waits for the broker to accept the session.
+    pub fn connect(host: &str, port: u16, options: &ConnectOptions) -> Result<Self, String> {
+        let address = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| format!("Can't resolve {}: {}", host, e))?
+            .next()
+            .ok_or_else(|| format!("Can't resolve {}", host))?;
+        let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+            .map_err(|e| format!("Can't connect to {}:{}: {}", host, port, e))?;
+        let _ = stream.set_nodelay(true);
+        stream
+            .set_read_timeout(Some(CONNECT_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+        stream
+            .write_all(&connect_packet(options))
+            .map_err(|e| e.to_string())?;
+        match read_packet(&mut stream).map_err(|e| e.to_string())? {
+            Packet::ConnAck { return_code: 0 } => {}
+            Packet::ConnAck { return_code: 4 | 5 } => {
+                return Err("The broker rejected the username or password".to_string())
+            }
+            Packet::ConnAck { return_code } => {
+                return Err(format!(
+                    "The broker refused the connection ({})",
+                    return_code
+                ))
+            }
+            other => return Err(format!("Expected CONNACK, got {:?}", other)),
+        }
+        stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+        Ok(Self {
+            stream,
+            next_packet_id: 0,
+        })
+    }
+
+    pub fn reader(&self) -> Result<TcpStream, String> {
+        self.stream.try_clone().map_err(|e| e.to_string())
+    }
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.stream
+            .write_all(bytes)
+            .map_err(|e| format!("MQTT connection lost: {}", e))
+    }
+
+    pub fn publish(&mut self, topic: &str, payload: &[u8], retain: bool) -> Result<(), String> {
+        self.send(&publish_packet(topic, payload, retain))
+    }
+
+    pub fn subscribe(&mut self, filters: &[&str]) -> Result<(), String> {
+        self.next_packet_id = self.next_packet_id.wrapping_add(1).max(1);
+        self.send(&subscribe_packet(self.next_packet_id, filters))
+    }
+
+    pub fn puback(&mut self, packet_id: u16) -> Result<(), String> {
+        let id = packet_id.to_be_bytes();
+        self.send(&[PUBACK, 2, id[0], id[1]])
+    }
+
+    pub fn ping(&mut self) -> Result<(), String> {
+        self.send(&[PINGREQ, 0])
+    }
+
+    /// Clean disconnect; the broker doesn't publish the last will.
+    pub fn disconnect(mut self) {
+        let _ = self.send(&[DISCONNECT, 0]);
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // What the broker stand-in sees on the wire: the first header byte and the body
+    fn read_raw(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let mut header = [0u8; 1];
+        stream.read_exact(&mut header).unwrap();
+        let mut len = 0usize;
+        let mut shift = 0;
+        loop {
+            let mut byte = [0u8; 1];
+            stream.read_exact(&mut byte).unwrap();
+            len |= ((byte[0] & 0x7F) as usize) << shift;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        let mut body = vec![0u8; len];
+        stream.read_exact(&mut body).unwrap();
+        (header[0], body)
+    }
+
+    fn take(body: &[u8], pos: &mut usize) -> Vec<u8> {
+        let len = u16::from_be_bytes([body[*pos], body[*pos + 1]]) as usize;
+        let bytes = body[*pos + 2..*pos + 2 + len].to_vec();
+        *pos += 2 + len;
+        bytes
+    }
+
+    fn options() -> ConnectOptions {
+        ConnectOptions {
+            client_id: "windisplay-test".to_string(),
+            username: Some("user".to_string()),
+            password: Some("secret".to_string()),
+            keep_alive_secs: 60,
+            will: Some(("windisplay/status".to_string(), b"offline".to_vec())),
+        }
+    }
+
+    /// Accepts one connection on a local port and hands it to `broker`.
+    fn stand_in<F>(broker: F) -> (u16, thread::JoinHandle<()>)
+    where
+        F: FnOnce(TcpStream) + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || broker(listener.accept().unwrap().0));
+        (port, handle)
+    }
+
+    #[test]
+    fn session_against_broker_stand_in() {
+        let (port, broker) = stand_in(|mut stream| {
+            let (header, body) = read_raw(&mut stream);
+            assert_eq!(header, CONNECT);
+            let mut pos = 0;
+            assert_eq!(take(&body, &mut pos), b"MQTT");
+            assert_eq!(body[pos], 4);
+            // Clean session, will, retained will, password, username
+            assert_eq!(body[pos + 1], 0x02 | 0x04 | 0x20 | 0x40 | 0x80);
+            assert_eq!(u16::from_be_bytes([body[pos + 2], body[pos + 3]]), 60);
+            pos += 4;
+            assert_eq!(take(&body, &mut pos), b"windisplay-test");
+            assert_eq!(take(&body, &mut pos), b"windisplay/status");
+            assert_eq!(take(&body, &mut pos), b"offline");
+            assert_eq!(take(&body, &mut pos), b"user");
+            assert_eq!(take(&body, &mut pos), b"secret");
+            assert_eq!(pos, body.len());
+            stream.write_all(&[CONNACK, 2, 0, 0]).unwrap();
+
+            // SUBSCRIBE must carry the reserved 0b0010 flags
+            let (header, body) = read_raw(&mut stream);
+            assert_eq!(header, 0x82);
+            assert_eq!(&body[..2], &[0, 1]);
+            let mut pos = 2;
+            assert_eq!(take(&body, &mut pos), b"windisplay/+/+/set");
+            assert_eq!(&body[pos..], &[0]);
+            stream.write_all(&[SUBACK, 3, 0, 1, 0]).unwrap();
+
+            let (header, body) = read_raw(&mut stream);
+            assert_eq!(header, PUBLISH | 0x01);
+            let mut pos = 0;
+            assert_eq!(take(&body, &mut pos), b"windisplay/status");
+            assert_eq!(&body[pos..], b"online");
+
+            // A QoS 1 command, which the client acknowledges
+            let mut command = vec![PUBLISH | 0x02, 0];
+            let mut body = Vec::new();
+            push_str(&mut body, b"windisplay/SER1/brightness/set");
+            body.extend_from_slice(&[0x12, 0x34]);
+            body.extend_from_slice(b"40");
+            command[1] = body.len() as u8;
+            command.extend(body);
+            stream.write_all(&command).unwrap();
+            assert_eq!(read_raw(&mut stream), (PUBACK, vec![0x12, 0x34]));
+
+            assert_eq!(read_raw(&mut stream), (PINGREQ, vec![]));
+            stream.write_all(&[PINGRESP, 0]).unwrap();
+            assert_eq!(read_raw(&mut stream), (DISCONNECT, vec![]));
+        });
+
+        let mut connection = MqttConnection::connect("127.0.0.1", port, &options()).unwrap();
+        let mut reader = connection.reader().unwrap();
+        connection.subscribe(&["windisplay/+/+/set"]).unwrap();
+        assert_eq!(read_packet(&mut reader).unwrap(), Packet::SubAck);
+        connection
+            .publish("windisplay/status", b"online", true)
+            .unwrap();
+        assert_eq!(
+            read_packet(&mut reader).unwrap(),
+            Packet::Publish {
+                topic: "windisplay/SER1/brightness/set".to_string(),
+                payload: b"40".to_vec(),
+                packet_id: Some(0x1234),
+            }
+        );
+        connection.puback(0x1234).unwrap();
+        connection.ping().unwrap();
+        assert_eq!(read_packet(&mut reader).unwrap(), Packet::PingResp);
+        connection.disconnect();
+        broker.join().unwrap();
+    }
+
+    #[test]
+    fn connect_without_credentials_or_will() {
+        let (port, broker) = stand_in(|mut stream| {
+            let (_, body) = read_raw(&mut stream);
+            assert_eq!(body[7], 0x02);
+            let mut pos = 10;
+            assert_eq!(take(&body, &mut pos), b"plain");
+            assert_eq!(pos, body.len());
+            stream.write_all(&[CONNACK, 2, 0, 0]).unwrap();
+        });
+        let options = ConnectOptions {
+            client_id: "plain".to_string(),
+            username: None,
+            password: None,
+            keep_alive_secs: 30,
+            will: None,
+        };
+        assert!(MqttConnection::connect("127.0.0.1", port, &options).is_ok());
+        broker.join().unwrap();
+    }
+
+    #[test]
+    fn refused_connections() {
+        for (code, message) in [
+            (4, "The broker rejected the username or password"),
+            (5, "The broker rejected the username or password"),
+            (2, "The broker refused the connection (2)"),
+        ] {
+            let (port, broker) = stand_in(move |mut stream| {
+                read_raw(&mut stream);
+                stream.write_all(&[CONNACK, 2, 0, code]).unwrap();
+            });
+            let err = MqttConnection::connect("127.0.0.1", port, &options())
+                .err()
+                .unwrap();
+            assert_eq!(err, message);
+            broker.join().unwrap();
+        }
+
+        let (port, broker) = stand_in(|mut stream| {
+            read_raw(&mut stream);
+            stream.write_all(&[PINGRESP, 0]).unwrap();
+        });
+        let err = MqttConnection::connect("127.0.0.1", port, &options())
+            .err()
+            .unwrap();
+        assert_eq!(err, "Expected CONNACK, got PingResp");
+        broker.join().unwrap();
+    }
+
+    #[test]
+    fn remaining_length_boundaries() {
+        for (len, encoded) in [
+            (0, vec![0x00]),
+            (127, vec![0x7F]),
+            (128, vec![0x80, 0x01]),
+            (16383, vec![0xFF, 0x7F]),
+            (16384, vec![0x80, 0x80, 0x01]),
+        ] {
+            let framed = packet(PUBLISH, vec![0xAB; len]);
+            assert_eq!(framed[0], PUBLISH);
+            assert_eq!(
+                &framed[1..1 + encoded.len()],
+                &encoded[..],
+                "length {}",
+                len
+            );
+            assert_eq!(framed.len(), 1 + encoded.len() + len);
+
+            // The same boundaries through a real PUBLISH: 2 + 3 bytes of topic, then payload
+            if len >= 5 {
+                let payload: Vec<u8> = (0..len - 5).map(|i| i as u8).collect();
+                let framed = publish_packet("a/b", &payload, false);
+                assert_eq!(
+                    &framed[1..1 + encoded.len()],
+                    &encoded[..],
+                    "length {}",
+                    len
+                );
+                assert_eq!(
+                    read_packet(&mut &framed[..]).unwrap(),
+                    Packet::Publish {
+                        topic: "a/b".to_string(),
+                        payload,
+                        packet_id: None,
+                    }
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_packets() {
+        // More than four length bytes
+        let err = read_packet(&mut &[PUBLISH, 0x80, 0x80, 0x80, 0x80, 0x01][..]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        // Larger than anything the bridge expects
+        let err = read_packet(&mut &[PUBLISH, 0x80, 0x80, 0x80, 0x01][..]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        // Topic length runs past the body
+        let err = read_packet(&mut &[PUBLISH, 3, 0, 9, b'a'][..]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(read_packet(&mut &[CONNACK, 1, 0][..]).is_err());
+        assert_eq!(
+            read_packet(&mut &[PUBACK, 2, 0, 1][..]).unwrap(),
+            Packet::Other(PUBACK)
+        );
+    }
+}
@@ -0,0 +1,571 @@
+// Optional MQTT bridge for office/home automation. Each monitor publishes under
+// "{topicPrefix}/{id}/..." where id is its EDID serial (monitor_identity::identity_key), and
+// "{topicPrefix}/{id}/{brightness|input|power}/set" are accepted as commands. With discovery on,
+// Home Assistant picks the monitors up as devices with brightness, power and input entities.
+use std::collections::{HashMap, HashSet};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Listener};
+
+use crate::displays::DisplayInfo;
+use crate::mqtt::{ConnectOptions, MqttConnection, Packet};
+use crate::settings::MqttSettings;
+
+const EVENTS: [&str; 2] = ["display-changed", "brightness-changed"];
+const KEEP_ALIVE_SECS: u16 = 60;
+const PING_INTERVAL: Duration = Duration::from_secs(KEEP_ALIVE_SECS as u64 / 2);
+// Changes made outside the app (monitor buttons, other tools) show up within this
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+// Slider drags and hotkey bursts are published once they settle
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(500);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(300);
+
+enum Message {
+    Refresh,
+    Settings(MqttSettings),
+    // Tagged with the session that read it, so a dead connection's leftovers are ignored
+    Packet(u64, Packet),
+    Disconnected(u64, String),
+}
+
+enum SessionEnd {
+    Settings(MqttSettings),
+    Lost(String),
+    Closed,
+}
+
+struct Topics {
+    prefix: String,
+    discovery_prefix: String,
+}
+
+impl Topics {
+    fn new(settings: &MqttSettings) -> Self {
+        Self {
+            prefix: settings.topic_prefix.clone(),
+            discovery_prefix: settings.discovery_prefix.clone(),
+        }
+    }
+
+    fn availability(&self) -> String {
+        format!("{}/status", self.prefix)
+    }
+
+    fn monitor(&self, id: &str, field: &str) -> String {
+        format!("{}/{}/{}", self.prefix, id, field)
+    }
+
+    fn commands(&self) -> String {
+        format!("{}/+/+/set", self.prefix)
+    }
+
+    /// Splits "{prefix}/{id}/{field}/set" into (id, field).
+    fn parse_command<'a>(&self, topic: &'a str) -> Option<(&'a str, &'a str)> {
+        let rest = topic.strip_prefix(&self.prefix)?.strip_prefix('/')?;
+        let rest = rest.strip_suffix("/set")?;
+        rest.split_once('/')
+    }
+
+    fn discovery(&self, component: &str, id: &str, field: &str) -> String {
+        format!(
+            "{}/{}/windisplay_{}/{}/config",
+            self.discovery_prefix, component, id, field
+        )
+    }
+}
+
+// Topic levels can't hold wildcards or slashes; serials are plain alphanumerics anyway
+fn topic_id(monitor: &DisplayInfo) -> String {
+    crate::monitor_identity::identity_key(monitor)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn client_id() -> String {
+    let host = std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_default();
+    format!("windisplay-{}", host.to_lowercase())
+}
+
+/// Home Assistant discovery messages for one monitor: (topic, config). `inputs` are the keys
+/// of the inputs it offers.
+fn discovery_configs(
+    topics: &Topics,
+    id: &str,
+    monitor: &DisplayInfo,
+    inputs: &[String],
+) -> Vec<(String, Value)> {
+    let device = json!({
+        "identifiers": [format!("windisplay_{}", id)],
+        "name": monitor.friendly_name,
+        "manufacturer": monitor.manufacturer,
+        "model": monitor.model,
+        "serial_number": monitor.serial,
+    });
+    let entity = |field: &str, name: &str| {
+        json!({
+            "name": name,
+            "unique_id": format!("windisplay_{}_{}", id, field),
+            "state_topic": topics.monitor(id, field),
+            "command_topic": format!("{}/set", topics.monitor(id, field)),
+            "availability_topic": topics.availability(),
+            "device": device,
+        })
+    };
+
+    let mut brightness = entity("brightness", "Brightness");
+    brightness["min"] = json!(0);
+    brightness["max"] = json!(100);
+    brightness["unit_of_measurement"] = json!("%");
+    brightness["icon"] = json!("mdi:brightness-6");
+    let mut power = entity("power", "Power");
+    power["payload_on"] = json!("ON");
+    power["payload_off"] = json!("OFF");
+    power["icon"] = json!("mdi:monitor");
+    let mut input = entity("input", "Input");
+    input["options"] = json!(inputs);
+    input["icon"] = json!("mdi:video-input-hdmi");
+
+    vec![
+        (topics.discovery("number", id, "brightness"), brightness),
+        (topics.discovery("switch", id, "power"), power),
+        (topics.discovery("select", id, "input"), input),
+    ]
+}
+
+struct Session<'a> {
+    app_handle: &'a AppHandle,
+    settings: &'a MqttSettings,
+    topics: Topics,
+    connection: MqttConnection,
+    // Topic id -> device name, refreshed with every publish
+    devices: HashMap<String, String>,
+    // Monitors whose discovery config went out on this connection
+    announced: HashSet<String>,
+}
+
+impl Session<'_> {
+    fn publish_state(&mut self) -> Result<(), String> {
+        let monitors = match crate::displays::get_all_monitors() {
+            Ok(monitors) => monitors,
+            Err(e) => {
+                log::warn!("MQTT: can't list monitors: {}", e);
+                return Ok(());
+            }
+        };
+        let provider = crate::displays::active_provider();
+        self.devices.clear();
+        for monitor in &monitors {
+            let id = topic_id(monitor);
+            self.devices.insert(id.clone(), monitor.device_name.clone());
+
+            if self.settings.discovery && self.announced.insert(id.clone()) {
+                let caps = provider
+                    .get_monitor_ddc_caps(monitor.device_name.clone())
+                    .ok();
+                let inputs: Vec<String> = crate::inputs::inputs_for(
+                    &monitor.manufacturer,
+                    caps.as_deref(),
+                    &HashMap::new(),
+                )
+                .into_iter()
+                .map(|input| input.key)
+                .collect();
+                for (topic, config) in discovery_configs(&self.topics, &id, monitor, &inputs) {
+                    self.connection
+                        .publish(&topic, config.to_string().as_bytes(), true)?;
+                }
+            }
+
+            let state = serde_json::to_string(monitor).map_err(|e| e.to_string())?;
+            self.connection
+                .publish(&self.topics.monitor(&id, "state"), state.as_bytes(), true)?;
+            let power = if monitor.enabled { "ON" } else { "OFF" };
+            self.connection
+                .publish(&self.topics.monitor(&id, "power"), power.as_bytes(), true)?;
+            if let Ok(info) = provider.get_monitor_brightness(monitor.device_name.clone()) {
+                let span = info.max.saturating_sub(info.min).max(1);
+                let percent = (info.current.saturating_sub(info.min) * 100 + span / 2) / span;
+                self.connection.publish(
+                    &self.topics.monitor(&id, "brightness"),
+                    percent.min(100).to_string().as_bytes(),
+                    true,
+                )?;
+            }
+            if let Ok(input) = provider.get_monitor_input_source(monitor.device_name.clone()) {
+                self.connection.publish(
+                    &self.topics.monitor(&id, "input"),
+                    input.as_bytes(),
+                    true,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_command(&self, topic: &str, payload: &[u8]) {
+        let Some((id, field)) = self.topics.parse_command(topic) else {
+            return;
+        };
+        let Some(device_name) = self.devices.get(id) else {
+            log::warn!("MQTT: command for unknown monitor '{}'", id);
+            return;
+        };
+        let value = String::from_utf8_lossy(payload).trim().to_string();
+        let (method, params) = match field {
+            // Home Assistant's number entity sends "42.0"
+            "brightness" => match value.parse::<f64>() {
+                Ok(percent) => (
+                    "set_monitor_brightness",
+                    json!({
+                        "deviceName": device_name,
+                        "percent": percent.round().clamp(0.0, 100.0) as u32,
+                    }),
+                ),
+                Err(_) => {
+                    log::warn!("MQTT: invalid brightness '{}' for {}", value, id);
+                    return;
+                }
+            },
+            "input" => (
+                "set_monitor_input_source",
+                json!({ "deviceName": device_name, "input": value }),
+            ),
+            "power" => match value.to_ascii_uppercase().as_str() {
+                "ON" | "TRUE" | "1" => (
+                    "set_monitor_power",
+                    json!({ "deviceName": device_name, "powerOn": true }),
+                ),
+                "OFF" | "FALSE" | "0" => (
+                    "set_monitor_power",
+                    json!({ "deviceName": device_name, "powerOn": false }),
+                ),
+                _ => {
+                    log::warn!("MQTT: invalid power state '{}' for {}", value, id);
+                    return;
+                }
+            },
+            _ => return,
+        };
+        // dispatch announces the change, which schedules the republish
+        if let Err(e) = crate::rpc::dispatch(self.app_handle, method, params) {
+            log::warn!("MQTT: {} for {} failed: {}", method, id, e.message);
+        }
+    }
+}
+
+fn spawn_reader(mut reader: TcpStream, generation: u64, tx: Sender<Message>) {
+    std::thread::spawn(move || loop {
+        match crate::mqtt::read_packet(&mut reader) {
+            Ok(packet) => {
+                if tx.send(Message::Packet(generation, packet)).is_err() {
+                    break;
+                }
+            }
+            Err(e) => {
+                let _ = tx.send(Message::Disconnected(generation, e.to_string()));
+                break;
+            }
+        }
+    });
+}
+
+fn run_session(
+    app_handle: &AppHandle,
+    settings: &MqttSettings,
+    generation: u64,
+    tx: &Sender<Message>,
+    rx: &Receiver<Message>,
+) -> Result<SessionEnd, String> {
+    let topics = Topics::new(settings);
+    let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+    let options = ConnectOptions {
+        client_id: client_id(),
+        username: non_empty(&settings.username),
+        password: non_empty(&settings.password),
+        keep_alive_secs: KEEP_ALIVE_SECS,
+        will: Some((topics.availability(), b"offline".to_vec())),
+    };
+    let connection = MqttConnection::connect(&settings.host, settings.port, &options)?;
+    spawn_reader(connection.reader()?, generation, tx.clone());
+    log::info!(
+        "MQTT: connected to {}:{} as {}",
+        settings.host,
+        settings.port,
+        options.client_id
+    );
+
+    let mut session = Session {
+        app_handle,
+        settings,
+        topics,
+        connection,
+        devices: HashMap::new(),
+        announced: HashSet::new(),
+    };
+    session
+        .connection
+        .subscribe(&[&session.topics.commands()])?;
+    session
+        .connection
+        .publish(&session.topics.availability(), b"online", true)?;
+
+    let mut refresh_at = Some(Instant::now());
+    let mut next_poll = Instant::now() + POLL_INTERVAL;
+    let mut next_ping = Instant::now() + PING_INTERVAL;
+    loop {
+        let now = Instant::now();
+        if refresh_at.is_some_and(|at| at <= now) || next_poll <= now {
+            session.publish_state()?;
+            refresh_at = None;
+            next_poll = Instant::now() + POLL_INTERVAL;
+        }
+        if next_ping <= now {
+            session.connection.ping()?;
+            next_ping = now + PING_INTERVAL;
+        }
+
+        let deadline = refresh_at
+            .unwrap_or(next_poll)
+            .min(next_poll)
+            .min(next_ping);
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(Message::Refresh) => {
+                refresh_at.get_or_insert_with(|| Instant::now() + REFRESH_DEBOUNCE);
+            }
+            Ok(Message::Settings(new_settings)) => {
+                if new_settings != *settings {
+                    let _ = session.connection.publish(
+                        &session.topics.availability(),
+                        b"offline",
+                        true,
+                    );
+                    session.connection.disconnect();
+                    return Ok(SessionEnd::Settings(new_settings));
+                }
+            }
+            Ok(Message::Packet(g, packet)) if g == generation => {
+                if let Packet::Publish {
+                    topic,
+                    payload,
+                    packet_id,
+                } = packet
+                {
+                    if let Some(id) = packet_id {
+                        session.connection.puback(id)?;
+                    }
+                    session.handle_command(&topic, &payload);
+                }
+            }
+            Ok(Message::Disconnected(g, e)) if g == generation => return Err(e),
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(SessionEnd::Closed),
+        }
+    }
+}
+
+fn run(app_handle: AppHandle, tx: Sender<Message>, rx: Receiver<Message>) {
+    let mut settings = crate::settings::get(&app_handle).mqtt;
+    let mut generation = 0;
+    let mut retry_delay = RECONNECT_DELAY;
+    loop {
+        if !settings.enabled || settings.host.is_empty() {
+            match rx.recv() {
+                Ok(Message::Settings(new_settings)) => settings = new_settings,
+                Ok(_) => {}
+                Err(_) => return,
+            }
+            continue;
+        }
+
+        generation += 1;
+        let started = Instant::now();
+        let end = run_session(&app_handle, &settings, generation, &tx, &rx)
+            .unwrap_or_else(SessionEnd::Lost);
+        match end {
+            SessionEnd::Settings(new_settings) => {
+                settings = new_settings;
+                retry_delay = RECONNECT_DELAY;
+            }
+            SessionEnd::Closed => return,
+            SessionEnd::Lost(e) => {
+                // A connection that held for a while starts the backoff over
+                if started.elapsed() > MAX_RECONNECT_DELAY {
+                    retry_delay = RECONNECT_DELAY;
+                }
+                log::warn!("MQTT: {}, retrying in {:?}", e, retry_delay);
+                let retry_at = Instant::now() + retry_delay;
+                retry_delay = (retry_delay * 2).min(MAX_RECONNECT_DELAY);
+                // Keep following settings while waiting, so a fixed host connects right away
+                while let Some(wait) = retry_at.checked_duration_since(Instant::now()) {
+                    match rx.recv_timeout(wait) {
+                        Ok(Message::Settings(new_settings)) if new_settings != settings => {
+                            settings = new_settings;
+                            retry_delay = RECONNECT_DELAY;
+                            break;
+                        }
+                        Ok(_) => {}
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+            }
+        }
+    }
+}
+
+static START_ONCE: Once = Once::new();
+
+/// Starts the bridge; it stays idle until `settings.mqtt.enabled` and follows later edits.
+pub fn start_mqtt_service(app_handle: AppHandle) {
+    START_ONCE.call_once(|| {
+        let (tx, rx) = mpsc::channel();
+        for event in EVENTS {
+            let tx = tx.clone();
+            app_handle.listen(event, move |_| {
+                let _ = tx.send(Message::Refresh);
+            });
+        }
+        let settings_rx = crate::settings::subscribe();
+        let settings_tx = tx.clone();
+        std::thread::spawn(move || {
+            while let Ok(settings) = settings_rx.recv() {
+                if settings_tx.send(Message::Settings(settings.mqtt)).is_err() {
+                    break;
+                }
+            }
+        });
+        std::thread::spawn(move || run(app_handle, tx, rx));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displays::Displays;
+    use crate::fakeDisplays::FakeDisplays;
+
+    fn with_prefix(prefix: &str) -> Topics {
+        Topics::new(&MqttSettings {
+            topic_prefix: prefix.to_string(),
+            ..MqttSettings::default()
+        })
+    }
+
+    fn monitor() -> DisplayInfo {
+        let mut monitor = FakeDisplays::new().get_all_monitors().unwrap().remove(0);
+        monitor.friendly_name = "DELL U2720Q".to_string();
+        monitor.manufacturer = "DEL".to_string();
+        monitor.model = "DELL U2720Q".to_string();
+        monitor.serial = "SER123".to_string();
+        monitor
+    }
+
+    #[test]
+    fn parses_command_topics() {
+        let topics = with_prefix("windisplay");
+        assert_eq!(topics.commands(), "windisplay/+/+/set");
+        assert_eq!(
+            topics.parse_command("windisplay/SER123/brightness/set"),
+            Some(("SER123", "brightness"))
+        );
+        assert_eq!(
+            topics.parse_command(&format!("{}/set", topics.monitor("SER123", "input"))),
+            Some(("SER123", "input"))
+        );
+        for topic in [
+            "windisplay/SER123/brightness",
+            "windisplay/SER123/set",
+            "windisplayx/SER123/brightness/set",
+            "other/SER123/brightness/set",
+            "windisplay/status",
+            "",
+        ] {
+            assert_eq!(topics.parse_command(topic), None, "{}", topic);
+        }
+
+        let nested = with_prefix("home/office/displays");
+        assert_eq!(
+            nested.parse_command("home/office/displays/SER123/power/set"),
+            Some(("SER123", "power"))
+        );
+        assert_eq!(nested.parse_command("home/office/SER123/power/set"), None);
+    }
+
+    #[test]
+    fn topic_ids_fall_back_to_model_without_slashes() {
+        assert_eq!(topic_id(&monitor()), "SER123");
+        let mut unserialized = monitor();
+        unserialized.serial = String::new();
+        unserialized.model = "U2720Q/B+".to_string();
+        assert_eq!(topic_id(&unserialized), "DEL_U2720Q_B_");
+    }
+
+    #[test]
+    fn home_assistant_discovery_payloads() {
+        let topics = with_prefix("windisplay");
+        let inputs = vec!["dp1".to_string(), "hdmi1".to_string()];
+        let configs = discovery_configs(&topics, "SER123", &monitor(), &inputs);
+        let config_topics: Vec<&str> = configs.iter().map(|(topic, _)| topic.as_str()).collect();
+        assert_eq!(
+            config_topics,
+            [
+                "homeassistant/number/windisplay_SER123/brightness/config",
+                "homeassistant/switch/windisplay_SER123/power/config",
+                "homeassistant/select/windisplay_SER123/input/config",
+            ]
+        );
+
+        let device = json!({
+            "identifiers": ["windisplay_SER123"],
+            "name": "DELL U2720Q",
+            "manufacturer": "DEL",
+            "model": "DELL U2720Q",
+            "serial_number": "SER123",
+        });
+        for ((_, config), field) in configs.iter().zip(["brightness", "power", "input"]) {
+            assert_eq!(config["unique_id"], format!("windisplay_SER123_{}", field));
+            assert_eq!(
+                config["state_topic"],
+                format!("windisplay/SER123/{}", field)
+            );
+            assert_eq!(
+                config["command_topic"],
+                format!("windisplay/SER123/{}/set", field)
+            );
+            assert_eq!(config["availability_topic"], "windisplay/status");
+            assert_eq!(config["device"], device);
+            // Every command topic is one the bridge subscribes to and can route back
+            let command = config["command_topic"].as_str().unwrap();
+            assert_eq!(topics.parse_command(command), Some(("SER123", field)));
+        }
+
+        let brightness = &configs[0].1;
+        assert_eq!(brightness["name"], "Brightness");
+        assert_eq!(
+            (brightness["min"].clone(), brightness["max"].clone()),
+            (json!(0), json!(100))
+        );
+        assert_eq!(brightness["unit_of_measurement"], "%");
+        let power = &configs[1].1;
+        assert_eq!(
+            (power["payload_on"].clone(), power["payload_off"].clone()),
+            (json!("ON"), json!("OFF"))
+        );
+        let input = &configs[2].1;
+        assert_eq!(input["options"], json!(["dp1", "hdmi1"]));
+    }
+}
@@ -0,0 +1,387 @@
+// Machine-wide managed configuration. IT can enforce values users can't change and push defaults
+// that users may change afterwards. Precedence, lowest first: built-in defaults, user settings
+// (with policy defaults seeded into them once), enforced policy.
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Policy key for "Start at login"; it isn't part of settings.json.
+pub const AUTOSTART_KEY: &str = "autostart";
+
+/// One policy layer. Both maps are partial settings objects; nested keys may also be written
+/// as dotted paths, e.g. "autoHdr.enabled".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Policy {
+    #[serde(default)]
+    pub enforced: Map<String, Value>,
+    #[serde(default)]
+    pub defaults: Map<String, Value>,
+}
+
+impl Policy {
+    pub fn is_empty(&self) -> bool {
+        self.enforced.is_empty() && self.defaults.is_empty()
+    }
+
+    /// Enforced "Start at login" state, if any.
+    pub fn autostart(&self) -> Option<bool> {
+        self.enforced.get(AUTOSTART_KEY).and_then(Value::as_bool)
+    }
+}
+
+/// Where machine-wide policy comes from.
+pub trait PolicySource {
+    fn describe(&self) -> String;
+    /// Ok(None) when this source has no policy configured.
+    fn load(&self) -> Result<Option<Policy>, String>;
+}
+
+/// Read-only JSON file, e.g. `%ProgramData%\WinDisplay\policy.json`.
+pub struct FilePolicySource {
+    pub path: PathBuf,
+}
+
+impl PolicySource for FilePolicySource {
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&self) -> Result<Option<Policy>, String> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map(Some)
+                .map_err(|e| format!("Invalid policy file {}: {}", self.path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {}: {}", self.path.display(), e)),
+        }
+    }
+}
+
+fn policy_file_path() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    let dir = std::env::var_os("ProgramData")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+        .join("WinDisplay");
+    #[cfg(not(target_os = "windows"))]
+    let dir = PathBuf::from("/etc/windisplay");
+    dir.join("policy.json")
+}
+
+// Highest precedence first: Group Policy beats the file
+fn sources() -> Vec<Box<dyn PolicySource>> {
+    vec![
+        #[cfg(target_os = "windows")]
+        Box::new(crate::winPolicy::RegistryPolicySource),
+        Box::new(FilePolicySource {
+            path: policy_file_path(),
+        }),
+    ]
+}
+
+// Top-level "a.b": 1 -> {"a": {"b": 1}}, merged into the objects already there
+fn expand_dotted(map: Map<String, Value>) -> Map<String, Value> {
+    let mut out = Map::new();
+    for (key, value) in map {
+        let mut nested = value;
+        for part in key.rsplit('.') {
+            nested = Value::Object(Map::from_iter([(part.to_string(), nested)]));
+        }
+        if let Value::Object(nested) = nested {
+            overlay(&mut out, &nested);
+        }
+    }
+    out
+}
+
+// Copies `top` over `base`; objects merge key by key, anything else is replaced
+fn overlay(base: &mut Map<String, Value>, top: &Map<String, Value>) {
+    for (key, value) in top {
+        match (base.get_mut(key), value) {
+            (Some(Value::Object(existing)), Value::Object(inner)) => overlay(existing, inner),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+// Dotted paths of every non-object value
+fn leaves(map: &Map<String, Value>, prefix: &str, out: &mut Vec<(String, Value)>) {
+    for (key, value) in map {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Object(inner) if !inner.is_empty() => leaves(inner, &path, out),
+            _ => out.push((path, value.clone())),
+        }
+    }
+}
+
+fn get_path<'a>(map: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+    let mut parts = path.split('.');
+    let mut value = map.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_object()?.get(part)?;
+    }
+    Some(value)
+}
+
+// Registry DWORDs arrive as numbers; turn them into booleans where the setting is a boolean
+fn coerce_types(map: &mut Map<String, Value>, template: &Map<String, Value>) {
+    for (key, value) in map.iter_mut() {
+        match (value, template.get(key)) {
+            (Value::Object(inner), Some(Value::Object(t))) => coerce_types(inner, t),
+            (value @ Value::Number(_), Some(Value::Bool(_))) => {
+                *value = Value::Bool(value.as_f64() != Some(0.0));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Combines layers, highest precedence first, into one policy with uniform nesting and types.
+/// `template` is a full settings object used to give registry values their proper types.
+pub fn combine(layers: Vec<Policy>, template: &Map<String, Value>) -> Policy {
+    let mut template = template.clone();
+    template.insert(AUTOSTART_KEY.to_string(), Value::Bool(false));
+    let mut combined = Policy::default();
+    for layer in layers.into_iter().rev() {
+        overlay(&mut combined.enforced, &expand_dotted(layer.enforced));
+        overlay(&mut combined.defaults, &expand_dotted(layer.defaults));
+    }
+    coerce_types(&mut combined.enforced, &template);
+    coerce_types(&mut combined.defaults, &template);
+    combined
+}
+
+/// User settings with the enforced policy on top, plus the dotted paths that are locked.
+pub fn merge(user: &Map<String, Value>, policy: &Policy) -> (Map<String, Value>, Vec<String>) {
+    let mut enforced = policy.enforced.clone();
+    enforced.remove(AUTOSTART_KEY);
+    let mut settings = user.clone();
+    overlay(&mut settings, &enforced);
+    let mut locked = Vec::new();
+    leaves(&policy.enforced, "", &mut locked);
+    (settings, locked.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Writes policy defaults into the user's settings the first time each one is seen, or when IT
+/// changes it. `seeded` records what was written (dotted path -> value). Returns true if `user`
+/// or `seeded` changed and should be saved.
+pub fn seed_defaults(
+    user: &mut Map<String, Value>,
+    policy: &Policy,
+    seeded: &mut Map<String, Value>,
+) -> bool {
+    let mut defaults = Vec::new();
+    leaves(&policy.defaults, "", &mut defaults);
+    let mut changed = false;
+    for (path, value) in defaults {
+        if path == AUTOSTART_KEY {
+            continue;
+        }
+        let pushed_before = seeded.get(&path) == Some(&value);
+        if pushed_before && get_path(user, &path).is_some() {
+            continue;
+        }
+        overlay(
+            user,
+            &expand_dotted(Map::from_iter([(path.clone(), value.clone())])),
+        );
+        seeded.insert(path, value);
+        changed = true;
+    }
+    changed
+}
+
+/// Machine policy, read once; a changed policy applies after restarting the app.
+pub fn current() -> &'static Policy {
+    static POLICY: OnceLock<Policy> = OnceLock::new();
+    POLICY.get_or_init(|| {
+        let mut layers = Vec::new();
+        for source in sources() {
+            match source.load() {
+                Ok(Some(policy)) if !policy.is_empty() => {
+                    log::info!("Policy loaded from {}", source.describe());
+                    layers.push(policy);
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Ignoring policy from {}: {}", source.describe(), e),
+            }
+        }
+        let template = match serde_json::to_value(crate::settings::Settings::default()) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        combine(layers, &template)
+    })
+}
+
+/// Enforced values and locked paths for the UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyStatus {
+    pub enforced: Map<String, Value>,
+    pub locked: Vec<String>,
+}
+
+#[tauri::command]
+pub fn get_policy() -> PolicyStatus {
+    let policy = current();
+    let (_, locked) = merge(&Map::new(), policy);
+    PolicyStatus {
+        enforced: policy.enforced.clone(),
+        locked,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("not an object: {}", value),
+        }
+    }
+
+    fn policy(enforced: Value, defaults: Value) -> Policy {
+        Policy {
+            enforced: object(enforced),
+            defaults: object(defaults),
+        }
+    }
+
+    fn template() -> Map<String, Value> {
+        object(json!({
+            "linkBrightness": false,
+            "autoHdr": { "enabled": false, "debounceMs": 2000 },
+            "httpApi": { "enabled": false, "port": 8765 },
+        }))
+    }
+
+    #[test]
+    fn registry_beats_the_file() {
+        let registry = policy(
+            json!({ "autoHdr.enabled": 1, "httpApi.port": 9000, "autostart": 0 }),
+            json!({ "linkBrightness": 0 }),
+        );
+        let file = policy(
+            json!({
+                "autoHdr": { "enabled": false, "debounceMs": 500 },
+                "httpApi": { "port": 8000 },
+                "theme": "dark",
+            }),
+            json!({ "linkBrightness": true, "httpApi.enabled": true }),
+        );
+        let combined = combine(vec![registry, file], &template());
+        // Dotted registry values land inside the file's objects; DWORDs become booleans only
+        // where the setting is one
+        assert_eq!(
+            Value::Object(combined.enforced.clone()),
+            json!({
+                "autoHdr": { "enabled": true, "debounceMs": 500 },
+                "httpApi": { "port": 9000 },
+                "theme": "dark",
+                "autostart": false,
+            })
+        );
+        assert_eq!(
+            Value::Object(combined.defaults.clone()),
+            json!({ "linkBrightness": false, "httpApi": { "enabled": true } })
+        );
+        assert_eq!(combined.autostart(), Some(false));
+    }
+
+    #[test]
+    fn dotted_paths_nest_and_merge() {
+        let combined = combine(
+            vec![policy(
+                json!({ "mqtt.host": "broker.corp", "mqtt.port": 1884, "a.b.c": "deep" }),
+                json!({}),
+            )],
+            &Map::new(),
+        );
+        assert_eq!(
+            Value::Object(combined.enforced),
+            json!({
+                "mqtt": { "host": "broker.corp", "port": 1884 },
+                "a": { "b": { "c": "deep" } },
+            })
+        );
+        assert!(combine(Vec::new(), &template()).is_empty());
+    }
+
+    #[test]
+    fn enforced_values_win_and_are_locked() {
+        let user = object(json!({
+            "theme": "dark",
+            "autoHdr": { "enabled": false, "apps": ["vlc.exe"] },
+        }));
+        let policy = policy(
+            json!({ "autoHdr": { "enabled": true }, "linkBrightness": true, "autostart": true }),
+            json!({ "theme": "light" }),
+        );
+        let (settings, mut locked) = merge(&user, &policy);
+        assert_eq!(
+            Value::Object(settings),
+            json!({
+                "theme": "dark",
+                "linkBrightness": true,
+                "autoHdr": { "enabled": true, "apps": ["vlc.exe"] },
+            })
+        );
+        locked.sort();
+        assert_eq!(
+            locked,
+            vec!["autoHdr.enabled", "autostart", "linkBrightness"]
+        );
+    }
+
+    #[test]
+    fn defaults_are_seeded_once_and_again_when_changed() {
+        let mut policy = policy(
+            json!({}),
+            json!({ "autoHdr": { "enabled": true }, "theme": "dark", "autostart": true }),
+        );
+        let mut user = object(json!({ "autoHdr": { "apps": ["vlc.exe"] } }));
+        let mut seeded = Map::new();
+
+        assert!(seed_defaults(&mut user, &policy, &mut seeded));
+        assert_eq!(
+            Value::Object(user.clone()),
+            json!({ "autoHdr": { "enabled": true, "apps": ["vlc.exe"] }, "theme": "dark" })
+        );
+        assert_eq!(
+            Value::Object(seeded.clone()),
+            json!({ "autoHdr.enabled": true, "theme": "dark" })
+        );
+        assert!(!seed_defaults(&mut user, &policy, &mut seeded));
+
+        // The user's own choice sticks while IT's default stays the same
+        user.insert("theme".to_string(), json!("light"));
+        assert!(!seed_defaults(&mut user, &policy, &mut seeded));
+        assert_eq!(user["theme"], "light");
+
+        // A key the user's settings lost (e.g. reset) gets the default back
+        user.remove("autoHdr");
+        assert!(seed_defaults(&mut user, &policy, &mut seeded));
+        assert_eq!(user["autoHdr"], json!({ "enabled": true }));
+
+        // A changed default is pushed once more, over the user's choice
+        policy.defaults.insert("theme".to_string(), json!("system"));
+        assert!(seed_defaults(&mut user, &policy, &mut seeded));
+        assert_eq!(user["theme"], "system");
+        assert_eq!(seeded["theme"], "system");
+        assert!(!seed_defaults(&mut user, &policy, &mut seeded));
+    }
+}
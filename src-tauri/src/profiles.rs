@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::displays::Displays;
+
+/// One monitor's part of a saved display setup. Monitors are matched by EDID identity
+/// (see `monitor_identity`) so a setup still applies after they move to another port.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorSetup {
+    pub identity: String,
+    pub width: u32,
+    pub height: u32,
+    pub refresh_hz: u32,
+    // Orientation in degrees: 0, 90, 180, 270
+    pub orientation: u32,
+    pub scale_percent: u32,
+    // Default ICC profile file name; None leaves the monitor's profile alone
+    #[serde(default)]
+    pub color_profile: Option<String>,
+}
+
+/// A named display setup, stored under the "profiles" key of settings.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayProfile {
+    pub name: String,
+    pub monitors: Vec<MonitorSetup>,
+}
+
+/// Captures the current state of every connected monitor.
+pub fn capture(provider: &dyn Displays, name: &str) -> Result<DisplayProfile, String> {
+    let monitors = provider.get_all_monitors()?;
+    crate::monitor_identity::remember(&monitors);
+    Ok(DisplayProfile {
+        name: name.to_string(),
+        monitors: monitors
+            .iter()
+            .map(|m| MonitorSetup {
+                identity: crate::monitor_identity::identity_key(m),
+                width: m.current.width,
+                height: m.current.height,
+                refresh_hz: m.current.refresh_hz,
+                orientation: m.orientation,
+                scale_percent: (m.scale * 100.0).round() as u32,
+                color_profile: m.color_profile.clone(),
+            })
+            .collect(),
+    })
+}
+
+/// Applies a saved setup to the monitors that are currently connected.
+/// Only settings that differ are changed; monitors missing from the setup are left alone.
+pub fn apply(provider: &dyn Displays, profile: &DisplayProfile) -> Result<(), String> {
+    let monitors = provider.get_all_monitors()?;
+    crate::monitor_identity::remember(&monitors);
+
+    let mut errors: Vec<String> = Vec::new();
+    for setup in &profile.monitors {
+        let Some(monitor) = monitors
+            .iter()
+            .find(|m| crate::monitor_identity::identity_key(m) == setup.identity)
+        else {
+            log::info!(
+                "Profile '{}': monitor {} is not connected, skipping",
+                profile.name,
+                setup.identity
+            );
+            continue;
+        };
+        let device_name = monitor.device_name.clone();
+
+        if monitor.orientation != setup.orientation {
+            if let Err(e) = provider.set_monitor_orientation(device_name.clone(), setup.orientation)
+            {
+                errors.push(format!("{}: orientation: {}", monitor.friendly_name, e));
+            }
+        }
+        if monitor.current.width != setup.width
+            || monitor.current.height != setup.height
+            || monitor.current.refresh_hz != setup.refresh_hz
+        {
+            if let Err(e) = provider.set_monitor_resolution(
+                device_name.clone(),
+                setup.width,
+                setup.height,
+                Some(setup.refresh_hz),
+            ) {
+                errors.push(format!("{}: resolution: {}", monitor.friendly_name, e));
+            }
+        }
+        if (monitor.scale * 100.0).round() as u32 != setup.scale_percent {
+            if let Err(e) = provider.set_monitor_scale(device_name.clone(), setup.scale_percent) {
+                errors.push(format!("{}: scale: {}", monitor.friendly_name, e));
+            }
+        }
+        if let Some(color_profile) = &setup.color_profile {
+            if monitor.color_profile.as_ref() != Some(color_profile) {
+                if let Err(e) =
+                    provider.set_monitor_color_profile(device_name.clone(), color_profile.clone())
+                {
+                    errors.push(format!("{}: color profile: {}", monitor.friendly_name, e));
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+/// Whether the connected monitors are set up as `profile` says. Monitors it doesn't cover are
+/// ignored, but at least one of its monitors has to be connected.
+pub fn matches(profile: &DisplayProfile, monitors: &[crate::displays::DisplayInfo]) -> bool {
+    let mut connected = 0;
+    for setup in &profile.monitors {
+        let Some(monitor) = monitors
+            .iter()
+            .find(|m| crate::monitor_identity::identity_key(m) == setup.identity)
+        else {
+            continue;
+        };
+        connected += 1;
+        if monitor.orientation != setup.orientation
+            || monitor.current.width != setup.width
+            || monitor.current.height != setup.height
+            || monitor.current.refresh_hz != setup.refresh_hz
+            || (monitor.scale * 100.0).round() as u32 != setup.scale_percent
+            || setup
+                .color_profile
+                .as_ref()
+                .is_some_and(|p| monitor.color_profile.as_ref() != Some(p))
+        {
+            return false;
+        }
+    }
+    connected > 0
+}
+
+#[tauri::command]
+pub fn list_display_profiles(app_handle: AppHandle) -> Vec<DisplayProfile> {
+    crate::settings::display_profiles_handle(&app_handle)
+}
+
+/// Saves the current setup under `name`, replacing an existing profile with the same name.
+#[tauri::command]
+pub fn save_display_profile(app_handle: AppHandle, name: String) -> Result<DisplayProfile, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name must not be empty".to_string());
+    }
+    let profile = capture(crate::displays::active_provider().as_ref(), &name)?;
+    let mut profiles = crate::settings::display_profiles_handle(&app_handle);
+    match profiles.iter_mut().find(|p| p.name == name) {
+        Some(existing) => *existing = profile.clone(),
+        None => profiles.push(profile.clone()),
+    }
+    crate::settings::save_display_profiles_handle(&app_handle, &profiles)?;
+    crate::tray_menu::refresh();
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn apply_display_profile(app_handle: AppHandle, name: String) -> Result<(), String> {
+    let profile = crate::settings::display_profiles_handle(&app_handle)
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Profile '{}' not found", name))?;
+    apply(crate::displays::active_provider().as_ref(), &profile)
+}
+
+#[tauri::command]
+pub fn delete_display_profile(app_handle: AppHandle, name: String) -> Result<(), String> {
+    let mut profiles = crate::settings::display_profiles_handle(&app_handle);
+    let before = profiles.len();
+    profiles.retain(|p| p.name != name);
+    if profiles.len() == before {
+        return Err(format!("Profile '{}' not found", name));
+    }
+    crate::settings::save_display_profiles_handle(&app_handle, &profiles)?;
+    crate::tray_menu::refresh();
+    Ok(())
+}
@@ -0,0 +1,243 @@
+// Provider that forwards every call to the running app over IPC, so CLI changes go through the
+// same state (schedule overrides, linked brightness, UI refresh) as changes made in the app.
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::displays::{
+    BrightnessInfo, ColorProfileInfo, ColorTemperatureInfo, DisplayInfo, Displays, Resolution,
+};
+use crate::ipc::IpcClient;
+
+pub struct RemoteDisplays {
+    client: Mutex<IpcClient>,
+}
+
+impl RemoteDisplays {
+    pub fn new(client: IpcClient) -> Self {
+        Self {
+            client: Mutex::new(client),
+        }
+    }
+
+    pub fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, String> {
+        let result = self
+            .client
+            .lock()
+            .map_err(|_| "IPC client lock poisoned".to_string())?
+            .call(method, params)?;
+        serde_json::from_value(result)
+            .map_err(|e| format!("Unexpected result for {}: {}", method, e))
+    }
+
+    pub fn fade_monitor_brightness(
+        &self,
+        device_name: String,
+        target: u32,
+        duration_ms: u64,
+    ) -> Result<(), String> {
+        self.call(
+            "fade_monitor_brightness",
+            json!({ "deviceName": device_name, "target": target, "durationMs": duration_ms }),
+        )
+    }
+}
+
+impl Displays for RemoteDisplays {
+    fn get_all_monitors(&self) -> Result<Vec<DisplayInfo>, String> {
+        self.call("get_all_monitors", json!({}))
+    }
+
+    fn get_all_monitors_short(&self) -> Result<Vec<String>, String> {
+        Ok(self
+            .get_all_monitors()?
+            .into_iter()
+            .map(|m| m.device_name)
+            .collect())
+    }
+
+    fn set_monitor_resolution(
+        &self,
+        device_name: String,
+        width: u32,
+        height: u32,
+        refresh_hz: Option<u32>,
+    ) -> Result<(), String> {
+        self.call(
+            "set_monitor_resolution",
+            json!({
+                "deviceName": device_name,
+                "width": width,
+                "height": height,
+                "refreshHz": refresh_hz,
+            }),
+        )
+    }
+
+    fn list_monitor_modes(
+        &self,
+        device_name: String,
+        include_raw: bool,
+    ) -> Result<Vec<Resolution>, String> {
+        self.call(
+            "list_monitor_modes",
+            json!({ "deviceName": device_name, "includeRaw": include_raw }),
+        )
+    }
+
+    fn set_monitor_mode(
+        &self,
+        device_name: String,
+        request: crate::mode_selection::ModeRequest,
+    ) -> Result<Resolution, String> {
+        self.call(
+            "set_monitor_mode",
+            json!({ "deviceName": device_name, "request": request }),
+        )
+    }
+
+    fn set_monitor_orientation(
+        &self,
+        device_name: String,
+        orientation_degrees: u32,
+    ) -> Result<(), String> {
+        self.call(
+            "set_monitor_orientation",
+            json!({ "deviceName": device_name, "orientationDegrees": orientation_degrees }),
+        )
+    }
+
+    fn get_monitor_brightness(&self, device_name: String) -> Result<BrightnessInfo, String> {
+        self.call(
+            "get_monitor_brightness",
+            json!({ "deviceName": device_name }),
+        )
+    }
+
+    fn set_monitor_brightness(&self, device_name: String, percent: u32) -> Result<(), String> {
+        self.call(
+            "set_monitor_brightness",
+            json!({ "deviceName": device_name, "percent": percent }),
+        )
+    }
+
+    fn set_monitor_dimming(&self, device_name: String, percent: u32) -> Result<(), String> {
+        self.call(
+            "set_monitor_dimming",
+            json!({ "deviceName": device_name, "percent": percent }),
+        )
+    }
+
+    fn set_monitor_color_temperature(
+        &self,
+        device_name: String,
+        kelvin: u32,
+    ) -> Result<ColorTemperatureInfo, String> {
+        self.call(
+            "set_monitor_color_temperature",
+            json!({ "deviceName": device_name, "kelvin": kelvin }),
+        )
+    }
+
+    fn get_monitor_color_temperature(
+        &self,
+        device_name: String,
+    ) -> Result<ColorTemperatureInfo, String> {
+        self.call(
+            "get_monitor_color_temperature",
+            json!({ "deviceName": device_name }),
+        )
+    }
+
+    fn list_color_profiles(&self, device_name: String) -> Result<Vec<ColorProfileInfo>, String> {
+        self.call("list_color_profiles", json!({ "deviceName": device_name }))
+    }
+
+    fn get_monitor_color_profile(&self, device_name: String) -> Result<Option<String>, String> {
+        self.call(
+            "get_monitor_color_profile",
+            json!({ "deviceName": device_name }),
+        )
+    }
+
+    fn set_monitor_color_profile(
+        &self,
+        device_name: String,
+        profile: String,
+    ) -> Result<(), String> {
+        self.call(
+            "set_monitor_color_profile",
+            json!({ "deviceName": device_name, "profile": profile }),
+        )
+    }
+
+    // The app shows the overlays on its own windows; the handle isn't needed here
+    fn identify_monitors(&self, _app_handle: tauri::AppHandle) -> Result<(), String> {
+        self.call("identify_monitors", json!({}))
+    }
+
+    fn set_monitor_scale(&self, device_name: String, scale_percent: u32) -> Result<(), String> {
+        self.call(
+            "set_monitor_scale",
+            json!({ "deviceName": device_name, "scalePercent": scale_percent }),
+        )
+    }
+
+    fn set_custom_scale(&self, percent: Option<u32>) -> Result<(), String> {
+        self.call("set_custom_scale", json!({ "percent": percent }))
+    }
+
+    fn set_monitor_scaling_mode(&self, device_name: String, mode: String) -> Result<(), String> {
+        self.call(
+            "set_monitor_scaling_mode",
+            json!({ "deviceName": device_name, "mode": mode }),
+        )
+    }
+
+    fn enable_hdr(&self, device_name: String, enable: bool) -> Result<(), String> {
+        self.call(
+            "enable_hdr",
+            json!({ "deviceName": device_name, "enable": enable }),
+        )
+    }
+
+    fn set_monitor_vrr(&self, device_name: String, enable: bool) -> Result<(), String> {
+        self.call(
+            "set_monitor_vrr",
+            json!({ "deviceName": device_name, "enable": enable }),
+        )
+    }
+
+    fn set_sdr_white_level(&self, device_name: String, nits: u32) -> Result<(), String> {
+        self.call(
+            "set_sdr_white_level",
+            json!({ "deviceName": device_name, "nits": nits }),
+        )
+    }
+
+    fn set_monitor_input_source(&self, device_name: String, input: String) -> Result<(), String> {
+        self.call(
+            "set_monitor_input_source",
+            json!({ "deviceName": device_name, "input": input }),
+        )
+    }
+
+    fn get_monitor_input_source(&self, device_name: String) -> Result<String, String> {
+        self.call(
+            "get_monitor_input_source",
+            json!({ "deviceName": device_name }),
+        )
+    }
+
+    fn get_monitor_ddc_caps(&self, device_name: String) -> Result<String, String> {
+        self.call("get_monitor_ddc_caps", json!({ "deviceName": device_name }))
+    }
+
+    fn set_monitor_power(&self, device_name: String, power_on: bool) -> Result<(), String> {
+        self.call(
+            "set_monitor_power",
+            json!({ "deviceName": device_name, "powerOn": power_on }),
+        )
+    }
+}
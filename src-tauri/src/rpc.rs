@@ -0,0 +1,450 @@
+// JSON-RPC 2.0 over the running app. Methods mirror the Tauri commands in displays.rs (same
+// names, camelCase params like `invoke`), plus input names, display profiles, KVM presets and
+// configuration import/export.
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+// The provider refused or failed the call; the message is its error
+pub const SERVER_ERROR: i64 = -32000;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Request {
+    #[serde(default)]
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl Response {
+    fn new(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(value) => (Some(value), None),
+            Err(e) => (None, Some(e)),
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+fn error(code: i64, message: impl Into<String>) -> RpcError {
+    RpcError {
+        code,
+        message: message.into(),
+    }
+}
+
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() {
+        Value::Object(Default::default())
+    } else {
+        params
+    };
+    serde_json::from_value(params).map_err(|e| error(INVALID_PARAMS, e.to_string()))
+}
+
+fn reply<T: Serialize>(result: Result<T, String>) -> Result<Value, RpcError> {
+    let value = result.map_err(|e| error(SERVER_ERROR, e))?;
+    serde_json::to_value(value).map_err(|e| error(SERVER_ERROR, e.to_string()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Device {
+    device_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetResolution {
+    device_name: String,
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    refresh_hz: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListModes {
+    device_name: String,
+    #[serde(default)]
+    include_raw: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetMode {
+    device_name: String,
+    request: crate::mode_selection::ModeRequest,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetOrientation {
+    device_name: String,
+    orientation_degrees: u32,
+}
+
+// Brightness and dimming
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetPercent {
+    device_name: String,
+    percent: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FadeBrightness {
+    device_name: String,
+    target: u32,
+    duration_ms: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetColorTemperature {
+    device_name: String,
+    kelvin: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetColorProfile {
+    device_name: String,
+    profile: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetScale {
+    device_name: String,
+    scale_percent: u32,
+}
+
+#[derive(Deserialize)]
+struct SetCustomScale {
+    #[serde(default)]
+    percent: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetScalingMode {
+    device_name: String,
+    mode: String,
+}
+
+// HDR and VRR
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetEnabled {
+    device_name: String,
+    enable: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetSdrWhite {
+    device_name: String,
+    nits: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetInput {
+    device_name: String,
+    input: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetInputName {
+    device_name: String,
+    input: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetPower {
+    device_name: String,
+    power_on: bool,
+}
+
+#[derive(Deserialize)]
+struct ProfileName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ExportConfig {
+    path: String,
+    #[serde(default)]
+    format: Option<crate::config_transfer::FileFormat>,
+}
+
+#[derive(Deserialize)]
+struct ImportConfig {
+    path: String,
+    #[serde(default)]
+    mode: crate::config_transfer::ImportMode,
+    #[serde(default)]
+    format: Option<crate::config_transfer::FileFormat>,
+}
+
+/// Runs one method. Changes are announced to the UI the same way the app's own changes are.
+pub fn dispatch(app_handle: &AppHandle, method: &str, raw: Value) -> Result<Value, RpcError> {
+    use crate::displays as d;
+
+    let result = match method {
+        "set_monitor_brightness" => {
+            let p: SetPercent = params(raw)?;
+            reply(d::set_monitor_brightness(
+                app_handle.clone(),
+                p.device_name,
+                p.percent,
+            ))
+        }
+        "identify_monitors" => reply(d::active_provider().identify_monitors(app_handle.clone())),
+        "set_monitor_input_source" => {
+            let p: SetInput = params(raw)?;
+            reply(d::set_monitor_input_source(
+                app_handle.clone(),
+                p.device_name,
+                p.input,
+            ))
+        }
+        "list_monitor_inputs" => {
+            let p: Device = params(raw)?;
+            reply(crate::inputs::list_monitor_inputs(
+                app_handle.clone(),
+                p.device_name,
+            ))
+        }
+        "set_input_name" => {
+            let p: SetInputName = params(raw)?;
+            reply(crate::inputs::set_input_name(
+                app_handle.clone(),
+                p.device_name,
+                p.input,
+                p.name,
+            ))
+        }
+        "list_display_profiles" => reply(Ok(crate::profiles::list_display_profiles(
+            app_handle.clone(),
+        ))),
+        "apply_display_profile" => {
+            let p: ProfileName = params(raw)?;
+            reply(crate::profiles::apply_display_profile(
+                app_handle.clone(),
+                p.name,
+            ))
+        }
+        "list_kvm_presets" => reply(Ok(crate::kvm::list_kvm_presets(app_handle.clone()))),
+        "apply_kvm_preset" => {
+            let p: ProfileName = params(raw)?;
+            reply(crate::kvm::apply_kvm_preset(app_handle.clone(), p.name))
+        }
+        "export_config" => {
+            let p: ExportConfig = params(raw)?;
+            reply(crate::config_transfer::export_config(
+                app_handle.clone(),
+                p.path,
+                p.format,
+            ))
+        }
+        "import_config" => {
+            let p: ImportConfig = params(raw)?;
+            reply(crate::config_transfer::import_config(
+                app_handle.clone(),
+                p.path,
+                p.mode,
+                p.format,
+            ))
+        }
+        _ => dispatch_provider(method, raw),
+    }?;
+    announce(app_handle, method);
+    Ok(result)
+}
+
+/// Methods that only need the display provider.
+pub fn dispatch_provider(method: &str, raw: Value) -> Result<Value, RpcError> {
+    use crate::displays as d;
+
+    match method {
+        "get_all_monitors" => reply(d::get_all_monitors()),
+        "set_monitor_resolution" => {
+            let p: SetResolution = params(raw)?;
+            reply(d::set_monitor_resolution(
+                p.device_name,
+                p.width,
+                p.height,
+                p.refresh_hz,
+            ))
+        }
+        "list_monitor_modes" => {
+            let p: ListModes = params(raw)?;
+            reply(d::list_monitor_modes(p.device_name, p.include_raw))
+        }
+        "set_monitor_mode" => {
+            let p: SetMode = params(raw)?;
+            reply(d::set_monitor_mode(p.device_name, p.request))
+        }
+        "set_monitor_orientation" => {
+            let p: SetOrientation = params(raw)?;
+            reply(d::set_monitor_orientation(
+                p.device_name,
+                p.orientation_degrees,
+            ))
+        }
+        "get_monitor_brightness" => {
+            let p: Device = params(raw)?;
+            reply(d::get_monitor_brightness(p.device_name))
+        }
+        "fade_monitor_brightness" => {
+            let p: FadeBrightness = params(raw)?;
+            crate::brightness_schedule::note_manual_override(&p.device_name);
+            reply(crate::brightness_fade::fade_monitor_brightness(
+                d::active_provider().as_ref(),
+                p.device_name,
+                p.target,
+                p.duration_ms,
+            ))
+        }
+        "set_monitor_dimming" => {
+            let p: SetPercent = params(raw)?;
+            reply(d::set_monitor_dimming(p.device_name, p.percent))
+        }
+        "set_monitor_color_temperature" => {
+            let p: SetColorTemperature = params(raw)?;
+            reply(d::set_monitor_color_temperature(p.device_name, p.kelvin))
+        }
+        "get_monitor_color_temperature" => {
+            let p: Device = params(raw)?;
+            reply(d::get_monitor_color_temperature(p.device_name))
+        }
+        "set_monitor_scale" => {
+            let p: SetScale = params(raw)?;
+            reply(d::set_monitor_scale(p.device_name, p.scale_percent))
+        }
+        "set_custom_scale" => {
+            let p: SetCustomScale = params(raw)?;
+            reply(d::set_custom_scale(p.percent))
+        }
+        "set_monitor_scaling_mode" => {
+            let p: SetScalingMode = params(raw)?;
+            reply(d::set_monitor_scaling_mode(p.device_name, p.mode))
+        }
+        "enable_hdr" => {
+            let p: SetEnabled = params(raw)?;
+            reply(d::enable_hdr(p.device_name, p.enable))
+        }
+        "set_monitor_vrr" => {
+            let p: SetEnabled = params(raw)?;
+            reply(d::set_monitor_vrr(p.device_name, p.enable))
+        }
+        "set_sdr_white_level" => {
+            let p: SetSdrWhite = params(raw)?;
+            reply(d::set_sdr_white_level(p.device_name, p.nits))
+        }
+        "get_monitor_input_source" => {
+            let p: Device = params(raw)?;
+            reply(d::get_monitor_input_source(p.device_name))
+        }
+        "get_monitor_ddc_caps" => {
+            let p: Device = params(raw)?;
+            reply(d::get_monitor_ddc_caps(p.device_name))
+        }
+        "set_monitor_power" => {
+            let p: SetPower = params(raw)?;
+            reply(d::set_monitor_power(p.device_name, p.power_on))
+        }
+        "list_color_profiles" => {
+            let p: Device = params(raw)?;
+            reply(d::list_color_profiles(p.device_name))
+        }
+        "get_monitor_color_profile" => {
+            let p: Device = params(raw)?;
+            reply(d::get_monitor_color_profile(p.device_name))
+        }
+        "set_monitor_color_profile" => {
+            let p: SetColorProfile = params(raw)?;
+            reply(d::set_monitor_color_profile(p.device_name, p.profile))
+        }
+        _ => Err(error(
+            METHOD_NOT_FOUND,
+            format!("Unknown method '{}'", method),
+        )),
+    }
+}
+
+// Lets the UI refresh after a change it didn't make itself
+fn announce(app_handle: &AppHandle, method: &str) {
+    let event = match method {
+        "set_monitor_brightness" | "fade_monitor_brightness" | "set_monitor_dimming" => {
+            "brightness-changed"
+        }
+        m if m.starts_with("set_") || m.starts_with("apply_") || m == "enable_hdr" => {
+            "display-changed"
+        }
+        _ => return,
+    };
+    let _ = app_handle.emit(event, ());
+}
+
+/// Handles one request line and returns the response line.
+pub fn handle_line(app_handle: &AppHandle, line: &str) -> String {
+    respond(line, |method, params| dispatch(app_handle, method, params))
+}
+
+/// Parses a request line, runs it through `dispatch` and serializes the response.
+pub fn respond(
+    line: &str,
+    dispatch: impl FnOnce(&str, Value) -> Result<Value, RpcError>,
+) -> String {
+    let response = match serde_json::from_str::<Value>(line) {
+        Err(e) => Response::new(Value::Null, Err(error(PARSE_ERROR, e.to_string()))),
+        Ok(value) => match serde_json::from_value::<Request>(value) {
+            Err(e) => Response::new(Value::Null, Err(error(INVALID_REQUEST, e.to_string()))),
+            Ok(request) => {
+                let outcome = dispatch(&request.method, request.params);
+                if let Err(e) = &outcome {
+                    log::warn!("RPC {} failed: {}", request.method, e.message);
+                }
+                Response::new(request.id, outcome)
+            }
+        },
+    };
+    serde_json::to_string(&response).unwrap_or_default()
+}
@@ -1,50 +1,902 @@
-use serde_json::Value;
-use tauri::{App, AppHandle};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Listener, Manager, Runtime};
 use tauri_plugin_store::StoreExt;
 
-pub fn should_register_brightness_hotkeys_app(app: &App) -> bool {
-    if let Ok(store) = app.store("settings.json") {
-        if let Some(Value::Object(map)) = store.get("settings") {
-            if let Some(s) = map
-                .get("keyboardBrightnessShortcut")
-                .and_then(|v| v.as_str())
+use crate::brightness_calibration::CalibrationCurve;
+use crate::brightness_schedule::BrightnessSchedule;
+use crate::kvm::KvmPreset;
+use crate::state_persistence::RestoreSettings;
+
+pub const STORE_FILE: &str = "settings.json";
+pub const SETTINGS_KEY: &str = "settings";
+// Backend-owned keys next to "settings"
+pub const PROFILES_KEY: &str = "profiles";
+pub const MONITOR_STATE_KEY: &str = "monitorState";
+// Secret of the local HTTP API; kept out of "settings" so exports and the UI don't carry it
+const HTTP_API_TOKEN_KEY: &str = "httpApiToken";
+// Policy defaults already written into "settings", so users can change them afterwards
+const POLICY_SEEDED_KEY: &str = "policyDefaults";
+
+// tauri.conf.json "identifier"; the store lives in the app data directory named after it
+const APP_IDENTIFIER: &str = "com.zpix1.windisplay";
+
+/// Schema version written with the settings; bump it together with a new entry in `MIGRATIONS`.
+pub const SETTINGS_VERSION: u32 = 1;
+
+// Longest Auto-HDR debounce accepted; anything longer feels broken rather than calm
+const MAX_AUTO_HDR_DEBOUNCE_MS: u64 = 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardBrightnessShortcut {
+    AllScreens,
+    ScreenWithMouse,
+    // F14/F15 are left to Windows
+    #[default]
+    System,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    #[default]
+    System,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_debounce_ms() -> u64 {
+    2000
+}
+
+/// Auto-HDR settings stored under `settings.autoHdr`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoHdrSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    // Any app covering its whole monitor triggers HDR, not just the allow-listed ones
+    #[serde(default = "default_true")]
+    pub fullscreen: bool,
+    // Executable names (e.g. "mpc-hc64.exe") that trigger HDR whenever they're in the foreground
+    #[serde(default)]
+    pub apps: Vec<String>,
+    // How long the foreground app has to stay put before HDR is switched, so alt-tab doesn't flicker
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+}
+
+impl Default for AutoHdrSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fullscreen: default_true(),
+            apps: Vec::new(),
+            debounce_ms: default_debounce_ms(),
+        }
+    }
+}
+
+fn default_http_api_port() -> u16 {
+    8765
+}
+
+/// Local HTTP/WebSocket API settings stored under `settings.httpApi`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    // Bound on 127.0.0.1 only
+    #[serde(default = "default_http_api_port")]
+    pub port: u16,
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_http_api_port(),
+        }
+    }
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+/// MQTT bridge settings stored under `settings.mqtt`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    // Empty means an anonymous connection; the password is left out of exports
+    pub username: String,
+    pub password: String,
+    // Monitors publish under "{topicPrefix}/{EDID serial}/..."
+    pub topic_prefix: String,
+    // Home Assistant MQTT discovery
+    pub discovery: bool,
+    pub discovery_prefix: String,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: default_mqtt_port(),
+            username: String::new(),
+            password: String::new(),
+            topic_prefix: "windisplay".to_string(),
+            discovery: true,
+            discovery_prefix: "homeassistant".to_string(),
+        }
+    }
+}
+
+/// Everything stored under the "settings" key of settings.json. The UI owns most of it and
+/// rewrites the whole object on every edit; keys missing from the file take their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub version: u32,
+    #[serde(rename = "showUIOnMonitorChange")]
+    pub show_ui_on_monitor_change: bool,
+    pub keyboard_brightness_shortcut: KeyboardBrightnessShortcut,
+    pub show_startup_notification: bool,
+    #[serde(rename = "shouldHideUIOnFocusOut")]
+    pub should_hide_ui_on_focus_out: bool,
+    pub theme: ThemeMode,
+    pub link_brightness: bool,
+    pub brightness_schedule: BrightnessSchedule,
+    // Calibration curve per monitor identity
+    pub brightness_calibration: HashMap<String, CalibrationCurve>,
+    pub auto_hdr: AutoHdrSettings,
+    pub restore_monitor_state: RestoreSettings,
+    pub http_api: HttpApiSettings,
+    pub mqtt: MqttSettings,
+    pub kvm_presets: Vec<KvmPreset>,
+    // Monitor identity -> input key ("hdmi1") -> the user's name for it ("Work Laptop")
+    pub input_names: HashMap<String, HashMap<String, String>>,
+    // Keys this build doesn't know about, kept so saving from the backend doesn't drop them
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            show_ui_on_monitor_change: false,
+            keyboard_brightness_shortcut: KeyboardBrightnessShortcut::System,
+            show_startup_notification: true,
+            should_hide_ui_on_focus_out: true,
+            theme: ThemeMode::System,
+            link_brightness: false,
+            brightness_schedule: BrightnessSchedule::default(),
+            brightness_calibration: HashMap::new(),
+            auto_hdr: AutoHdrSettings::default(),
+            restore_monitor_state: RestoreSettings::default(),
+            http_api: HttpApiSettings::default(),
+            mqtt: MqttSettings::default(),
+            kvm_presets: Vec::new(),
+            input_names: HashMap::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Clamps out-of-range values in place and describes each fix.
+    pub fn validate(&mut self) -> Vec<String> {
+        use crate::color_temperature::{MAX_KELVIN, MIN_KELVIN};
+
+        let mut fixes = Vec::new();
+
+        let schedule = &mut self.brightness_schedule;
+        if schedule
+            .latitude
+            .is_some_and(|lat| !(-90.0..=90.0).contains(&lat))
+        {
+            fixes.push("brightnessSchedule.latitude is out of range, ignoring it".to_string());
+            schedule.latitude = None;
+        }
+        if schedule
+            .longitude
+            .is_some_and(|lon| !(-180.0..=180.0).contains(&lon))
+        {
+            fixes.push("brightnessSchedule.longitude is out of range, ignoring it".to_string());
+            schedule.longitude = None;
+        }
+        if schedule.transition_minutes > 24 * 60 {
+            fixes.push("brightnessSchedule.transitionMinutes capped at 24 hours".to_string());
+            schedule.transition_minutes = 24 * 60;
+        }
+        let step_count = schedule.steps.len();
+        schedule.steps.retain(|step| {
+            // Any coordinates will do, this only checks the syntax
+            crate::brightness_schedule::resolve_step_minutes(&step.at, Some((0.0, 0.0))).is_some()
+        });
+        if schedule.steps.len() != step_count {
+            fixes.push(format!(
+                "Dropped {} brightness schedule step(s) with an invalid time",
+                step_count - schedule.steps.len()
+            ));
+        }
+        for step in &mut schedule.steps {
+            if step.brightness > 100 {
+                fixes.push(format!(
+                    "Schedule step at {}: brightness capped at 100%",
+                    step.at
+                ));
+                step.brightness = 100;
+            }
+            if step.dimming.is_some_and(|d| d > 100) {
+                fixes.push(format!(
+                    "Schedule step at {}: dimming capped at 100%",
+                    step.at
+                ));
+                step.dimming = Some(100);
+            }
+            if let Some(kelvin) = step.color_temperature {
+                let clamped = kelvin.clamp(MIN_KELVIN, MAX_KELVIN);
+                if clamped != kelvin {
+                    fixes.push(format!(
+                        "Schedule step at {}: color temperature clamped to {}K",
+                        step.at, clamped
+                    ));
+                    step.color_temperature = Some(clamped);
+                }
+            }
+        }
+
+        for (identity, curve) in &mut self.brightness_calibration {
+            if curve.points.iter().any(|(l, n)| *l > 100 || *n > 100) {
+                fixes.push(format!(
+                    "Calibration for {}: points clamped to 0-100%",
+                    identity
+                ));
+                for (l, n) in &mut curve.points {
+                    *l = (*l).min(100);
+                    *n = (*n).min(100);
+                }
+            }
+        }
+
+        let auto_hdr = &mut self.auto_hdr;
+        if auto_hdr.debounce_ms > MAX_AUTO_HDR_DEBOUNCE_MS {
+            fixes.push(format!(
+                "autoHdr.debounceMs capped at {}",
+                MAX_AUTO_HDR_DEBOUNCE_MS
+            ));
+            auto_hdr.debounce_ms = MAX_AUTO_HDR_DEBOUNCE_MS;
+        }
+        auto_hdr.apps = auto_hdr
+            .apps
+            .iter()
+            .map(|app| app.trim().to_string())
+            .filter(|app| !app.is_empty())
+            .collect();
+
+        // Privileged ports would need elevation, which the tray app never has
+        if self.http_api.port < 1024 {
+            fixes.push(format!(
+                "httpApi.port {} is reserved, using {}",
+                self.http_api.port,
+                default_http_api_port()
+            ));
+            self.http_api.port = default_http_api_port();
+        }
+
+        let mqtt = &mut self.mqtt;
+        mqtt.host = mqtt.host.trim().to_string();
+        if mqtt.port == 0 {
+            fixes.push(format!(
+                "mqtt.port 0 is invalid, using {}",
+                default_mqtt_port()
+            ));
+            mqtt.port = default_mqtt_port();
+        }
+        // Wildcards and empty levels would make the bridge subscribe to other clients' topics
+        let defaults = MqttSettings::default();
+        for (name, prefix, default) in [
+            ("topicPrefix", &mut mqtt.topic_prefix, defaults.topic_prefix),
+            (
+                "discoveryPrefix",
+                &mut mqtt.discovery_prefix,
+                defaults.discovery_prefix,
+            ),
+        ] {
+            let trimmed = prefix.trim().trim_matches('/');
+            if trimmed.is_empty()
+                || trimmed.contains(['+', '#'])
+                || trimmed.split('/').any(str::is_empty)
             {
-                return matches!(s, "all_screens" | "screen_with_mouse");
+                fixes.push(format!(
+                    "mqtt.{} '{}' is not a valid topic, using '{}'",
+                    name, prefix, default
+                ));
+                *prefix = default;
+            } else if trimmed != prefix.as_str() {
+                *prefix = trimmed.to_string();
             }
         }
+
+        for (identity, names) in &mut self.input_names {
+            names.retain(|input, name| {
+                *name = name.trim().to_string();
+                if name.is_empty() {
+                    return false;
+                }
+                // Keys are what get_monitor_input_source reports, so names show up for them
+                if crate::inputs::code_of(input).map(crate::inputs::key_of) != Some(input.clone()) {
+                    fixes.push(format!(
+                        "Input names for {}: dropped '{}' for unknown input '{}'",
+                        identity, name, input
+                    ));
+                    return false;
+                }
+                true
+            });
+        }
+        self.input_names.retain(|_, names| !names.is_empty());
+
+        let mut names = HashSet::new();
+        self.kvm_presets.retain_mut(|preset| {
+            preset.name = preset.name.trim().to_string();
+            if preset.name.is_empty() || !names.insert(preset.name.clone()) {
+                fixes.push(format!(
+                    "Dropped KVM preset with an empty or repeated name '{}'",
+                    preset.name
+                ));
+                return false;
+            }
+            let input_names = &self.input_names;
+            preset.targets.retain(|t| {
+                if t.monitor.trim().is_empty() || t.input.trim().is_empty() {
+                    return false;
+                }
+                // Input keys, codes and the names the user gave the monitor's inputs all resolve
+                let input = match input_names.get(&t.monitor) {
+                    Some(names) => crate::inputs::resolve_name(names, &t.input),
+                    None => t.input.clone(),
+                };
+                if crate::inputs::code_of(&input).is_none() {
+                    fixes.push(format!(
+                        "KVM preset '{}': dropped unknown input '{}' for {}",
+                        preset.name, t.input, t.monitor
+                    ));
+                    return false;
+                }
+                true
+            });
+            preset.hotkey = preset.hotkey.trim().to_string();
+            if !preset.hotkey.is_empty()
+                && preset
+                    .hotkey
+                    .parse::<global_hotkey::hotkey::HotKey>()
+                    .is_err()
+            {
+                fixes.push(format!(
+                    "KVM preset '{}': unknown hotkey '{}'",
+                    preset.name, preset.hotkey
+                ));
+                preset.hotkey.clear();
+            }
+            preset.usb_device = preset.usb_device.trim().to_string();
+            if !preset.usb_device.is_empty()
+                && crate::kvm::parse_usb_id(&preset.usb_device).is_none()
+            {
+                fixes.push(format!(
+                    "KVM preset '{}': USB device '{}' isn't VID:PID",
+                    preset.name, preset.usb_device
+                ));
+                preset.usb_device.clear();
+            }
+            true
+        });
+
+        fixes
     }
-    false
 }
 
-pub fn should_show_ui_on_monitor_change_handle(handle: &AppHandle) -> bool {
-    if let Ok(store) = handle.store("settings.json") {
-        if let Some(Value::Object(map)) = store.get("settings") {
-            if let Some(v) = map.get("showUIOnMonitorChange").and_then(|v| v.as_bool()) {
-                return v;
-            }
+type Migration = fn(&mut Map<String, Value>);
+
+// MIGRATIONS[n] upgrades a version-n settings object to version n + 1
+const MIGRATIONS: [Migration; 1] = [migrate_v0_to_v1];
+
+// Version 0 is every file written before settings had a version; its keys are the same
+fn migrate_v0_to_v1(_map: &mut Map<String, Value>) {}
+
+/// Upgrades a raw settings object to `SETTINGS_VERSION`. Returns false when it is already
+/// current, or was written by a newer build (then it's read as-is, unknown keys kept).
+pub fn migrate(map: &mut Map<String, Value>) -> bool {
+    let version = map.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > SETTINGS_VERSION {
+        log::warn!(
+            "Settings were written by a newer version (schema {}, this build knows {})",
+            version,
+            SETTINGS_VERSION
+        );
+        return false;
+    }
+    if version == SETTINGS_VERSION {
+        return false;
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        log::info!("Migrating settings from schema {} to {}", from, from + 1);
+        migration(map);
+    }
+    map.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+    true
+}
+
+// A bad value only resets its own key to the default instead of the whole settings object
+fn parse_lenient(map: Map<String, Value>) -> Settings {
+    if let Ok(settings) = serde_json::from_value(Value::Object(map.clone())) {
+        return settings;
+    }
+    let mut accepted = Map::new();
+    for (key, value) in map {
+        let mut candidate = accepted.clone();
+        candidate.insert(key.clone(), value);
+        if serde_json::from_value::<Settings>(Value::Object(candidate.clone())).is_ok() {
+            accepted = candidate;
+        } else {
+            log::warn!("Invalid {} in settings, using the default", key);
+        }
+    }
+    serde_json::from_value(Value::Object(accepted)).unwrap_or_default()
+}
+
+fn to_object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        Value::Null => Map::new(),
+        other => {
+            log::warn!("Settings are not an object ({}), using defaults", other);
+            Map::new()
         }
     }
-    false
 }
 
-pub fn should_show_startup_notification_app(app: &App) -> bool {
-    if let Ok(store) = app.store("settings.json") {
-        if let Some(Value::Object(map)) = store.get("settings") {
-            if let Some(v) = map.get("showStartupNotification").and_then(|v| v.as_bool()) {
-                return v;
+fn parse_validated(map: Map<String, Value>) -> Settings {
+    let mut settings = parse_lenient(map);
+    for fix in settings.validate() {
+        log::warn!("Settings: {}", fix);
+    }
+    settings
+}
+
+/// Migrates, parses and validates a raw "settings" value.
+pub fn from_value(value: Value) -> Settings {
+    let mut map = to_object(value);
+    migrate(&mut map);
+    parse_validated(map)
+}
+
+// What the app runs with: the user's settings with enforced machine policy on top
+fn effective(value: Value) -> Settings {
+    let mut map = to_object(value);
+    migrate(&mut map);
+    let (map, _) = crate::policy::merge(&map, crate::policy::current());
+    parse_validated(map)
+}
+
+// Parsed settings, refreshed whenever the store changes; services read them every tick
+fn cache() -> &'static RwLock<Option<Settings>> {
+    static CACHE: OnceLock<RwLock<Option<Settings>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(None))
+}
+
+fn subscribers() -> &'static Mutex<Vec<Sender<Settings>>> {
+    static SUBSCRIBERS: OnceLock<Mutex<Vec<Sender<Settings>>>> = OnceLock::new();
+    SUBSCRIBERS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn read_store<R: Runtime, M: Manager<R>>(manager: &M, parse: fn(Value) -> Settings) -> Settings {
+    match manager.store(STORE_FILE) {
+        Ok(store) => parse(store.get(SETTINGS_KEY).unwrap_or(Value::Null)),
+        Err(e) => {
+            log::warn!("Failed to open {}: {}", STORE_FILE, e);
+            Settings::default()
+        }
+    }
+}
+
+/// Current settings. Works with both `App` (during setup) and `AppHandle`.
+pub fn get<R: Runtime, M: Manager<R>>(manager: &M) -> Settings {
+    if let Some(settings) = cache().read().ok().and_then(|cache| cache.clone()) {
+        return settings;
+    }
+    let settings = read_store(manager, effective);
+    if let Ok(mut cache) = cache().write() {
+        *cache = Some(settings.clone());
+    }
+    settings
+}
+
+/// The user's own settings, without enforced policy. Edits saved with `save` start from these,
+/// otherwise enforced values would be written into settings.json and outlive the policy.
+pub fn get_user<R: Runtime, M: Manager<R>>(manager: &M) -> Settings {
+    read_store(manager, from_value)
+}
+
+/// Receives the new settings after every change, from the UI or the backend.
+pub fn subscribe() -> Receiver<Settings> {
+    let (tx, rx) = mpsc::channel();
+    if let Ok(mut subscribers) = subscribers().lock() {
+        subscribers.push(tx);
+    }
+    rx
+}
+
+fn publish<R: Runtime>(handle: &AppHandle<R>, settings: Settings) {
+    if let Ok(mut cache) = cache().write() {
+        *cache = Some(settings.clone());
+    }
+    if let Ok(mut subscribers) = subscribers().lock() {
+        subscribers.retain(|tx| tx.send(settings.clone()).is_ok());
+    }
+    let _ = handle.emit("settings-changed", &settings);
+}
+
+/// Saves `settings` (validated); subscribers hear about it through the store watcher.
+pub fn save<R: Runtime>(handle: &AppHandle<R>, mut settings: Settings) -> Result<(), String> {
+    settings.version = SETTINGS_VERSION;
+    for fix in settings.validate() {
+        log::warn!("Settings: {}", fix);
+    }
+    let value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+    let store = handle.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(SETTINGS_KEY, value.clone());
+    store.save().map_err(|e| e.to_string())?;
+    // store.set already raised "store://change"; the watcher notifies subscribers from that
+    if let Ok(mut cache) = cache().write() {
+        *cache = Some(effective(value));
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct StoreChange {
+    path: std::path::PathBuf,
+    key: String,
+    #[serde(default)]
+    value: Option<Value>,
+}
+
+/// Migrates settings.json on startup, seeds new policy defaults into it and watches it for edits
+/// made by the UI.
+pub fn start_settings_watcher<R: Runtime>(handle: &AppHandle<R>) {
+    if let Ok(store) = handle.store(STORE_FILE) {
+        let (mut map, existed) = match store.get(SETTINGS_KEY) {
+            Some(Value::Object(map)) => (map, true),
+            _ => (Map::new(), false),
+        };
+        let mut seeded = match store.get(POLICY_SEEDED_KEY) {
+            Some(Value::Object(seeded)) => seeded,
+            _ => Map::new(),
+        };
+        let migrated = migrate(&mut map) && existed;
+        let seeded_now =
+            crate::policy::seed_defaults(&mut map, crate::policy::current(), &mut seeded);
+        if seeded_now {
+            store.set(POLICY_SEEDED_KEY, Value::Object(seeded));
+        }
+        if migrated || seeded_now {
+            store.set(SETTINGS_KEY, Value::Object(map));
+            if let Err(e) = store.save() {
+                log::warn!("Failed to save migrated settings: {}", e);
             }
         }
     }
-    true
+
+    let handle_for_events = handle.clone();
+    handle.listen("store://change", move |event| {
+        let Ok(change) = serde_json::from_str::<StoreChange>(event.payload()) else {
+            return;
+        };
+        if change.key != SETTINGS_KEY || !change.path.ends_with(STORE_FILE) {
+            return;
+        }
+        let settings = effective(change.value.unwrap_or(Value::Null));
+        publish(&handle_for_events, settings);
+    });
 }
 
-pub fn should_hide_ui_on_focus_out_handle(handle: &AppHandle) -> bool {
-    if let Ok(store) = handle.store("settings.json") {
-        if let Some(Value::Object(map)) = store.get("settings") {
-            if let Some(v) = map.get("shouldHideUIOnFocusOut").and_then(|v| v.as_bool()) {
-                return v;
+fn load_key<R: Runtime, T: serde::de::DeserializeOwned + Default>(
+    handle: &AppHandle<R>,
+    key: &str,
+) -> T {
+    if let Ok(store) = handle.store(STORE_FILE) {
+        if let Some(v) = store.get(key) {
+            match serde_json::from_value(v) {
+                Ok(value) => return value,
+                Err(e) => log::warn!("Invalid {} in settings: {e}", key),
             }
         }
     }
-    true
-}
\ No newline at end of file
+    T::default()
+}
+
+fn save_key<R: Runtime, T: Serialize>(
+    handle: &AppHandle<R>,
+    key: &str,
+    value: &T,
+) -> Result<(), String> {
+    let store = handle.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(key, serde_json::to_value(value).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())
+}
+
+// Saved display setups live next to (not inside) "settings", which the UI rewrites wholesale
+pub fn display_profiles_handle(handle: &AppHandle) -> Vec<crate::profiles::DisplayProfile> {
+    load_key(handle, PROFILES_KEY)
+}
+
+pub fn save_display_profiles_handle(
+    handle: &AppHandle,
+    profiles: &[crate::profiles::DisplayProfile],
+) -> Result<(), String> {
+    save_key(handle, PROFILES_KEY, &profiles)
+}
+
+// Remembered monitor state is backend-owned too
+pub fn monitor_states_handle(
+    handle: &AppHandle,
+) -> HashMap<String, crate::state_persistence::MonitorState> {
+    load_key(handle, MONITOR_STATE_KEY)
+}
+
+pub fn save_monitor_states_handle(
+    handle: &AppHandle,
+    states: &HashMap<String, crate::state_persistence::MonitorState>,
+) -> Result<(), String> {
+    save_key(handle, MONITOR_STATE_KEY, states)
+}
+
+pub fn http_api_token_handle(handle: &AppHandle) -> String {
+    load_key(handle, HTTP_API_TOKEN_KEY)
+}
+
+pub fn save_http_api_token_handle(handle: &AppHandle, token: &str) -> Result<(), String> {
+    save_key(handle, HTTP_API_TOKEN_KEY, &token)
+}
+
+/// Every key of settings.json, for exporting the configuration.
+pub fn store_snapshot(handle: &AppHandle) -> Result<Map<String, Value>, String> {
+    let store = handle.store(STORE_FILE).map_err(|e| e.to_string())?;
+    Ok(store.entries().into_iter().collect())
+}
+
+/// Writes the configuration keys of an imported snapshot; "settings" goes through the watcher
+/// like any other edit, so services and the UI pick it up.
+pub fn restore_snapshot(handle: &AppHandle, snapshot: Map<String, Value>) -> Result<(), String> {
+    if let Some(settings) = snapshot.get(SETTINGS_KEY) {
+        save(handle, from_value(settings.clone()))?;
+    }
+    let store = handle.store(STORE_FILE).map_err(|e| e.to_string())?;
+    for key in [PROFILES_KEY, MONITOR_STATE_KEY] {
+        if let Some(value) = snapshot.get(key) {
+            store.set(key, value.clone());
+        }
+    }
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Location of settings.json for the CLI, which runs without the Tauri app.
+pub fn store_file_path() -> Result<PathBuf, String> {
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER).join(STORE_FILE))
+        .ok_or_else(|| "Could not find the application data directory".to_string())
+}
+
+/// Reads settings.json directly; a missing file is an empty store.
+pub fn read_store_file(path: &Path) -> Result<Map<String, Value>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Map::new()),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+pub fn write_store_file(path: &Path, store: &Map<String, Value>) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> Map<String, Value> {
+        to_object(value)
+    }
+
+    #[test]
+    fn migrates_unversioned_settings_once() {
+        let mut map = object(json!({ "theme": "dark", "linkBrightness": true }));
+        assert!(migrate(&mut map));
+        assert_eq!(map["version"], json!(SETTINGS_VERSION));
+        assert_eq!(map["theme"], json!("dark"));
+        assert_eq!(map["linkBrightness"], json!(true));
+
+        // Running again is a no-op, so settings the UI writes back aren't migrated twice
+        let migrated = map.clone();
+        assert!(!migrate(&mut map));
+        assert_eq!(map, migrated);
+    }
+
+    #[test]
+    fn keeps_settings_from_newer_builds() {
+        let mut map = object(json!({ "version": SETTINGS_VERSION + 1, "newKey": [1, 2] }));
+        let original = map.clone();
+        assert!(!migrate(&mut map));
+        assert_eq!(map, original);
+
+        // Unknown keys survive a round trip through Settings
+        let settings = from_value(Value::Object(original));
+        assert_eq!(settings.extra["newKey"], json!([1, 2]));
+        let saved = serde_json::to_value(&settings).unwrap();
+        assert_eq!(saved["newKey"], json!([1, 2]));
+    }
+
+    #[test]
+    fn default_settings_round_trip() {
+        let saved = serde_json::to_value(Settings::default()).unwrap();
+        assert_eq!(saved["version"], json!(SETTINGS_VERSION));
+        let mut map = object(saved);
+        assert!(!migrate(&mut map));
+        let mut settings = parse_lenient(map);
+        assert!(settings.validate().is_empty());
+        assert_eq!(settings.theme, ThemeMode::System);
+    }
+
+    #[test]
+    fn bad_values_only_reset_their_own_key() {
+        let settings = from_value(json!({
+            "theme": 5,
+            "linkBrightness": true,
+            "httpApi": { "enabled": "yes" },
+            "mqtt": { "host": "broker.lan", "port": 1884 },
+        }));
+        assert_eq!(settings.theme, ThemeMode::System);
+        assert!(settings.link_brightness);
+        assert_eq!(settings.http_api, HttpApiSettings::default());
+        assert_eq!(settings.mqtt.host, "broker.lan");
+        assert_eq!(settings.mqtt.port, 1884);
+        // Missing keys inside a section take their defaults
+        assert_eq!(settings.mqtt.topic_prefix, "windisplay");
+    }
+
+    #[test]
+    fn non_objects_read_as_defaults() {
+        for value in [Value::Null, json!([1, 2]), json!("settings")] {
+            let settings = from_value(value);
+            assert_eq!(settings.version, SETTINGS_VERSION);
+            assert!(settings.show_startup_notification);
+        }
+    }
+
+    #[test]
+    fn validate_clamps_schedule_and_calibration() {
+        let mut settings = from_value(json!({
+            "brightnessSchedule": {
+                "latitude": 95.0,
+                "longitude": -200.0,
+                "transitionMinutes": 5000,
+                "steps": [
+                    { "at": "07:00", "brightness": 150, "dimming": 120 },
+                    { "at": "sunset+30", "brightness": 40, "colorTemperature": 500 },
+                    { "at": "teatime", "brightness": 50 },
+                ],
+            },
+            "brightnessCalibration": { "DEL-1": { "points": [[50, 120], [130, 80]] } },
+        }));
+        // from_value already validated; nothing is left to fix
+        assert!(settings.validate().is_empty());
+        let schedule = &settings.brightness_schedule;
+        assert_eq!((schedule.latitude, schedule.longitude), (None, None));
+        assert_eq!(schedule.transition_minutes, 24 * 60);
+        assert_eq!(schedule.steps.len(), 2);
+        assert_eq!(schedule.steps[0].brightness, 100);
+        assert_eq!(schedule.steps[0].dimming, Some(100));
+        assert_eq!(schedule.steps[1].color_temperature, Some(1000));
+        assert_eq!(
+            settings.brightness_calibration["DEL-1"].points,
+            vec![(50, 100), (100, 80)]
+        );
+    }
+
+    #[test]
+    fn validate_reports_each_fix() {
+        let mut settings = Settings::default();
+        settings.auto_hdr.debounce_ms = 120_000;
+        settings.auto_hdr.apps = vec![" vlc.exe ".to_string(), "  ".to_string()];
+        settings.http_api.port = 80;
+        settings.mqtt.port = 0;
+        settings.mqtt.host = " broker ".to_string();
+        settings.mqtt.topic_prefix = "home/+/displays".to_string();
+        settings.mqtt.discovery_prefix = "/homeassistant/".to_string();
+        let fixes = settings.validate();
+
+        assert_eq!(settings.auto_hdr.debounce_ms, MAX_AUTO_HDR_DEBOUNCE_MS);
+        assert_eq!(settings.auto_hdr.apps, vec!["vlc.exe"]);
+        assert_eq!(settings.http_api.port, default_http_api_port());
+        assert_eq!(settings.mqtt.port, default_mqtt_port());
+        assert_eq!(settings.mqtt.host, "broker");
+        assert_eq!(settings.mqtt.topic_prefix, "windisplay");
+        // Stray slashes are trimmed quietly
+        assert_eq!(settings.mqtt.discovery_prefix, "homeassistant");
+        assert_eq!(fixes.len(), 4, "{:?}", fixes);
+        assert!(fixes.iter().any(|f| f.contains("topicPrefix")));
+    }
+
+    #[test]
+    fn validate_cleans_kvm_presets_and_input_names() {
+        let mut settings = from_value(json!({
+            "kvmPresets": [
+                {
+                    "name": " Laptop ",
+                    "targets": [
+                        { "monitor": "DEL-1", "input": "hdmi1" },
+                        { "monitor": " ", "input": "dp1" },
+                        { "monitor": "DEL-1", "input": "work laptop" },
+                        { "monitor": "DEL-2", "input": "Work Laptop" },
+                        { "monitor": "DEL-1", "input": "0x0F" },
+                    ],
+                    "hotkey": "Ctrl+Alt+NotAKey",
+                    "usbDevice": "logitech",
+                },
+                { "name": "Laptop", "targets": [] },
+                { "name": "", "targets": [] },
+                { "name": "Desktop", "hotkey": "Ctrl+Alt+1", "usbDevice": "046D:C52B" },
+            ],
+            "inputNames": {
+                "DEL-1": { "hdmi1": " Work Laptop ", "dp1": " ", "hdmi9": "Nothing" },
+                "DEL-2": { "dp1": "" },
+            },
+        }));
+        assert!(settings.validate().is_empty());
+
+        let presets = &settings.kvm_presets;
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].name, "Laptop");
+        // The name resolves on the monitor it was given for, not on another one
+        let inputs: Vec<&str> = presets[0]
+            .targets
+            .iter()
+            .map(|t| t.input.as_str())
+            .collect();
+        assert_eq!(inputs, ["hdmi1", "work laptop", "0x0F"]);
+        assert_eq!(presets[0].hotkey, "");
+        assert_eq!(presets[0].usb_device, "");
+        assert_eq!(presets[1].hotkey, "Ctrl+Alt+1");
+        assert_eq!(presets[1].usb_device, "046D:C52B");
+
+        assert_eq!(settings.input_names.len(), 1);
+        assert_eq!(
+            settings.input_names["DEL-1"],
+            HashMap::from([("hdmi1".to_string(), "Work Laptop".to_string())])
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// Offline sunrise/sunset calculation based on the NOAA "General Solar Position Calculations".
+// Accurate to a couple of minutes, which is plenty for brightness schedules.
+
+use std::f64::consts::PI;
+
+/// Sunrise and sunset for a given day, in minutes after UTC midnight.
+/// Values may fall outside 0..1440 for longitudes far from Greenwich.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunTimes {
+    pub sunrise_utc_minutes: f64,
+    pub sunset_utc_minutes: f64,
+}
+
+/// Computes sunrise/sunset for `day_of_year` (1-based) at the given coordinates in degrees.
+/// Longitude is positive to the east. Returns None during polar day or polar night.
+pub fn sun_times(day_of_year: u32, latitude: f64, longitude: f64) -> Option<SunTimes> {
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return None;
+    }
+
+    // Fractional year in radians, evaluated at solar noon
+    let gamma = 2.0 * PI / 365.0 * (day_of_year as f64 - 1.0);
+
+    // Equation of time in minutes
+    let eqtime = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2.0 * gamma).cos()
+            - 0.040849 * (2.0 * gamma).sin());
+
+    // Solar declination in radians
+    let decl = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+
+    // Hour angle for the official zenith (90.833 degrees accounts for refraction and the solar disc)
+    let lat = latitude.to_radians();
+    let cos_ha = 90.833_f64.to_radians().cos() / (lat.cos() * decl.cos()) - lat.tan() * decl.tan();
+    if !(-1.0..=1.0).contains(&cos_ha) {
+        return None;
+    }
+    let ha_deg = cos_ha.acos().to_degrees();
+
+    Some(SunTimes {
+        sunrise_utc_minutes: 720.0 - 4.0 * (longitude + ha_deg) - eqtime,
+        sunset_utc_minutes: 720.0 - 4.0 * (longitude - ha_deg) - eqtime,
+    })
+}
+
+/// Converts minutes after UTC midnight into minutes after local midnight (0..1440).
+pub fn utc_minutes_to_local(utc_minutes: f64, utc_offset_minutes: i32) -> f64 {
+    (utc_minutes + utc_offset_minutes as f64).rem_euclid(1440.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= 3.0,
+            "{} is not within 3 minutes of {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn london_midsummer() {
+        // 21 June: sunrise 03:43 UTC, sunset 20:21 UTC
+        let sun = sun_times(172, 51.5074, -0.1278).unwrap();
+        assert_near(sun.sunrise_utc_minutes, 3.0 * 60.0 + 43.0);
+        assert_near(sun.sunset_utc_minutes, 20.0 * 60.0 + 21.0);
+    }
+
+    #[test]
+    fn new_york_sunset_falls_after_utc_midnight() {
+        // 21 June: sunrise 09:25 UTC, sunset 00:31 UTC the next day
+        let sun = sun_times(172, 40.7128, -74.006).unwrap();
+        assert_near(sun.sunrise_utc_minutes, 9.0 * 60.0 + 25.0);
+        assert_near(sun.sunset_utc_minutes, 1440.0 + 31.0);
+        assert_near(
+            utc_minutes_to_local(sun.sunset_utc_minutes, -4 * 60),
+            20.0 * 60.0 + 31.0,
+        );
+    }
+
+    #[test]
+    fn polar_day_and_night() {
+        // Tromsø
+        assert_eq!(sun_times(172, 69.65, 18.96), None);
+        assert_eq!(sun_times(355, 69.65, 18.96), None);
+    }
+
+    #[test]
+    fn rejects_out_of_range_coordinates() {
+        assert_eq!(sun_times(172, 91.0, 0.0), None);
+        assert_eq!(sun_times(172, 0.0, 181.0), None);
+    }
+
+    #[test]
+    fn local_minutes_wrap() {
+        assert_eq!(utc_minutes_to_local(30.0, -60), 1410.0);
+        assert_eq!(utc_minutes_to_local(1430.0, 60), 50.0);
+    }
+}
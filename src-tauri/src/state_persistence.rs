@@ -0,0 +1,221 @@
+// Remembers the brightness, input and power state set on each monitor and puts it back on
+// startup and when a monitor is reconnected, since many monitors reset DDC settings on hot-plug.
+use std::collections::HashSet;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Mutex, Once, OnceLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Listener};
+
+use crate::displays::Displays;
+
+// How often recorded changes are written to settings.json
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+// Monitors often ignore DDC/CI for a moment after being connected or woken
+const SETTLE_DELAY: Duration = Duration::from_secs(3);
+
+/// Last values set on one monitor, stored by monitor identity under "monitorState".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power: Option<bool>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Which settings are restored, stored under `settings.restoreMonitorState`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSettings {
+    #[serde(default = "default_true")]
+    pub brightness: bool,
+    // Opt-in and only at startup: the last input may be another computer (KVM presets), and
+    // switching back to it on every reconnect would take the monitor away from this one
+    #[serde(default)]
+    pub input: bool,
+    // Only ever turns monitors back on; one turned off stays under the user's control
+    #[serde(default = "default_true")]
+    pub power: bool,
+}
+
+impl Default for RestoreSettings {
+    fn default() -> Self {
+        Self {
+            brightness: true,
+            input: false,
+            power: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Change {
+    Brightness(u32),
+    Input(String),
+    Power(bool),
+}
+
+// Changes by GDI device name, waiting to be resolved to an identity and saved. Recording has to be
+// cheap: hotkeys and fades set brightness many times a second.
+fn pending() -> &'static Mutex<Vec<(String, Change)>> {
+    static PENDING: OnceLock<Mutex<Vec<(String, Change)>>> = OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Notes a value the provider has just applied to `device_name`.
+pub fn record(device_name: &str, change: Change) {
+    if let Ok(mut pending) = pending().lock() {
+        pending.push((device_name.to_string(), change));
+    }
+}
+
+// Saves recorded changes; later changes to the same setting win
+fn flush(app_handle: &AppHandle, provider: &dyn Displays) {
+    let changes = match pending().lock() {
+        Ok(mut pending) if !pending.is_empty() => std::mem::take(&mut *pending),
+        _ => return,
+    };
+    let mut states = crate::settings::monitor_states_handle(app_handle);
+    for (device_name, change) in changes {
+        let Some(identity) = crate::monitor_identity::identity_for_device(provider, &device_name)
+        else {
+            continue;
+        };
+        let state = states.entry(identity).or_default();
+        match change {
+            Change::Brightness(percent) => state.brightness = Some(percent),
+            Change::Input(input) => state.input = Some(input),
+            Change::Power(on) => state.power = Some(on),
+        }
+    }
+    if let Err(e) = crate::settings::save_monitor_states_handle(app_handle, &states) {
+        log::warn!("Failed to save monitor state: {}", e);
+    }
+}
+
+// Restores the saved state of monitors that weren't connected last time; `connected` holds the
+// identities seen on the previous run and is updated. Input is only restored at `startup`.
+fn restore(
+    app_handle: &AppHandle,
+    provider: &dyn Displays,
+    connected: &mut HashSet<String>,
+    startup: bool,
+) {
+    let monitors = match provider.get_all_monitors() {
+        Ok(monitors) => monitors,
+        Err(e) => {
+            log::warn!("Monitor state restore: failed to list monitors: {}", e);
+            return;
+        }
+    };
+    crate::monitor_identity::remember(&monitors);
+    let identities: Vec<String> = monitors
+        .iter()
+        .map(crate::monitor_identity::identity_key)
+        .collect();
+    let previous = std::mem::replace(connected, identities.iter().cloned().collect());
+
+    let options = crate::settings::get(app_handle).restore_monitor_state;
+    let restore_input = options.input && startup;
+    if !options.brightness && !restore_input && !options.power {
+        return;
+    }
+    let states = crate::settings::monitor_states_handle(app_handle);
+
+    for (monitor, identity) in monitors.iter().zip(&identities) {
+        if previous.contains(identity) {
+            continue;
+        }
+        let Some(state) = states.get(identity) else {
+            continue;
+        };
+        let device_name = monitor.device_name.clone();
+
+        // Power first: a monitor in standby won't take the other settings
+        if options.power && state.power == Some(true) && !monitor.enabled {
+            log::info!("Restoring power on {}", monitor.friendly_name);
+            if let Err(e) = provider.set_monitor_power(device_name.clone(), true) {
+                log::warn!(
+                    "Failed to restore power on {}: {}",
+                    monitor.friendly_name,
+                    e
+                );
+            }
+        }
+        if restore_input {
+            if let Some(input) = &state.input {
+                let current = provider.get_monitor_input_source(device_name.clone());
+                if !current.is_ok_and(|c| c.eq_ignore_ascii_case(input)) {
+                    log::info!("Restoring input {} on {}", input, monitor.friendly_name);
+                    if let Err(e) =
+                        provider.set_monitor_input_source(device_name.clone(), input.clone())
+                    {
+                        log::warn!(
+                            "Failed to restore input on {}: {}",
+                            monitor.friendly_name,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+        if options.brightness {
+            if let Some(percent) = state.brightness {
+                let current = provider.get_monitor_brightness(device_name.clone());
+                if !current.is_ok_and(|b| b.percent() == percent) {
+                    log::info!(
+                        "Restoring brightness {}% on {}",
+                        percent,
+                        monitor.friendly_name
+                    );
+                    if let Err(e) = provider.set_monitor_brightness(device_name.clone(), percent) {
+                        log::warn!(
+                            "Failed to restore brightness on {}: {}",
+                            monitor.friendly_name,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+static START_ONCE: Once = Once::new();
+
+pub fn start_state_persistence_service(app_handle: AppHandle) {
+    START_ONCE.call_once(|| {
+        let (tx, rx) = mpsc::channel::<()>();
+        app_handle.listen("display-changed", move |_| {
+            let _ = tx.send(());
+        });
+        std::thread::spawn(move || {
+            let provider = crate::displays::active_provider();
+            let mut connected = HashSet::new();
+            std::thread::sleep(SETTLE_DELAY);
+            restore(&app_handle, provider.as_ref(), &mut connected, true);
+            loop {
+                match rx.recv_timeout(FLUSH_INTERVAL) {
+                    Ok(()) => {
+                        // One hot-plug fires several events; handle them as one
+                        std::thread::sleep(SETTLE_DELAY);
+                        while rx.try_recv().is_ok() {}
+                        flush(&app_handle, provider.as_ref());
+                        // Most events are our own changes; only new monitors need restoring
+                        restore(&app_handle, provider.as_ref(), &mut connected, false);
+                    }
+                    Err(RecvTimeoutError::Timeout) => flush(&app_handle, provider.as_ref()),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+    });
+}
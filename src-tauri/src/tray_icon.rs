@@ -0,0 +1,488 @@
+// Tray icon drawn in code: a monitor glyph with the matching profile's initial and badges for HDR
+// and night light. It is rendered at the exact pixel size the tray uses at the current DPI, so it
+// stays sharp instead of being scaled from a bitmap, in a color that follows the taskbar's light or
+// dark mode.
+use std::sync::{Mutex, OnceLock};
+
+use tauri::image::Image;
+use tauri::tray::TrayIcon;
+use tauri::AppHandle;
+
+type Rgba = [u8; 4];
+
+const TRANSPARENT: Rgba = [0, 0, 0, 0];
+// Badge colors, readable on both light and dark taskbars
+const HDR_COLOR: Rgba = [255, 185, 0, 255];
+const NIGHT_LIGHT_COLOR: Rgba = [255, 140, 40, 255];
+// Subsamples per pixel along each axis, for antialiased curves
+const SAMPLES: u32 = 4;
+
+/// State shown on the icon.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IconState {
+    // Any monitor has HDR on
+    pub hdr: bool,
+    // Any monitor is warmer than neutral
+    pub night_light: bool,
+    // Initial of the display profile matching the current setup
+    pub profile: Option<char>,
+}
+
+// Shapes in pixel coordinates; later layers paint over earlier ones
+enum Shape {
+    Rect {
+        x0: f32,
+        y0: f32,
+        x1: f32,
+        y1: f32,
+    },
+    Circle {
+        cx: f32,
+        cy: f32,
+        r: f32,
+    },
+    // A circle with the same circle moved by (dx, dy) cut out of it
+    Crescent {
+        cx: f32,
+        cy: f32,
+        r: f32,
+        dx: f32,
+        dy: f32,
+    },
+}
+
+impl Shape {
+    fn contains(&self, x: f32, y: f32) -> bool {
+        let in_circle = |cx: f32, cy: f32, r: f32| (x - cx).powi(2) + (y - cy).powi(2) <= r * r;
+        match *self {
+            Shape::Rect { x0, y0, x1, y1 } => x >= x0 && x < x1 && y >= y0 && y < y1,
+            Shape::Circle { cx, cy, r } => in_circle(cx, cy, r),
+            Shape::Crescent { cx, cy, r, dx, dy } => {
+                in_circle(cx, cy, r) && !in_circle(cx + dx, cy + dy, r)
+            }
+        }
+    }
+}
+
+// 3×5 pixel font for profile initials, one row per byte
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b110, 0b101, 0b010],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b010, 0b101, 0b010, 0b101, 0b010],
+        '9' => [0b010, 0b101, 0b011, 0b001, 0b110],
+        _ => return None,
+    })
+}
+
+/// Initial shown for a profile: its first ASCII letter or digit, or a dot when it has none.
+pub fn profile_initial(name: &str) -> char {
+    name.chars()
+        .find(|c| c.is_ascii_alphanumeric())
+        .map_or('•', |c| c.to_ascii_uppercase())
+}
+
+// A monitor outline with badges, laid out on a 16-unit grid. Straight edges are snapped to whole
+// pixels so they stay sharp at every size.
+fn layers(state: &IconState, size: u32, fg: Rgba) -> Vec<(Shape, Rgba)> {
+    let s = size as f32;
+    let unit = s / 16.0;
+    let px = |units: f32| (units * unit).round();
+    let line = px(1.0).max(1.0);
+    let rect = |x0: f32, y0: f32, x1: f32, y1: f32| Shape::Rect { x0, y0, x1, y1 };
+    let circle = |cx: f32, cy: f32, r: f32| Shape::Circle { cx, cy, r };
+
+    let (left, top, right, bottom) = (px(0.5), px(2.0), s - px(0.5), px(12.0));
+    let mut layers = vec![
+        (rect(left, top, right, bottom), fg),
+        (
+            rect(left + line, top + line, right - line, bottom - line),
+            TRANSPARENT,
+        ),
+        // Stand
+        (rect(px(7.0), bottom, s - px(7.0), px(14.0)), fg),
+        (rect(px(4.0), px(14.0), s - px(4.0), px(14.0) + line), fg),
+    ];
+
+    if let Some(initial) = state.profile {
+        // Whole-pixel cells, as large as the screen allows
+        let inner = bottom - top - 2.0 * line;
+        let cell = ((inner - 1.0) / 5.0).floor().max(1.0);
+        let x0 = ((left + right - 3.0 * cell) / 2.0).round();
+        let y0 = ((top + bottom - 5.0 * cell) / 2.0).round();
+        let rows = glyph(initial).unwrap_or([0, 0, 0b010, 0, 0]);
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) != 0 {
+                    let x = x0 + col as f32 * cell;
+                    let y = y0 + row as f32 * cell;
+                    layers.push((rect(x, y, x + cell, y + cell), fg));
+                }
+            }
+        }
+    }
+
+    // Badges sit on the corners with a transparent gap around them
+    if state.night_light {
+        let (cx, cy) = (s - 2.5 * unit, 3.0 * unit);
+        layers.push((circle(cx, cy, 3.5 * unit), TRANSPARENT));
+        layers.push((
+            Shape::Crescent {
+                cx,
+                cy,
+                r: 2.5 * unit,
+                dx: 1.2 * unit,
+                dy: -unit,
+            },
+            NIGHT_LIGHT_COLOR,
+        ));
+    }
+    if state.hdr {
+        let (cx, cy) = (s - 2.5 * unit, s - 2.5 * unit);
+        layers.push((circle(cx, cy, 3.5 * unit), TRANSPARENT));
+        layers.push((circle(cx, cy, 2.5 * unit), HDR_COLOR));
+    }
+    layers
+}
+
+/// Draws the icon as straight (not premultiplied) RGBA, `size` pixels square.
+fn render(state: &IconState, size: u32, fg: Rgba) -> Vec<u8> {
+    let layers = layers(state, size, fg);
+    let mut rgba = Vec::with_capacity((size * size * 4) as usize);
+    for py in 0..size {
+        for px in 0..size {
+            // Premultiplied sum over the subsamples
+            let mut sum = [0f32; 4];
+            for sy in 0..SAMPLES {
+                for sx in 0..SAMPLES {
+                    let x = px as f32 + (sx as f32 + 0.5) / SAMPLES as f32;
+                    let y = py as f32 + (sy as f32 + 0.5) / SAMPLES as f32;
+                    if let Some((_, color)) = layers.iter().rev().find(|(s, _)| s.contains(x, y)) {
+                        let alpha = color[3] as f32 / 255.0;
+                        for (total, &channel) in sum.iter_mut().zip(&color[..3]) {
+                            *total += channel as f32 * alpha;
+                        }
+                        sum[3] += alpha;
+                    }
+                }
+            }
+            let coverage = sum[3] / (SAMPLES * SAMPLES) as f32;
+            if coverage == 0.0 {
+                rgba.extend_from_slice(&TRANSPARENT);
+                continue;
+            }
+            for channel in &sum[..3] {
+                rgba.push((channel / sum[3]).round() as u8);
+            }
+            rgba.push((coverage * 255.0).round() as u8);
+        }
+    }
+    rgba
+}
+
+// Tray icons are 16 pixels at 100% scaling
+const BASE_SIZE: f64 = 16.0;
+const LIGHT_TASKBAR_COLOR: Rgba = [32, 32, 32, 255];
+const DARK_TASKBAR_COLOR: Rgba = [255, 255, 255, 255];
+
+// The taskbar sits on the primary monitor, so its scale decides the icon size
+fn icon_size(app_handle: &AppHandle) -> u32 {
+    let scale = app_handle
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map_or(1.0, |m| m.scale_factor());
+    ((BASE_SIZE * scale).round() as u32).max(BASE_SIZE as u32)
+}
+
+#[cfg(target_os = "windows")]
+fn light_taskbar() -> bool {
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let wide = |s: &str| s.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let key = wide(r"Software\Microsoft\Windows\CurrentVersion\Themes\Personalize");
+    let value = wide("SystemUsesLightTheme");
+    let mut data: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let rc = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            windows::core::PCWSTR(key.as_ptr()),
+            windows::core::PCWSTR(value.as_ptr()),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut data as *mut u32 as *mut core::ffi::c_void),
+            Some(&mut size),
+        )
+    };
+    rc.is_ok() && data != 0
+}
+
+#[cfg(not(target_os = "windows"))]
+fn light_taskbar() -> bool {
+    false
+}
+
+// What the tray shows now, so unchanged state doesn't redraw it
+fn last_drawn() -> &'static Mutex<Option<(IconState, u32, Rgba)>> {
+    static LAST: OnceLock<Mutex<Option<(IconState, u32, Rgba)>>> = OnceLock::new();
+    LAST.get_or_init(|| Mutex::new(None))
+}
+
+/// Shows `state` on the tray icon at the tray's current size and taskbar color.
+pub fn update(app_handle: &AppHandle, tray: &TrayIcon, state: IconState) {
+    let size = icon_size(app_handle);
+    let fg = if light_taskbar() {
+        LIGHT_TASKBAR_COLOR
+    } else {
+        DARK_TASKBAR_COLOR
+    };
+    let drawn = Some((state, size, fg));
+    let Ok(mut last) = last_drawn().lock() else {
+        return;
+    };
+    if *last == drawn {
+        return;
+    }
+    let image = Image::new_owned(render(&state, size, fg), size, size);
+    match tray.set_icon(Some(image)) {
+        Ok(()) => *last = drawn,
+        Err(e) => log::warn!("Failed to update the tray icon: {}", e),
+    }
+}
+
+/// Redraws the icon in the taskbar's current color, e.g. after a switch between light and dark
+/// mode; the state shown stays the same.
+#[cfg(target_os = "windows")]
+pub fn theme_changed(app_handle: &AppHandle) {
+    let Some((state, _, _)) = last_drawn().lock().ok().and_then(|last| *last) else {
+        return;
+    };
+    if let Some(tray) = app_handle.tray_by_id(crate::tray_menu::TRAY_ID) {
+        update(app_handle, &tray, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZES: [u32; 3] = [16, 20, 24];
+    const FG: Rgba = DARK_TASKBAR_COLOR;
+
+    fn pixel(image: &[u8], size: u32, x: u32, y: u32) -> Rgba {
+        let i = ((y * size + x) * 4) as usize;
+        [image[i], image[i + 1], image[i + 2], image[i + 3]]
+    }
+
+    // Pixels whose color differs between two renders at the same size
+    fn changed(a: &[u8], b: &[u8], size: u32) -> Vec<(u32, u32)> {
+        (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .filter(|&(x, y)| pixel(a, size, x, y) != pixel(b, size, x, y))
+            .collect()
+    }
+
+    // Every changed pixel lies within `r` of (cx, cy)
+    fn within(pixels: &[(u32, u32)], cx: f32, cy: f32, r: f32) -> bool {
+        pixels.iter().all(|&(x, y)| {
+            ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt() <= r
+        })
+    }
+
+    #[test]
+    fn renders_monitor_outline_with_transparent_corners() {
+        let all = IconState {
+            hdr: true,
+            night_light: true,
+            profile: Some('W'),
+        };
+        for size in SIZES {
+            let unit = size as f32 / 16.0;
+            let top = (2.0 * unit).round() as u32;
+            let image = render(&IconState::default(), size, FG);
+            assert_eq!(image.len(), (size * size * 4) as usize);
+            for (x, y) in [(0, 0), (size - 1, 0), (0, size - 1), (size - 1, size - 1)] {
+                assert_eq!(
+                    pixel(&image, size, x, y),
+                    TRANSPARENT,
+                    "{}px ({}, {})",
+                    size,
+                    x,
+                    y
+                );
+            }
+            // Badges only take the corners on the right
+            let image = render(&all, size, FG);
+            for (x, y) in [(0, 0), (0, size - 1)] {
+                assert_eq!(
+                    pixel(&image, size, x, y),
+                    TRANSPARENT,
+                    "{}px ({}, {})",
+                    size,
+                    x,
+                    y
+                );
+            }
+
+            let image = render(&IconState::default(), size, FG);
+            let mid = size / 2;
+            // Top edge, empty screen and stand
+            assert_eq!(pixel(&image, size, mid, top), FG, "{}px", size);
+            assert_eq!(pixel(&image, size, mid, top + 1 + size / 4), TRANSPARENT);
+            assert_eq!(pixel(&image, size, mid, (13.0 * unit) as u32), FG);
+            // Follows the taskbar color
+            let light = render(&IconState::default(), size, LIGHT_TASKBAR_COLOR);
+            assert_eq!(pixel(&light, size, mid, top), LIGHT_TASKBAR_COLOR);
+        }
+    }
+
+    #[test]
+    fn profile_initial_fills_the_screen_area() {
+        assert_eq!(profile_initial("work"), 'W');
+        assert_eq!(profile_initial("  2 screens"), '2');
+        assert_eq!(profile_initial("—"), '•');
+        for size in SIZES {
+            let unit = size as f32 / 16.0;
+            let px = |units: f32| (units * unit).round() as u32;
+            let line = px(1.0).max(1);
+            let (left, top, right, bottom) = (px(0.5), px(2.0), size - px(0.5), px(12.0));
+            let base = render(&IconState::default(), size, FG);
+
+            // 'M' covers every row and column of its 3×5 cells
+            let image = render(
+                &IconState {
+                    profile: Some('M'),
+                    ..Default::default()
+                },
+                size,
+                FG,
+            );
+            let glyph = changed(&base, &image, size);
+            assert!(glyph.iter().all(|&(x, y)| pixel(&image, size, x, y) == FG));
+            let (x0, x1) = (
+                glyph.iter().map(|p| p.0).min().unwrap(),
+                glyph.iter().map(|p| p.0).max().unwrap() + 1,
+            );
+            let (y0, y1) = (
+                glyph.iter().map(|p| p.1).min().unwrap(),
+                glyph.iter().map(|p| p.1).max().unwrap() + 1,
+            );
+            assert!(x0 >= left + line && x1 <= right - line, "{}px", size);
+            assert!(y0 >= top + line && y1 <= bottom - line, "{}px", size);
+            assert_eq!((x1 - x0) * 5, (y1 - y0) * 3, "{}px", size);
+            assert!(x0 - (left + line) <= (right - line) - x1 + 1);
+            assert!((right - line) - x1 <= x0 - (left + line) + 1);
+
+            // Characters without a glyph show a centered dot
+            let dot = render(
+                &IconState {
+                    profile: Some('•'),
+                    ..Default::default()
+                },
+                size,
+                FG,
+            );
+            let cells = changed(&base, &dot, size);
+            assert!(!cells.is_empty());
+            assert!(cells
+                .iter()
+                .all(|p| p.0 > x0 && p.0 < x1 - 1 && p.1 > y0 && p.1 < y1 - 1));
+        }
+    }
+
+    #[test]
+    fn badges_sit_on_their_corners_with_a_gap() {
+        for size in SIZES {
+            let s = size as f32;
+            let unit = s / 16.0;
+            let base = render(&IconState::default(), size, FG);
+
+            let hdr = render(
+                &IconState {
+                    hdr: true,
+                    ..Default::default()
+                },
+                size,
+                FG,
+            );
+            let (cx, cy) = (s - 2.5 * unit, s - 2.5 * unit);
+            assert_eq!(
+                pixel(&hdr, size, cx as u32, cy as u32),
+                HDR_COLOR,
+                "{}px",
+                size
+            );
+            let badge = changed(&base, &hdr, size);
+            assert!(within(&badge, cx, cy, 3.5 * unit + 1.0), "{}px", size);
+            // The gap cuts into the outline instead of the badge touching it
+            assert!(badge.iter().any(|&(x, y)| {
+                pixel(&base, size, x, y) == FG && pixel(&hdr, size, x, y)[3] < 255
+            }));
+
+            let night = render(
+                &IconState {
+                    night_light: true,
+                    ..Default::default()
+                },
+                size,
+                FG,
+            );
+            let (cx, cy) = (s - 2.5 * unit, 3.0 * unit);
+            let badge = changed(&base, &night, size);
+            assert!(within(&badge, cx, cy, 3.5 * unit + 1.0), "{}px", size);
+            assert!(badge
+                .iter()
+                .any(|&(x, y)| pixel(&night, size, x, y) == NIGHT_LIGHT_COLOR));
+            // A crescent: its circle's middle is cut out
+            assert_eq!(pixel(&night, size, cx as u32, cy as u32), TRANSPARENT);
+
+            // Both badges together don't overlap
+            let both = render(
+                &IconState {
+                    hdr: true,
+                    night_light: true,
+                    ..Default::default()
+                },
+                size,
+                FG,
+            );
+            assert_eq!(
+                changed(&base, &both, size).len(),
+                changed(&base, &hdr, size).len() + badge.len()
+            );
+        }
+    }
+}
@@ -0,0 +1,576 @@
+// Tray context menu: the fixed entries plus quick actions for every monitor, display profiles and
+// KVM presets. It is rebuilt in the background whenever displays or settings change (brightness
+// changes only refresh the brightness shown), so common actions work without opening the window;
+// the tooltip and icon (see `tray_icon`) follow along.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, Once, OnceLock};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Listener, Wry};
+
+use crate::displays::{ColorProfileInfo, DisplayInfo};
+use crate::inputs::InputSource;
+
+pub const TRAY_ID: &str = "main";
+
+const AUTOSTART_BASE_LABEL: &str = "Start at login";
+// Items whose action is looked up in `actions()`
+const ACTION_MENU_PREFIX: &str = "tray:";
+// Slider drags and bursts of changes rebuild the menu once they settle
+const REBUILD_DEBOUNCE: Duration = Duration::from_millis(500);
+const EVENTS: [(&str, Refresh); 2] = [
+    ("display-changed", Refresh::Full),
+    ("brightness-changed", Refresh::Brightness),
+];
+const BRIGHTNESS_PRESETS: [u32; 5] = [0, 25, 50, 75, 100];
+const ROTATIONS: [(u32, &str); 4] = [
+    (0, "Landscape"),
+    (90, "Portrait"),
+    (180, "Landscape (flipped)"),
+    (270, "Portrait (flipped)"),
+];
+// Longer resolution lists are cut to the largest ones
+const MAX_RESOLUTIONS: usize = 12;
+// Warmer than this counts as night light; monitors' own presets often read a bit off neutral
+const NIGHT_LIGHT_KELVIN: u32 = crate::color_temperature::NEUTRAL_KELVIN - 500;
+// Windows cuts tray tooltips off after this many characters
+const TOOLTIP_MAX_CHARS: usize = 127;
+
+fn checked_label(label: &str, checked: bool) -> String {
+    if checked {
+        format!("{label} ✓")
+    } else {
+        label.to_string()
+    }
+}
+
+fn autostart_label(enabled: bool) -> String {
+    checked_label(AUTOSTART_BASE_LABEL, enabled)
+}
+
+// A full rebuild reads every monitor over WMI and DDC/CI; brightness changes (hotkeys, fades,
+// the schedule) only re-read brightness so they don't compete with the fade for the bus
+#[derive(Clone, Copy, PartialEq)]
+enum Refresh {
+    Full,
+    Brightness,
+}
+
+#[derive(Clone)]
+enum Action {
+    // Run through rpc::dispatch, which also announces the change
+    Rpc(&'static str, Value),
+    Kvm(String),
+}
+
+fn actions() -> &'static Mutex<HashMap<String, Action>> {
+    static ACTIONS: OnceLock<Mutex<HashMap<String, Action>>> = OnceLock::new();
+    ACTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Ids are never reused, so a click on a menu that was just replaced can't hit another action
+static NEXT_ACTION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// What the menu shows for one monitor; read up front because DDC/CI is slow.
+struct MonitorState {
+    info: DisplayInfo,
+    brightness: Option<u32>,
+    input: Option<String>,
+    inputs: Vec<InputSource>,
+    color_profiles: Vec<ColorProfileInfo>,
+    kelvin: Option<u32>,
+}
+
+#[derive(Default)]
+struct MenuState {
+    monitors: Vec<MonitorState>,
+    profiles: Vec<String>,
+    // Profile the current setup matches
+    active_profile: Option<String>,
+    kvm_presets: Vec<String>,
+}
+
+fn read_state(app_handle: &AppHandle) -> MenuState {
+    let provider = crate::displays::active_provider();
+    let monitors = crate::displays::get_all_monitors().unwrap_or_else(|e| {
+        log::warn!("Tray menu: can't list monitors: {}", e);
+        Vec::new()
+    });
+    let profiles = crate::profiles::list_display_profiles(app_handle.clone());
+    let active_profile = profiles
+        .iter()
+        .find(|p| crate::profiles::matches(p, &monitors))
+        .map(|p| p.name.clone());
+    let monitors = monitors
+        .into_iter()
+        .map(|info| {
+            let device_name = info.device_name.clone();
+            // A monitor that is off doesn't answer DDC/CI
+            let ddc = info.enabled;
+            let switchable = ddc && info.supports_input_switch != Some(false);
+            let kelvin = if ddc {
+                provider
+                    .get_monitor_color_temperature(device_name.clone())
+                    .ok()
+                    .map(|t| t.kelvin)
+            } else {
+                None
+            };
+            let brightness = if ddc {
+                provider
+                    .get_monitor_brightness(device_name.clone())
+                    .ok()
+                    .map(|b| b.percent())
+            } else {
+                None
+            };
+            let (input, inputs) = if switchable {
+                (
+                    provider.get_monitor_input_source(device_name.clone()).ok(),
+                    crate::inputs::inputs_of(app_handle, &info),
+                )
+            } else {
+                (None, Vec::new())
+            };
+            let color_profiles = provider
+                .list_color_profiles(device_name)
+                .map(|list| {
+                    list.into_iter()
+                        .filter(|p| p.associated || p.is_default)
+                        .collect()
+                })
+                .unwrap_or_default();
+            MonitorState {
+                info,
+                brightness,
+                input,
+                inputs,
+                color_profiles,
+                kelvin,
+            }
+        })
+        .collect();
+    MenuState {
+        monitors,
+        profiles: profiles.into_iter().map(|p| p.name).collect(),
+        active_profile,
+        kvm_presets: crate::settings::get(app_handle)
+            .kvm_presets
+            .into_iter()
+            .map(|p| p.name)
+            .collect(),
+    }
+}
+
+/// Re-reads only the brightness of each monitor, keeping the rest of `state`.
+fn read_brightness(state: &mut MenuState) {
+    let provider = crate::displays::active_provider();
+    for monitor in state.monitors.iter_mut().filter(|m| m.info.enabled) {
+        if let Ok(info) = provider.get_monitor_brightness(monitor.info.device_name.clone()) {
+            monitor.brightness = Some(info.percent());
+        }
+    }
+}
+
+fn monitor_name(info: &DisplayInfo) -> &str {
+    if info.friendly_name.is_empty() {
+        info.device_name.trim_start_matches("\\\\.\\")
+    } else {
+        &info.friendly_name
+    }
+}
+
+struct MenuBuilder<'a> {
+    app_handle: &'a AppHandle,
+    actions: HashMap<String, Action>,
+}
+
+impl MenuBuilder<'_> {
+    fn action(&mut self, label: &str, action: Action) -> tauri::Result<MenuItem<Wry>> {
+        let id = format!(
+            "{ACTION_MENU_PREFIX}{}",
+            NEXT_ACTION_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        let item = MenuItem::with_id(self.app_handle, id.clone(), label, true, None::<&str>)?;
+        self.actions.insert(id, action);
+        Ok(item)
+    }
+
+    fn rpc(
+        &mut self,
+        label: &str,
+        method: &'static str,
+        params: Value,
+    ) -> tauri::Result<MenuItem<Wry>> {
+        self.action(label, Action::Rpc(method, params))
+    }
+
+    fn placeholder(&self, label: &str) -> tauri::Result<MenuItem<Wry>> {
+        MenuItem::new(self.app_handle, label, false, None::<&str>)
+    }
+
+    fn submenu(&self, label: &str) -> tauri::Result<Submenu<Wry>> {
+        Submenu::new(self.app_handle, label, true)
+    }
+
+    fn monitor_menu(&mut self, monitor: &MonitorState) -> tauri::Result<Submenu<Wry>> {
+        let info = &monitor.info;
+        let device = info.device_name.as_str();
+        let menu = self.submenu(monitor_name(info))?;
+
+        if let Some(current) = monitor.brightness {
+            let brightness = self.submenu(&format!("Brightness: {}%", current))?;
+            for percent in BRIGHTNESS_PRESETS {
+                brightness.append(&self.rpc(
+                    &checked_label(&format!("{}%", percent), percent == current),
+                    "set_monitor_brightness",
+                    json!({ "deviceName": device, "percent": percent }),
+                )?)?;
+            }
+            menu.append(&brightness)?;
+        }
+
+        if !monitor.inputs.is_empty() {
+            let inputs = self.submenu("Input")?;
+            for input in &monitor.inputs {
+                let label = match &input.name {
+                    Some(name) => format!("{} ({})", name, input.label),
+                    None => input.label.clone(),
+                };
+                inputs.append(&self.rpc(
+                    &checked_label(&label, monitor.input.as_ref() == Some(&input.key)),
+                    "set_monitor_input_source",
+                    json!({ "deviceName": device, "input": input.key }),
+                )?)?;
+            }
+            menu.append(&inputs)?;
+        }
+
+        if info.enabled {
+            let current = &info.current;
+            let modes: Vec<_> = info
+                .modes
+                .iter()
+                .filter(|m| !m.interlaced && !m.scaled)
+                .collect();
+
+            let mut sizes: Vec<(u32, u32)> = modes.iter().map(|m| (m.width, m.height)).collect();
+            sizes.sort_by_key(|&(w, h)| std::cmp::Reverse((w * h, w)));
+            sizes.dedup();
+            sizes.truncate(MAX_RESOLUTIONS);
+            if !sizes.contains(&(current.width, current.height)) {
+                sizes.push((current.width, current.height));
+            }
+            let resolutions = self.submenu(&format!(
+                "Resolution: {} × {}",
+                current.width, current.height
+            ))?;
+            for (width, height) in sizes {
+                // Keep the refresh rate when the new size offers it
+                let refresh_hz = modes
+                    .iter()
+                    .any(|m| {
+                        m.width == width && m.height == height && m.refresh_hz == current.refresh_hz
+                    })
+                    .then_some(current.refresh_hz);
+                resolutions.append(&self.rpc(
+                    &checked_label(
+                        &format!("{} × {}", width, height),
+                        (width, height) == (current.width, current.height),
+                    ),
+                    "set_monitor_resolution",
+                    json!({
+                        "deviceName": device,
+                        "width": width,
+                        "height": height,
+                        "refreshHz": refresh_hz,
+                    }),
+                )?)?;
+            }
+            menu.append(&resolutions)?;
+
+            let mut rates: Vec<u32> = modes
+                .iter()
+                .filter(|m| m.width == current.width && m.height == current.height)
+                .map(|m| m.refresh_hz)
+                .collect();
+            rates.sort_unstable_by(|a, b| b.cmp(a));
+            rates.dedup();
+            if rates.len() > 1 {
+                let refresh = self.submenu(&format!("Refresh rate: {} Hz", current.refresh_hz))?;
+                for hz in rates {
+                    refresh.append(&self.rpc(
+                        &checked_label(&format!("{} Hz", hz), hz == current.refresh_hz),
+                        "set_monitor_resolution",
+                        json!({
+                            "deviceName": device,
+                            "width": current.width,
+                            "height": current.height,
+                            "refreshHz": hz,
+                        }),
+                    )?)?;
+                }
+                menu.append(&refresh)?;
+            }
+
+            let rotation = self.submenu("Rotation")?;
+            for (degrees, label) in ROTATIONS {
+                rotation.append(&self.rpc(
+                    &checked_label(label, degrees == info.orientation),
+                    "set_monitor_orientation",
+                    json!({ "deviceName": device, "orientationDegrees": degrees }),
+                )?)?;
+            }
+            menu.append(&rotation)?;
+
+            let profiles = self.submenu("Color profile")?;
+            if monitor.color_profiles.is_empty() {
+                profiles.append(&self.placeholder("No associated profiles")?)?;
+            }
+            for profile in &monitor.color_profiles {
+                profiles.append(&self.rpc(
+                    &checked_label(&profile.description, profile.is_default),
+                    "set_monitor_color_profile",
+                    json!({ "deviceName": device, "profile": profile.name }),
+                )?)?;
+            }
+            menu.append(&profiles)?;
+
+            if info.hdr_status != "unsupported" {
+                let on = info.hdr_status == "on";
+                menu.append(&self.rpc(
+                    &checked_label("HDR", on),
+                    "enable_hdr",
+                    json!({ "deviceName": device, "enable": !on }),
+                )?)?;
+            }
+        }
+
+        menu.append(&self.rpc(
+            if info.enabled { "Turn off" } else { "Turn on" },
+            "set_monitor_power",
+            json!({ "deviceName": device, "powerOn": !info.enabled }),
+        )?)?;
+        Ok(menu)
+    }
+
+    fn build(&mut self, state: &MenuState) -> tauri::Result<Menu<Wry>> {
+        let app = self.app_handle;
+        let menu = Menu::new(app)?;
+        menu.append(&MenuItem::with_id(app, "show", "Show", true, Some(""))?)?;
+        let autostart_item = {
+            use tauri_plugin_autostart::ManagerExt;
+            let enabled = app.autolaunch().is_enabled().unwrap_or(false);
+            let locked = crate::policy::current().autostart().is_some();
+            MenuItem::with_id(
+                app,
+                "autostart_toggle",
+                autostart_label(enabled),
+                !locked,
+                Some(""),
+            )?
+        };
+        menu.append(&autostart_item)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+        for monitor in &state.monitors {
+            menu.append(&self.monitor_menu(monitor)?)?;
+        }
+
+        let profiles = self.submenu("Display profiles")?;
+        if state.profiles.is_empty() {
+            profiles.append(&self.placeholder("No display profiles")?)?;
+        }
+        for name in &state.profiles {
+            profiles.append(&self.rpc(
+                &checked_label(name, state.active_profile.as_ref() == Some(name)),
+                "apply_display_profile",
+                json!({ "name": name }),
+            )?)?;
+        }
+        menu.append(&profiles)?;
+
+        let kvm = self.submenu("Switch inputs")?;
+        if state.kvm_presets.is_empty() {
+            kvm.append(&self.placeholder("No KVM presets")?)?;
+        }
+        for name in &state.kvm_presets {
+            kvm.append(&self.action(name, Action::Kvm(name.clone()))?)?;
+        }
+        menu.append(&kvm)?;
+
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+        menu.append(&MenuItem::with_id(app, "about", "About", true, Some(""))?)?;
+        menu.append(&MenuItem::with_id(app, "exit", "Exit", true, Some(""))?)?;
+        Ok(menu)
+    }
+}
+
+fn build_menu(app_handle: &AppHandle, state: &MenuState) -> tauri::Result<Menu<Wry>> {
+    let mut builder = MenuBuilder {
+        app_handle,
+        actions: HashMap::new(),
+    };
+    let menu = builder.build(state)?;
+    if let Ok(mut actions) = actions().lock() {
+        *actions = builder.actions;
+    }
+    Ok(menu)
+}
+
+/// The menu the tray starts with; monitors are filled in by the first rebuild.
+pub fn initial_menu(app_handle: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    build_menu(app_handle, &MenuState::default())
+}
+
+// "WinDisplay: Work" and a line per monitor, e.g. "DELL U2720Q: 40%, 3840×2160 @ 60 Hz, HDR"
+fn tooltip(state: &MenuState) -> String {
+    let mut lines = vec![match &state.active_profile {
+        Some(name) => format!("WinDisplay: {}", name),
+        None => "WinDisplay".to_string(),
+    }];
+    for monitor in &state.monitors {
+        let info = &monitor.info;
+        let mut parts = Vec::new();
+        if info.enabled {
+            if let Some(percent) = monitor.brightness {
+                parts.push(format!("{}%", percent));
+            }
+            parts.push(format!(
+                "{}×{} @ {} Hz",
+                info.current.width, info.current.height, info.current.refresh_hz
+            ));
+            if info.hdr_status == "on" {
+                parts.push("HDR".to_string());
+            }
+        } else {
+            parts.push("off".to_string());
+        }
+        lines.push(format!("{}: {}", monitor_name(info), parts.join(", ")));
+    }
+    let tooltip = lines.join("\n");
+    if tooltip.chars().count() <= TOOLTIP_MAX_CHARS {
+        return tooltip;
+    }
+    let mut cut: String = tooltip.chars().take(TOOLTIP_MAX_CHARS - 1).collect();
+    cut.push('…');
+    cut
+}
+
+fn icon_state(state: &MenuState) -> crate::tray_icon::IconState {
+    crate::tray_icon::IconState {
+        hdr: state.monitors.iter().any(|m| m.info.hdr_status == "on"),
+        night_light: state
+            .monitors
+            .iter()
+            .any(|m| m.kelvin.is_some_and(|k| k < NIGHT_LIGHT_KELVIN)),
+        profile: state
+            .active_profile
+            .as_deref()
+            .map(crate::tray_icon::profile_initial),
+    }
+}
+
+fn rebuild(app_handle: &AppHandle, state: &MenuState) {
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app_handle, state) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                log::warn!("Failed to update the tray menu: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to build the tray menu: {}", e),
+    }
+    if let Err(e) = tray.set_tooltip(Some(tooltip(state))) {
+        log::warn!("Failed to update the tray tooltip: {}", e);
+    }
+    crate::tray_icon::update(app_handle, &tray, icon_state(state));
+}
+
+fn refresh_sender() -> &'static OnceLock<Sender<Refresh>> {
+    static REFRESH: OnceLock<Sender<Refresh>> = OnceLock::new();
+    &REFRESH
+}
+
+/// Asks for a rebuild, e.g. after a change that raises no event.
+pub fn refresh() {
+    if let Some(tx) = refresh_sender().get() {
+        let _ = tx.send(Refresh::Full);
+    }
+}
+
+/// Runs the action behind a tray item built here; ids from a replaced menu are ignored.
+pub fn handle_menu_event(app_handle: &AppHandle, id: &str) {
+    if !id.starts_with(ACTION_MENU_PREFIX) {
+        return;
+    }
+    let Some(action) = actions()
+        .lock()
+        .ok()
+        .and_then(|actions| actions.get(id).cloned())
+    else {
+        return;
+    };
+    // DDC/CI and mode changes take a while; keep the tray responsive
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || match action {
+        Action::Rpc(method, params) => {
+            if let Err(e) = crate::rpc::dispatch(&app_handle, method, params) {
+                log::warn!("Tray: {} failed: {}", method, e.message);
+            }
+        }
+        Action::Kvm(name) => crate::kvm::run_preset(&app_handle, &name),
+    });
+}
+
+static START_ONCE: Once = Once::new();
+
+/// Fills in the tray menu and keeps it current as displays and settings change.
+pub fn start_menu_updates(app_handle: AppHandle) {
+    START_ONCE.call_once(|| {
+        let (tx, rx) = mpsc::channel();
+        for (event, kind) in EVENTS {
+            let tx = tx.clone();
+            app_handle.listen(event, move |_| {
+                let _ = tx.send(kind);
+            });
+        }
+        // Input names and KVM presets live in settings
+        let settings_rx = crate::settings::subscribe();
+        let settings_tx = tx.clone();
+        std::thread::spawn(move || {
+            while settings_rx.recv().is_ok() {
+                if settings_tx.send(Refresh::Full).is_err() {
+                    break;
+                }
+            }
+        });
+        let _ = tx.send(Refresh::Full);
+        let _ = refresh_sender().set(tx);
+        std::thread::spawn(move || {
+            let mut last: Option<MenuState> = None;
+            while let Ok(mut kind) = rx.recv() {
+                while let Ok(next) = rx.recv_timeout(REBUILD_DEBOUNCE) {
+                    if next == Refresh::Full {
+                        kind = Refresh::Full;
+                    }
+                }
+                let state = match (kind, last.take()) {
+                    (Refresh::Brightness, Some(mut state)) => {
+                        read_brightness(&mut state);
+                        state
+                    }
+                    _ => read_state(&app_handle),
+                };
+                rebuild(&app_handle, &state);
+                last = Some(state);
+            }
+        });
+    });
+}
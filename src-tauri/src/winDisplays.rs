@@ -1,4 +1,8 @@
-use crate::displays::{BrightnessInfo, DisplayInfo, Displays, Resolution, ScaleInfo};
+use crate::displays::{
+    BrightnessInfo, ColorProfileInfo, ColorTemperatureInfo, DisplayInfo, Displays, Resolution,
+    ScaleInfo, VrrInfo,
+};
+use crate::state_persistence::Change;
 use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -30,12 +34,72 @@ impl Displays for WinDisplays {
         set_monitor_resolution_windows(device_name, width, height, refresh_hz)
     }
 
+    fn list_monitor_modes(
+        &self,
+        device_name: String,
+        include_raw: bool,
+    ) -> Result<Vec<Resolution>, String> {
+        let modes: Vec<Resolution> = enum_display_modes(&device_name, include_raw)
+            .into_iter()
+            .map(|(mode, _)| mode)
+            .collect();
+        if modes.is_empty() {
+            return Err(format!("No display modes reported for {}", device_name));
+        }
+        Ok(modes)
+    }
+
+    fn set_monitor_mode(
+        &self,
+        device_name: String,
+        request: crate::mode_selection::ModeRequest,
+    ) -> Result<Resolution, String> {
+        set_monitor_mode_windows(&device_name, &request)
+    }
+
     fn get_monitor_brightness(&self, device_name: String) -> Result<BrightnessInfo, String> {
         get_monitor_brightness_windows(device_name)
     }
 
     fn set_monitor_brightness(&self, device_name: String, percent: u32) -> Result<(), String> {
-        set_monitor_brightness_windows(device_name, percent)
+        set_monitor_brightness_windows(device_name.clone(), percent)?;
+        crate::state_persistence::record(&device_name, Change::Brightness(percent));
+        Ok(())
+    }
+
+    fn set_monitor_dimming(&self, device_name: String, percent: u32) -> Result<(), String> {
+        update_gamma_state(&device_name, |state| state.dimming = percent.min(100))
+    }
+
+    fn set_monitor_color_temperature(
+        &self,
+        device_name: String,
+        kelvin: u32,
+    ) -> Result<ColorTemperatureInfo, String> {
+        set_monitor_color_temperature_windows(&device_name, kelvin)
+    }
+
+    fn get_monitor_color_temperature(
+        &self,
+        device_name: String,
+    ) -> Result<ColorTemperatureInfo, String> {
+        get_monitor_color_temperature_windows(&device_name)
+    }
+
+    fn list_color_profiles(&self, device_name: String) -> Result<Vec<ColorProfileInfo>, String> {
+        list_color_profiles_windows(&device_name)
+    }
+
+    fn get_monitor_color_profile(&self, device_name: String) -> Result<Option<String>, String> {
+        get_default_color_profile_windows(&device_name)
+    }
+
+    fn set_monitor_color_profile(
+        &self,
+        device_name: String,
+        profile: String,
+    ) -> Result<(), String> {
+        set_default_color_profile_windows(&device_name, &profile)
     }
 
     fn identify_monitors(&self, app_handle: tauri::AppHandle) -> Result<(), String> {
@@ -54,15 +118,19 @@ impl Displays for WinDisplays {
         set_monitor_scale_windows(&device_name, scale_percent)
     }
 
+    fn set_custom_scale(&self, percent: Option<u32>) -> Result<(), String> {
+        set_custom_scale_windows(percent)
+    }
+
+    fn set_monitor_scaling_mode(&self, device_name: String, mode: String) -> Result<(), String> {
+        set_monitor_scaling_mode_windows(&device_name, &mode)
+    }
+
     fn enable_hdr(&self, device_name: String, enable: bool) -> Result<(), String> {
-        // Find logical display index matching `device_name` in the same order as get_all_monitors_windows
-        let monitors = get_all_monitors_windows()?;
-        let index = monitors
-            .iter()
-            .position(|m| m.device_name == device_name)
+        let target = crate::winHdr::target_for_device(&device_name)
             .ok_or_else(|| "Display not found".to_string())?;
 
-        match crate::winHdr::set_hdr_status_by_index(index, enable) {
+        match crate::winHdr::set_hdr_status(target, enable) {
             Some(crate::winHdr::Status::Unsupported) => {
                 Err("HDR unsupported on this display".to_string())
             }
@@ -71,8 +139,27 @@ impl Displays for WinDisplays {
         }
     }
 
+    fn set_monitor_vrr(&self, device_name: String, enable: bool) -> Result<(), String> {
+        set_monitor_vrr_windows(&device_name, enable)
+    }
+
+    fn set_sdr_white_level(&self, device_name: String, nits: u32) -> Result<(), String> {
+        use crate::displays::{MAX_SDR_WHITE_NITS, MIN_SDR_WHITE_NITS};
+        if !(MIN_SDR_WHITE_NITS..=MAX_SDR_WHITE_NITS).contains(&nits) {
+            return Err(format!(
+                "SDR white level must be between {} and {} nits",
+                MIN_SDR_WHITE_NITS, MAX_SDR_WHITE_NITS
+            ));
+        }
+        let target = crate::winHdr::target_for_device(&device_name)
+            .ok_or_else(|| "Display not found".to_string())?;
+        crate::winHdr::set_sdr_white_level(target, nits)
+    }
+
     fn set_monitor_input_source(&self, device_name: String, input: String) -> Result<(), String> {
-        set_monitor_input_source_windows(device_name, input)
+        set_monitor_input_source_windows(device_name.clone(), input.clone())?;
+        crate::state_persistence::record(&device_name, Change::Input(input));
+        Ok(())
     }
 
     fn get_monitor_input_source(&self, device_name: String) -> Result<String, String> {
@@ -88,7 +175,9 @@ impl Displays for WinDisplays {
     }
 
     fn set_monitor_power(&self, device_name: String, power_on: bool) -> Result<(), String> {
-        set_monitor_power_windows(&device_name, power_on)
+        set_monitor_power_windows(&device_name, power_on)?;
+        crate::state_persistence::record(&device_name, Change::Power(power_on));
+        Ok(())
     }
 }
 
@@ -305,7 +394,7 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
 
     log::debug!("get_all_monitors_windows: start");
     let mut displays: Vec<DisplayInfo> = Vec::new();
-    // Pre-fetch HDR display list and match by index as requested
+    // Pre-fetch HDR display list; entries are matched to GDI devices by name below
     let hdr_displays = crate::winHdr::get_displays();
     log::debug!("HDR displays fetched: count={}", hdr_displays.len());
     let mut logical_display_index: usize = 0;
@@ -317,6 +406,7 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
         edid_entries.len()
     );
     let mut used_edid: Vec<bool> = vec![false; edid_entries.len()];
+    let custom_scale = get_custom_scale_percent();
 
     let mut device_index: u32 = 0;
     loop {
@@ -378,12 +468,35 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
             )
         };
 
-        let current = Resolution {
-            width: current_mode.dmPelsWidth as u32,
-            height: current_mode.dmPelsHeight as u32,
-            bits_per_pixel: current_mode.dmBitsPerPel as u32,
-            refresh_hz: current_mode.dmDisplayFrequency as u32,
-        };
+        let active_path = query_active_path(&device_name);
+        let mut current = devmode_resolution(&current_mode);
+        // DEVMODE only has whole hertz; the active path knows the exact rate
+        if let Some(rate) = active_path.and_then(|p| {
+            crate::mode_selection::rational_refresh_rate(
+                p.targetInfo.refreshRate.Numerator,
+                p.targetInfo.refreshRate.Denominator,
+            )
+        }) {
+            current.refresh_rate = rate;
+        }
+        let scaling_mode: Option<String> = active_path
+            .and_then(|p| scaling_mode_name(p.targetInfo.scaling))
+            .map(str::to_string);
+        let edid = active_path
+            .as_ref()
+            .and_then(read_monitor_edid)
+            .and_then(|data| match crate::edid::parse_edid(&data) {
+                Ok(edid) => Some(edid),
+                Err(e) => {
+                    log::debug!("Failed to parse EDID of {}: {}", device_name, e);
+                    None
+                }
+            });
+        let vrr = edid.as_ref().and_then(|e| e.vrr).map(|range| VrrInfo {
+            min_hz: range.min_hz,
+            max_hz: range.max_hz,
+            enabled: get_monitor_vrr_windows(&device_name),
+        });
 
         // Orientation is exposed by dmDisplayOrientation (0,1,2,3) which maps to 0,90,180,270 degrees.
         use windows::Win32::Graphics::Gdi::{DMDO_180, DMDO_270, DMDO_90, DMDO_DEFAULT};
@@ -397,35 +510,7 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
             };
 
         let mut modes: Vec<Resolution> = Vec::new();
-        let mut mode_index: u32 = 0;
-        loop {
-            let mut dm: DEVMODEW = unsafe { zeroed() };
-            dm.dmSize = size_of::<DEVMODEW>() as u16;
-            let device_name_wide = to_wide_null_terminated(&device_name);
-            let ok_mode: BOOL = unsafe {
-                EnumDisplaySettingsExW(
-                    windows::core::PCWSTR(device_name_wide.as_ptr()),
-                    windows::Win32::Graphics::Gdi::ENUM_DISPLAY_SETTINGS_MODE(mode_index),
-                    &mut dm,
-                    windows::Win32::Graphics::Gdi::ENUM_DISPLAY_SETTINGS_FLAGS(0),
-                )
-            };
-            if !ok_mode.as_bool() {
-                log::debug!(
-                    "End of mode enumeration for device_name='{}' after {} modes",
-                    device_name,
-                    mode_index
-                );
-                break;
-            }
-
-            let res = Resolution {
-                width: dm.dmPelsWidth as u32,
-                height: dm.dmPelsHeight as u32,
-                bits_per_pixel: dm.dmBitsPerPel as u32,
-                refresh_hz: dm.dmDisplayFrequency as u32,
-            };
-
+        for (res, _) in enum_display_modes(&device_name, false) {
             if !modes.iter().any(|m| {
                 m.width == res.width
                     && m.height == res.height
@@ -434,9 +519,12 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
             }) {
                 modes.push(res);
             }
-
-            mode_index += 1;
         }
+        log::debug!(
+            "Enumerated {} distinct modes for device_name='{}'",
+            modes.len(),
+            device_name
+        );
 
         // Prefer OS-reported preferred/native mode via DisplayConfig; fall back to largest area mode
         let max_native = if let Some((nw, nh)) = query_preferred_native_resolution(&device_name) {
@@ -455,8 +543,7 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
                 .unwrap_or(Resolution {
                     width: nw,
                     height: nh,
-                    bits_per_pixel: current.bits_per_pixel,
-                    refresh_hz: current.refresh_hz,
+                    ..current.clone()
                 })
         } else {
             log::debug!(
@@ -605,9 +692,28 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
             }
             active = e.Active.unwrap_or(false);
         }
-
-        // Determine per-monitor scaling (DPI / 96)
-        let scale_factor: f32 = get_monitor_scale_for_device(&device_name);
+        // WMI leaves these empty for some monitors; the EDID has them too
+        if let Some(edid) = &edid {
+            if manufacturer.is_empty() {
+                manufacturer = edid.manufacturer_id.clone();
+            }
+            if model.is_empty() {
+                model = edid.name.clone().unwrap_or_default();
+            }
+            if serial.is_empty() {
+                serial = edid.serial.clone().unwrap_or_else(|| {
+                    if edid.serial_number != 0 {
+                        edid.serial_number.to_string()
+                    } else {
+                        String::new()
+                    }
+                });
+            }
+            if year_of_manufacture == 0 {
+                year_of_manufacture = edid.year_of_manufacture as u32;
+                week_of_manufacture = edid.week_of_manufacture as u32;
+            }
+        }
 
         // Query available scales and recommended state via DisplayConfig undocumented API
         let scales: Vec<ScaleInfo> = match get_scales_for_device(&device_name) {
@@ -615,7 +721,20 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
             Err(_) => Vec::new(),
         };
 
-        let hdr_status: String = match hdr_displays.get(logical_display_index) {
+        // The current DPI step is exact; GetDpiForMonitor depends on our own DPI awareness,
+        // so it's only the fallback. Custom scaling overrides both.
+        let scale_factor: f32 = match (custom_scale, scales.iter().find(|s| s.is_current)) {
+            (Some(percent), _) => percent as f32 / 100.0,
+            (None, Some(current_step)) => current_step.scale,
+            (None, None) => get_monitor_scale_for_device(&device_name),
+        };
+
+        let hdr_display = hdr_displays
+            .iter()
+            .find(|h| h.device_name.eq_ignore_ascii_case(&device_name));
+        let display_target = hdr_display.map(|h| h.target);
+        let advanced_color = hdr_display.and_then(|h| h.advanced_color.clone());
+        let hdr_status: String = match hdr_display {
             Some(h) => match h.status {
                 crate::winHdr::Status::Unsupported => "unsupported".to_string(),
                 crate::winHdr::Status::Off => "off".to_string(),
@@ -635,6 +754,9 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
         // - None = No DDC/CI support (e.g., internal display), assume enabled
         let enabled: bool = get_monitor_power_status_windows(&device_name).unwrap_or(true);
 
+        let color_profile: Option<String> =
+            get_default_color_profile_windows(&device_name).unwrap_or(None);
+
         displays.push(DisplayInfo {
             device_name,
             friendly_name,
@@ -657,7 +779,12 @@ fn get_all_monitors_windows() -> Result<Vec<DisplayInfo>, String> {
             scale: scale_factor,
             scales,
             hdr_status,
+            advanced_color,
             supports_input_switch,
+            color_profile,
+            display_target,
+            scaling_mode,
+            vrr,
         });
 
         if let Some(last) = displays.last() {
@@ -694,59 +821,111 @@ fn set_monitor_resolution_windows(
     height: u32,
     refresh_hz: Option<u32>,
 ) -> Result<(), String> {
+    let request = crate::mode_selection::ModeRequest {
+        width,
+        height,
+        refresh_rate: refresh_hz.map(|hz| hz as f64),
+        policy: crate::mode_selection::ModePolicy::Exact,
+        interlaced: false,
+    };
+    set_monitor_mode_windows(&device_name, &request).map(|_| ())
+}
+
+fn devmode_resolution(dm: &windows::Win32::Graphics::Gdi::DEVMODEW) -> Resolution {
+    use windows::Win32::Graphics::Gdi::{DMDFO_DEFAULT, DM_INTERLACED};
+
+    let (display_flags, fixed_output) = unsafe {
+        (
+            dm.Anonymous2.dmDisplayFlags,
+            dm.Anonymous1.Anonymous2.dmDisplayFixedOutput,
+        )
+    };
+    Resolution {
+        width: dm.dmPelsWidth,
+        height: dm.dmPelsHeight,
+        bits_per_pixel: dm.dmBitsPerPel,
+        refresh_hz: dm.dmDisplayFrequency,
+        refresh_rate: crate::mode_selection::nominal_refresh_rate(dm.dmDisplayFrequency),
+        interlaced: (display_flags & DM_INTERLACED.0) != 0,
+        scaled: fixed_output != DMDFO_DEFAULT,
+    }
+}
+
+// Every mode EnumDisplaySettingsExW reports for the device, with the DEVMODE to apply it.
+// `raw` adds modes the monitor doesn't advertise in its EDID (EDS_RAWMODE).
+fn enum_display_modes(
+    device_name: &str,
+    raw: bool,
+) -> Vec<(Resolution, windows::Win32::Graphics::Gdi::DEVMODEW)> {
     use std::mem::{size_of, zeroed};
     use windows::Win32::Foundation::BOOL;
     use windows::Win32::Graphics::Gdi::{
-        ChangeDisplaySettingsExW, EnumDisplaySettingsExW, DEVMODEW, DISP_CHANGE_SUCCESSFUL,
+        EnumDisplaySettingsExW, DEVMODEW, EDS_RAWMODE, ENUM_DISPLAY_SETTINGS_FLAGS,
+        ENUM_DISPLAY_SETTINGS_MODE,
     };
 
+    let flags = if raw {
+        EDS_RAWMODE
+    } else {
+        ENUM_DISPLAY_SETTINGS_FLAGS(0)
+    };
+    let wide = to_wide_null_terminated(device_name);
+    let mut modes = Vec::new();
     let mut mode_index: u32 = 0;
-    let mut chosen: Option<DEVMODEW> = None;
     loop {
         let mut dm: DEVMODEW = unsafe { zeroed() };
         dm.dmSize = size_of::<DEVMODEW>() as u16;
-        let wide = to_wide_null_terminated(&device_name);
         let ok: BOOL = unsafe {
             EnumDisplaySettingsExW(
                 windows::core::PCWSTR(wide.as_ptr()),
-                windows::Win32::Graphics::Gdi::ENUM_DISPLAY_SETTINGS_MODE(mode_index),
+                ENUM_DISPLAY_SETTINGS_MODE(mode_index),
                 &mut dm,
-                windows::Win32::Graphics::Gdi::ENUM_DISPLAY_SETTINGS_FLAGS(0),
+                flags,
             )
         };
         if !ok.as_bool() {
             break;
         }
-
-        let w = dm.dmPelsWidth;
-        let h = dm.dmPelsHeight;
-        let hz = dm.dmDisplayFrequency;
-        if w == width && h == height {
-            if let Some(target_hz) = refresh_hz {
-                if hz == target_hz {
-                    chosen = Some(dm);
-                    break;
-                }
-            } else {
-                chosen = match chosen {
-                    None => Some(dm),
-                    Some(prev) => {
-                        if dm.dmDisplayFrequency > prev.dmDisplayFrequency {
-                            Some(dm)
-                        } else {
-                            Some(prev)
-                        }
-                    }
-                };
-            }
-        }
-
+        modes.push((devmode_resolution(&dm), dm));
         mode_index += 1;
     }
+    modes
+}
 
-    let mut devmode = chosen.ok_or_else(|| "Requested resolution not supported".to_string())?;
+fn set_monitor_mode_windows(
+    device_name: &str,
+    request: &crate::mode_selection::ModeRequest,
+) -> Result<Resolution, String> {
+    use windows::Win32::Graphics::Gdi::{ChangeDisplaySettingsExW, DISP_CHANGE_SUCCESSFUL};
+
+    // Raw modes are only considered when the request can't be met from the advertised ones
+    let mut modes = enum_display_modes(device_name, false);
+    let list: Vec<Resolution> = modes.iter().map(|(m, _)| m.clone()).collect();
+    let native = query_preferred_native_resolution(device_name);
+    let index = match crate::mode_selection::select_mode(&list, request, native) {
+        Some(index) => index,
+        None => {
+            modes = enum_display_modes(device_name, true);
+            let list: Vec<Resolution> = modes.iter().map(|(m, _)| m.clone()).collect();
+            crate::mode_selection::select_mode(&list, request, native)
+                .ok_or_else(|| "Requested resolution not supported".to_string())?
+        }
+    };
+    let (chosen, mut devmode) = modes.swap_remove(index);
+    log::info!(
+        "Setting {} to {}x{} @ {} Hz{}",
+        device_name,
+        chosen.width,
+        chosen.height,
+        chosen.refresh_rate,
+        if chosen.interlaced {
+            " (interlaced)"
+        } else {
+            ""
+        }
+    );
 
-    let wide = to_wide_null_terminated(&device_name);
+    let wide = to_wide_null_terminated(device_name);
     let status = unsafe {
         ChangeDisplaySettingsExW(
             windows::core::PCWSTR(wide.as_ptr()),
@@ -758,7 +937,7 @@ fn set_monitor_resolution_windows(
     };
 
     if status == DISP_CHANGE_SUCCESSFUL {
-        Ok(())
+        Ok(chosen)
     } else {
         Err(format!(
             "ChangeDisplaySettingsExW failed with code: {:?}",
@@ -774,8 +953,9 @@ fn set_monitor_orientation_windows(
     use std::mem::{size_of, zeroed};
     use windows::Win32::Foundation::BOOL;
     use windows::Win32::Graphics::Gdi::{
-        ChangeDisplaySettingsExW, EnumDisplaySettingsExW, DEVMODEW, DISP_CHANGE_SUCCESSFUL,
-        DM_DISPLAYORIENTATION, DM_PELSHEIGHT, DM_PELSWIDTH, CDS_UPDATEREGISTRY,
+        ChangeDisplaySettingsExW, EnumDisplaySettingsExW, CDS_NORESET, CDS_TYPE,
+        CDS_UPDATEREGISTRY, DEVMODEW, DISP_CHANGE_SUCCESSFUL, DM_DISPLAYORIENTATION, DM_PELSHEIGHT,
+        DM_PELSWIDTH, DM_POSITION,
     };
 
     let mut dm: DEVMODEW = unsafe { zeroed() };
@@ -833,16 +1013,77 @@ fn set_monitor_orientation_windows(
     // Ensure the fields are marked as valid
     dm.dmFields |= DM_DISPLAYORIENTATION | DM_PELSWIDTH | DM_PELSHEIGHT;
 
+    // Rotating changes the monitor's footprint; move the neighbours along so the desktop keeps
+    // its arrangement. Every change is staged with CDS_NORESET and applied together at the end.
+    let layout = current_desktop_layout();
+    let moved = layout
+        .iter()
+        .position(|(name, _)| name.eq_ignore_ascii_case(&device_name))
+        .map(|index| {
+            let rects: Vec<_> = layout.iter().map(|(_, rect)| *rect).collect();
+            let primary = primary_monitor_name_windows()
+                .and_then(|primary| {
+                    layout
+                        .iter()
+                        .position(|(name, _)| name.eq_ignore_ascii_case(&primary))
+                })
+                .unwrap_or(index);
+            let fixed = crate::layout::resize_in_layout(
+                &rects,
+                index,
+                primary,
+                (dm.dmPelsWidth, dm.dmPelsHeight),
+            );
+            (index, fixed)
+        });
+
+    if let Some((index, fixed)) = &moved {
+        unsafe {
+            dm.Anonymous1.Anonymous2.dmPosition.x = fixed[*index].x;
+            dm.Anonymous1.Anonymous2.dmPosition.y = fixed[*index].y;
+        }
+        dm.dmFields |= DM_POSITION;
+    }
+
     let status = unsafe {
         ChangeDisplaySettingsExW(
             windows::core::PCWSTR(wide.as_ptr()),
             Some(&mut dm),
             None,
-            CDS_UPDATEREGISTRY,
+            CDS_UPDATEREGISTRY | CDS_NORESET,
             None,
         )
     };
+    if status != DISP_CHANGE_SUCCESSFUL {
+        return Err(format!(
+            "ChangeDisplaySettingsExW failed with code: {:?}",
+            status
+        ));
+    }
+
+    if let Some((index, fixed)) = &moved {
+        for (i, ((name, old), new)) in layout.iter().zip(fixed).enumerate() {
+            if i == *index || old == new {
+                continue;
+            }
+            log::info!(
+                "Moving {} from ({}, {}) to ({}, {}) after rotating {}",
+                name,
+                old.x,
+                old.y,
+                new.x,
+                new.y,
+                device_name
+            );
+            if let Err(e) = stage_monitor_position(name, new.x, new.y) {
+                log::warn!("{}", e);
+            }
+        }
+    }
 
+    let status = unsafe {
+        ChangeDisplaySettingsExW(windows::core::PCWSTR::null(), None, None, CDS_TYPE(0), None)
+    };
     if status == DISP_CHANGE_SUCCESSFUL {
         Ok(())
     } else {
@@ -853,6 +1094,99 @@ fn set_monitor_orientation_windows(
     }
 }
 
+// GDI device name of the primary monitor
+fn primary_monitor_name_windows() -> Option<String> {
+    use std::mem::{size_of, zeroed};
+    use windows::Win32::Graphics::Gdi::{
+        EnumDisplayDevicesW, DISPLAY_DEVICEW, DISPLAY_DEVICE_PRIMARY_DEVICE,
+    };
+
+    let mut device_index: u32 = 0;
+    loop {
+        let mut dd: DISPLAY_DEVICEW = unsafe { zeroed() };
+        dd.cb = size_of::<DISPLAY_DEVICEW>() as u32;
+        if !unsafe { EnumDisplayDevicesW(None, device_index, &mut dd, 0) }.as_bool() {
+            return None;
+        }
+        if dd.StateFlags & DISPLAY_DEVICE_PRIMARY_DEVICE != 0 {
+            return Some(widestr_to_string(&dd.DeviceName));
+        }
+        device_index += 1;
+    }
+}
+
+// Desktop rectangle of every attached monitor, by GDI device name
+fn current_desktop_layout() -> Vec<(String, crate::layout::MonitorRect)> {
+    use std::mem::{size_of, zeroed};
+    use windows::Win32::Graphics::Gdi::{EnumDisplaySettingsExW, DEVMODEW};
+
+    get_all_monitor_names_windows()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|name| {
+            let mut dm: DEVMODEW = unsafe { zeroed() };
+            dm.dmSize = size_of::<DEVMODEW>() as u16;
+            let wide = to_wide_null_terminated(&name);
+            let ok = unsafe {
+                EnumDisplaySettingsExW(
+                    windows::core::PCWSTR(wide.as_ptr()),
+                    windows::Win32::Graphics::Gdi::ENUM_CURRENT_SETTINGS,
+                    &mut dm,
+                    windows::Win32::Graphics::Gdi::ENUM_DISPLAY_SETTINGS_FLAGS(0),
+                )
+            };
+            if !ok.as_bool() {
+                return None;
+            }
+            let position = unsafe { dm.Anonymous1.Anonymous2.dmPosition };
+            Some((
+                name,
+                crate::layout::MonitorRect {
+                    x: position.x,
+                    y: position.y,
+                    width: dm.dmPelsWidth,
+                    height: dm.dmPelsHeight,
+                },
+            ))
+        })
+        .collect()
+}
+
+// Queues a position change; it takes effect with the next global ChangeDisplaySettingsExW
+fn stage_monitor_position(device_name: &str, x: i32, y: i32) -> Result<(), String> {
+    use std::mem::{size_of, zeroed};
+    use windows::Win32::Graphics::Gdi::{
+        ChangeDisplaySettingsExW, CDS_NORESET, CDS_UPDATEREGISTRY, DEVMODEW,
+        DISP_CHANGE_SUCCESSFUL, DM_POSITION,
+    };
+
+    let mut dm: DEVMODEW = unsafe { zeroed() };
+    dm.dmSize = size_of::<DEVMODEW>() as u16;
+    dm.dmFields = DM_POSITION;
+    unsafe {
+        dm.Anonymous1.Anonymous2.dmPosition.x = x;
+        dm.Anonymous1.Anonymous2.dmPosition.y = y;
+    }
+    let wide = to_wide_null_terminated(device_name);
+    let status = unsafe {
+        ChangeDisplaySettingsExW(
+            windows::core::PCWSTR(wide.as_ptr()),
+            Some(&dm),
+            None,
+            CDS_UPDATEREGISTRY | CDS_NORESET,
+            None,
+        )
+    };
+    if status == DISP_CHANGE_SUCCESSFUL {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to move {} to ({}, {}): {:?}",
+            device_name, x, y, status
+        ))
+    }
+}
+
 fn find_hmonitor_by_device_name(
     device_name: &str,
 ) -> Option<windows::Win32::Graphics::Gdi::HMONITOR> {
@@ -976,19 +1310,27 @@ fn read_monitor_brightness_range(device_name: &str) -> Result<(u32, u32, u32), S
 
 fn get_monitor_brightness_windows(device_name: String) -> Result<BrightnessInfo, String> {
     match read_monitor_brightness_range(&device_name) {
-        Ok((min, current, max)) => Ok(BrightnessInfo { min, current, max }),
+        Ok((min, current, max)) => Ok(BrightnessInfo {
+            min,
+            current,
+            max,
+            source: "ddc".to_string(),
+        }),
         Err(_e) => {
             // Fallback: WMI (internal displays)
-            if let Some(b) = wmi_get_brightness_via_powershell() {
+            if let Some(b) = wmi_get_brightness_via_powershell(&device_name) {
                 Ok(b)
             } else {
-                log::warn!(
-                    "Failed to get brightness via DDC/CI and WMI fallback - returning dummy value"
+                // Last resort: software brightness via the gamma ramp
+                log::debug!(
+                    "No DDC/CI or WMI brightness for '{}' - using gamma ramp",
+                    device_name
                 );
                 Ok(BrightnessInfo {
                     min: 0,
-                    current: 50,
+                    current: get_gamma_state(&device_name).brightness,
                     max: 100,
+                    source: "gamma".to_string(),
                 })
             }
         }
@@ -1001,10 +1343,10 @@ fn set_monitor_brightness_windows(device_name: String, percent: u32) -> Result<(
     let pct = percent.min(100);
     let (min, max) = match get_cached_brightness_range(&device_name) {
         Some((min, max)) if max >= min => (min, max),
-        _ => {
-            let (min, _cur, max) = read_monitor_brightness_range(&device_name)?;
-            (min, max)
-        }
+        _ => match read_monitor_brightness_range(&device_name) {
+            Ok((min, _cur, max)) => (min, max),
+            Err(_e) => return set_brightness_fallback(&device_name, pct),
+        },
     };
     let span = max - min;
     let value = min + ((span as u64 * pct as u64 + 50) / 100) as u32;
@@ -1017,117 +1359,609 @@ fn set_monitor_brightness_windows(device_name: String, percent: u32) -> Result<(
         Ok(())
     }) {
         Ok(()) => Ok(()),
-        Err(_e) => {
-            // Fallback to WMI (usually internal panel only)
-            if wmi_set_brightness_via_powershell(pct) {
-                Ok(())
-            } else {
-                Err("Failed to set brightness via DDC/CI and WMI fallback".to_string())
-            }
-        }
+        Err(_e) => set_brightness_fallback(&device_name, pct),
     }
 }
 
-fn set_monitor_input_source_windows(device_name: String, input: String) -> Result<(), String> {
-    // DDC/CI VCP code 0x60 selects input source. Values are VESA defined, common ones below.
-    // We'll accept a human string and map to code, unknown => parse as number.
-    fn map_input_to_code(s: &str) -> Option<u32> {
-        let k = s.trim().to_ascii_lowercase();
-        match k.as_str() {
-            // VGA / Analog
-            "vga" | "vga1" | "01" => Some(0x01),
-            "vga2" | "02" => Some(0x02),
-            // DVI
-            "dvi" | "dvi1" | "03" => Some(0x03),
-            "dvi2" | "04" => Some(0x04),
-            // DisplayPort
-            "displayport" | "dp" | "dp1" | "0F" => Some(0x0F),
-            "dp2" | "10" => Some(0x10),
-            // HDMI
-            "hdmi" | "hdmi1" | "11" => Some(0x11),
-            "hdmi2" | "12" => Some(0x12),
-            "hdmi3" | "13" => Some(0x13),
-            // USB-C / Thunderbolt (often DP Alt-Mode; map to DP codes)
-            "usbc" | "usb-c" | "tb" | "thunderbolt" | "usbc1" | "19" => Some(0x19),
-            "usbc2" | "1A" => Some(0x1A),
-            "usbc3" | "1B" => Some(0x1B),
-            "usbc4" | "31" => Some(0x31),
-            // LG alternative codes
-            "dp1_lg" | "D0" => Some(0xD0),
-            "dp2_usbc_lg" | "D1" => Some(0xD1),
-            "usbc_lg" | "D2" => Some(0xD2),
-            "hdmi1_lg" | "90" => Some(0x90),
-            "hdmi2_lg" | "91" => Some(0x91),
-            // Legacy sources
-            "component" | "component1" | "0C" => Some(0x0C),
-            "component2" | "0D" => Some(0x0D),
-            "component3" | "0E" => Some(0x0E),
-            "composite" | "composite1" | "05" => Some(0x05),
-            "composite2" | "06" => Some(0x06),
-            "s-video" | "svideo1" | "07" => Some(0x07),
-            "svideo2" | "08" => Some(0x08),
-            // Tuner
-            "tuner" | "tuner1" | "09" => Some(0x09),
-            "tuner2" | "0A" => Some(0x0A),
-            "tuner3" | "0B" => Some(0x0B),
-            _ => {
-                // try hex or decimal
-                if let Ok(v) = u32::from_str_radix(k.trim_start_matches("0x"), 16) {
-                    Some(v)
-                } else if let Ok(v) = k.parse::<u32>() {
-                    Some(v)
-                } else {
-                    None
-                }
-            }
-        }
+fn set_brightness_fallback(device_name: &str, pct: u32) -> Result<(), String> {
+    // Fallback to WMI (usually internal panel only)
+    if wmi_set_brightness_via_powershell(device_name, pct) {
+        return Ok(());
     }
-
-    let code = map_input_to_code(&input).ok_or_else(|| "Unknown input label".to_string())?;
-
-    use windows::Win32::Devices::Display::SetVCPFeature;
-    with_first_physical_monitor(&device_name, |pm| {
-        let ok = unsafe { SetVCPFeature(pm.hPhysicalMonitor, 0x60, code) };
-        if ok == 0 {
-            return Err(
-                "SetVCPFeature(0x60) failed (monitor may not support input switching via DDC/CI)"
-                    .to_string(),
-            );
-        }
-        // Verify change was applied
-        use windows::Win32::Devices::Display::{GetVCPFeatureAndVCPFeatureReply, MC_VCP_CODE_TYPE};
-        let mut feature_type: MC_VCP_CODE_TYPE = MC_VCP_CODE_TYPE(0);
-        let mut current_value: u32 = 0;
-        let mut max_value: u32 = 0;
-        let ok2 = unsafe {
-            GetVCPFeatureAndVCPFeatureReply(
-                pm.hPhysicalMonitor,
-                0x60,
-                Some(&mut feature_type),
-                &mut current_value,
-                Some(&mut max_value),
-            )
-        };
-        if ok2 == 0 {
-            log::warn!("Failed to verify input switch (GetVCPFeature) - assuming success");
-            return Ok(());
-        }
-        let read_code = current_value & 0xFF;
-        if read_code != code {
-            log::error!(
-                "Monitor ignored input change (DDC/CI 2): {} != {}",
-                read_code,
-                code
-            );
-            return Err(format!(
-                "Monitor ignored input change, it might not support DDC/CI or connection method is not supported",
-            ));
-        }
-        Ok(())
+    // Last resort: scale the gamma ramp
+    update_gamma_state(device_name, |state| state.brightness = pct).map_err(|e| {
+        format!(
+            "Failed to set brightness via DDC/CI, WMI and gamma ramp fallbacks: {}",
+            e
+        )
     })
 }
 
-fn get_monitor_input_source_windows(device_name: String) -> Result<String, String> {
+fn gamma_states() -> &'static Mutex<HashMap<String, crate::gamma_ramp::GammaState>> {
+    static STATES: OnceLock<Mutex<HashMap<String, crate::gamma_ramp::GammaState>>> =
+        OnceLock::new();
+    STATES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// What this process last applied; otherwise read back from the ramp on the device, so a CLI run
+// sees the dimming the app left behind
+fn get_gamma_state(device_name: &str) -> crate::gamma_ramp::GammaState {
+    gamma_states()
+        .lock()
+        .ok()
+        .and_then(|states| states.get(device_name).copied())
+        .or_else(|| {
+            read_gamma_ramp(device_name)
+                .ok()
+                .and_then(|ramp| crate::gamma_ramp::state_from_ramp(&ramp))
+        })
+        .unwrap_or_default()
+}
+
+// GdiIcmGammaRange decides how far a ramp may stray from identity (see gamma_ramp)
+fn gamma_range() -> u32 {
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_DWORD};
+
+    let key = to_wide_null_terminated(r"SOFTWARE\Microsoft\Windows NT\CurrentVersion\ICM");
+    let value = to_wide_null_terminated("GdiIcmGammaRange");
+    let mut data: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let rc = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            windows::core::PCWSTR(key.as_ptr()),
+            windows::core::PCWSTR(value.as_ptr()),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut data as *mut u32 as *mut core::ffi::c_void),
+            Some(&mut size),
+        )
+    };
+    if rc.is_ok() && data > 0 {
+        data
+    } else {
+        crate::gamma_ramp::DEFAULT_GAMMA_RANGE
+    }
+}
+
+// Applies `f` to the monitor's software gamma state and uploads the resulting ramp
+fn update_gamma_state<F: FnOnce(&mut crate::gamma_ramp::GammaState)>(
+    device_name: &str,
+    f: F,
+) -> Result<(), String> {
+    let mut state = get_gamma_state(device_name);
+    f(&mut state);
+    let levels = state.channel_levels();
+    let range = gamma_range();
+    let min_level = crate::gamma_ramp::min_level_for_range(range);
+    if levels.iter().any(|&level| level < min_level) {
+        log::debug!(
+            "Gamma ramp for '{}' limited to {:.0}% output (GdiIcmGammaRange {})",
+            device_name,
+            min_level * 100.0,
+            range
+        );
+    }
+    apply_gamma_ramp(
+        device_name,
+        &crate::gamma_ramp::build_ramp_within(levels, range),
+    )?;
+    if let Ok(mut states) = gamma_states().lock() {
+        states.insert(device_name.to_string(), state);
+    }
+    Ok(())
+}
+
+// Runs `f` with a device context for the display
+fn with_display_dc<T, F: FnOnce(windows::Win32::Graphics::Gdi::HDC) -> Result<T, String>>(
+    device_name: &str,
+    f: F,
+) -> Result<T, String> {
+    use windows::Win32::Graphics::Gdi::{CreateDCW, DeleteDC};
+
+    let wide = to_wide_null_terminated(device_name);
+    let hdc = unsafe {
+        CreateDCW(
+            windows::core::PCWSTR(wide.as_ptr()),
+            windows::core::PCWSTR(wide.as_ptr()),
+            None,
+            None,
+        )
+    };
+    if hdc.is_invalid() {
+        return Err("CreateDCW failed for gamma ramp".to_string());
+    }
+    let result = f(hdc);
+    unsafe { DeleteDC(hdc) };
+    result
+}
+
+fn apply_gamma_ramp(device_name: &str, ramp: &crate::gamma_ramp::GammaRamp) -> Result<(), String> {
+    use windows::Win32::UI::ColorSystem::SetDeviceGammaRamp;
+
+    with_display_dc(device_name, |hdc| {
+        let ok = unsafe { SetDeviceGammaRamp(hdc, ramp.as_ptr() as *const core::ffi::c_void) };
+        if !ok.as_bool() {
+            // Ramps are built within GdiIcmGammaRange, so this is a driver refusing gamma
+            return Err("SetDeviceGammaRamp rejected the ramp".to_string());
+        }
+        Ok(())
+    })
+}
+
+fn read_gamma_ramp(device_name: &str) -> Result<Box<crate::gamma_ramp::GammaRamp>, String> {
+    use windows::Win32::UI::ColorSystem::GetDeviceGammaRamp;
+
+    with_display_dc(device_name, |hdc| {
+        let mut ramp: Box<crate::gamma_ramp::GammaRamp> = Box::new([[0u16; 256]; 3]);
+        let ok = unsafe { GetDeviceGammaRamp(hdc, ramp.as_mut_ptr() as *mut core::ffi::c_void) };
+        if !ok.as_bool() {
+            return Err("GetDeviceGammaRamp failed".to_string());
+        }
+        Ok(ramp)
+    })
+}
+
+// Reads a continuous VCP feature, returning (current, max)
+fn read_vcp_feature(
+    pm: &windows::Win32::Devices::Display::PHYSICAL_MONITOR,
+    code: u8,
+) -> Option<(u32, u32)> {
+    use windows::Win32::Devices::Display::{GetVCPFeatureAndVCPFeatureReply, MC_VCP_CODE_TYPE};
+    let mut feature_type: MC_VCP_CODE_TYPE = MC_VCP_CODE_TYPE(0);
+    let mut current_value: u32 = 0;
+    let mut max_value: u32 = 0;
+    let ok = unsafe {
+        GetVCPFeatureAndVCPFeatureReply(
+            pm.hPhysicalMonitor,
+            code,
+            Some(&mut feature_type),
+            &mut current_value,
+            Some(&mut max_value),
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+    Some((current_value, max_value))
+}
+
+// Last color temperature applied per monitor; gamma and RGB gains can't be read back as Kelvin
+fn color_temperatures() -> &'static Mutex<HashMap<String, ColorTemperatureInfo>> {
+    static TEMPS: OnceLock<Mutex<HashMap<String, ColorTemperatureInfo>>> = OnceLock::new();
+    TEMPS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Tries VCP 0x0C (needs >= 3000K), then the RGB video gains. Returns the source used.
+fn set_color_temperature_ddc(device_name: &str, kelvin: u32) -> Result<&'static str, String> {
+    use crate::color_temperature::{kelvin_to_vcp_color_temp, kelvin_to_vcp_gains};
+    use windows::Win32::Devices::Display::SetVCPFeature;
+
+    with_first_physical_monitor(device_name, |pm| {
+        if kelvin >= crate::color_temperature::VCP_COLOR_TEMP_BASE_KELVIN {
+            // VCP 0x0B reports the Kelvin step size used by 0x0C
+            if let Some((increment, _)) = read_vcp_feature(&pm, 0x0B) {
+                if increment > 0 && read_vcp_feature(&pm, 0x0C).is_some() {
+                    let value = kelvin_to_vcp_color_temp(kelvin, increment);
+                    if unsafe { SetVCPFeature(pm.hPhysicalMonitor, 0x0C, value) } != 0 {
+                        return Ok("ddc");
+                    }
+                }
+            }
+        }
+
+        let (Some((_, red_max)), Some((_, green_max)), Some((_, blue_max))) = (
+            read_vcp_feature(&pm, 0x16),
+            read_vcp_feature(&pm, 0x18),
+            read_vcp_feature(&pm, 0x1A),
+        ) else {
+            return Err(
+                "Monitor does not expose DDC/CI color temperature or RGB gains".to_string(),
+            );
+        };
+        let gains = kelvin_to_vcp_gains(kelvin, [red_max, green_max, blue_max]);
+        for (code, value) in [(0x16u8, gains[0]), (0x18, gains[1]), (0x1A, gains[2])] {
+            if unsafe { SetVCPFeature(pm.hPhysicalMonitor, code, value) } == 0 {
+                return Err(format!("SetVCPFeature(0x{:02X}) failed", code));
+            }
+        }
+        Ok("ddc_gain")
+    })
+}
+
+fn set_monitor_color_temperature_windows(
+    device_name: &str,
+    kelvin: u32,
+) -> Result<ColorTemperatureInfo, String> {
+    use crate::color_temperature::{white_balance, MAX_KELVIN, MIN_KELVIN};
+
+    let kelvin = kelvin.clamp(MIN_KELVIN, MAX_KELVIN);
+    let source = match set_color_temperature_ddc(device_name, kelvin) {
+        Ok(source) => {
+            // Drop any software tint left over from an earlier gamma fallback
+            if get_gamma_state(device_name).rgb != [1.0, 1.0, 1.0] {
+                let _ = update_gamma_state(device_name, |state| state.rgb = [1.0, 1.0, 1.0]);
+            }
+            source
+        }
+        Err(e) => {
+            log::debug!(
+                "DDC/CI color temperature unavailable for '{}' ({}) - using gamma ramp",
+                device_name,
+                e
+            );
+            update_gamma_state(device_name, |state| state.rgb = white_balance(kelvin))
+                .map_err(|e| format!("Failed to set color temperature: {}", e))?;
+            "gamma"
+        }
+    };
+
+    let info = ColorTemperatureInfo {
+        kelvin,
+        source: source.to_string(),
+    };
+    if let Ok(mut temps) = color_temperatures().lock() {
+        temps.insert(device_name.to_string(), info.clone());
+    }
+    Ok(info)
+}
+
+fn get_monitor_color_temperature_windows(
+    device_name: &str,
+) -> Result<ColorTemperatureInfo, String> {
+    if let Some(info) = color_temperatures()
+        .lock()
+        .ok()
+        .and_then(|temps| temps.get(device_name).cloned())
+    {
+        return Ok(info);
+    }
+    // Nothing applied by us yet: report the monitor's own setting when it exposes one
+    let from_ddc = with_first_physical_monitor(device_name, |pm| {
+        match (read_vcp_feature(&pm, 0x0B), read_vcp_feature(&pm, 0x0C)) {
+            (Some((increment, _)), Some((value, _))) if increment > 0 => Ok(
+                crate::color_temperature::vcp_color_temp_to_kelvin(value, increment),
+            ),
+            _ => Err("VCP 0x0C unsupported".to_string()),
+        }
+    });
+    Ok(match from_ddc {
+        Ok(kelvin) => ColorTemperatureInfo {
+            kelvin,
+            source: "ddc".to_string(),
+        },
+        Err(_) => ColorTemperatureInfo {
+            kelvin: crate::color_temperature::NEUTRAL_KELVIN,
+            source: "gamma".to_string(),
+        },
+    })
+}
+
+// WCS device class for monitors ('mntr')
+const CLASS_MONITOR: u32 = 0x6D6E_7472;
+
+// Registry key of the monitor attached to `device_name`, which is how WCS identifies displays
+fn monitor_device_key(device_name: &str) -> Option<String> {
+    use std::mem::{size_of, zeroed};
+    use windows::Win32::Graphics::Gdi::{EnumDisplayDevicesW, DISPLAY_DEVICEW};
+
+    let wide = to_wide_null_terminated(device_name);
+    let mut mon: DISPLAY_DEVICEW = unsafe { zeroed() };
+    mon.cb = size_of::<DISPLAY_DEVICEW>() as u32;
+    let ok = unsafe { EnumDisplayDevicesW(windows::core::PCWSTR(wide.as_ptr()), 0, &mut mon, 0) };
+    if !ok.as_bool() {
+        return None;
+    }
+    let key = widestr_to_string(&mon.DeviceKey);
+    if key.is_empty() {
+        None
+    } else {
+        Some(key)
+    }
+}
+
+fn color_directory() -> Result<std::path::PathBuf, String> {
+    use windows::Win32::UI::ColorSystem::GetColorDirectoryW;
+
+    // Size is reported in bytes
+    let mut size: u32 = 0;
+    let _ = unsafe {
+        GetColorDirectoryW(
+            windows::core::PCWSTR::null(),
+            windows::core::PWSTR::null(),
+            &mut size,
+        )
+    };
+    if size == 0 {
+        return Err("GetColorDirectoryW failed".to_string());
+    }
+    let mut buf: Vec<u16> = vec![0; size as usize / 2 + 1];
+    let ok = unsafe {
+        GetColorDirectoryW(
+            windows::core::PCWSTR::null(),
+            windows::core::PWSTR(buf.as_mut_ptr()),
+            &mut size,
+        )
+    };
+    if !ok.as_bool() {
+        return Err("GetColorDirectoryW failed".to_string());
+    }
+    Ok(std::path::PathBuf::from(widestr_to_string(&buf)))
+}
+
+// WCS may hand back full paths; we always report bare file names
+fn profile_file_name(name: &str) -> String {
+    std::path::Path::new(name)
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.to_string())
+}
+
+// Monitor-class ICC profiles, all installed ones or only those associated with `device_key`
+fn enum_color_profiles(device_key: Option<&str>) -> Result<Vec<String>, String> {
+    use std::mem::{size_of, zeroed};
+    use windows::Win32::UI::ColorSystem::{
+        WcsEnumColorProfiles, WcsEnumColorProfilesSize, ENUMTYPEW, ENUM_TYPE_VERSION,
+        ET_DEVICECLASS, ET_DEVICENAME, WCS_PROFILE_MANAGEMENT_SCOPE_CURRENT_USER,
+    };
+
+    let wide_key = device_key.map(to_wide_null_terminated);
+    let mut filter: ENUMTYPEW = unsafe { zeroed() };
+    filter.dwSize = size_of::<ENUMTYPEW>() as u32;
+    filter.dwVersion = ENUM_TYPE_VERSION;
+    filter.dwFields = ET_DEVICECLASS;
+    filter.dwDeviceClass = CLASS_MONITOR;
+    if let Some(key) = &wide_key {
+        filter.dwFields |= ET_DEVICENAME;
+        filter.pDeviceName = windows::core::PCWSTR(key.as_ptr());
+    }
+
+    let mut size: u32 = 0;
+    let ok = unsafe {
+        WcsEnumColorProfilesSize(
+            WCS_PROFILE_MANAGEMENT_SCOPE_CURRENT_USER,
+            &filter,
+            &mut size,
+        )
+    };
+    if !ok.as_bool() {
+        return Err("WcsEnumColorProfilesSize failed".to_string());
+    }
+    if size == 0 {
+        return Ok(Vec::new());
+    }
+    let mut buf: Vec<u8> = vec![0; size as usize];
+    let mut count: u32 = 0;
+    let ok = unsafe {
+        WcsEnumColorProfiles(
+            WCS_PROFILE_MANAGEMENT_SCOPE_CURRENT_USER,
+            &filter,
+            &mut buf,
+            Some(&mut count),
+        )
+    };
+    if !ok.as_bool() {
+        return Err("WcsEnumColorProfiles failed".to_string());
+    }
+    // Double-null-terminated list of UTF-16 names
+    let units: Vec<u16> = buf
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    Ok(units
+        .split(|&c| c == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| profile_file_name(&String::from_utf16_lossy(name)))
+        .collect())
+}
+
+fn get_default_color_profile_windows(device_name: &str) -> Result<Option<String>, String> {
+    use windows::Win32::UI::ColorSystem::{
+        WcsGetDefaultColorProfile, WcsGetDefaultColorProfileSize, CPST_NONE, CPT_ICC,
+        WCS_PROFILE_MANAGEMENT_SCOPE_CURRENT_USER,
+    };
+
+    let key =
+        monitor_device_key(device_name).ok_or_else(|| "Monitor device not found".to_string())?;
+    let wide_key = to_wide_null_terminated(&key);
+
+    // Fails when nothing is associated, in which case Windows falls back to sRGB
+    let mut size: u32 = 0;
+    let ok = unsafe {
+        WcsGetDefaultColorProfileSize(
+            WCS_PROFILE_MANAGEMENT_SCOPE_CURRENT_USER,
+            windows::core::PCWSTR(wide_key.as_ptr()),
+            CPT_ICC,
+            CPST_NONE,
+            0,
+            &mut size,
+        )
+    };
+    if !ok.as_bool() || size == 0 {
+        return Ok(None);
+    }
+    let mut buf: Vec<u16> = vec![0; size as usize / 2 + 1];
+    let ok = unsafe {
+        WcsGetDefaultColorProfile(
+            WCS_PROFILE_MANAGEMENT_SCOPE_CURRENT_USER,
+            windows::core::PCWSTR(wide_key.as_ptr()),
+            CPT_ICC,
+            CPST_NONE,
+            0,
+            size,
+            windows::core::PWSTR(buf.as_mut_ptr()),
+        )
+    };
+    if !ok.as_bool() {
+        return Ok(None);
+    }
+    let name = widestr_to_string(&buf);
+    Ok(if name.is_empty() {
+        None
+    } else {
+        Some(profile_file_name(&name))
+    })
+}
+
+fn list_color_profiles_windows(device_name: &str) -> Result<Vec<ColorProfileInfo>, String> {
+    let key =
+        monitor_device_key(device_name).ok_or_else(|| "Monitor device not found".to_string())?;
+    let installed = enum_color_profiles(None)?;
+    // Enumeration by device fails outright when the monitor has no associations
+    let associated = enum_color_profiles(Some(&key)).unwrap_or_default();
+    let default = get_default_color_profile_windows(device_name)?;
+    let dir = color_directory()?;
+
+    let mut names = installed;
+    for name in &associated {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let parsed = std::fs::read(dir.join(&name))
+                .map_err(|e| e.to_string())
+                .and_then(|data| crate::icc::parse_profile(&data));
+            if let Err(e) = &parsed {
+                log::debug!("Failed to parse color profile '{}': {}", name, e);
+            }
+            let parsed = parsed.ok();
+            ColorProfileInfo {
+                description: parsed
+                    .as_ref()
+                    .map(|p| p.description.clone())
+                    .filter(|d| !d.is_empty())
+                    .unwrap_or_else(|| name.clone()),
+                associated: associated.contains(&name),
+                is_default: default.as_deref() == Some(name.as_str()),
+                white_point: parsed.as_ref().and_then(|p| p.white_point),
+                gamut: parsed.as_ref().and_then(|p| p.gamut),
+                name,
+            }
+        })
+        .collect())
+}
+
+fn set_default_color_profile_windows(device_name: &str, profile: &str) -> Result<(), String> {
+    use windows::Win32::Foundation::TRUE;
+    use windows::Win32::UI::ColorSystem::{
+        InstallColorProfileW, WcsAssociateColorProfileWithDevice, WcsSetDefaultColorProfile,
+        WcsSetUsePerUserProfiles, CPST_NONE, CPT_ICC, WCS_PROFILE_MANAGEMENT_SCOPE_CURRENT_USER,
+    };
+
+    let key =
+        monitor_device_key(device_name).ok_or_else(|| "Monitor device not found".to_string())?;
+    let wide_key = to_wide_null_terminated(&key);
+
+    // A path to a profile outside the color directory (e.g. fresh from a calibrator) is installed first
+    let source = std::path::Path::new(profile);
+    let name = profile_file_name(profile);
+    if source.is_absolute() && source.exists() {
+        let wide_path = to_wide_null_terminated(profile);
+        let ok = unsafe {
+            InstallColorProfileW(
+                windows::core::PCWSTR::null(),
+                windows::core::PCWSTR(wide_path.as_ptr()),
+            )
+        };
+        if !ok.as_bool() {
+            return Err(format!("Failed to install color profile '{}'", profile));
+        }
+    } else if !color_directory()?.join(&name).exists() {
+        return Err(format!("Color profile '{}' is not installed", name));
+    }
+
+    let wide_name = to_wide_null_terminated(&name);
+    // Per-user associations are ignored unless the device opts into them
+    let _ = unsafe {
+        WcsSetUsePerUserProfiles(
+            windows::core::PCWSTR(wide_key.as_ptr()),
+            CLASS_MONITOR,
+            TRUE,
+        )
+    };
+    let ok = unsafe {
+        WcsAssociateColorProfileWithDevice(
+            WCS_PROFILE_MANAGEMENT_SCOPE_CURRENT_USER,
+            windows::core::PCWSTR(wide_name.as_ptr()),
+            windows::core::PCWSTR(wide_key.as_ptr()),
+        )
+    };
+    if !ok.as_bool() {
+        return Err(format!(
+            "Failed to associate color profile '{}' with the monitor",
+            name
+        ));
+    }
+    let ok = unsafe {
+        WcsSetDefaultColorProfile(
+            WCS_PROFILE_MANAGEMENT_SCOPE_CURRENT_USER,
+            windows::core::PCWSTR(wide_key.as_ptr()),
+            CPT_ICC,
+            CPST_NONE,
+            0,
+            windows::core::PCWSTR(wide_name.as_ptr()),
+        )
+    };
+    if !ok.as_bool() {
+        return Err(format!(
+            "Failed to make '{}' the default color profile",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn set_monitor_input_source_windows(device_name: String, input: String) -> Result<(), String> {
+    // DDC/CI VCP code 0x60 selects input source; accepts a key ("hdmi1"), alias or hex code
+    let code = crate::inputs::code_of(&input).ok_or_else(|| "Unknown input label".to_string())?;
+
+    use windows::Win32::Devices::Display::SetVCPFeature;
+    with_first_physical_monitor(&device_name, |pm| {
+        let ok = unsafe { SetVCPFeature(pm.hPhysicalMonitor, 0x60, code) };
+        if ok == 0 {
+            return Err(
+                "SetVCPFeature(0x60) failed (monitor may not support input switching via DDC/CI)"
+                    .to_string(),
+            );
+        }
+        // Verify change was applied
+        use windows::Win32::Devices::Display::{GetVCPFeatureAndVCPFeatureReply, MC_VCP_CODE_TYPE};
+        let mut feature_type: MC_VCP_CODE_TYPE = MC_VCP_CODE_TYPE(0);
+        let mut current_value: u32 = 0;
+        let mut max_value: u32 = 0;
+        let ok2 = unsafe {
+            GetVCPFeatureAndVCPFeatureReply(
+                pm.hPhysicalMonitor,
+                0x60,
+                Some(&mut feature_type),
+                &mut current_value,
+                Some(&mut max_value),
+            )
+        };
+        if ok2 == 0 {
+            log::warn!("Failed to verify input switch (GetVCPFeature) - assuming success");
+            return Ok(());
+        }
+        let read_code = current_value & 0xFF;
+        if read_code != code {
+            log::error!(
+                "Monitor ignored input change (DDC/CI 2): {} != {}",
+                read_code,
+                code
+            );
+            return Err(format!(
+                "Monitor ignored input change, it might not support DDC/CI or connection method is not supported",
+            ));
+        }
+        Ok(())
+    })
+}
+
+fn get_monitor_input_source_windows(device_name: String) -> Result<String, String> {
     use windows::Win32::Devices::Display::{GetVCPFeatureAndVCPFeatureReply, MC_VCP_CODE_TYPE};
     with_first_physical_monitor(&device_name, |pm| {
         let mut feature_type: MC_VCP_CODE_TYPE = MC_VCP_CODE_TYPE(0);
@@ -1147,20 +1981,7 @@ fn get_monitor_input_source_windows(device_name: String) -> Result<String, Strin
         }
         // Some monitors return extra bits in the high bytes. VCP 0x60 uses the low 8 bits.
         let code: u32 = (current_value & 0xFF) as u32;
-        // Map a few common codes back to labels (best effort)
-        let label = match code {
-            0x01 => "vga1",
-            0x02 => "vga2",
-            0x03 => "dvi1",
-            0x04 => "dvi2",
-            0x0F => "dp1",
-            0x10 => "dp2",
-            0x11 => "hdmi1",
-            0x12 => "hdmi2",
-            0x13 => "hdmi3",
-            _ => return Ok(format!("0x{:02X}", code)),
-        };
-        Ok(label.to_string())
+        Ok(crate::inputs::key_of(code))
     })
 }
 
@@ -1329,17 +2150,53 @@ fn set_monitor_power_windows(device_name: &str, power_on: bool) -> Result<(), St
     })
 }
 
-// WMI brightness fallback helpers (Windows internal displays)
-fn wmi_get_brightness_via_powershell() -> Option<BrightnessInfo> {
-    let script = r#"
+// WMI InstanceName prefix of the monitor on `device_name`, from its device interface:
+// "\\?\DISPLAY#BOE0867#4&2a1d2d0&0&UID8388688#{guid}" -> "DISPLAY\BOE0867\4&2a1d2d0&0&UID8388688_"
+fn wmi_instance_prefix(device_name: &str) -> Option<String> {
+    use std::mem::{size_of, zeroed};
+    use windows::Win32::Graphics::Gdi::{EnumDisplayDevicesW, DISPLAY_DEVICEW};
+    // EDD_GET_DEVICE_INTERFACE_NAME
+    const GET_DEVICE_INTERFACE_NAME: u32 = 0x1;
+
+    let wide = to_wide_null_terminated(device_name);
+    let mut mon: DISPLAY_DEVICEW = unsafe { zeroed() };
+    mon.cb = size_of::<DISPLAY_DEVICEW>() as u32;
+    let ok = unsafe {
+        EnumDisplayDevicesW(
+            windows::core::PCWSTR(wide.as_ptr()),
+            0,
+            &mut mon,
+            GET_DEVICE_INTERFACE_NAME,
+        )
+    };
+    if !ok.as_bool() {
+        return None;
+    }
+    let interface = widestr_to_string(&mon.DeviceID);
+    let path = interface.strip_prefix(r"\\?\")?;
+    let (instance, _class) = path.rsplit_once('#')?;
+    // Quoted into PowerShell below; instance ids never hold quotes or wildcards
+    if instance.is_empty() || instance.contains(['\'', '*', '?', '[', ']']) {
+        return None;
+    }
+    Some(format!("{}_", instance.replace('#', "\\")))
+}
+
+// WMI brightness fallback helpers (Windows internal displays). Only the WMI monitor behind
+// `device_name` is used, never whichever one happens to be active.
+fn wmi_get_brightness_via_powershell(device_name: &str) -> Option<BrightnessInfo> {
+    let prefix = wmi_instance_prefix(device_name)?;
+    let script = format!(
+        r#"
 $inst = Get-CimInstance -Namespace root/WMI -Class WmiMonitorBrightness -ErrorAction SilentlyContinue |
-  Where-Object { $_.Active -eq $true } |
+  Where-Object {{ $_.Active -eq $true -and $_.InstanceName -like '{prefix}*' }} |
   Select-Object -First 1
-if ($inst) {
-  [pscustomobject]@{ Current = [uint32]$inst.CurrentBrightness } | ConvertTo-Json -Compress
-}
-"#;
-    let raw = run_powershell_hidden(script)?;
+if ($inst) {{
+  [pscustomobject]@{{ Current = [uint32]$inst.CurrentBrightness }} | ConvertTo-Json -Compress
+}}
+"#
+    );
+    let raw = run_powershell_hidden(&script)?;
     if raw.trim().is_empty() {
         return None;
     }
@@ -1354,18 +2211,22 @@ if ($inst) {
                 min: 0,
                 current: cur.min(100),
                 max: 100,
+                source: "wmi".to_string(),
             });
         }
     }
     None
 }
 
-fn wmi_set_brightness_via_powershell(percent: u32) -> bool {
+fn wmi_set_brightness_via_powershell(device_name: &str, percent: u32) -> bool {
+    let Some(prefix) = wmi_instance_prefix(device_name) else {
+        return false;
+    };
     let pct = percent.min(100);
     let script = format!(
         r#"$b = [byte]({pct});
 $inst = Get-CimInstance -Namespace root/WMI -Class WmiMonitorBrightnessMethods -ErrorAction SilentlyContinue |
-  Where-Object {{ $_.Active -eq $true }} | Select-Object -First 1
+  Where-Object {{ $_.Active -eq $true -and $_.InstanceName -like '{prefix}*' }} | Select-Object -First 1
 if ($inst) {{
   $r = Invoke-CimMethod -InputObject $inst -MethodName WmiSetBrightness -Arguments @{{ Timeout = 0; Brightness = $b }} -ErrorAction SilentlyContinue;
   if ($r -and ($r.ReturnValue -eq 0)) {{ 'OK' }}
@@ -1419,18 +2280,37 @@ fn get_monitor_scale_for_device(device_name: &str) -> f32 {
 }
 
 #[cfg(windows)]
-mod displayconfig_ffi {
+pub(crate) mod displayconfig_ffi {
     #![allow(non_camel_case_types)]
     #![allow(non_snake_case)]
     #![allow(dead_code)]
     use windows::Win32::Foundation::{BOOL, LUID};
 
     pub const QDC_ONLY_ACTIVE_PATHS: u32 = 0x00000002;
+    pub const QDC_VIRTUAL_REFRESH_RATE_AWARE: u32 = 0x00000040;
     pub const DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME: i32 = 1;
     pub const DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME: i32 = 2;
     pub const DISPLAYCONFIG_DEVICE_INFO_GET_DPI_SCALE: i32 = -3; // undocumented
     pub const DISPLAYCONFIG_DEVICE_INFO_SET_DPI_SCALE: i32 = -4; // undocumented
 
+    // SetDisplayConfig flags
+    pub const SDC_USE_SUPPLIED_DISPLAY_CONFIG: u32 = 0x00000020;
+    pub const SDC_APPLY: u32 = 0x00000080;
+    pub const SDC_SAVE_TO_DATABASE: u32 = 0x00000200;
+    pub const SDC_ALLOW_CHANGES: u32 = 0x00000400;
+    pub const SDC_VIRTUAL_REFRESH_RATE_AWARE: u32 = 0x00040000;
+
+    // DISPLAYCONFIG_PATH_INFO flags
+    pub const DISPLAYCONFIG_PATH_BOOST_REFRESH_RATE: u32 = 0x00000010;
+
+    // DISPLAYCONFIG_SCALING
+    pub const DISPLAYCONFIG_SCALING_IDENTITY: u32 = 1;
+    pub const DISPLAYCONFIG_SCALING_CENTERED: u32 = 2;
+    pub const DISPLAYCONFIG_SCALING_STRETCHED: u32 = 3;
+    pub const DISPLAYCONFIG_SCALING_ASPECTRATIOCENTEREDMAX: u32 = 4;
+    pub const DISPLAYCONFIG_SCALING_CUSTOM: u32 = 5;
+    pub const DISPLAYCONFIG_SCALING_PREFERRED: u32 = 128;
+
     /// DISPLAYCONFIG_TARGET_DEVICE_NAME structure for getting the monitor's GDI device path
     #[repr(C)]
     #[derive(Clone, Copy)]
@@ -1500,17 +2380,8 @@ mod displayconfig_ffi {
         pub flags: u32,
     }
 
-    // Oversized union payload as in test.py to satisfy API
-    #[repr(C)]
-    #[derive(Clone, Copy)]
-    pub struct DISPLAYCONFIG_MODE_INFO {
-        pub infoType: u32,
-        pub id: u32,
-        pub adapterId: LUID,
-        pub _blob: [u8; 128],
-    }
-
-    // Intentionally no Default impl: we construct instances using `zeroed()`
+    // Taken from the windows crate so mode arrays have the stride the API actually uses
+    pub use windows::Win32::Devices::Display::DISPLAYCONFIG_MODE_INFO;
 
     #[link(name = "user32")]
     extern "system" {
@@ -1529,90 +2400,192 @@ mod displayconfig_ffi {
             current_topology_id: *mut core::ffi::c_void,
         ) -> i32;
 
+        pub fn SetDisplayConfig(
+            num_path_array_elements: u32,
+            path_array: *mut DISPLAYCONFIG_PATH_INFO,
+            num_mode_info_array_elements: u32,
+            mode_info_array: *mut DISPLAYCONFIG_MODE_INFO,
+            flags: u32,
+        ) -> i32;
+
         pub fn DisplayConfigGetDeviceInfo(packet: *mut DISPLAYCONFIG_DEVICE_INFO_HEADER) -> i32;
         pub fn DisplayConfigSetDeviceInfo(packet: *mut DISPLAYCONFIG_DEVICE_INFO_HEADER) -> i32;
     }
 }
 
-#[allow(dead_code)]
-fn get_scales_for_device(device_name: &str) -> Result<Vec<ScaleInfo>, String> {
+// Active DisplayConfig paths and modes, as passed to QueryDisplayConfig/SetDisplayConfig
+pub(crate) fn query_active_config() -> Result<
+    (
+        Vec<displayconfig_ffi::DISPLAYCONFIG_PATH_INFO>,
+        Vec<displayconfig_ffi::DISPLAYCONFIG_MODE_INFO>,
+    ),
+    String,
+> {
+    query_config(displayconfig_ffi::QDC_ONLY_ACTIVE_PATHS)
+}
+
+fn query_config(
+    flags: u32,
+) -> Result<
+    (
+        Vec<displayconfig_ffi::DISPLAYCONFIG_PATH_INFO>,
+        Vec<displayconfig_ffi::DISPLAYCONFIG_MODE_INFO>,
+    ),
+    String,
+> {
+    use crate::winDisplays::displayconfig_ffi::*;
+    use std::mem::zeroed;
+
+    let mut num_paths: u32 = 0;
+    let mut num_modes: u32 = 0;
+    unsafe {
+        let rc = GetDisplayConfigBufferSizes(flags, &mut num_paths, &mut num_modes);
+        if rc != 0 {
+            return Err(format!("GetDisplayConfigBufferSizes failed: {}", rc));
+        }
+    }
+
+    let mut paths: Vec<DISPLAYCONFIG_PATH_INFO> = vec![unsafe { zeroed() }; num_paths as usize];
+    let mut modes: Vec<DISPLAYCONFIG_MODE_INFO> = vec![unsafe { zeroed() }; num_modes as usize];
+    unsafe {
+        let rc = QueryDisplayConfig(
+            flags,
+            &mut num_paths,
+            paths.as_mut_ptr(),
+            &mut num_modes,
+            modes.as_mut_ptr(),
+            core::ptr::null_mut(),
+        );
+        if rc != 0 {
+            return Err(format!("QueryDisplayConfig failed: {}", rc));
+        }
+    }
+    paths.truncate(num_paths as usize);
+    modes.truncate(num_modes as usize);
+    Ok((paths, modes))
+}
+
+// Index of the path whose source is the GDI device `device_name` (e.g. \\.\DISPLAY1)
+fn find_path_for_device(
+    paths: &[displayconfig_ffi::DISPLAYCONFIG_PATH_INFO],
+    device_name: &str,
+) -> Option<usize> {
     use crate::winDisplays::displayconfig_ffi::*;
     use std::mem::{size_of, zeroed};
-    use windows::Win32::Foundation::LUID;
 
-    #[repr(C)]
-    struct DpiScaleGetStruct {
-        header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
-        min_scale_rel: i32,
-        cur_scale_rel: i32,
-        max_scale_rel: i32,
+    paths.iter().position(|p| {
+        let mut src_name: DISPLAYCONFIG_SOURCE_DEVICE_NAME = unsafe { zeroed() };
+        src_name.header.size = size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32;
+        src_name.header.adapterId = p.sourceInfo.adapterId;
+        src_name.header.id = p.sourceInfo.id;
+        src_name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME;
+        let rc = unsafe { DisplayConfigGetDeviceInfo(&mut src_name.header) };
+        rc == 0 && widestr_to_string(&src_name.viewGdiDeviceName) == device_name
+    })
+}
+
+fn query_active_path(device_name: &str) -> Option<displayconfig_ffi::DISPLAYCONFIG_PATH_INFO> {
+    let (paths, _) = query_active_config().ok()?;
+    paths
+        .get(find_path_for_device(&paths, device_name)?)
+        .copied()
+}
+
+fn scaling_mode_name(scaling: u32) -> Option<&'static str> {
+    use crate::winDisplays::displayconfig_ffi::*;
+    match scaling {
+        DISPLAYCONFIG_SCALING_IDENTITY => Some("identity"),
+        DISPLAYCONFIG_SCALING_CENTERED => Some("centered"),
+        DISPLAYCONFIG_SCALING_STRETCHED => Some("stretched"),
+        DISPLAYCONFIG_SCALING_ASPECTRATIOCENTEREDMAX => Some("aspect_ratio"),
+        DISPLAYCONFIG_SCALING_CUSTOM => Some("custom"),
+        DISPLAYCONFIG_SCALING_PREFERRED => Some("preferred"),
+        _ => None,
     }
+}
 
-    // Common Windows scaling steps
-    const DPI_VALS: [i32; 12] = [100, 125, 150, 175, 200, 225, 250, 300, 350, 400, 450, 500];
+fn set_monitor_scaling_mode_windows(device_name: &str, mode: &str) -> Result<(), String> {
+    use crate::winDisplays::displayconfig_ffi::*;
 
-    // Resolve adapterId + sourceId for the given device name
-    let (adapter_id, source_id): (LUID, u32) = {
-        let mut num_paths: u32 = 0;
-        let mut num_modes: u32 = 0;
-        unsafe {
-            let rc =
-                GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut num_paths, &mut num_modes);
-            if rc != 0 {
-                return Err(format!("GetDisplayConfigBufferSizes failed: {}", rc));
-            }
+    let scaling = match mode {
+        "identity" => DISPLAYCONFIG_SCALING_IDENTITY,
+        "centered" => DISPLAYCONFIG_SCALING_CENTERED,
+        "stretched" => DISPLAYCONFIG_SCALING_STRETCHED,
+        "aspect_ratio" => DISPLAYCONFIG_SCALING_ASPECTRATIOCENTEREDMAX,
+        "preferred" => DISPLAYCONFIG_SCALING_PREFERRED,
+        _ => {
+            return Err(format!(
+                "Unknown scaling mode '{}'. Supported: {}",
+                mode,
+                crate::displays::SCALING_MODES.join(", ")
+            ))
         }
+    };
 
-        let mut paths: Vec<DISPLAYCONFIG_PATH_INFO> = vec![unsafe { zeroed() }; num_paths as usize];
-        let mut modes: Vec<DISPLAYCONFIG_MODE_INFO> = vec![unsafe { zeroed() }; num_modes as usize];
-        unsafe {
-            let rc = QueryDisplayConfig(
-                QDC_ONLY_ACTIVE_PATHS,
-                &mut num_paths,
-                paths.as_mut_ptr(),
-                &mut num_modes,
-                modes.as_mut_ptr(),
-                core::ptr::null_mut(),
-            );
-            if rc != 0 {
-                return Err(format!("QueryDisplayConfig failed: {}", rc));
-            }
-        }
+    // Re-apply the active configuration with only this path's scaling changed. The modes are
+    // passed back as queried, which relies on DISPLAYCONFIG_MODE_INFO having the real layout.
+    const _: () = assert!(std::mem::size_of::<DISPLAYCONFIG_MODE_INFO>() == 64);
+    let (mut paths, mut modes) = query_active_config()?;
+    let idx = find_path_for_device(&paths, device_name)
+        .ok_or_else(|| "Could not map device name to DisplayConfig source".to_string())?;
+    if paths[idx].targetInfo.scaling == scaling {
+        return Ok(());
+    }
+    paths[idx].targetInfo.scaling = scaling;
 
-        let mut found: Option<(LUID, u32)> = None;
-        for p in &paths {
-            let mut src_name: DISPLAYCONFIG_SOURCE_DEVICE_NAME = unsafe { zeroed() };
-            src_name.header.size = size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32;
-            src_name.header.adapterId = p.sourceInfo.adapterId;
-            src_name.header.id = p.sourceInfo.id;
-            // SAFETY: using documented GET_SOURCE_NAME
-            src_name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME;
-            let rc = unsafe { DisplayConfigGetDeviceInfo(&mut src_name.header) };
-            if rc != 0 {
-                continue;
-            }
-            let name = widestr_to_string(&src_name.viewGdiDeviceName);
-            if name == device_name {
-                found = Some((p.sourceInfo.adapterId, p.sourceInfo.id));
-                break;
-            }
-        }
-        found.ok_or_else(|| "Could not map device name to DisplayConfig source".to_string())?
+    let rc = unsafe {
+        SetDisplayConfig(
+            paths.len() as u32,
+            paths.as_mut_ptr(),
+            modes.len() as u32,
+            modes.as_mut_ptr(),
+            SDC_APPLY | SDC_USE_SUPPLIED_DISPLAY_CONFIG | SDC_SAVE_TO_DATABASE | SDC_ALLOW_CHANGES,
+        )
     };
+    if rc != 0 {
+        return Err(format!("SetDisplayConfig failed: {}", rc));
+    }
+    log::info!("Scaling mode for {} set to {}", device_name, mode);
+    Ok(())
+}
+
+#[repr(C)]
+struct DpiScaleGetStruct {
+    header: displayconfig_ffi::DISPLAYCONFIG_DEVICE_INFO_HEADER,
+    min_scale_rel: i32,
+    cur_scale_rel: i32,
+    max_scale_rel: i32,
+}
+
+// Allowed DPI steps of the source driving `device_name`, plus the path it belongs to
+fn query_dpi_range(
+    device_name: &str,
+) -> Result<
+    (
+        crate::dpi_scale::DpiRange,
+        displayconfig_ffi::DISPLAYCONFIG_PATH_INFO,
+    ),
+    String,
+> {
+    use crate::winDisplays::displayconfig_ffi::*;
+    use std::mem::size_of;
+
+    let (paths, _) = query_active_config()?;
+    let path = find_path_for_device(&paths, device_name)
+        .map(|idx| paths[idx])
+        .ok_or_else(|| "Could not map device name to DisplayConfig source".to_string())?;
 
-    // Build GET packet
     let mut pkt = DpiScaleGetStruct {
         header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
             r#type: DISPLAYCONFIG_DEVICE_INFO_GET_DPI_SCALE, // undocumented
             size: size_of::<DpiScaleGetStruct>() as u32,
-            adapterId: adapter_id,
-            id: source_id,
+            adapterId: path.sourceInfo.adapterId,
+            id: path.sourceInfo.id,
         },
         min_scale_rel: 0,
         cur_scale_rel: 0,
         max_scale_rel: 0,
     };
-
     let rc = unsafe { DisplayConfigGetDeviceInfo(&mut pkt.header) };
     if rc != 0 {
         return Err(format!(
@@ -1621,33 +2594,241 @@ fn get_scales_for_device(device_name: &str) -> Result<Vec<ScaleInfo>, String> {
         ));
     }
 
-    // Relative semantics:
-    // - min_scale_rel: steps DOWN from recommended to reach minimum (100%).
-    // - cur_scale_rel: steps from recommended to current.
-    // - max_scale_rel: steps UP from recommended to maximum.
-    // So:
-    // recommended_idx = -min_scale_rel
-    // min_idx = recommended_idx + min_scale_rel (usually 0)
-    // max_idx = recommended_idx + max_scale_rel
-    let recommended_idx_i32 = -pkt.min_scale_rel;
-    let recommended_idx = recommended_idx_i32.clamp(0, (DPI_VALS.len() as i32) - 1) as usize;
-
-    let mut min_idx = (recommended_idx_i32 + pkt.min_scale_rel).max(0) as usize;
-    let mut max_idx =
-        (recommended_idx_i32 + pkt.max_scale_rel).min((DPI_VALS.len() as i32) - 1) as usize;
-    if min_idx > max_idx {
-        core::mem::swap(&mut min_idx, &mut max_idx);
-    }
-
-    let mut out: Vec<ScaleInfo> = Vec::new();
-    for idx in min_idx..=max_idx {
-        let scale = (DPI_VALS[idx] as f32) / 100.0;
-        out.push(ScaleInfo {
-            scale,
-            is_recommended: idx == recommended_idx,
-        });
+    let range = crate::dpi_scale::DpiRange::from_relative(crate::dpi_scale::RelativeDpi {
+        min_rel: pkt.min_scale_rel,
+        cur_rel: pkt.cur_scale_rel,
+        max_rel: pkt.max_scale_rel,
+    });
+    Ok((range, path))
+}
+
+fn get_scales_for_device(device_name: &str) -> Result<Vec<ScaleInfo>, String> {
+    let (range, _) = query_dpi_range(device_name)?;
+    Ok((range.min_idx..=range.max_idx)
+        .map(|idx| ScaleInfo {
+            scale: crate::dpi_scale::DPI_STEPS[idx] as f32 / 100.0,
+            is_recommended: idx == range.recommended_idx,
+            is_current: idx == range.current_idx,
+        })
+        .collect())
+}
+
+const DESKTOP_KEY: &str = r"Control Panel\Desktop";
+
+fn read_desktop_dword(name: &str) -> Option<u32> {
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let key = to_wide_null_terminated(DESKTOP_KEY);
+    let value = to_wide_null_terminated(name);
+    let mut data: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let rc = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            windows::core::PCWSTR(key.as_ptr()),
+            windows::core::PCWSTR(value.as_ptr()),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut data as *mut u32 as *mut core::ffi::c_void),
+            Some(&mut size),
+        )
+    };
+    rc.is_ok().then_some(data)
+}
+
+/// System-wide custom scaling in percent, None while Windows uses per-monitor scaling.
+fn get_custom_scale_percent() -> Option<u32> {
+    if read_desktop_dword("Win8DpiScaling")? != 1 {
+        return None;
     }
-    Ok(out)
+    let dpi = read_desktop_dword("LogPixels")?;
+    (dpi > 0).then(|| crate::dpi_scale::dpi_to_percent(dpi))
+}
+
+// Custom scaling replaces per-monitor scaling for every monitor and applies at next sign-in
+fn set_custom_scale_windows(percent: Option<u32>) -> Result<(), String> {
+    use crate::dpi_scale::{percent_to_dpi, MAX_CUSTOM_PERCENT, MIN_CUSTOM_PERCENT};
+    use windows::Win32::System::Registry::{
+        RegCloseKey, RegCreateKeyW, RegDeleteValueW, RegSetValueExW, HKEY, HKEY_CURRENT_USER,
+        REG_DWORD,
+    };
+
+    if let Some(p) = percent {
+        if !(MIN_CUSTOM_PERCENT..=MAX_CUSTOM_PERCENT).contains(&p) {
+            return Err(format!(
+                "Custom scale must be between {}% and {}%",
+                MIN_CUSTOM_PERCENT, MAX_CUSTOM_PERCENT
+            ));
+        }
+    }
+
+    let key_path = to_wide_null_terminated(DESKTOP_KEY);
+    let mut hkey: HKEY = HKEY::default();
+    let rc = unsafe {
+        RegCreateKeyW(
+            HKEY_CURRENT_USER,
+            windows::core::PCWSTR(key_path.as_ptr()),
+            &mut hkey,
+        )
+    };
+    if rc.is_err() {
+        return Err(format!("Failed to open Control Panel\\Desktop: {:?}", rc));
+    }
+
+    let set_dword = |name: &str, value: u32| {
+        let name = to_wide_null_terminated(name);
+        unsafe {
+            RegSetValueExW(
+                hkey,
+                windows::core::PCWSTR(name.as_ptr()),
+                0,
+                REG_DWORD,
+                Some(&value.to_le_bytes()),
+            )
+        }
+    };
+    let rc = match percent {
+        Some(p) => {
+            let rc = set_dword("LogPixels", percent_to_dpi(p));
+            if rc.is_ok() {
+                set_dword("Win8DpiScaling", 1)
+            } else {
+                rc
+            }
+        }
+        None => {
+            let name = to_wide_null_terminated("LogPixels");
+            let _ = unsafe { RegDeleteValueW(hkey, windows::core::PCWSTR(name.as_ptr())) };
+            set_dword("Win8DpiScaling", 0)
+        }
+    };
+    unsafe { RegCloseKey(hkey) };
+    if rc.is_err() {
+        return Err(format!("Failed to write custom scaling: {:?}", rc));
+    }
+
+    match percent {
+        Some(p) => log::info!("Custom scaling set to {}%, applies after signing out", p),
+        None => log::info!("Custom scaling turned off, applies after signing out"),
+    }
+    Ok(())
+}
+
+// Raw EDID as cached by the monitor driver, e.g.
+// HKLM\SYSTEM\CurrentControlSet\Enum\DISPLAY\DEL4109\5&2a1b&0&UID4353\Device Parameters\EDID
+fn read_monitor_edid(path: &displayconfig_ffi::DISPLAYCONFIG_PATH_INFO) -> Option<Vec<u8>> {
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_BINARY};
+
+    // \\?\DISPLAY#DEL4109#5&2a1b&0&UID4353#{e6f07b5f-ee97-4a90-b076-33f57bf4eaa7}
+    let device_path = get_monitor_registry_id(path.targetInfo.adapterId, path.targetInfo.id)?;
+    let parts: Vec<&str> = device_path.split('#').collect();
+    if parts.len() < 3 {
+        return None;
+    }
+    let key = to_wide_null_terminated(&format!(
+        r"SYSTEM\CurrentControlSet\Enum\DISPLAY\{}\{}\Device Parameters",
+        parts[1], parts[2]
+    ));
+    let value = to_wide_null_terminated("EDID");
+
+    let mut size: u32 = 0;
+    let rc = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            windows::core::PCWSTR(key.as_ptr()),
+            windows::core::PCWSTR(value.as_ptr()),
+            RRF_RT_REG_BINARY,
+            None,
+            None,
+            Some(&mut size),
+        )
+    };
+    if rc.is_err() || size == 0 {
+        log::debug!("No cached EDID for {}: {:?}", device_path, rc);
+        return None;
+    }
+    let mut data = vec![0u8; size as usize];
+    let rc = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            windows::core::PCWSTR(key.as_ptr()),
+            windows::core::PCWSTR(value.as_ptr()),
+            RRF_RT_REG_BINARY,
+            None,
+            Some(data.as_mut_ptr() as *mut core::ffi::c_void),
+            Some(&mut size),
+        )
+    };
+    if rc.is_err() {
+        return None;
+    }
+    data.truncate(size as usize);
+    Some(data)
+}
+
+// Windows' per-monitor "Dynamic refresh rate" switch: with it on, the path runs with adaptive sync
+// and the refresh rate follows the content. The flag is only reported and accepted when both
+// QueryDisplayConfig and SetDisplayConfig are told the caller is virtual refresh rate aware.
+const VRR_QUERY_FLAGS: u32 =
+    displayconfig_ffi::QDC_ONLY_ACTIVE_PATHS | displayconfig_ffi::QDC_VIRTUAL_REFRESH_RATE_AWARE;
+
+fn get_monitor_vrr_windows(device_name: &str) -> bool {
+    use crate::winDisplays::displayconfig_ffi::*;
+
+    let Ok((paths, _)) = query_config(VRR_QUERY_FLAGS) else {
+        return false;
+    };
+    find_path_for_device(&paths, device_name)
+        .is_some_and(|idx| paths[idx].flags & DISPLAYCONFIG_PATH_BOOST_REFRESH_RATE != 0)
+}
+
+fn set_monitor_vrr_windows(device_name: &str, enable: bool) -> Result<(), String> {
+    use crate::winDisplays::displayconfig_ffi::*;
+
+    let (mut paths, mut modes) = query_config(VRR_QUERY_FLAGS)?;
+    let idx = find_path_for_device(&paths, device_name)
+        .ok_or_else(|| "Could not map device name to DisplayConfig source".to_string())?;
+    let supported = read_monitor_edid(&paths[idx])
+        .and_then(|data| crate::edid::parse_edid(&data).ok())
+        .is_some_and(|edid| edid.vrr.is_some());
+    if !supported {
+        return Err(format!(
+            "{} doesn't support variable refresh rate",
+            device_name
+        ));
+    }
+    let enabled = paths[idx].flags & DISPLAYCONFIG_PATH_BOOST_REFRESH_RATE != 0;
+    if enabled == enable {
+        return Ok(());
+    }
+    if enable {
+        paths[idx].flags |= DISPLAYCONFIG_PATH_BOOST_REFRESH_RATE;
+    } else {
+        paths[idx].flags &= !DISPLAYCONFIG_PATH_BOOST_REFRESH_RATE;
+    }
+
+    let rc = unsafe {
+        SetDisplayConfig(
+            paths.len() as u32,
+            paths.as_mut_ptr(),
+            modes.len() as u32,
+            modes.as_mut_ptr(),
+            SDC_APPLY
+                | SDC_USE_SUPPLIED_DISPLAY_CONFIG
+                | SDC_SAVE_TO_DATABASE
+                | SDC_ALLOW_CHANGES
+                | SDC_VIRTUAL_REFRESH_RATE_AWARE,
+        )
+    };
+    if rc != 0 {
+        return Err(format!("SetDisplayConfig failed: {}", rc));
+    }
+    log::info!(
+        "Variable refresh rate {} for {}",
+        if enable { "enabled" } else { "disabled" },
+        device_name
+    );
+    Ok(())
 }
 
 fn get_all_monitor_names_windows() -> Result<Vec<String>, String> {
@@ -1866,16 +3047,7 @@ fn extract_monitor_id_from_path(path: &str) -> Option<String> {
 
 fn set_monitor_scale_windows(device_name: &str, scale_percent: u32) -> Result<(), String> {
     use crate::winDisplays::displayconfig_ffi::*;
-    use std::mem::{size_of, zeroed};
-    use windows::Win32::Foundation::LUID;
-
-    #[repr(C)]
-    struct DpiScaleGetStruct {
-        header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
-        min_scale_rel: i32,
-        cur_scale_rel: i32,
-        max_scale_rel: i32,
-    }
+    use std::mem::size_of;
 
     #[repr(C)]
     struct DpiScaleSetStruct {
@@ -1883,112 +3055,23 @@ fn set_monitor_scale_windows(device_name: &str, scale_percent: u32) -> Result<()
         scale_rel: i32,
     }
 
-    const DPI_VALS: [i32; 12] = [100, 125, 150, 175, 200, 225, 250, 300, 350, 400, 450, 500];
-
-    // Validate input
-    let target_idx = DPI_VALS
-        .iter()
-        .position(|v| *v == scale_percent as i32)
-        .ok_or_else(|| {
-            format!(
-                "Unsupported DPI {}%. Supported: {:?}",
-                scale_percent, DPI_VALS
-            )
-        })? as i32;
-
-    // Resolve adapterId + sourceId + targetId for the given device name
-    let (adapter_id, source_id, target_adapter_id, target_id): (LUID, u32, LUID, u32) = {
-        let mut num_paths: u32 = 0;
-        let mut num_modes: u32 = 0;
-        unsafe {
-            let rc =
-                GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut num_paths, &mut num_modes);
-            if rc != 0 {
-                return Err(format!("GetDisplayConfigBufferSizes failed: {}", rc));
-            }
-        }
-
-        let mut paths: Vec<DISPLAYCONFIG_PATH_INFO> = vec![unsafe { zeroed() }; num_paths as usize];
-        let mut modes: Vec<DISPLAYCONFIG_MODE_INFO> = vec![unsafe { zeroed() }; num_modes as usize];
-        unsafe {
-            let rc = QueryDisplayConfig(
-                QDC_ONLY_ACTIVE_PATHS,
-                &mut num_paths,
-                paths.as_mut_ptr(),
-                &mut num_modes,
-                modes.as_mut_ptr(),
-                core::ptr::null_mut(),
-            );
-            if rc != 0 {
-                return Err(format!("QueryDisplayConfig failed: {}", rc));
-            }
-        }
-
-        let mut found: Option<(LUID, u32, LUID, u32)> = None;
-        for p in &paths {
-            let mut src_name: DISPLAYCONFIG_SOURCE_DEVICE_NAME = unsafe { zeroed() };
-            src_name.header.size = size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32;
-            src_name.header.adapterId = p.sourceInfo.adapterId;
-            src_name.header.id = p.sourceInfo.id;
-            src_name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME;
-            let rc = unsafe { DisplayConfigGetDeviceInfo(&mut src_name.header) };
-            if rc != 0 {
-                continue;
-            }
-            let name = widestr_to_string(&src_name.viewGdiDeviceName);
-            if name == device_name {
-                found = Some((
-                    p.sourceInfo.adapterId,
-                    p.sourceInfo.id,
-                    p.targetInfo.adapterId,
-                    p.targetInfo.id,
-                ));
-                break;
-            }
-        }
-        found.ok_or_else(|| "Could not map device name to DisplayConfig source".to_string())?
-    };
-
-    // Fetch recommended index via GET packet
-    let mut get_pkt = DpiScaleGetStruct {
-        header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
-            r#type: DISPLAYCONFIG_DEVICE_INFO_GET_DPI_SCALE,
-            size: size_of::<DpiScaleGetStruct>() as u32,
-            adapterId: adapter_id,
-            id: source_id,
-        },
-        min_scale_rel: 0,
-        cur_scale_rel: 0,
-        max_scale_rel: 0,
-    };
-    let rc = unsafe { DisplayConfigGetDeviceInfo(&mut get_pkt.header) };
-    if rc != 0 {
-        return Err(format!(
-            "DisplayConfigGetDeviceInfo(GET_DPI) failed: {}",
-            rc
-        ));
-    }
-    // Compute recommended and bounds from relative values
-    let recommended_idx: i32 = -get_pkt.min_scale_rel;
-    let min_idx: i32 = (recommended_idx + get_pkt.min_scale_rel).max(0);
-    let max_idx: i32 = (recommended_idx + get_pkt.max_scale_rel).min((DPI_VALS.len() as i32) - 1);
-
-    if target_idx < min_idx || target_idx > max_idx {
+    if let Some(custom) = get_custom_scale_percent() {
         return Err(format!(
-            "Unsupported DPI {}% for this display. Supported range: {}%-{}%",
-            scale_percent, DPI_VALS[min_idx as usize], DPI_VALS[max_idx as usize]
+            "Custom scaling ({}%) is active for all displays; turn it off to set per-monitor scale",
+            custom
         ));
     }
 
-    let rel = target_idx - recommended_idx;
+    let (range, path) = query_dpi_range(device_name)?;
+    let rel = range.relative_for(scale_percent)?;
 
     // Build SET packet
     let mut set_pkt = DpiScaleSetStruct {
         header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
             r#type: DISPLAYCONFIG_DEVICE_INFO_SET_DPI_SCALE, // undocumented
             size: size_of::<DpiScaleSetStruct>() as u32,
-            adapterId: adapter_id,
-            id: source_id,
+            adapterId: path.sourceInfo.adapterId,
+            id: path.sourceInfo.id,
         },
         scale_rel: rel,
     };
@@ -2003,10 +3086,12 @@ fn set_monitor_scale_windows(device_name: &str, scale_percent: u32) -> Result<()
     // Write to registry for persistence across reboots
     // Calculate DPI value: scale_percent% = dpi_value/96 * 100
     // So dpi_value = scale_percent * 96 / 100
-    let dpi_value = (scale_percent * 96 / 100) as u32;
+    let dpi_value = crate::dpi_scale::percent_to_dpi(scale_percent);
 
     // Get the monitor's device path for registry key lookup
-    if let Some(device_path) = get_monitor_registry_id(target_adapter_id, target_id) {
+    if let Some(device_path) =
+        get_monitor_registry_id(path.targetInfo.adapterId, path.targetInfo.id)
+    {
         if let Err(e) = write_dpi_to_registry(&device_path, dpi_value) {
             log::warn!("Failed to persist DPI to registry: {}", e);
             // Don't fail the whole operation - the display change was applied
@@ -2018,12 +3103,12 @@ fn set_monitor_scale_windows(device_name: &str, scale_percent: u32) -> Result<()
     Ok(())
 }
 
-fn widestr_to_string(buf: &[u16]) -> String {
+pub(crate) fn widestr_to_string(buf: &[u16]) -> String {
     let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
     String::from_utf16_lossy(&buf[..len])
 }
 
-fn to_wide_null_terminated(s: &str) -> Vec<u16> {
+pub(crate) fn to_wide_null_terminated(s: &str) -> Vec<u16> {
     use std::ffi::OsStr;
     use std::os::windows::ffi::OsStrExt;
     OsStr::new(s)
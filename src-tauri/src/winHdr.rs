@@ -6,98 +6,16 @@
 
 use std::mem::{size_of, zeroed};
 
-#[cfg(windows)]
-mod displayconfig_ffi_hdr {
-    #![allow(non_camel_case_types)]
-    #![allow(non_snake_case)]
-    #![allow(dead_code)]
-    use windows::Win32::Foundation::LUID;
-
-    pub const QDC_ONLY_ACTIVE_PATHS: u32 = 0x00000002;
-
-    #[repr(C)]
-    #[derive(Clone, Copy, Default)]
-    pub struct DISPLAYCONFIG_DEVICE_INFO_HEADER {
-        pub r#type: i32,
-        pub size: u32,
-        pub adapterId: LUID,
-        pub id: u32,
-    }
-
-    #[repr(C)]
-    #[derive(Clone, Copy, Default)]
-    pub struct DISPLAYCONFIG_PATH_SOURCE_INFO {
-        pub adapterId: LUID,
-        pub id: u32,
-        pub modeInfoIdx: u32,
-        pub statusFlags: u32,
-    }
-
-    #[repr(C)]
-    #[derive(Clone, Copy, Default)]
-    pub struct DISPLAYCONFIG_RATIONAL {
-        pub Numerator: u32,
-        pub Denominator: u32,
-    }
-
-    #[repr(C)]
-    #[derive(Clone, Copy, Default)]
-    pub struct DISPLAYCONFIG_PATH_TARGET_INFO {
-        pub adapterId: LUID,
-        pub id: u32,
-        pub modeInfoIdx: u32,
-        pub outputTechnology: u32,
-        pub rotation: u32,
-        pub scaling: u32,
-        pub refreshRate: DISPLAYCONFIG_RATIONAL,
-        pub scanLineOrdering: u32,
-        pub targetAvailable: windows::Win32::Foundation::BOOL,
-        pub statusFlags: u32,
-    }
-
-    #[repr(C)]
-    #[derive(Clone, Copy, Default)]
-    pub struct DISPLAYCONFIG_PATH_INFO {
-        pub sourceInfo: DISPLAYCONFIG_PATH_SOURCE_INFO,
-        pub targetInfo: DISPLAYCONFIG_PATH_TARGET_INFO,
-        pub flags: u32,
-    }
+use windows::Win32::Foundation::LUID;
 
-    #[repr(C)]
-    #[derive(Clone, Copy)]
-    pub struct DISPLAYCONFIG_MODE_INFO {
-        pub infoType: u32,
-        pub id: u32,
-        pub adapterId: LUID,
-        pub _blob: [u8; 128],
-    }
+use crate::displays::{AdvancedColorInfo, DisplayTarget};
+use crate::winDisplays::widestr_to_string;
 
-    #[link(name = "user32")]
-    extern "system" {
-        pub fn GetDisplayConfigBufferSizes(
-            flags: u32,
-            num_paths: *mut u32,
-            num_modes: *mut u32,
-        ) -> i32;
-
-        pub fn QueryDisplayConfig(
-            flags: u32,
-            num_paths: *mut u32,
-            path_info_array: *mut DISPLAYCONFIG_PATH_INFO,
-            mode_info_array_count: *mut u32,
-            mode_info_array: *mut DISPLAYCONFIG_MODE_INFO,
-            current_topology_id: *mut core::ffi::c_void,
-        ) -> i32;
-
-        pub fn DisplayConfigGetDeviceInfo(packet: *mut DISPLAYCONFIG_DEVICE_INFO_HEADER) -> i32;
-        pub fn DisplayConfigSetDeviceInfo(packet: *mut DISPLAYCONFIG_DEVICE_INFO_HEADER) -> i32;
-    }
-}
-
-use displayconfig_ffi_hdr::{
-    DisplayConfigGetDeviceInfo, DisplayConfigSetDeviceInfo, GetDisplayConfigBufferSizes,
-    QueryDisplayConfig, DISPLAYCONFIG_DEVICE_INFO_HEADER, DISPLAYCONFIG_MODE_INFO,
-    DISPLAYCONFIG_PATH_INFO, QDC_ONLY_ACTIVE_PATHS,
+use crate::winDisplays::displayconfig_ffi::{
+    DisplayConfigGetDeviceInfo, DisplayConfigSetDeviceInfo,
+    DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME, DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+    DISPLAYCONFIG_DEVICE_INFO_HEADER, DISPLAYCONFIG_PATH_INFO, DISPLAYCONFIG_SOURCE_DEVICE_NAME,
+    DISPLAYCONFIG_TARGET_DEVICE_NAME,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -111,30 +29,28 @@ pub enum Status {
 pub struct Display {
     pub name: String,
     pub status: Status,
+    // Adapter LUID + target id the display is addressed by
+    pub target: DisplayTarget,
+    // GDI device name of the source driving the target (e.g. \\.\DISPLAY1), empty if unknown
+    pub device_name: String,
+    pub advanced_color: Option<AdvancedColorInfo>,
 }
 
 // DISPLAYCONFIG_DEVICE_INFO_TYPE constants (subset)
-const DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME: i32 = 2;
 const DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_BASE_TYPE: i32 = 6;
 const DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO: i32 = 9;
 const DISPLAYCONFIG_DEVICE_INFO_SET_ADVANCED_COLOR_STATE: i32 = 10;
+const DISPLAYCONFIG_DEVICE_INFO_GET_SDR_WHITE_LEVEL: i32 = 11;
+// Undocumented, used by the Settings app's "SDR content brightness" slider
+const DISPLAYCONFIG_DEVICE_INFO_SET_SDR_WHITE_LEVEL: i32 = -18;
+
+// SDR white level is reported in thousandths of the 80-nit sRGB reference white
+const SDR_WHITE_LEVEL_REFERENCE_NITS: u32 = 80;
 
 // DISPLAYCONFIG_VIDEO_OUTPUT_TECHNOLOGY constants (subset)
 const DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER: u32 = 0xFFFFFFFF; // (uint32)-1
 const DISPLAYCONFIG_OUTPUT_TECHNOLOGY_INTERNAL: u32 = 0x80000000; // bit-flag for internal
 
-#[repr(C)]
-struct DISPLAYCONFIG_TARGET_DEVICE_NAME {
-    header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
-    flags: u32,
-    outputTechnology: u32,
-    edidManufactureId: u16,
-    edidProductCodeId: u16,
-    connectorInstance: u32,
-    monitorFriendlyDeviceName: [u16; 64],
-    monitorDevicePath: [u16; 128],
-}
-
 #[repr(C)]
 struct DISPLAYCONFIG_TARGET_BASE_TYPE {
     header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
@@ -142,7 +58,8 @@ struct DISPLAYCONFIG_TARGET_BASE_TYPE {
 }
 
 // Per Windows headers, this struct contains a union with bitfields. We map it as a u32 bitmask.
-// Bit 0 = advancedColorSupported, Bit 1 = advancedColorEnabled.
+// Bit 0 = advancedColorSupported, Bit 1 = advancedColorEnabled,
+// Bit 2 = wideColorEnforced, Bit 3 = advancedColorForceDisabled.
 #[repr(C)]
 struct DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO_RUST {
     header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
@@ -151,63 +68,113 @@ struct DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO_RUST {
     bitsPerColorChannel: u32,
 }
 
+#[repr(C)]
+struct DISPLAYCONFIG_SDR_WHITE_LEVEL {
+    header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
+    SDRWhiteLevel: u32,
+}
+
+#[repr(C)]
+struct DISPLAYCONFIG_SET_SDR_WHITE_LEVEL {
+    header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
+    SDRWhiteLevel: u32,
+    finalValue: u8,
+}
+
 #[repr(C)]
 struct DISPLAYCONFIG_SET_ADVANCED_COLOR_STATE_RUST {
     header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
     enableAdvancedColor: u32,
 }
 
-fn widestr_to_string(buf: &[u16]) -> String {
-    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
-    String::from_utf16_lossy(&buf[..len])
+fn luid_to_u64(luid: LUID) -> u64 {
+    ((luid.HighPart as u32 as u64) << 32) | luid.LowPart as u64
 }
 
-fn for_each_display<F: FnMut(&DISPLAYCONFIG_MODE_INFO)>(mut f: F) {
-    let mut path_count: u32 = 0;
-    let mut mode_count: u32 = 0;
-    unsafe {
-        if GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_count, &mut mode_count) != 0
-        {
-            return;
-        }
+fn u64_to_luid(value: u64) -> LUID {
+    LUID {
+        LowPart: value as u32,
+        HighPart: (value >> 32) as u32 as i32,
     }
+}
 
-    let mut paths: Vec<DISPLAYCONFIG_PATH_INFO> = vec![unsafe { zeroed() }; path_count as usize];
-    let mut modes: Vec<DISPLAYCONFIG_MODE_INFO> = vec![unsafe { zeroed() }; mode_count as usize];
-    unsafe {
-        if QueryDisplayConfig(
-            QDC_ONLY_ACTIVE_PATHS,
-            &mut path_count,
-            paths.as_mut_ptr(),
-            &mut mode_count,
-            modes.as_mut_ptr(),
-            core::ptr::null_mut(),
-        ) != 0
-        {
-            return;
-        }
+fn header_for(target: DisplayTarget, r#type: i32, size: usize) -> DISPLAYCONFIG_DEVICE_INFO_HEADER {
+    DISPLAYCONFIG_DEVICE_INFO_HEADER {
+        r#type,
+        size: size as u32,
+        adapterId: u64_to_luid(target.adapter_luid),
+        id: target.target_id,
     }
+}
 
+// Calls `f` with every active path; targets come straight from the path, not its mode entry
+fn for_each_path<F: FnMut(&DISPLAYCONFIG_PATH_INFO)>(mut f: F) {
+    let Ok((paths, _)) = crate::winDisplays::query_active_config() else {
+        return;
+    };
     for path in &paths {
-        let mode_idx = path.targetInfo.modeInfoIdx as usize;
-        if mode_idx < modes.len() {
-            f(&modes[mode_idx]);
+        f(path);
+    }
+}
+
+fn path_target(path: &DISPLAYCONFIG_PATH_INFO) -> DisplayTarget {
+    DisplayTarget {
+        adapter_luid: luid_to_u64(path.targetInfo.adapterId),
+        target_id: path.targetInfo.id,
+    }
+}
+
+fn path_gdi_device_name(path: &DISPLAYCONFIG_PATH_INFO) -> Option<String> {
+    let mut src_name: DISPLAYCONFIG_SOURCE_DEVICE_NAME = unsafe { zeroed() };
+    src_name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME;
+    src_name.header.size = size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>() as u32;
+    src_name.header.adapterId = path.sourceInfo.adapterId;
+    src_name.header.id = path.sourceInfo.id;
+    unsafe {
+        if DisplayConfigGetDeviceInfo(&mut src_name.header) != 0 {
+            return None;
         }
     }
+    Some(widestr_to_string(&src_name.viewGdiDeviceName))
+}
+
+/// Returns the target driven by the GDI device `device_name` (e.g. \\.\DISPLAY1).
+pub fn target_for_device(device_name: &str) -> Option<DisplayTarget> {
+    let mut found: Option<DisplayTarget> = None;
+    for_each_path(|path| {
+        if found.is_none()
+            && path_gdi_device_name(path)
+                .map(|name| name.eq_ignore_ascii_case(device_name))
+                .unwrap_or(false)
+        {
+            found = Some(path_target(path));
+        }
+    });
+    found
 }
 
-fn get_display_hdr_status(mode: &DISPLAYCONFIG_MODE_INFO) -> Status {
+fn query_advanced_color(
+    target: DisplayTarget,
+) -> Option<DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO_RUST> {
     let mut get_info: DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO_RUST = unsafe { zeroed() };
-    get_info.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO;
-    get_info.header.size = size_of::<DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO_RUST>() as u32;
-    get_info.header.adapterId = mode.adapterId;
-    get_info.header.id = mode.id;
+    get_info.header = header_for(
+        target,
+        DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO,
+        size_of::<DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO_RUST>(),
+    );
 
     unsafe {
         if DisplayConfigGetDeviceInfo(&mut get_info.header) != 0 {
-            return Status::Unsupported;
+            return None;
         }
     }
+    Some(get_info)
+}
+
+pub fn get_hdr_status(target: DisplayTarget) -> Status {
+    let Some(get_info) = query_advanced_color(target) else {
+        return Status::Unsupported;
+    };
 
     let supported = (get_info.colorInfo & 0x1) != 0;
     if !supported {
@@ -221,12 +188,73 @@ fn get_display_hdr_status(mode: &DISPLAYCONFIG_MODE_INFO) -> Status {
     }
 }
 
+// DISPLAYCONFIG_COLOR_ENCODING
+fn color_encoding_name(encoding: u32) -> &'static str {
+    match encoding {
+        0 => "rgb",
+        1 => "ycbcr444",
+        2 => "ycbcr422",
+        3 => "ycbcr420",
+        4 => "intensity",
+        _ => "unknown",
+    }
+}
+
+/// Signal encoding, bit depth and SDR white level of a target.
+pub fn get_advanced_color_info(target: DisplayTarget) -> Option<AdvancedColorInfo> {
+    let info = query_advanced_color(target)?;
+    Some(AdvancedColorInfo {
+        encoding: color_encoding_name(info.colorEncoding).to_string(),
+        bits_per_channel: info.bitsPerColorChannel,
+        wide_color_enforced: (info.colorInfo & 0x4) != 0,
+        force_disabled: (info.colorInfo & 0x8) != 0,
+        sdr_white_level_nits: get_sdr_white_level(target),
+    })
+}
+
+/// SDR content brightness of a target in nits.
+pub fn get_sdr_white_level(target: DisplayTarget) -> Option<u32> {
+    let mut level: DISPLAYCONFIG_SDR_WHITE_LEVEL = unsafe { zeroed() };
+    level.header = header_for(
+        target,
+        DISPLAYCONFIG_DEVICE_INFO_GET_SDR_WHITE_LEVEL,
+        size_of::<DISPLAYCONFIG_SDR_WHITE_LEVEL>(),
+    );
+    unsafe {
+        if DisplayConfigGetDeviceInfo(&mut level.header) != 0 {
+            return None;
+        }
+    }
+    Some(((level.SDRWhiteLevel as u64 * SDR_WHITE_LEVEL_REFERENCE_NITS as u64 + 500) / 1000) as u32)
+}
+
+/// Sets the SDR content brightness of a target in nits (only visible while HDR is on).
+pub fn set_sdr_white_level(target: DisplayTarget, nits: u32) -> Result<(), String> {
+    let mut level: DISPLAYCONFIG_SET_SDR_WHITE_LEVEL = unsafe { zeroed() };
+    level.header = header_for(
+        target,
+        DISPLAYCONFIG_DEVICE_INFO_SET_SDR_WHITE_LEVEL,
+        size_of::<DISPLAYCONFIG_SET_SDR_WHITE_LEVEL>(),
+    );
+    level.SDRWhiteLevel = nits * 1000 / SDR_WHITE_LEVEL_REFERENCE_NITS;
+    // Intermediate values are previews; 1 commits the level like releasing the slider does
+    level.finalValue = 1;
+    let status = unsafe { DisplayConfigSetDeviceInfo(&mut level.header) };
+    if status != 0 {
+        return Err(format!(
+            "Failed to set SDR white level (DisplayConfigSetDeviceInfo returned {})",
+            status
+        ));
+    }
+    Ok(())
+}
+
 pub fn get_windows_hdr_status() -> Status {
     let mut any_supported = false;
     let mut any_enabled = false;
 
-    for_each_display(|mode| {
-        let s = get_display_hdr_status(mode);
+    for_each_path(|path| {
+        let s = get_hdr_status(path_target(path));
         any_supported |= s != Status::Unsupported;
         any_enabled |= s == Status::On;
     });
@@ -242,17 +270,20 @@ pub fn get_windows_hdr_status() -> Status {
     }
 }
 
-fn set_display_hdr_status(mode: &DISPLAYCONFIG_MODE_INFO, enable: bool) -> Option<Status> {
+/// Turns advanced color (HDR) on or off for one target. Returns the resulting status,
+/// or None if the target doesn't support HDR or the change failed.
+pub fn set_hdr_status(target: DisplayTarget, enable: bool) -> Option<Status> {
     // If not supported, skip
-    if get_display_hdr_status(mode) == Status::Unsupported {
+    if get_hdr_status(target) == Status::Unsupported {
         return None;
     }
 
     let mut set_info: DISPLAYCONFIG_SET_ADVANCED_COLOR_STATE_RUST = unsafe { zeroed() };
-    set_info.header.r#type = DISPLAYCONFIG_DEVICE_INFO_SET_ADVANCED_COLOR_STATE;
-    set_info.header.size = size_of::<DISPLAYCONFIG_SET_ADVANCED_COLOR_STATE_RUST>() as u32;
-    set_info.header.adapterId = mode.adapterId;
-    set_info.header.id = mode.id;
+    set_info.header = header_for(
+        target,
+        DISPLAYCONFIG_DEVICE_INFO_SET_ADVANCED_COLOR_STATE,
+        size_of::<DISPLAYCONFIG_SET_ADVANCED_COLOR_STATE_RUST>(),
+    );
     set_info.enableAdvancedColor = if enable { 1 } else { 0 };
 
     unsafe {
@@ -261,13 +292,13 @@ fn set_display_hdr_status(mode: &DISPLAYCONFIG_MODE_INFO, enable: bool) -> Optio
         }
     }
 
-    Some(get_display_hdr_status(mode))
+    Some(get_hdr_status(target))
 }
 
 pub fn set_windows_hdr_status(enable: bool) -> Option<Status> {
     let mut result: Option<Status> = None;
-    for_each_display(|mode| {
-        if let Some(new_status) = set_display_hdr_status(mode, enable) {
+    for_each_path(|path| {
+        if let Some(new_status) = set_hdr_status(path_target(path), enable) {
             result = match result {
                 None => Some(new_status),
                 Some(prev) => Some(if (prev as i32) > (new_status as i32) {
@@ -289,24 +320,13 @@ pub fn toggle_hdr_status() -> Option<Status> {
     }
 }
 
-pub fn set_hdr_status_by_index(target_index: usize, enable: bool) -> Option<Status> {
-    let mut idx: usize = 0;
-    let mut result: Option<Status> = None;
-    for_each_display(|mode| {
-        if idx == target_index {
-            result = set_display_hdr_status(mode, enable);
-        }
-        idx += 1;
-    });
-    result
-}
-
-fn get_fallback_display_name(mode: &DISPLAYCONFIG_MODE_INFO) -> String {
+fn get_fallback_display_name(target: DisplayTarget) -> String {
     let mut base: DISPLAYCONFIG_TARGET_BASE_TYPE = unsafe { zeroed() };
-    base.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_BASE_TYPE;
-    base.header.size = size_of::<DISPLAYCONFIG_TARGET_BASE_TYPE>() as u32;
-    base.header.adapterId = mode.adapterId;
-    base.header.id = mode.id;
+    base.header = header_for(
+        target,
+        DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_BASE_TYPE,
+        size_of::<DISPLAYCONFIG_TARGET_BASE_TYPE>(),
+    );
     unsafe {
         if DisplayConfigGetDeviceInfo(&mut base.header) == 0 {
             if base.baseOutputTechnology != DISPLAYCONFIG_OUTPUT_TECHNOLOGY_OTHER
@@ -321,28 +341,36 @@ fn get_fallback_display_name(mode: &DISPLAYCONFIG_MODE_INFO) -> String {
 
 pub fn get_displays() -> Vec<Display> {
     let mut out: Vec<Display> = Vec::new();
-    for_each_display(|mode| {
-        let status = get_display_hdr_status(mode);
+    for_each_path(|path| {
+        let target = path_target(path);
+        let status = get_hdr_status(target);
 
         let mut dev_name: DISPLAYCONFIG_TARGET_DEVICE_NAME = unsafe { zeroed() };
-        dev_name.header.r#type = DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME;
-        dev_name.header.size = size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>() as u32;
-        dev_name.header.adapterId = mode.adapterId;
-        dev_name.header.id = mode.id;
+        dev_name.header = header_for(
+            target,
+            DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+            size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>(),
+        );
         let name = unsafe {
             if DisplayConfigGetDeviceInfo(&mut dev_name.header) == 0 {
                 let friendly = widestr_to_string(&dev_name.monitorFriendlyDeviceName);
                 if !friendly.is_empty() {
                     friendly
                 } else {
-                    get_fallback_display_name(mode)
+                    get_fallback_display_name(target)
                 }
             } else {
-                get_fallback_display_name(mode)
+                get_fallback_display_name(target)
             }
         };
 
-        out.push(Display { name, status });
+        out.push(Display {
+            name,
+            status,
+            target,
+            device_name: path_gdi_device_name(path).unwrap_or_default(),
+            advanced_color: get_advanced_color_info(target),
+        });
     });
     out
 }
@@ -0,0 +1,124 @@
+// Group Policy source: values under HKLM\SOFTWARE\Policies\WinDisplay are enforced, values under
+// its "Recommended" subkey are defaults. Value names are setting keys, dotted for nested ones
+// (e.g. "autoHdr.enabled"); DWORDs are numbers or booleans, strings holding JSON arrays or
+// objects (e.g. "brightnessSchedule") are parsed.
+use serde_json::{Map, Value};
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::ERROR_MORE_DATA;
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegEnumValueW, RegOpenKeyExW, HKEY, HKEY_LOCAL_MACHINE, KEY_READ, REG_DWORD,
+    REG_QWORD, REG_SZ, REG_VALUE_TYPE,
+};
+
+use crate::policy::{Policy, PolicySource};
+use crate::winDisplays::{to_wide_null_terminated, widestr_to_string};
+
+const POLICY_KEY: &str = r"SOFTWARE\Policies\WinDisplay";
+const RECOMMENDED_KEY: &str = r"SOFTWARE\Policies\WinDisplay\Recommended";
+
+fn registry_value(kind: REG_VALUE_TYPE, data: &[u8]) -> Option<Value> {
+    match kind {
+        REG_DWORD if data.len() >= 4 => {
+            Some(Value::from(u32::from_le_bytes(data[..4].try_into().ok()?)))
+        }
+        REG_QWORD if data.len() >= 8 => {
+            Some(Value::from(u64::from_le_bytes(data[..8].try_into().ok()?)))
+        }
+        REG_SZ => {
+            let wide: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            let text = widestr_to_string(&wide);
+            let trimmed = text.trim_start();
+            if trimmed.starts_with('[') || trimmed.starts_with('{') {
+                serde_json::from_str(trimmed).ok()
+            } else {
+                Some(Value::String(text))
+            }
+        }
+        _ => None,
+    }
+}
+
+// Every value of the key; None when the key doesn't exist
+fn read_values(path: &str) -> Option<Map<String, Value>> {
+    let key_path = to_wide_null_terminated(path);
+    let mut hkey = HKEY::default();
+    let rc = unsafe {
+        RegOpenKeyExW(
+            HKEY_LOCAL_MACHINE,
+            PCWSTR(key_path.as_ptr()),
+            0,
+            KEY_READ,
+            &mut hkey,
+        )
+    };
+    if rc.is_err() {
+        return None;
+    }
+
+    let mut values = Map::new();
+    let mut name_buf = [0u16; 256];
+    let mut data_buf = vec![0u8; 64 * 1024];
+    let mut index = 0;
+    loop {
+        let mut name_len = name_buf.len() as u32;
+        let mut data_len = data_buf.len() as u32;
+        let mut kind = 0u32;
+        let rc = unsafe {
+            RegEnumValueW(
+                hkey,
+                index,
+                PWSTR(name_buf.as_mut_ptr()),
+                &mut name_len,
+                None,
+                Some(&mut kind as *mut u32),
+                Some(data_buf.as_mut_ptr()),
+                Some(&mut data_len as *mut u32),
+            )
+        };
+        if rc == ERROR_MORE_DATA {
+            log::warn!(
+                "Policy value #{} under {} is too large, skipping",
+                index,
+                path
+            );
+            index += 1;
+            continue;
+        }
+        if rc.is_err() {
+            break;
+        }
+        let name = widestr_to_string(&name_buf[..name_len as usize]);
+        match registry_value(REG_VALUE_TYPE(kind), &data_buf[..data_len as usize]) {
+            Some(value) => {
+                values.insert(name, value);
+            }
+            None => log::warn!("Policy value {}\\{} has an unsupported type", path, name),
+        }
+        index += 1;
+    }
+    unsafe { RegCloseKey(hkey) };
+    Some(values)
+}
+
+pub struct RegistryPolicySource;
+
+impl PolicySource for RegistryPolicySource {
+    fn describe(&self) -> String {
+        format!(r"HKLM\{}", POLICY_KEY)
+    }
+
+    fn load(&self) -> Result<Option<Policy>, String> {
+        let enforced = read_values(POLICY_KEY);
+        let defaults = read_values(RECOMMENDED_KEY);
+        if enforced.is_none() && defaults.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Policy {
+            enforced: enforced.unwrap_or_default(),
+            defaults: defaults.unwrap_or_default(),
+        }))
+    }
+}
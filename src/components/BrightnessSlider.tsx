@@ -20,6 +20,7 @@ export function BrightnessSlider({
   onError,
 }: Props) {
   const [pct, setPct] = useState<number | null>(null);
+  const [source, setSource] = useState<string | null>(null);
   const { monitors } = useMonitorsContext();
 
   const fetchBrightness = useCallback(async () => {
@@ -32,8 +33,10 @@ export function BrightnessSlider({
         min: number;
         current: number;
         max: number;
+        source: "ddc" | "wmi" | "gamma";
       }>("get_monitor_brightness", { deviceName });
       if (info) {
+        setSource(info.source);
         const span = Math.max(1, info.max - info.min);
         const val = Math.round(((info.current - info.min) / span) * 100);
         setPct(Math.max(0, Math.min(100, val)));
@@ -83,7 +86,7 @@ export function BrightnessSlider({
   return (
     <div className="field">
       <label className="label" htmlFor="brightness-range">
-        Brightness
+        Brightness{source === "gamma" ? " (software)" : ""}
       </label>
       <Slider
         id="brightness-range"
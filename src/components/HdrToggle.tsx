@@ -14,9 +14,12 @@ type HdrToggleProps = {
 export function HdrToggle({ monitor, disabled, onError }: HdrToggleProps) {
   const [busy, setBusy] = useState(false);
   const { mutation } = useMonitorsMutation();
+  const bits = monitor.advanced_color?.bits_per_channel;
   const label = {
-    on: "HDR Enabled",
-    off: "HDR Disabled",
+    on: bits ? `HDR Enabled (${bits}-bit)` : "HDR Enabled",
+    off: monitor.advanced_color?.force_disabled
+      ? "HDR Blocked by Windows"
+      : "HDR Disabled",
     unsupported: "HDR Unsupported",
   }[monitor.hdr_status];
 
@@ -4,7 +4,9 @@ import { RefreshRateSlider } from "./RefreshRateSlider";
 import { aspectKey, DisplayInfo } from "../lib/Resolutions";
 import { OrientationSelector } from "./OrientationTextToggle";
 import { ScaleSlider } from "./ScaleSlider.tsx";
+import { SdrWhiteLevelSlider } from "./SdrWhiteLevelSlider";
 import { HdrToggle } from "./HdrToggle.tsx";
+import { VrrToggle } from "./VrrToggle.tsx";
 import { InputSourceSelector } from "./InputSourceSelector";
 import { PowerToggle } from "./PowerToggle";
 
@@ -74,6 +76,20 @@ export default function MonitorControls({
         <HdrToggle monitor={monitor} disabled={disabled} onError={onError} />
       )}
 
+      {monitor.hdr_status === "on" &&
+        monitor.advanced_color?.sdr_white_level_nits != null && (
+          <SdrWhiteLevelSlider
+            deviceName={monitor.device_name}
+            nits={monitor.advanced_color.sdr_white_level_nits}
+            disabled={disabled}
+            onError={onError}
+          />
+        )}
+
+      {monitor.vrr && (
+        <VrrToggle monitor={monitor} disabled={disabled} onError={onError} />
+      )}
+
       <PowerToggle monitor={monitor} disabled={disabled} onError={onError} />
     </>
   );
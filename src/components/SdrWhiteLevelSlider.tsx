@@ -0,0 +1,61 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Slider } from "./ui/Slider/Slider";
+import { BrightnessIcon } from "./ui/icons/BrightnessIcon";
+import { useMonitorsMutation } from "../hooks/useMonitorsMutation";
+
+// Same range as "SDR content brightness" in Windows HDR settings
+const MIN_NITS = 80;
+const MAX_NITS = 480;
+
+type Props = {
+  deviceName: string | null;
+  nits: number;
+  disabled?: boolean;
+  onError?: (msg: string) => void;
+};
+
+export function SdrWhiteLevelSlider({
+  deviceName,
+  nits,
+  disabled,
+  onError,
+}: Props) {
+  const { mutation } = useMonitorsMutation();
+  const [value, setValue] = useState<number>(nits);
+  useEffect(() => setValue(nits), [nits]);
+
+  const apply = async (next: number) => {
+    if (!deviceName) return;
+    try {
+      await mutation(() =>
+        invoke("set_sdr_white_level", { deviceName, nits: next })
+      );
+    } catch (e) {
+      if (onError) onError((e as Error).message ?? String(e));
+    }
+  };
+
+  return (
+    <div className="field">
+      <label className="label" htmlFor="sdr-white-level-range">
+        SDR content brightness
+      </label>
+      <Slider
+        id="sdr-white-level-range"
+        min={MIN_NITS}
+        max={MAX_NITS}
+        step={4}
+        disabled={disabled}
+        value={value}
+        onChange={setValue}
+        onValueSubmit={(next) => {
+          setValue(next);
+          void apply(next);
+        }}
+        icon={<BrightnessIcon />}
+        label={`${value} nits`}
+      />
+    </div>
+  );
+}
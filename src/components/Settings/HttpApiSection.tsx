@@ -0,0 +1,78 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { error as logError } from "@tauri-apps/plugin-log";
+import { useSettings } from "../../hooks/useSettings";
+
+export function HttpApiSection() {
+  const { settings, updateSettings, isLocked } = useSettings();
+  const [token, setToken] = useState<string | null>(null);
+  const { httpApi } = settings;
+
+  useEffect(() => {
+    if (!httpApi.enabled || token !== null) return;
+    invoke<string>("get_http_api_token")
+      .then(setToken)
+      .catch((err) => logError(`Failed to load the API token: ${err}`));
+  }, [httpApi.enabled, token]);
+
+  const regenerate = async () => {
+    try {
+      setToken(await invoke<string>("regenerate_http_api_token"));
+    } catch (err) {
+      logError(`Failed to regenerate the API token: ${err}`);
+    }
+  };
+
+  return (
+    <div className="settings-section">
+      <label className="settings-checkbox-label">
+        <input
+          type="checkbox"
+          checked={httpApi.enabled}
+          onChange={(e) =>
+            updateSettings({
+              httpApi: { ...httpApi, enabled: e.target.checked },
+            })
+          }
+          className="settings-checkbox"
+          disabled={isLocked("httpApi.enabled")}
+        />
+        <span className="settings-checkbox-text">
+          Local API for home automation (Home Assistant, Stream Deck)
+        </span>
+      </label>
+      {httpApi.enabled && (
+        <>
+          <input
+            type="number"
+            className="settings-text-input"
+            min={1024}
+            max={65535}
+            title="Port on 127.0.0.1"
+            disabled={isLocked("httpApi.port")}
+            defaultValue={httpApi.port}
+            onBlur={(e) => {
+              const port = Number(e.target.value);
+              if (Number.isInteger(port) && port >= 1024 && port <= 65535) {
+                updateSettings({ httpApi: { ...httpApi, port } });
+              } else {
+                e.target.value = String(httpApi.port);
+              }
+            }}
+          />
+          <input
+            type="text"
+            className="settings-text-input"
+            readOnly
+            title="Send as 'Authorization: Bearer <token>'"
+            value={token ?? ""}
+            onFocus={(e) => e.target.select()}
+          />
+          <button className="button" onClick={() => void regenerate()}>
+            New token
+          </button>
+        </>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,71 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { useMonitorsContext } from "../../context/MonitorsContext";
+import { useInputSources } from "../../hooks/useInputSources";
+import { useSettings } from "../../hooks/useSettings";
+
+function MonitorInputNames({ deviceName }: Readonly<{ deviceName: string }>) {
+  const { reloadSettings, isLocked } = useSettings();
+  const inputs = useInputSources(deviceName);
+  const [error, setError] = useState<string | null>(null);
+
+  // Applied on blur; an empty name removes it
+  const rename = async (input: string, name: string) => {
+    try {
+      await invoke("set_input_name", { deviceName, input, name });
+      await reloadSettings();
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  return (
+    <>
+      {inputs.map((input) => (
+        <label key={input.key} className="settings-radio-label">
+          <span className="settings-checkbox-text">{input.label}</span>
+          <input
+            type="text"
+            className="settings-text-input"
+            placeholder="Name, e.g. Work Laptop"
+            disabled={isLocked("inputNames")}
+            defaultValue={input.name ?? ""}
+            onBlur={(e) => {
+              if (e.target.value.trim() !== (input.name ?? "")) {
+                rename(input.key, e.target.value);
+              }
+            }}
+          />
+        </label>
+      ))}
+      {error && <div className="settings-section-title-note">{error}</div>}
+    </>
+  );
+}
+
+export function InputNamesSection() {
+  const { monitors } = useMonitorsContext();
+  const [deviceName, setDeviceName] = useState<string>("");
+  const selected = deviceName || monitors[0]?.device_name;
+
+  if (!selected) return null;
+
+  return (
+    <div className="settings-section">
+      <div className="settings-section-title">Input names</div>
+      <select
+        className="settings-text-input"
+        value={selected}
+        onChange={(e) => setDeviceName(e.target.value)}
+      >
+        {monitors.map((monitor) => (
+          <option key={monitor.device_name} value={monitor.device_name}>
+            {monitor.display_name}
+          </option>
+        ))}
+      </select>
+      <MonitorInputNames key={selected} deviceName={selected} />
+    </div>
+  );
+}
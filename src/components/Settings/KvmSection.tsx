@@ -0,0 +1,187 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { useMonitorsContext } from "../../context/MonitorsContext";
+import { inputDisplayName, useInputSources } from "../../hooks/useInputSources";
+import { useSettings, type KvmPreset } from "../../hooks/useSettings";
+
+type KvmResult = {
+  monitor: string;
+  input: string;
+  error: string | null;
+};
+
+type TargetSelectProps = {
+  deviceName: string;
+  displayName: string;
+  value: string;
+  onChange: (input: string) => void;
+};
+
+function TargetSelect({
+  deviceName,
+  displayName,
+  value,
+  onChange,
+}: Readonly<TargetSelectProps>) {
+  const inputs = useInputSources(deviceName);
+  return (
+    <label className="settings-radio-label">
+      <span className="settings-checkbox-text">{displayName}</span>
+      <select value={value} onChange={(e) => onChange(e.target.value)}>
+        <option value="">Leave as is</option>
+        {inputs.map((input) => (
+          <option key={input.key} value={input.key}>
+            {inputDisplayName(input)}
+          </option>
+        ))}
+      </select>
+    </label>
+  );
+}
+
+export function KvmSection() {
+  const { settings, reloadSettings, isLocked } = useSettings();
+  const { monitors } = useMonitorsContext();
+  const [name, setName] = useState("");
+  // Device name -> input key; monitors left out keep their input
+  const [inputs, setInputs] = useState<Record<string, string>>({});
+  const [hotkey, setHotkey] = useState("");
+  const [usbDevice, setUsbDevice] = useState("");
+  const [usbEvent, setUsbEvent] = useState<KvmPreset["usbEvent"]>("connect");
+  const [status, setStatus] = useState<string | null>(null);
+  const locked = isLocked("kvmPresets");
+
+  const save = async () => {
+    const targets = Object.entries(inputs)
+      .filter(([, input]) => input)
+      .map(([monitor, input]) => ({ monitor, input }));
+    const preset: KvmPreset = {
+      name,
+      targets,
+      hotkey: hotkey.trim(),
+      usbDevice: usbDevice.trim(),
+      usbEvent,
+    };
+    try {
+      // The backend stores targets by monitor identity
+      await invoke("save_kvm_preset", { preset });
+      await reloadSettings();
+      setName("");
+      setInputs({});
+      setHotkey("");
+      setUsbDevice("");
+      setStatus(`Saved '${preset.name.trim()}'`);
+    } catch (err) {
+      setStatus(`Failed to save preset: ${err}`);
+    }
+  };
+
+  const remove = async (presetName: string) => {
+    try {
+      await invoke("delete_kvm_preset", { name: presetName });
+      await reloadSettings();
+      setStatus(null);
+    } catch (err) {
+      setStatus(`Failed to delete preset: ${err}`);
+    }
+  };
+
+  const apply = async (presetName: string) => {
+    setStatus(`Switching to '${presetName}'...`);
+    try {
+      const results = await invoke<KvmResult[]>("apply_kvm_preset", {
+        name: presetName,
+      });
+      const failed = results.filter((r) => r.error);
+      setStatus(
+        failed.length === 0
+          ? `Switched ${results.length} monitor(s) to '${presetName}'`
+          : failed.map((r) => `${r.monitor}: ${r.error}`).join("; ")
+      );
+    } catch (err) {
+      setStatus(`Failed to switch: ${err}`);
+    }
+  };
+
+  return (
+    <div className="settings-section">
+      <div className="settings-section-title">KVM presets</div>
+      {settings.kvmPresets.map((preset) => (
+        <div key={preset.name} className="settings-checkbox-label">
+          <span className="settings-checkbox-text">
+            {preset.name}
+            {preset.hotkey && ` (${preset.hotkey})`}
+          </span>
+          <button className="button" onClick={() => apply(preset.name)}>
+            Switch
+          </button>
+          <button
+            className="button"
+            disabled={locked}
+            onClick={() => remove(preset.name)}
+          >
+            Delete
+          </button>
+        </div>
+      ))}
+      {!locked && (
+        <>
+          <input
+            type="text"
+            className="settings-text-input"
+            placeholder="New preset name, e.g. Work Laptop"
+            value={name}
+            onChange={(e) => setName(e.target.value)}
+          />
+          {monitors.map((monitor) => (
+            <TargetSelect
+              key={monitor.device_name}
+              deviceName={monitor.device_name}
+              displayName={monitor.display_name}
+              value={inputs[monitor.device_name] ?? ""}
+              onChange={(input) =>
+                setInputs({ ...inputs, [monitor.device_name]: input })
+              }
+            />
+          ))}
+          <input
+            type="text"
+            className="settings-text-input"
+            placeholder="Hotkey (optional), e.g. Ctrl+Alt+1"
+            value={hotkey}
+            onChange={(e) => setHotkey(e.target.value)}
+          />
+          <input
+            type="text"
+            className="settings-text-input"
+            placeholder="USB device VID:PID (optional), e.g. 046D:C52B"
+            value={usbDevice}
+            onChange={(e) => setUsbDevice(e.target.value)}
+          />
+          {usbDevice.trim() && (
+            <select
+              className="settings-text-input"
+              value={usbEvent}
+              onChange={(e) =>
+                setUsbEvent(e.target.value as KvmPreset["usbEvent"])
+              }
+            >
+              <option value="connect">When it connects</option>
+              <option value="disconnect">When it disconnects</option>
+            </select>
+          )}
+          <button
+            className="button"
+            disabled={!name.trim() || !Object.values(inputs).some(Boolean)}
+            onClick={save}
+          >
+            Save preset
+          </button>
+        </>
+      )}
+      {status && (
+        <div className="settings-section-title-note">{status}</div>
+      )}
+    </div>
+  );
+}
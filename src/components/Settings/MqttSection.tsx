@@ -0,0 +1,84 @@
+import { useSettings, type MqttSettings } from "../../hooks/useSettings";
+
+type TextField = "host" | "username" | "password" | "topicPrefix";
+
+export function MqttSection() {
+  const { settings, updateSettings, isLocked } = useSettings();
+  const { mqtt } = settings;
+
+  const update = (changes: Partial<MqttSettings>) =>
+    updateSettings({ mqtt: { ...mqtt, ...changes } });
+
+  // Applied on blur so the bridge doesn't reconnect on every keystroke
+  const textInput = (
+    field: TextField,
+    placeholder: string,
+    type = "text",
+  ) => (
+    <input
+      type={type}
+      className="settings-text-input"
+      placeholder={placeholder}
+      title={placeholder}
+      disabled={isLocked(`mqtt.${field}`)}
+      defaultValue={mqtt[field]}
+      onBlur={(e) => {
+        const value =
+          field === "password" ? e.target.value : e.target.value.trim();
+        if (value !== mqtt[field]) update({ [field]: value });
+      }}
+    />
+  );
+
+  return (
+    <div className="settings-section">
+      <label className="settings-checkbox-label">
+        <input
+          type="checkbox"
+          checked={mqtt.enabled}
+          onChange={(e) => update({ enabled: e.target.checked })}
+          className="settings-checkbox"
+          disabled={isLocked("mqtt.enabled")}
+        />
+        <span className="settings-checkbox-text">Publish monitors to MQTT</span>
+      </label>
+      {mqtt.enabled && (
+        <>
+          {textInput("host", "Broker host")}
+          <input
+            type="number"
+            className="settings-text-input"
+            min={1}
+            max={65535}
+            title="Broker port"
+            disabled={isLocked("mqtt.port")}
+            defaultValue={mqtt.port}
+            onBlur={(e) => {
+              const port = Number(e.target.value);
+              if (Number.isInteger(port) && port >= 1 && port <= 65535) {
+                update({ port });
+              } else {
+                e.target.value = String(mqtt.port);
+              }
+            }}
+          />
+          {textInput("username", "Username (optional)")}
+          {textInput("password", "Password (optional)", "password")}
+          {textInput("topicPrefix", "Topic prefix")}
+          <label className="settings-checkbox-label">
+            <input
+              type="checkbox"
+              checked={mqtt.discovery}
+              onChange={(e) => update({ discovery: e.target.checked })}
+              className="settings-checkbox"
+              disabled={isLocked("mqtt.discovery")}
+            />
+            <span className="settings-checkbox-text">
+              Home Assistant discovery
+            </span>
+          </label>
+        </>
+      )}
+    </div>
+  );
+}
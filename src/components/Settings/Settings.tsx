@@ -9,6 +9,10 @@ import {
 import { relaunch } from "@tauri-apps/plugin-process";
 import "./Settings.css";
 import { CheckUpdatesButton } from "./CheckUpdatesButton";
+import { HttpApiSection } from "./HttpApiSection";
+import { InputNamesSection } from "./InputNamesSection";
+import { KvmSection } from "./KvmSection";
+import { MqttSection } from "./MqttSection";
 
 type SettingsProps = {
   isOpen: boolean;
@@ -20,7 +24,8 @@ const RESTART_REQUIRED_KEYS: Array<keyof Settings> = [
 ];
 
 export function Settings({ isOpen, onClose }: SettingsProps) {
-  const { settings, updateSettings, loading } = useSettings();
+  const { settings, updateSettings, loading, isLocked, isManaged } =
+    useSettings();
   const initialSettingsRef = useRef<Settings | null>(null);
   useEffect(() => {
     if (!loading && initialSettingsRef.current === null) {
@@ -54,6 +59,11 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
           <div className="settings-loading">Loading settings...</div>
         ) : (
           <div className="settings-content">
+            {isManaged && (
+              <div className="settings-section settings-section-title-note">
+                Some settings are managed by your organization.
+              </div>
+            )}
             <div className="settings-section">
               <div className="settings-section-title">Theme</div>
               <div className="settings-radio-group">
@@ -65,6 +75,7 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
                     checked={settings.theme === "system"}
                     onChange={() => handleThemeChange("system")}
                     className="settings-radio"
+                    disabled={isLocked("theme")}
                   />
                   <span className="settings-radio-text">System default</span>
                 </label>
@@ -76,6 +87,7 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
                     checked={settings.theme === "light"}
                     onChange={() => handleThemeChange("light")}
                     className="settings-radio"
+                    disabled={isLocked("theme")}
                   />
                   <span className="settings-radio-text">Light</span>
                 </label>
@@ -87,6 +99,7 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
                     checked={settings.theme === "dark"}
                     onChange={() => handleThemeChange("dark")}
                     className="settings-radio"
+                    disabled={isLocked("theme")}
                   />
                   <span className="settings-radio-text">Dark</span>
                 </label>
@@ -113,6 +126,7 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
                     }
                     onChange={() => handleRadioChange("all_screens")}
                     className="settings-radio"
+                    disabled={isLocked("keyboardBrightnessShortcut")}
                   />
                   <span className="settings-radio-text">All screens</span>
                 </label>
@@ -128,6 +142,7 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
                     }
                     onChange={() => handleRadioChange("screen_with_mouse")}
                     className="settings-radio"
+                    disabled={isLocked("keyboardBrightnessShortcut")}
                   />
                   <span className="settings-radio-text">
                     Screen with mouse pointer
@@ -142,6 +157,7 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
                     checked={settings.keyboardBrightnessShortcut === "system"}
                     onChange={() => handleRadioChange("system")}
                     className="settings-radio"
+                    disabled={isLocked("keyboardBrightnessShortcut")}
                   />
                   <span className="settings-radio-text">
                     Use system behavior (no action)
@@ -149,6 +165,123 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
                 </label>
               </div>
             </div>
+            <div className="settings-section">
+              <label className="settings-checkbox-label">
+                <input
+                  type="checkbox"
+                  checked={settings.linkBrightness}
+                  onChange={(e) =>
+                    updateSettings({ linkBrightness: e.target.checked })
+                  }
+                  className="settings-checkbox"
+                  disabled={isLocked("linkBrightness")}
+                />
+                <span className="settings-checkbox-text">
+                  Link brightness across monitors
+                </span>
+              </label>
+            </div>
+            <div className="settings-section">
+              <label className="settings-checkbox-label">
+                <input
+                  type="checkbox"
+                  checked={settings.autoHdr.enabled}
+                  onChange={(e) =>
+                    updateSettings({
+                      autoHdr: {
+                        ...settings.autoHdr,
+                        enabled: e.target.checked,
+                      },
+                    })
+                  }
+                  className="settings-checkbox"
+                  disabled={isLocked("autoHdr.enabled")}
+                />
+                <span className="settings-checkbox-text">
+                  Turn on HDR while a fullscreen app is open
+                </span>
+              </label>
+              {settings.autoHdr.enabled && (
+                <input
+                  type="text"
+                  className="settings-text-input"
+                  placeholder="Also for apps, e.g. vlc.exe, mpc-hc64.exe"
+                  disabled={isLocked("autoHdr.apps")}
+                  defaultValue={settings.autoHdr.apps.join(", ")}
+                  onBlur={(e) =>
+                    updateSettings({
+                      autoHdr: {
+                        ...settings.autoHdr,
+                        apps: e.target.value
+                          .split(",")
+                          .map((app) => app.trim())
+                          .filter((app) => app.length > 0),
+                      },
+                    })
+                  }
+                />
+              )}
+            </div>
+            <div className="settings-section">
+              <div className="settings-section-title">
+                Restore After Reboot or Reconnect:
+              </div>
+              <label className="settings-checkbox-label">
+                <input
+                  type="checkbox"
+                  checked={settings.restoreMonitorState.brightness}
+                  onChange={(e) =>
+                    updateSettings({
+                      restoreMonitorState: {
+                        ...settings.restoreMonitorState,
+                        brightness: e.target.checked,
+                      },
+                    })
+                  }
+                  className="settings-checkbox"
+                  disabled={isLocked("restoreMonitorState.brightness")}
+                />
+                <span className="settings-checkbox-text">Brightness</span>
+              </label>
+              <label className="settings-checkbox-label">
+                <input
+                  type="checkbox"
+                  checked={settings.restoreMonitorState.input}
+                  onChange={(e) =>
+                    updateSettings({
+                      restoreMonitorState: {
+                        ...settings.restoreMonitorState,
+                        input: e.target.checked,
+                      },
+                    })
+                  }
+                  className="settings-checkbox"
+                  disabled={isLocked("restoreMonitorState.input")}
+                />
+                <span className="settings-checkbox-text">
+                  Input source (at startup only)
+                </span>
+              </label>
+              <label className="settings-checkbox-label">
+                <input
+                  type="checkbox"
+                  checked={settings.restoreMonitorState.power}
+                  onChange={(e) =>
+                    updateSettings({
+                      restoreMonitorState: {
+                        ...settings.restoreMonitorState,
+                        power: e.target.checked,
+                      },
+                    })
+                  }
+                  className="settings-checkbox"
+                  disabled={isLocked("restoreMonitorState.power")}
+                />
+                <span className="settings-checkbox-text">
+                  Turn monitors back on
+                </span>
+              </label>
+            </div>
             <div className="settings-section">
               <label className="settings-checkbox-label">
                 <input
@@ -156,6 +289,7 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
                   checked={settings.showUIOnMonitorChange}
                   onChange={(e) => handleCheckboxChange(e.target.checked)}
                   className="settings-checkbox"
+                  disabled={isLocked("showUIOnMonitorChange")}
                 />
                 <span className="settings-checkbox-text">
                   Automatically show UI when a monitor is connected or
@@ -172,12 +306,17 @@ export function Settings({ isOpen, onClose }: SettingsProps) {
                     updateSettings({ showStartupNotification: e.target.checked })
                   }
                   className="settings-checkbox"
+                  disabled={isLocked("showStartupNotification")}
                 />
                 <span className="settings-checkbox-text">
                   Show notification when app starts
                 </span>
               </label>
             </div>
+            <HttpApiSection />
+            <MqttSection />
+            <InputNamesSection />
+            <KvmSection />
             <div className="settings-section">
               <CheckUpdatesButton />
             </div>
@@ -0,0 +1,45 @@
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { DisplayInfo } from "../lib/Resolutions";
+import { RefreshRateIcon } from "./ui/icons/RefreshRateIcon";
+import { TextToggle } from "./ui/TextToggle/TextToggle";
+import { useMonitorsMutation } from "../hooks/useMonitorsMutation";
+
+type VrrToggleProps = {
+  monitor: DisplayInfo;
+  disabled?: boolean;
+  onError?: (msg: string) => void;
+};
+
+export function VrrToggle({ monitor, disabled, onError }: VrrToggleProps) {
+  const [busy, setBusy] = useState(false);
+  const { mutation } = useMonitorsMutation();
+  if (!monitor.vrr) return null;
+  const { min_hz, max_hz, enabled } = monitor.vrr;
+
+  return (
+    <TextToggle
+      toggled={enabled}
+      text={`VRR ${enabled ? "Enabled" : "Disabled"} (${min_hz}-${max_hz} Hz)`}
+      disabled={disabled || busy}
+      onClick={async () => {
+        if (busy) return;
+        try {
+          setBusy(true);
+          await mutation(() =>
+            invoke("set_monitor_vrr", {
+              deviceName: monitor.device_name,
+              enable: !enabled,
+            })
+          );
+        } catch (e) {
+          // Error already surfaced via MonitorsContext.mutation
+          if (onError) onError((e as Error).message ?? String(e));
+        } finally {
+          setBusy(false);
+        }
+      }}
+      icon={<RefreshRateIcon />}
+    />
+  );
+}
@@ -0,0 +1,37 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { useSettings } from "./useSettings";
+
+export type InputSource = {
+  key: string;
+  code: number;
+  label: string;
+  // Name the user gave the input, e.g. "Work Laptop"
+  name: string | null;
+};
+
+export function inputDisplayName(input: InputSource): string {
+  return input.name ? `${input.name} (${input.label})` : input.label;
+}
+
+// Inputs the monitor advertises, refetched when the user renames one
+export function useInputSources(deviceName: string) {
+  const { settings } = useSettings();
+  const [inputs, setInputs] = useState<InputSource[]>([]);
+
+  useEffect(() => {
+    let mounted = true;
+    invoke<InputSource[]>("list_monitor_inputs", { deviceName })
+      .then((list) => {
+        if (mounted) setInputs(list);
+      })
+      .catch(() => {
+        // Monitor went away; keep the last list
+      });
+    return () => {
+      mounted = false;
+    };
+  }, [deviceName, settings.inputNames]);
+
+  return inputs;
+}
@@ -1,5 +1,7 @@
 import { useSyncExternalStore, useCallback } from "react";
 import { Store } from "@tauri-apps/plugin-store";
+import { listen } from "@tauri-apps/api/event";
+import { invoke } from "@tauri-apps/api/core";
 import { error as logError } from "@tauri-apps/plugin-log";
 
 export type KeyboardBrightnessShortcut =
@@ -9,37 +11,169 @@ export type KeyboardBrightnessShortcut =
 
 export type ThemeMode = "light" | "dark" | "system";
 
+export type AutoHdrSettings = {
+  enabled: boolean;
+  // Any fullscreen app triggers HDR, not just the allow-listed ones
+  fullscreen: boolean;
+  // Executable names, e.g. "vlc.exe"
+  apps: string[];
+  debounceMs: number;
+};
+
+// Which monitor settings are put back after a reboot or reconnect
+export type RestoreMonitorState = {
+  brightness: boolean;
+  input: boolean;
+  power: boolean;
+};
+
+// Token-protected HTTP/WebSocket API on 127.0.0.1 for home automation
+export type HttpApiSettings = {
+  enabled: boolean;
+  port: number;
+};
+
+export type MqttSettings = {
+  enabled: boolean;
+  host: string;
+  port: number;
+  username: string;
+  password: string;
+  topicPrefix: string;
+  discovery: boolean;
+  discoveryPrefix: string;
+};
+
+export type KvmTarget = {
+  // Monitor identity, or a device name when creating a preset
+  monitor: string;
+  input: string;
+};
+
+export type KvmPreset = {
+  name: string;
+  targets: KvmTarget[];
+  hotkey: string;
+  // "VID:PID" of a USB device that triggers the preset
+  usbDevice: string;
+  usbEvent: "connect" | "disconnect";
+};
+
+// Schema version of the stored settings; keep in step with SETTINGS_VERSION in settings.rs,
+// otherwise the backend reads every save as an older schema and migrates it again
+const SETTINGS_VERSION = 1;
+
 export type Settings = {
+  version: number;
   showUIOnMonitorChange: boolean;
   keyboardBrightnessShortcut: KeyboardBrightnessShortcut;
   showStartupNotification: boolean;
   shouldHideUIOnFocusOut: boolean;
   theme: ThemeMode;
+  linkBrightness: boolean;
+  autoHdr: AutoHdrSettings;
+  restoreMonitorState: RestoreMonitorState;
+  httpApi: HttpApiSettings;
+  mqtt: MqttSettings;
+  kvmPresets: KvmPreset[];
+  // Monitor identity -> input key -> the user's name for that input
+  inputNames: Record<string, Record<string, string>>;
 };
 
 const DEFAULT_SETTINGS: Settings = {
+  version: SETTINGS_VERSION,
   showUIOnMonitorChange: false,
   keyboardBrightnessShortcut: "system",
   showStartupNotification: true,
   shouldHideUIOnFocusOut: true,
   theme: "system",
+  linkBrightness: false,
+  autoHdr: {
+    enabled: false,
+    fullscreen: true,
+    apps: [],
+    debounceMs: 2000,
+  },
+  restoreMonitorState: {
+    brightness: true,
+    input: false,
+    power: true,
+  },
+  httpApi: {
+    enabled: false,
+    port: 8765,
+  },
+  mqtt: {
+    enabled: false,
+    host: "localhost",
+    port: 1883,
+    username: "",
+    password: "",
+    topicPrefix: "windisplay",
+    discovery: true,
+    discoveryPrefix: "homeassistant",
+  },
+  kvmPresets: [],
+  inputNames: {},
 };
 
 const STORE_KEY = "settings";
 
+// Machine policy set by IT: enforced values win over the user's and can't be changed here
+type PolicyStatus = {
+  enforced: Record<string, unknown>;
+  // Dotted setting paths, e.g. "autoHdr.enabled"
+  locked: string[];
+};
+
+function isPlainObject(value: unknown): value is Record<string, unknown> {
+  return typeof value === "object" && value !== null && !Array.isArray(value);
+}
+
+function overlay<T>(base: T, top: Record<string, unknown>): T {
+  const result: Record<string, unknown> = {
+    ...(base as Record<string, unknown>),
+  };
+  for (const [key, value] of Object.entries(top)) {
+    result[key] =
+      isPlainObject(value) && isPlainObject(result[key])
+        ? overlay(result[key], value)
+        : value;
+  }
+  return result as T;
+}
+
 class SettingsStore {
   private store: Store | null = null;
+  // What the user chose; `settings` adds the enforced policy on top
+  private userSettings: Settings = DEFAULT_SETTINGS;
   private settings: Settings = DEFAULT_SETTINGS;
+  private policy: PolicyStatus = { enforced: {}, locked: [] };
   private loading: boolean = true;
   private listeners: Set<() => void> = new Set();
 
   async init() {
     try {
-      this.store = await Store.load("settings.json");
+      this.policy = await invoke<PolicyStatus>("get_policy");
+    } catch (err) {
+      logError(`Failed to load policy: ${err}`);
+    }
+    await this.load();
+    // An imported configuration replaces the settings behind our back
+    listen("config-imported", () => this.load());
+  }
+
+  // Also picks up edits the backend made through its own commands
+  async load() {
+    try {
+      if (!this.store) {
+        this.store = await Store.load("settings.json");
+      }
       const stored = await this.store.get<Settings>(STORE_KEY);
-      this.settings = stored
+      this.userSettings = stored
         ? { ...DEFAULT_SETTINGS, ...stored }
         : DEFAULT_SETTINGS;
+      this.settings = overlay(this.userSettings, this.policy.enforced);
     } catch (err) {
       logError(`Failed to load settings: ${err}`);
     } finally {
@@ -56,15 +190,20 @@ class SettingsStore {
     return this.loading;
   }
 
+  getLocked(): string[] {
+    return this.policy.locked;
+  }
+
   async updateSettings(partial: Partial<Settings>): Promise<void> {
-    this.settings = { ...this.settings, ...partial };
+    this.userSettings = { ...this.userSettings, ...partial };
+    this.settings = overlay(this.userSettings, this.policy.enforced);
     this.notify();
 
     try {
       if (!this.store) {
         this.store = await Store.load("settings.json");
       }
-      await this.store.set(STORE_KEY, this.settings);
+      await this.store.set(STORE_KEY, this.userSettings);
       await this.store.save();
     } catch (err) {
       logError(`Failed to save settings: ${err}`);
@@ -104,9 +243,23 @@ export function useSettings() {
     await settingsStore.updateSettings(partial);
   }, []);
 
+  const reloadSettings = useCallback(() => settingsStore.load(), []);
+
+  // True when IT enforces the setting at `path` or one of its parents
+  const isLocked = useCallback(
+    (path: string) =>
+      settingsStore
+        .getLocked()
+        .some((locked) => locked === path || path.startsWith(`${locked}.`)),
+    []
+  );
+
   return {
     settings,
     updateSettings,
+    reloadSettings,
     loading,
+    isLocked,
+    isManaged: settingsStore.getLocked().length > 0,
   };
 }
@@ -21,12 +21,45 @@ export type DisplayInfo = {
   scale: number;
   scales: ScaleInfo[];
   hdr_status: "on" | "off" | "unsupported";
+  advanced_color: AdvancedColorInfo | null;
   supports_input_switch?: boolean;
+  color_profile: string | null;
+  display_target: DisplayTarget | null;
+  // "identity" | "centered" | "stretched" | "aspect_ratio" | "custom" | "preferred"
+  scaling_mode: string | null;
+  // null when the monitor doesn't support variable refresh rate
+  vrr: VrrInfo | null;
+};
+
+export type VrrInfo = {
+  min_hz: number;
+  max_hz: number;
+  enabled: boolean;
+};
+
+export type AdvancedColorInfo = {
+  encoding:
+    | "rgb"
+    | "ycbcr444"
+    | "ycbcr422"
+    | "ycbcr420"
+    | "intensity"
+    | "unknown";
+  bits_per_channel: number;
+  wide_color_enforced: boolean;
+  force_disabled: boolean;
+  sdr_white_level_nits: number | null;
+};
+
+export type DisplayTarget = {
+  adapter_luid: number;
+  target_id: number;
 };
 
 export type ScaleInfo = {
   scale: number;
   is_recommended: boolean;
+  is_current: boolean;
 };
 
 export type Resolution = {
@@ -34,6 +67,10 @@ export type Resolution = {
   height: number;
   bits_per_pixel: number;
   refresh_hz: number;
+  // Exact rate, e.g. 59.94 for a mode listed as 59 Hz
+  refresh_rate: number;
+  interlaced: boolean;
+  scaled: boolean;
 };
 
 export type PopularResolution = {